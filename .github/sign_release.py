@@ -0,0 +1,30 @@
+"""Signs the release artifacts for the in-app updater.
+
+Writes `<artifact>.sig` next to each artifact given, holding the hex BIP-340
+signature of SHA-256(name || "\n" || version || "\n" || SHA-256(artifact)) made
+with the secret key in AKRON_RELEASE_SIGNING_KEY. The updater verifies it with
+AKRON_RELEASE_KEY, the matching x-only public key.
+"""
+
+import hashlib
+import os
+import sys
+
+from coincurve import PrivateKey
+
+
+def main():
+    key = PrivateKey(bytes.fromhex(os.environ["AKRON_RELEASE_SIGNING_KEY"]))
+    version = sys.argv[1]
+    for path in sys.argv[2:]:
+        name = os.path.basename(path)
+        with open(path, "rb") as artifact:
+            artifact_hash = hashlib.sha256(artifact.read()).digest()
+        digest = hashlib.sha256(f"{name}\n{version}\n".encode() + artifact_hash).digest()
+        with open(f"{path}.sig", "w") as signature:
+            signature.write(key.sign_schnorr(digest).hex())
+        print(f"Signed {name}")
+
+
+if __name__ == "__main__":
+    main()
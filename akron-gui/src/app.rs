@@ -1,24 +1,91 @@
-use crate::{pages::*, Config};
-use iced::{application, theme, window, Color, Element, Font, Subscription, Task};
+use crate::{
+    client::service_ports,
+    crash, instance,
+    pages::*,
+    ui_state::{UiState, WindowGeometry},
+    Config,
+};
+use iced::{
+    clipboard, theme,
+    widget::{center, stack, text},
+    window, Color, Element, Font, Point, Size, Subscription, Task,
+};
+use std::path::PathBuf;
 
 #[derive(Debug)]
-pub enum State {
-    Setup(setup::State),
-    Main(main::State),
+pub struct State {
+    /// The main window, closing it quits
+    window: window::Id,
+    /// Windows showing part of a session, with the session id
+    detached: Vec<(window::Id, usize, main::Detached)>,
+    screen: Screen,
+    /// Sessions kept running while another one is shown, each with its own config,
+    /// data directory, services and wallets
+    sessions: Vec<(usize, main::State)>,
+    next_session: usize,
+    data_dir: PathBuf,
+    /// Report of a crash of the last run, shown over the screen until dismissed
+    crash_report: Option<crash::Report>,
+    ui: UiState,
+    /// The first session was shown as it was left, done once per launch
+    ui_restored: bool,
+}
+
+fn window_icon() -> window::Icon {
+    window::icon::from_rgba(include_bytes!("../../assets/akron.rgba").to_vec(), 64, 64)
+        .expect("Failed to load icon")
+}
+
+#[derive(Debug)]
+enum Screen {
+    Setup(usize, setup::State),
+    Main(usize, main::State),
 }
 
 #[derive(Debug)]
 enum Message {
     Setup(setup::Message),
-    Main(main::Message),
+    Main(usize, main::Message),
+    Crash(crash::Message),
+    /// Another launch with the same data directory asked for the window
+    Focus,
+    Window(window::Id, window::Event),
 }
 
 impl State {
     pub fn run(config: Config) -> iced::Result {
+        let data_dir = config.data_dir().to_path_buf();
         let (state, task) = setup::State::run(config);
-        let state = Self::Setup(state);
-        let task = task.map(Message::Setup);
-        application("Akron", Self::update, Self::view)
+        let ui = UiState::load(&data_dir);
+        let geometry = ui.window;
+        let (window, open) = window::open(window::Settings {
+            size: geometry.map_or(window::Settings::default().size, |geometry| {
+                Size::new(geometry.width, geometry.height)
+            }),
+            position: geometry
+                .and_then(|geometry| geometry.position)
+                .map_or(window::Position::default(), |(x, y)| {
+                    window::Position::Specific(Point::new(x, y))
+                }),
+            min_size: Some((1300.0, 500.0).into()),
+            icon: Some(window_icon()),
+            // the UI state is saved before the window is closed
+            exit_on_close_request: false,
+            ..Default::default()
+        });
+        let state = Self {
+            window,
+            detached: Vec::new(),
+            screen: Screen::Setup(0, state),
+            sessions: Vec::new(),
+            next_session: 1,
+            crash_report: crash::Report::load(&data_dir),
+            ui,
+            ui_restored: false,
+            data_dir,
+        };
+        let task = Task::batch([open.discard(), task.map(Message::Setup)]);
+        iced::daemon(Self::title, Self::update, Self::view)
             .font(include_bytes!("../../assets/icons.ttf").as_slice())
             .font(include_bytes!("../../assets/fonts/Karla/static/Karla-Bold.ttf").as_slice())
             .font(include_bytes!("../../assets/fonts/Karla/static/Karla-SemiBold.ttf").as_slice())
@@ -30,19 +97,7 @@ impl State {
                 style: iced::font::Style::Normal,
             })
             .subscription(Self::subscription)
-            .window(window::Settings {
-                min_size: Some((1300.0, 500.0).into()),
-                icon: Some(
-                    window::icon::from_rgba(
-                        include_bytes!("../../assets/akron.rgba").to_vec(),
-                        64,
-                        64,
-                    )
-                    .expect("Failed to load icon"),
-                ),
-                ..Default::default()
-            })
-            .theme(|_| {
+            .theme(|_, _| {
                 theme::Theme::custom_with_fn(
                     "Bitcoin".into(),
                     theme::Palette {
@@ -63,42 +118,315 @@ impl State {
             .run_with(move || (state, task))
     }
 
+    /// Config of an additional session, kept apart from the first one so the sessions
+    /// don't share services or wallets
+    fn session_config(&self, id: usize) -> (Config, Option<String>) {
+        let dir = self.data_dir.join("sessions").join(id.to_string());
+        let error = std::fs::create_dir_all(&dir)
+            .err()
+            .map(|e| format!("Could not create {}: {}", dir.display(), e));
+        (Config::load(dir.join("config.json")), error)
+    }
+
+    /// Ports of the services run by the sessions, two sessions on the same network would
+    /// clash over them
+    fn ports_in_use(&self) -> Vec<u16> {
+        let current = match &self.screen {
+            Screen::Main(_, state) => Some(state),
+            Screen::Setup(..) => None,
+        };
+        self.sessions
+            .iter()
+            .map(|(_, state)| state)
+            .chain(current)
+            .filter_map(main::State::backend)
+            .flat_map(service_ports)
+            .collect()
+    }
+
+    /// Shows `screen`, the shown session keeps running in the background if `keep_current`
+    fn set_screen(&mut self, screen: Screen, keep_current: bool) {
+        let previous = std::mem::replace(&mut self.screen, screen);
+        if let (Screen::Main(id, state), true) = (previous, keep_current) {
+            self.sessions.push((id, state));
+        }
+        self.update_sessions();
+    }
+
+    fn update_sessions(&mut self) {
+        let mut sessions: Vec<(usize, String)> = self
+            .sessions
+            .iter()
+            .map(|(id, state)| (*id, state.session_label()))
+            .collect();
+        if let Screen::Main(id, state) = &self.screen {
+            sessions.push((*id, state.session_label()));
+        }
+        sessions.sort_by_key(|(id, _)| *id);
+        for (id, state) in self.sessions.iter_mut() {
+            state.set_sessions(*id, sessions.clone());
+        }
+        if let Screen::Main(id, state) = &mut self.screen {
+            state.set_sessions(*id, sessions);
+        }
+    }
+
+    fn show_session(&mut self, id: usize) -> Task<Message> {
+        let Some(index) = self.sessions.iter().position(|(i, _)| *i == id) else {
+            return Task::none();
+        };
+        let (id, state) = self.sessions.remove(index);
+        self.set_screen(Screen::Main(id, state), true);
+        Task::done(Message::Main(id, main::Message::Tick))
+    }
+
+    fn session(&self, id: usize) -> Option<&main::State> {
+        match &self.screen {
+            Screen::Main(current, state) if *current == id => Some(state),
+            _ => self
+                .sessions
+                .iter()
+                .find(|(i, _)| *i == id)
+                .map(|(_, state)| state),
+        }
+    }
+
+    /// Opens a window showing `detached` of the session `id`, or brings it forward when
+    /// it's open already
+    fn detach(&mut self, id: usize, detached: main::Detached) -> Task<Message> {
+        if let Some((window, ..)) = self
+            .detached
+            .iter()
+            .find(|(_, session, d)| *session == id && *d == detached)
+        {
+            return window::gain_focus(*window);
+        }
+        let (window, open) = window::open(window::Settings {
+            size: Size::new(900.0, 600.0),
+            icon: Some(window_icon()),
+            ..Default::default()
+        });
+        self.detached.push((window, id, detached));
+        open.discard()
+    }
+
+    /// Closes the windows showing part of the session `id`, which is gone
+    fn close_detached(&self, id: usize) -> Task<Message> {
+        Task::batch(
+            self.detached
+                .iter()
+                .filter(|(_, session, _)| *session == id)
+                .map(|(window, ..)| window::close(*window)),
+        )
+    }
+
+    /// Keeps the screen of the first session and the window for the next launch
+    fn save_ui(&mut self) {
+        if let Some(state) = self.session(0) {
+            let (screen, logs_expanded) = state.ui_state();
+            self.ui.screen = screen.map(str::to_string);
+            self.ui.logs_expanded = logs_expanded;
+        }
+        self.ui.save();
+    }
+
     fn update(&mut self, message: Message) -> Task<Message> {
-        match (&mut *self, message) {
-            (Self::Setup(state), Message::Setup(message)) => match state.update(message) {
-                setup::Action::Return(config, client) => {
-                    let (state, task) = main::State::run(config, client);
-                    let task = task.map(Message::Main);
-                    *self = Self::Main(state);
-                    task
+        match message {
+            Message::Window(id, event) if id != self.window => {
+                if let window::Event::Closed = event {
+                    self.detached.retain(|(window, ..)| *window != id);
                 }
-                setup::Action::Task(task) => task.map(Message::Setup),
-            },
-            (Self::Main(state), Message::Main(message)) => match state.update(message) {
-                main::Action::Return(mut config) => {
-                    config.reset();
-                    let (state, task) = setup::State::run(config);
-                    let task = task.map(Message::Setup);
-                    *self = Self::Setup(state);
-                    task
+                Task::none()
+            }
+            Message::Window(_, event) => {
+                match event {
+                    window::Event::Opened { position, size } => {
+                        self.ui.window = Some(WindowGeometry {
+                            width: size.width,
+                            height: size.height,
+                            position: position.map(|p| (p.x, p.y)),
+                        });
+                    }
+                    window::Event::Resized(size) => {
+                        if let Some(geometry) = &mut self.ui.window {
+                            geometry.width = size.width;
+                            geometry.height = size.height;
+                        }
+                    }
+                    window::Event::Moved(position) => {
+                        if let Some(geometry) = &mut self.ui.window {
+                            geometry.position = Some((position.x, position.y));
+                        }
+                    }
+                    window::Event::CloseRequested => {
+                        self.save_ui();
+                        return iced::exit();
+                    }
+                    _ => {}
+                }
+                Task::none()
+            }
+            Message::Focus => Task::batch([
+                window::minimize(self.window, false),
+                window::gain_focus(self.window),
+            ]),
+            Message::Crash(message) => match message {
+                crash::Message::Open => {
+                    if let Some(report) = self.crash_report.as_ref() {
+                        report.open();
+                    }
+                    Task::none()
+                }
+                crash::Message::Copy => match self.crash_report.as_ref() {
+                    Some(report) => clipboard::write(report.contents().to_string()),
+                    None => Task::none(),
+                },
+                crash::Message::Dismiss => {
+                    if let Some(report) = self.crash_report.take() {
+                        report.dismiss();
+                    }
+                    Task::none()
                 }
-                main::Action::Task(task) => task.map(Message::Main),
             },
-            _ => Task::none(),
+            Message::Setup(message) => {
+                let Screen::Setup(id, state) = &mut self.screen else {
+                    return Task::none();
+                };
+                let id = *id;
+                match state.update(message) {
+                    setup::Action::Return(config, client) => {
+                        if id == 0 && self.data_dir != config.data_dir() {
+                            // the data directory may have been changed on first run
+                            self.data_dir = config.data_dir().to_path_buf();
+                            let window = self.ui.window;
+                            self.ui = UiState::load(&self.data_dir);
+                            self.ui.window = window;
+                        }
+                        let (mut state, mut task) = main::State::run(config, client);
+                        if id == 0 && !self.ui_restored {
+                            self.ui_restored = true;
+                            task = task.chain(
+                                state.restore_ui(self.ui.screen.as_deref(), self.ui.logs_expanded),
+                            );
+                        }
+                        self.set_screen(Screen::Main(id, state), false);
+                        task.map(move |message| Message::Main(id, message))
+                    }
+                    setup::Action::Cancel => match self.sessions.last() {
+                        Some((id, _)) => self.show_session(*id),
+                        None => Task::none(),
+                    },
+                    setup::Action::Task(task) => task.map(Message::Setup),
+                }
+            }
+            Message::Main(id, message) => {
+                let action = match &mut self.screen {
+                    Screen::Main(current, state) if *current == id => state.update(message),
+                    _ => {
+                        // sessions in the background only finish their running tasks
+                        return match self.sessions.iter_mut().find(|(i, _)| *i == id) {
+                            Some((_, state)) => match state.update(message) {
+                                main::Action::Task(task) => {
+                                    task.map(move |message| Message::Main(id, message))
+                                }
+                                _ => Task::none(),
+                            },
+                            None => Task::none(),
+                        };
+                    }
+                };
+                match action {
+                    main::Action::Return(mut config) => {
+                        config.reset();
+                        let (state, task) = setup::State::run(config);
+                        self.set_screen(Screen::Setup(id, state), false);
+                        Task::batch([self.close_detached(id), task.map(Message::Setup)])
+                    }
+                    main::Action::Reconnect(config) => {
+                        let (state, task) = setup::State::run(config);
+                        self.set_screen(Screen::Setup(id, state), false);
+                        Task::batch([self.close_detached(id), task.map(Message::Setup)])
+                    }
+                    main::Action::RebuildChainData(config) => {
+                        let (state, task) = setup::State::rebuild(config);
+                        self.set_screen(Screen::Setup(id, state), false);
+                        Task::batch([self.close_detached(id), task.map(Message::Setup)])
+                    }
+                    main::Action::SwitchSession(target) => self.show_session(target),
+                    main::Action::OpenSession => {
+                        let id = self.next_session;
+                        self.next_session += 1;
+                        let (config, error) = self.session_config(id);
+                        let (state, task) =
+                            setup::State::run_session(config, self.ports_in_use(), error);
+                        self.set_screen(Screen::Setup(id, state), true);
+                        task.map(Message::Setup)
+                    }
+                    main::Action::CloseSession(target) => {
+                        self.sessions.retain(|(i, _)| *i != target);
+                        self.update_sessions();
+                        self.close_detached(target)
+                    }
+                    main::Action::Detach(detached) => self.detach(id, detached),
+                    main::Action::Task(task) => task.map(move |message| Message::Main(id, message)),
+                }
+            }
         }
     }
 
-    fn view(&self) -> Element<Message> {
-        match self {
-            Self::Setup(state) => state.view().map(Message::Setup),
-            Self::Main(state) => state.view().map(Message::Main),
+    fn title(&self, window: window::Id) -> String {
+        self.detached
+            .iter()
+            .find(|(id, ..)| *id == window)
+            .and_then(|(_, session, detached)| {
+                Some(self.session(*session)?.detached_title(*detached))
+            })
+            .unwrap_or_else(|| "Akron".to_string())
+    }
+
+    fn view(&self, window: window::Id) -> Element<Message> {
+        if let Some((_, session, detached)) = self.detached.iter().find(|(id, ..)| *id == window) {
+            let session = *session;
+            return match self.session(session) {
+                Some(state) => state
+                    .detached_view(*detached)
+                    .map(move |message| Message::Main(session, message)),
+                None => center(text("The session was closed")).into(),
+            };
+        }
+        let screen = match &self.screen {
+            Screen::Setup(_, state) => state.view().map(Message::Setup),
+            Screen::Main(id, state) => {
+                let id = *id;
+                state.view().map(move |message| Message::Main(id, message))
+            }
+        };
+        match self.crash_report.as_ref() {
+            Some(report) => stack![screen, report.view().map(Message::Crash)].into(),
+            None => screen,
         }
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        match self {
-            Self::Setup(state) => state.subscription().map(Message::Setup),
-            Self::Main(state) => state.subscription().map(Message::Main),
-        }
+        let screen = match &self.screen {
+            Screen::Setup(_, state) => state.subscription().map(Message::Setup),
+            Screen::Main(id, state) => state
+                .subscription()
+                .with(*id)
+                .map(|(id, message)| Message::Main(id, message)),
+        };
+        // sessions in the background keep ticking, listening and renewing
+        let sessions = self.sessions.iter().map(|(id, state)| {
+            state
+                .subscription()
+                .with(*id)
+                .map(|(id, message)| Message::Main(id, message))
+        });
+        Subscription::batch([
+            screen,
+            Subscription::batch(sessions),
+            Subscription::run(instance::focus_requests).map(|_| Message::Focus),
+            window::events().map(|(id, event)| Message::Window(id, event)),
+        ])
     }
 }
@@ -0,0 +1,175 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{write_atomic, Config};
+
+/// Raised when a backup can't be restored by older versions
+const VERSION: u32 = 1;
+
+/// Everything needed to set Akron up on another computer: the config, the wallets with
+/// their private keys and the files kept next to the config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppBackup {
+    version: u32,
+    config: Config,
+    /// spaced wallet exports, private keys included
+    wallets: Vec<String>,
+    /// Contents of the files in the data directory by their relative path
+    files: BTreeMap<String, String>,
+}
+
+/// Price history and the settings file of every wallet, relative to the data directory
+fn data_files(data_dir: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from("price_history.json")];
+    let wallets_dir = Path::new("wallets");
+    for network in fs::read_dir(data_dir.join(wallets_dir))
+        .into_iter()
+        .flatten()
+    {
+        let Ok(network) = network else {
+            continue;
+        };
+        for wallet in fs::read_dir(network.path()).into_iter().flatten() {
+            let Ok(wallet) = wallet else {
+                continue;
+            };
+            paths.push(
+                wallets_dir
+                    .join(network.file_name())
+                    .join(wallet.file_name())
+                    .join("config.json"),
+            );
+        }
+    }
+    paths
+}
+
+/// Where a restored file goes, only the files `data_files` backs up are accepted so a
+/// crafted backup can't write anywhere else in the data directory
+fn restore_path(data_dir: &Path, path: &str) -> Option<PathBuf> {
+    let is_name = |name: &str| {
+        !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', ':'])
+    };
+    match path.split('/').collect::<Vec<_>>().as_slice() {
+        ["price_history.json"] => Some(data_dir.join("price_history.json")),
+        ["wallets", network, wallet, "config.json"] if is_name(network) && is_name(wallet) => Some(
+            data_dir
+                .join("wallets")
+                .join(network)
+                .join(wallet)
+                .join("config.json"),
+        ),
+        _ => None,
+    }
+}
+
+impl AppBackup {
+    pub fn new(config: &Config, wallets: Vec<String>) -> Self {
+        let data_dir = config.data_dir();
+        let files = data_files(data_dir)
+            .into_iter()
+            .filter_map(|path| {
+                let contents = fs::read_to_string(data_dir.join(&path)).ok()?;
+                // the same separators on every platform
+                Some((path.to_str()?.replace('\\', "/"), contents))
+            })
+            .collect();
+        Self {
+            version: VERSION,
+            config: config.clone(),
+            wallets,
+            files,
+        }
+    }
+
+    /// Armored age file encrypted with `password`, as the private key exports
+    pub fn encrypt(&self, password: String) -> Result<String, String> {
+        let contents = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        let recipient = age::scrypt::Recipient::new(password.into());
+        age::encrypt_and_armor(&recipient, contents.as_bytes()).map_err(|e| e.to_string())
+    }
+
+    pub fn decrypt(contents: &str, password: String) -> Result<Self, String> {
+        let identity = age::scrypt::Identity::new(password.into());
+        let contents = age::decrypt(&identity, contents.as_bytes())
+            .map_err(|e| format!("Could not decrypt the backup: {}", e))?;
+        let backup: Self =
+            serde_json::from_slice(&contents).map_err(|e| format!("Not an Akron backup: {}", e))?;
+        if backup.version > VERSION {
+            return Err("The backup was made by a newer version of Akron".to_string());
+        }
+        Ok(backup)
+    }
+
+    /// Writes the files of the backup to the data directory of `current`, returning the
+    /// restored config with the wallets still to be imported into spaced
+    pub fn restore(self, current: &Config) -> Result<(Config, Vec<String>), String> {
+        let data_dir = current.data_dir();
+        for (path, contents) in &self.files {
+            let path = restore_path(data_dir, path)
+                .ok_or_else(|| format!("The backup has an invalid file path {}", path))?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            write_atomic(&path, contents.as_bytes())
+                .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        }
+        // where the config lives stays as it is on this computer
+        let config = Config {
+            path: current.path.clone(),
+            no_capture_logs: current.no_capture_logs,
+            data_dir_choice: current.data_dir_choice,
+            ..self.config
+        };
+        Ok((config, self.wallets))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_path_accepts_backed_up_files() {
+        let data_dir = Path::new("data");
+        assert_eq!(
+            restore_path(data_dir, "price_history.json"),
+            Some(data_dir.join("price_history.json"))
+        );
+        assert_eq!(
+            restore_path(data_dir, "wallets/mainnet/default/config.json"),
+            Some(
+                data_dir
+                    .join("wallets")
+                    .join("mainnet")
+                    .join("default")
+                    .join("config.json")
+            )
+        );
+    }
+
+    #[test]
+    fn restore_path_refuses_other_files() {
+        let data_dir = Path::new("data");
+        for path in [
+            "",
+            "config.json",
+            "../price_history.json",
+            "/price_history.json",
+            "wallets/mainnet/../config.json",
+            "wallets/../../default/config.json",
+            "wallets/mainnet/default/wallet.json",
+            "wallets/mainnet/a\\..\\b/config.json",
+            "wallets/mainnet/C:/config.json",
+            "wallets/mainnet/default/extra/config.json",
+            "spaces/mainnet/protocol.sdb",
+        ] {
+            assert_eq!(restore_path(data_dir, path), None, "{}", path);
+        }
+    }
+}
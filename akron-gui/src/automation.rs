@@ -0,0 +1,261 @@
+use iced::futures::{SinkExt, Stream};
+use serde_json::{json, Value};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+
+use crate::client::{Amount, FeeRate, SLabel};
+use crate::nostr::NostrEvent;
+
+/// Port of the automation API unless another one is set, e.g. for a second session.
+/// Scripts POST JSON-RPC 2.0 requests with an `Authorization: Bearer <token>` header,
+/// naming the loaded wallet each call is made with, e.g.
+/// `{"jsonrpc": "2.0", "id": 1, "method": "bid",
+/// "params": {"wallet": "default", "space": "@name", "amount": 1000}}`
+pub const AUTOMATION_PORT: u16 = 7228;
+
+/// Local address the automation API listens on
+pub fn automation_addr(port: u16) -> String {
+    format!("127.0.0.1:{}", port)
+}
+
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+
+type Reply = Arc<Mutex<Option<oneshot::Sender<Result<Value, String>>>>>;
+
+#[derive(Debug, Clone)]
+pub enum Call {
+    ListSpaces,
+    Balance,
+    Bid {
+        space: SLabel,
+        amount: Amount,
+        fee_rate: Option<FeeRate>,
+    },
+    SignEvent {
+        space: SLabel,
+        event: NostrEvent,
+    },
+}
+
+impl Call {
+    fn from_request(method: &str, params: &Value) -> Result<Self, String> {
+        let space = || {
+            params
+                .get("space")
+                .and_then(Value::as_str)
+                .ok_or("Missing space")
+                .and_then(|s| SLabel::from_str(s).map_err(|_| "Invalid space"))
+        };
+        match method {
+            "list_spaces" => Ok(Self::ListSpaces),
+            "balance" => Ok(Self::Balance),
+            "bid" => Ok(Self::Bid {
+                space: space()?,
+                amount: params
+                    .get("amount")
+                    .and_then(Value::as_u64)
+                    .map(Amount::from_sat)
+                    .ok_or("Missing amount in sat")?,
+                fee_rate: match params.get("fee_rate") {
+                    None | Some(Value::Null) => None,
+                    Some(fee_rate) => Some(
+                        fee_rate
+                            .as_u64()
+                            .and_then(FeeRate::from_sat_per_vb)
+                            .ok_or("Invalid fee rate in sat/vB")?,
+                    ),
+                },
+            }),
+            "sign_event" => Ok(Self::SignEvent {
+                space: space()?,
+                event: serde_json::from_value(params.get("event").ok_or("Missing event")?.clone())
+                    .map_err(|e| format!("Invalid event: {}", e))?,
+            }),
+            _ => Err(format!("Unknown method {}", method)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AutomationRequest {
+    pub wallet: String,
+    pub call: Call,
+    reply: Reply,
+}
+
+impl AutomationRequest {
+    /// Sends the outcome back to the script
+    pub fn respond(self, result: Result<Value, String>) {
+        if let Some(reply) = self.reply.lock().unwrap().take() {
+            let _ = reply.send(result);
+        }
+    }
+}
+
+/// Random token scripts authenticate with
+pub fn generate_token() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+/// Writes a new random token to `path`, only the owner can read it
+pub fn write_token(path: &std::path::Path) -> std::io::Result<String> {
+    let token = generate_token();
+    crate::write_atomic(path, token.as_bytes())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(token)
+}
+
+pub fn token_matches(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Yields the authenticated requests, or a single error when the address can't be listened on
+pub fn listen(token: String, port: u16) -> impl Stream<Item = Result<AutomationRequest, String>> {
+    iced::stream::channel(10, move |mut output| async move {
+        let addr = automation_addr(port);
+        let listener = match tokio::net::TcpListener::bind(&addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                let _ = output
+                    .send(Err(format!("Could not listen on {}: {}", addr, e)))
+                    .await;
+                return;
+            }
+        };
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(handle_connection(stream, token.clone(), output.clone()));
+        }
+    })
+}
+
+/// Reads the head and body of an HTTP POST, returning the bearer token and the body
+async fn read_request(stream: &mut TcpStream) -> Result<(Option<String>, Vec<u8>), String> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let head_end = loop {
+        if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        let read = stream.read(&mut buf).await.map_err(|e| e.to_string())?;
+        if read == 0 || data.len() > MAX_REQUEST_SIZE {
+            return Err("Incomplete request".to_string());
+        }
+        data.extend_from_slice(&buf[..read]);
+    };
+    let head = String::from_utf8_lossy(&data[..head_end]).to_string();
+    let mut lines = head.lines();
+    if !lines.next().is_some_and(|line| line.starts_with("POST ")) {
+        return Err("Only POST is supported".to_string());
+    }
+    let mut token = None;
+    let mut content_length = 0;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        if name.eq_ignore_ascii_case("authorization") {
+            token = value.trim().strip_prefix("Bearer ").map(str::to_string);
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse().map_err(|_| "Invalid Content-Length")?;
+        }
+    }
+    if content_length > MAX_REQUEST_SIZE {
+        return Err("Request too large".to_string());
+    }
+    let mut body = data.split_off(head_end);
+    while body.len() < content_length {
+        let read = stream.read(&mut buf).await.map_err(|e| e.to_string())?;
+        if read == 0 {
+            return Err("Incomplete request".to_string());
+        }
+        body.extend_from_slice(&buf[..read]);
+    }
+    body.truncate(content_length);
+    Ok((token, body))
+}
+
+async fn write_response(stream: &mut TcpStream, status: &str, body: &Value) {
+    let body = body.to_string();
+    let _ = stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .as_bytes(),
+        )
+        .await;
+}
+
+fn error(id: Value, code: i32, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    token: String,
+    mut output: iced::futures::channel::mpsc::Sender<Result<AutomationRequest, String>>,
+) {
+    let (given, body) = match read_request(&mut stream).await {
+        Ok(request) => request,
+        Err(err) => {
+            let body = error(Value::Null, -32600, err);
+            return write_response(&mut stream, "400 Bad Request", &body).await;
+        }
+    };
+    if !given.is_some_and(|given| token_matches(&given, &token)) {
+        let body = error(Value::Null, -32001, "Invalid token".to_string());
+        return write_response(&mut stream, "401 Unauthorized", &body).await;
+    }
+
+    let request = serde_json::from_slice::<Value>(&body).unwrap_or_default();
+    let id = request.get("id").cloned().unwrap_or_default();
+    let call = match request.get("method").and_then(Value::as_str) {
+        Some(method) => {
+            let params = request.get("params").unwrap_or(&Value::Null);
+            match params.get("wallet").and_then(Value::as_str) {
+                Some(wallet) => {
+                    Call::from_request(method, params).map(|call| (wallet.to_string(), call))
+                }
+                None => Err("Missing wallet".to_string()),
+            }
+        }
+        None => Err("Missing method".to_string()),
+    };
+    let result = match call {
+        Ok((wallet, call)) => {
+            let (sender, receiver) = oneshot::channel();
+            let request = AutomationRequest {
+                wallet,
+                call,
+                reply: Arc::new(Mutex::new(Some(sender))),
+            };
+            if output.send(Ok(request)).await.is_err() {
+                return;
+            }
+            receiver
+                .await
+                .unwrap_or_else(|_| Err("Request dropped".to_string()))
+        }
+        Err(err) => Err(err),
+    };
+    let body = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(err) => error(id, -32000, err),
+    };
+    write_response(&mut stream, "200 OK", &body).await;
+}
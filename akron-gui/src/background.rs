@@ -0,0 +1,237 @@
+use directories::BaseDirs;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Name of the service, also the launchd label and the Windows Run value
+const SERVICE_NAME: &str = "akron";
+const LAUNCHD_LABEL: &str = "io.impervious.akron";
+/// Key of the programs started when the user logs in on Windows
+const WINDOWS_RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+/// Definition running Akron headless at login, a systemd user unit on Linux, a launchd
+/// agent on macOS and a Run entry on Windows, where the headless mode can't talk to the
+/// service manager
+#[derive(Debug, Clone)]
+pub struct Definition {
+    /// Where the definition is installed
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// Command line of the headless mode with the data directory, as the executable and its
+/// arguments
+fn command(data_dir: &Path) -> Result<(String, Vec<String>), String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    Ok((
+        exe.to_string_lossy().into_owned(),
+        vec![
+            "--headless".to_string(),
+            "--data-dir".to_string(),
+            data_dir.to_string_lossy().into_owned(),
+        ],
+    ))
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn systemd_unit(exe: &str, args: &[String]) -> String {
+    // quoted words, with the specifiers systemd expands escaped
+    let quote = |s: &str| {
+        format!(
+            "\"{}\"",
+            s.replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('%', "%%")
+        )
+    };
+    let command: Vec<String> = std::iter::once(exe)
+        .chain(args.iter().map(String::as_str))
+        .map(quote)
+        .collect();
+    format!(
+        "[Unit]\n\
+         Description=Akron wallet in the background\n\
+         After=network-online.target\n\
+         \n\
+         [Service]\n\
+         ExecStart={}\n\
+         Restart=on-failure\n\
+         RestartSec=30\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        command.join(" ")
+    )
+}
+
+fn launchd_plist(exe: &str, args: &[String]) -> String {
+    let arguments: String = std::iter::once(exe)
+        .chain(args.iter().map(String::as_str))
+        .map(|arg| format!("        <string>{}</string>\n", escape_xml(arg)))
+        .collect();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+         \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \x20   <key>Label</key>\n\
+         \x20   <string>{}</string>\n\
+         \x20   <key>ProgramArguments</key>\n\
+         \x20   <array>\n\
+         {}\
+         \x20   </array>\n\
+         \x20   <key>RunAtLoad</key>\n\
+         \x20   <true/>\n\
+         \x20   <key>KeepAlive</key>\n\
+         \x20   <dict>\n\
+         \x20       <key>SuccessfulExit</key>\n\
+         \x20       <false/>\n\
+         \x20   </dict>\n\
+         </dict>\n\
+         </plist>\n",
+        LAUNCHD_LABEL, arguments
+    )
+}
+
+/// Registry file adding the Run entry, imported with `reg import`
+fn windows_reg(exe: &str, args: &[String]) -> String {
+    let command: Vec<String> = std::iter::once(exe)
+        .chain(args.iter().map(String::as_str))
+        .map(|arg| format!("\"{}\"", arg))
+        .collect();
+    let value = command.join(" ").replace('\\', "\\\\").replace('"', "\\\"");
+    format!(
+        "Windows Registry Editor Version 5.00\r\n\
+         \r\n\
+         [{}]\r\n\
+         \"{}\"=\"{}\"\r\n",
+        WINDOWS_RUN_KEY.replace("HKCU", "HKEY_CURRENT_USER"),
+        SERVICE_NAME,
+        value
+    )
+}
+
+/// Definition of the service for this platform and data directory
+pub fn definition(data_dir: &Path) -> Result<Definition, String> {
+    let (exe, args) = command(data_dir)?;
+    let dirs = BaseDirs::new().ok_or("Could not find the home directory")?;
+    let definition = match std::env::consts::OS {
+        "macos" => Definition {
+            path: dirs
+                .home_dir()
+                .join("Library/LaunchAgents")
+                .join(format!("{}.plist", LAUNCHD_LABEL)),
+            contents: launchd_plist(&exe, &args),
+        },
+        "windows" => Definition {
+            path: data_dir.join(format!("{}-service.reg", SERVICE_NAME)),
+            contents: windows_reg(&exe, &args),
+        },
+        _ => Definition {
+            path: dirs
+                .config_dir()
+                .join("systemd/user")
+                .join(format!("{}.service", SERVICE_NAME)),
+            contents: systemd_unit(&exe, &args),
+        },
+    };
+    Ok(definition)
+}
+
+/// Whether the service of this platform is installed
+pub fn is_installed(data_dir: &Path) -> bool {
+    definition(data_dir).is_ok_and(|definition| definition.path.exists())
+}
+
+async fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Could not run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Installs the service so it starts at the next login. It isn't started right away,
+/// the window holds the data directory until it's closed
+pub async fn install(data_dir: PathBuf) -> Result<String, String> {
+    let definition = definition(&data_dir)?;
+    if let Some(parent) = definition.path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tokio::fs::write(&definition.path, &definition.contents)
+        .await
+        .map_err(|e| e.to_string())?;
+    let path = definition.path.to_string_lossy().into_owned();
+    match std::env::consts::OS {
+        // agents in LaunchAgents are loaded at login
+        "macos" => {}
+        "windows" => run("reg", &["import", &path]).await?,
+        _ => {
+            run("systemctl", &["--user", "daemon-reload"]).await?;
+            run(
+                "systemctl",
+                &["--user", "enable", &format!("{}.service", SERVICE_NAME)],
+            )
+            .await?;
+        }
+    }
+    Ok(format!(
+        "Installed {}, Akron runs in the background from the next login",
+        definition.path.display()
+    ))
+}
+
+/// Stops and removes the service
+pub async fn uninstall(data_dir: PathBuf) -> Result<String, String> {
+    let definition = definition(&data_dir)?;
+    let path = definition.path.to_string_lossy().into_owned();
+    match std::env::consts::OS {
+        // fail when the agent isn't loaded or the entry was removed already
+        "macos" => _ = run("launchctl", &["unload", &path]).await,
+        "windows" => {
+            _ = run(
+                "reg",
+                &["delete", WINDOWS_RUN_KEY, "/v", SERVICE_NAME, "/f"],
+            )
+            .await
+        }
+        _ => {
+            run(
+                "systemctl",
+                &[
+                    "--user",
+                    "disable",
+                    "--now",
+                    &format!("{}.service", SERVICE_NAME),
+                ],
+            )
+            .await?
+        }
+    }
+    if let Err(e) = tokio::fs::remove_file(&definition.path).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(e.to_string());
+        }
+    }
+    if !matches!(std::env::consts::OS, "macos" | "windows") {
+        _ = run("systemctl", &["--user", "daemon-reload"]).await;
+    }
+    Ok("Removed the background service".to_string())
+}
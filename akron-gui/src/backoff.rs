@@ -0,0 +1,67 @@
+use std::future::Future;
+use std::time::Duration;
+
+/// Retries with waits doubling from `first_delay` up to `max_delay`, each shortened by a
+/// random part so clients started together don't retry in step
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    /// Tries in total, the first included
+    pub attempts: u32,
+    pub first_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Backoff {
+    /// Read-only calls to spaced, riding out a busy or restarting backend without
+    /// making the UI wait long
+    pub const RPC: Backoff = Backoff {
+        attempts: 3,
+        first_delay: Duration::from_millis(200),
+        max_delay: Duration::from_secs(1),
+    };
+
+    /// Waiting for a service started by Akron to answer, about two minutes
+    pub const START: Backoff = Backoff {
+        attempts: 20,
+        first_delay: Duration::from_millis(250),
+        max_delay: Duration::from_secs(8),
+    };
+
+    /// Polling a backend that doesn't answer, until it does
+    pub const POLL: Backoff = Backoff {
+        attempts: u32::MAX,
+        first_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(60),
+    };
+
+    /// Wait after the given number of failed tries
+    pub fn delay(&self, failures: u32) -> Duration {
+        let delay = self
+            .first_delay
+            .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+            .min(self.max_delay);
+        delay.mul_f64(1.0 - rand::random::<f64>() / 4.0)
+    }
+
+    /// Runs `call` until it succeeds, fails with an error that isn't `transient` or the
+    /// attempts run out, answering its last result
+    pub async fn retry<T, E, F>(
+        &self,
+        mut call: impl FnMut() -> F,
+        transient: impl Fn(&E) -> bool,
+    ) -> Result<T, E>
+    where
+        F: Future<Output = Result<T, E>>,
+    {
+        let mut failures = 0;
+        loop {
+            match call().await {
+                Err(e) if transient(&e) && failures + 1 < self.attempts => {
+                    failures += 1;
+                    tokio::time::sleep(self.delay(failures)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
@@ -1,5 +1,13 @@
+use iced::futures::future::{BoxFuture, FutureExt, Shared};
 use iced::{Subscription, Task};
-use jsonrpsee::{core::ClientError, http_client::HttpClient};
+use jsonrpsee::{
+    core::{
+        client::ClientT,
+        params::{ArrayParams, BatchRequestBuilder},
+        ClientError,
+    },
+    http_client::{HeaderMap, HeaderValue, HttpClient, HttpClientBuilder},
+};
 use tokio_stream::{wrappers::BroadcastStream, StreamExt};
 
 use spaces_client::{
@@ -13,13 +21,16 @@ use spaces_client::{
 use spaces_protocol::constants::ChainAnchor;
 
 pub use spaces_client::{
-    auth::{auth_token_from_creds, http_client_with_auth},
+    auth::auth_token_from_creds,
     rpc::ServerInfo,
-    wallets::{AddressKind, ListSpacesResponse, TxInfo, WalletInfoWithProgress, WalletResponse},
+    wallets::{
+        AddressKind, DescriptorInfo, ListSpacesResponse, TxInfo, WalletInfoWithProgress,
+        WalletResponse,
+    },
 };
 pub use spaces_protocol::{bitcoin::Txid, slabel::SLabel, Covenant, FullSpaceOut};
 pub use spaces_wallet::{
-    bitcoin::{Amount, FeeRate, OutPoint},
+    bitcoin::{bip32::DerivationPath, Amount, FeeRate, OutPoint},
     export::WalletExport,
     nostr::NostrEvent,
     tx_event::{
@@ -29,33 +40,915 @@ pub use spaces_wallet::{
     Balance, Listing,
 };
 
-use akrond::{runner::ServiceKind, Akron};
+pub use akrond::runner::ServiceKind;
+use akrond::Akron;
+pub use akrond::{CheckpointProgress, LogLevel, LogLine};
+use serde_json::json;
+use std::sync::Arc;
 
-use crate::ConfigBackend;
+use crate::{backoff::Backoff, proxy, tls, ConfigBackend, ProxyConfig, RpcTimeouts};
 
 #[derive(Debug, Clone)]
 pub struct Client {
     id: usize,
-    client: HttpClient,
+    endpoints: Arc<Endpoints>,
     shutdown: Option<tokio::sync::broadcast::Sender<()>>,
-    logs: Option<tokio::sync::broadcast::Sender<String>>,
+    logs: Option<tokio::sync::broadcast::Sender<LogLine>>,
+    /// Directory spaced keeps wallets in, only known when it's run by Akron
+    wallets_dir: Option<std::path::PathBuf>,
+    spaces_service: Option<SpacesService>,
+    network: ExtendedNetwork,
+    /// Set when other mirrors disagree with the loaded checkpoint
+    checkpoint_warning: Option<String>,
+    /// Set when the backend is of a version the wallet may not work with
+    version_warning: Option<String>,
+    /// Bitcoin RPC spaced syncs from, only known when spaced is run by Akron
+    chain_rpc: Option<ChainRpc>,
+}
+
+/// Spaced endpoints with their labels, requests go to the current one and the others
+/// are tried when it stops answering
+#[derive(Debug)]
+struct Endpoints {
+    clients: Vec<(String, HttpClient)>,
+    current: std::sync::Mutex<usize>,
+    /// Where switching endpoints is reported
+    logs: Option<tokio::sync::broadcast::Sender<LogLine>>,
+    /// Set once a batched request was refused, calls are made one by one afterwards
+    batch_unsupported: std::sync::atomic::AtomicBool,
+    spaces: std::sync::Mutex<SpaceCache>,
+    /// Wallet calls waiting for their answer, an identical call joins them instead of
+    /// being sent again
+    in_flight: std::sync::Mutex<rustc_hash::FxHashMap<(String, WalletCall), SharedUpdate>>,
+}
+
+type SharedUpdate = Shared<BoxFuture<'static, WalletUpdate>>;
+
+/// Spaces are looked up again after this long even when no new block was seen, e.g. while
+/// the server info isn't polled
+const SPACE_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// Answered space lookups, which only change with a new block
+#[derive(Debug, Default)]
+struct SpaceCache {
+    /// Height the entries were looked up at, as far as it's known
+    tip: Option<u32>,
+    entries: rustc_hash::FxHashMap<SLabel, (std::time::Instant, Option<FullSpaceOut>)>,
+}
+
+impl SpaceCache {
+    fn get(&self, slabel: &SLabel) -> Option<Option<FullSpaceOut>> {
+        self.entries
+            .get(slabel)
+            .filter(|(at, _)| at.elapsed() < SPACE_CACHE_TTL)
+            .map(|(_, space)| space.clone())
+    }
+
+    /// Keeps the space unless a block arrived since `tip` was read, the lookup may have
+    /// been answered before it
+    fn insert(&mut self, tip: Option<u32>, slabel: SLabel, space: Option<FullSpaceOut>) {
+        if self.tip == tip {
+            self.entries
+                .insert(slabel, (std::time::Instant::now(), space));
+        }
+    }
+
+    fn set_tip(&mut self, height: u32) {
+        if self.tip != Some(height) {
+            self.tip = Some(height);
+            self.entries.clear();
+        }
+    }
+}
+
+impl Endpoints {
+    fn current(&self) -> HttpClient {
+        self.clients[*self.current.lock().unwrap()].1.clone()
+    }
+
+    fn current_index(&self) -> usize {
+        *self.current.lock().unwrap()
+    }
+
+    /// Health check that fails over to the next endpoint answering
+    async fn get_server_info(&self) -> Result<ServerInfo, ClientError> {
+        let current = self.current_index();
+        let client = &self.clients[current].1;
+        match Backoff::RPC
+            .retry(|| client.get_server_info(), is_transient)
+            .await
+        {
+            Ok(info) => {
+                self.spaces.lock().unwrap().set_tip(info.tip.height);
+                Ok(info)
+            }
+            Err(e) => match self.fail_over(current, &e).await {
+                Some(info) => Ok(info),
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Switches from the endpoint at `from` to the next one answering. Spaces looked up
+    /// on the old endpoint are forgotten, the new one may be at another height
+    async fn fail_over(&self, from: usize, error: &ClientError) -> Option<ServerInfo> {
+        for index in (1..self.clients.len()).map(|i| (from + i) % self.clients.len()) {
+            if let Ok(info) = self.clients[index].1.get_server_info().await {
+                {
+                    let mut current = self.current.lock().unwrap();
+                    // another request switched already
+                    if *current != from {
+                        return Some(info);
+                    }
+                    *current = index;
+                }
+                if let Some(logs) = self.logs.as_ref() {
+                    _ = logs.send(LogLine::new(format!(
+                        "Spaced at {} is unreachable ({}), switched to {}",
+                        self.clients[from].0, error, self.clients[index].0
+                    )));
+                }
+                let mut spaces = self.spaces.lock().unwrap();
+                *spaces = SpaceCache::default();
+                spaces.set_tip(info.tip.height);
+                return Some(info);
+            }
+        }
+        None
+    }
+
+    /// Makes the calls that aren't in flight yet, batched when there are several and the
+    /// endpoint allows it
+    async fn send_wallet_calls(&self, calls: Vec<(String, WalletCall)>) -> Vec<WalletUpdate> {
+        use std::sync::atomic::Ordering;
+        let current = self.current_index();
+        let mut client = self.current();
+        if calls.len() > 1 && !self.batch_unsupported.load(Ordering::Relaxed) {
+            match batch_wallet_calls(&client, &calls).await {
+                Ok(updates) => return updates,
+                // retried one by one below, on the next endpoint when there's one
+                Err(e) if is_transient(&e) => {
+                    if self.fail_over(current, &e).await.is_some() {
+                        client = self.current();
+                    }
+                }
+                // the login is wrong, the calls one by one would be refused just the same
+                Err(e) if is_unauthorized(&e) => {
+                    let err = map_error(e);
+                    return calls
+                        .into_iter()
+                        .map(|(wallet, call)| call.update(wallet, Err(err.clone())))
+                        .collect();
+                }
+                Err(_) => self.batch_unsupported.store(true, Ordering::Relaxed),
+            }
+        }
+        let client = &client;
+        iced::futures::future::join_all(
+            calls
+                .into_iter()
+                .map(|(wallet, call)| call.call(client, wallet)),
+        )
+        .await
+    }
+
+    /// Answers the calls in order, sharing the answer of identical calls still in flight
+    async fn wallet_calls(self: Arc<Self>, calls: Vec<(String, WalletCall)>) -> Vec<WalletUpdate> {
+        let shared: Vec<_> = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            let mut new = Vec::new();
+            for key in &calls {
+                if !in_flight.contains_key(key) && !new.contains(key) {
+                    new.push(key.clone());
+                }
+            }
+            if !new.is_empty() {
+                let endpoints = self.clone();
+                let sent = new.clone();
+                let batch = async move { endpoints.send_wallet_calls(sent).await }
+                    .boxed()
+                    .shared();
+                for (index, (wallet, call)) in new.into_iter().enumerate() {
+                    let batch = batch.clone();
+                    let label = wallet.clone();
+                    let update = async move {
+                        batch.await.get(index).cloned().unwrap_or_else(|| {
+                            call.update(label, Err("No answer to the call".to_string()))
+                        })
+                    };
+                    in_flight.insert((wallet, call), update.boxed().shared());
+                }
+            }
+            calls
+                .into_iter()
+                .map(|key| {
+                    let update = in_flight[&key].clone();
+                    (key, update)
+                })
+                .collect()
+        };
+        let updates =
+            iced::futures::future::join_all(shared.iter().map(|(_, update)| update.clone())).await;
+        // answered calls are sent again on the next request
+        let mut in_flight = self.in_flight.lock().unwrap();
+        for (key, update) in &shared {
+            if in_flight
+                .get(key)
+                .is_some_and(|current| current.ptr_eq(update))
+            {
+                in_flight.remove(key);
+            }
+        }
+        updates
+    }
+
+    /// Space looked up on the current endpoint, answered from the cache while no new block
+    /// arrived
+    async fn get_space(&self, slabel: &SLabel) -> Result<Option<FullSpaceOut>, ClientError> {
+        use spaces_client::store::Sha256;
+        use spaces_protocol::hasher::KeyHasher;
+        let tip = {
+            let spaces = self.spaces.lock().unwrap();
+            if let Some(space) = spaces.get(slabel) {
+                return Ok(space);
+            }
+            spaces.tip
+        };
+        let current = self.current_index();
+        let client = self.current();
+        let hash = hex::encode(Sha256::hash(slabel.as_ref()));
+        let space = match Backoff::RPC
+            .retry(|| client.get_space(&hash), is_transient)
+            .await
+        {
+            Err(e) if is_transient(&e) && self.fail_over(current, &e).await.is_some() => {
+                // the cache was emptied for the new endpoint
+                let client = self.current();
+                return client.get_space(&hash).await;
+            }
+            result => result?,
+        };
+        self.spaces
+            .lock()
+            .unwrap()
+            .insert(tip, slabel.clone(), space.clone());
+        Ok(space)
+    }
+}
+
+/// Bitcoin RPC of yuki or a bitcoind
+#[derive(Debug, Clone)]
+struct ChainRpc {
+    url: String,
+    user: String,
+    password: String,
+    cookie: Option<String>,
+    /// Bitcoin Core, which reports and manages its peers. yuki only answers the calls
+    /// spaced makes and has no way to manage its peers
+    bitcoind: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Peer {
+    pub id: u64,
+    pub address: String,
+    pub agent: String,
+    pub latency: Option<std::time::Duration>,
+}
+
+#[derive(Debug, Clone)]
+pub enum PeerAction {
+    Add(String),
+    Disconnect(u64),
+    /// Bans the host of the peer address
+    Ban(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainStatus {
+    pub blocks: u64,
+    pub headers: u64,
+    pub peers: Option<Vec<Peer>>,
+    pub mempool_txs: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeStatus {
+    /// Services run by Akron, empty with a remote spaced
+    pub services: Vec<akrond::ServiceStatus>,
+    pub spaced: ClientResult<ServerInfo>,
+    pub chain: Option<ClientResult<ChainStatus>>,
+}
+
+async fn read_cookie(cookie: &str) -> Result<(String, String), String> {
+    let contents = tokio::fs::read_to_string(cookie)
+        .await
+        .map_err(|e| format!("Could not read the cookie file: {}", e))?;
+    let (user, password) = contents
+        .trim()
+        .split_once(':')
+        .ok_or_else(|| "Invalid cookie file".to_string())?;
+    Ok((user.to_string(), password.to_string()))
+}
+
+impl ChainRpc {
+    async fn call(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let (user, password) = match self.cookie.as_ref() {
+            Some(cookie) => read_cookie(cookie).await?,
+            None => (self.user.clone(), self.password.clone()),
+        };
+        let mut request = reqwest::Client::new()
+            .post(&self.url)
+            .timeout(std::time::Duration::from_secs(10))
+            .body(
+                json!({
+                    "jsonrpc": "1.0",
+                    "id": "akron",
+                    "method": method,
+                    "params": params,
+                })
+                .to_string(),
+            );
+        if !user.is_empty() {
+            request = request.basic_auth(user, Some(password));
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        let mut body: serde_json::Value = serde_json::from_str(&body)
+            .map_err(|_| "Not a bitcoind JSON-RPC server".to_string())?;
+        match body["error"].take() {
+            serde_json::Value::Null => Ok(body["result"].take()),
+            error => Err(error["message"]
+                .as_str()
+                .map_or_else(|| error.to_string(), str::to_string)),
+        }
+    }
+
+    async fn status(&self) -> ClientResult<ChainStatus> {
+        let info = self.call("getblockchaininfo", json!([])).await?;
+        // yuki only answers what spaced needs, so these may be missing
+        let peers = match self.bitcoind {
+            true => self.call("getpeerinfo", json!([])).await.ok(),
+            false => None,
+        };
+        let mempool = self.call("getmempoolinfo", json!([])).await.ok();
+        Ok(ChainStatus {
+            blocks: info["blocks"].as_u64().unwrap_or_default(),
+            headers: info["headers"].as_u64().unwrap_or_default(),
+            peers: peers
+                .as_ref()
+                .and_then(|peers| peers.as_array())
+                .map(|peers| {
+                    peers
+                        .iter()
+                        .map(|peer| Peer {
+                            id: peer["id"].as_u64().unwrap_or_default(),
+                            address: peer["addr"].as_str().unwrap_or_default().to_string(),
+                            agent: peer["subver"].as_str().unwrap_or_default().to_string(),
+                            latency: peer["pingtime"]
+                                .as_f64()
+                                .map(std::time::Duration::from_secs_f64),
+                        })
+                        .collect()
+                }),
+            mempool_txs: mempool.and_then(|info| info["size"].as_u64()),
+        })
+    }
+
+    async fn peer_action(&self, action: PeerAction) -> ClientResult<()> {
+        if !self.bitcoind {
+            return Err("Peers can only be managed with Bitcoin Core".to_string());
+        }
+        match action {
+            PeerAction::Add(address) => self.call("addnode", json!([address, "add"])).await,
+            PeerAction::Disconnect(id) => self.call("disconnectnode", json!(["", id])).await,
+            PeerAction::Ban(address) => {
+                let host = address
+                    .rsplit_once(':')
+                    .map_or(address.as_str(), |(host, _)| host)
+                    .trim_matches(['[', ']']);
+                self.call("setban", json!([host, "add"])).await
+            }
+        }
+        .map(|_| ())
+    }
+}
+
+/// Oldest Bitcoin Core spaced works with
+const MIN_BITCOIND_VERSION: u64 = 240000;
+/// First Bitcoin Core with testnet4
+const MIN_TESTNET4_BITCOIND_VERSION: u64 = 280000;
+/// spaced releases the wallet works with, from the one it's built against up to the next
+/// minor release, which may change the RPC
+const SPACED_VERSIONS: std::ops::Range<(u64, u64, u64)> = (0, 0, 7)..(0, 1, 0);
+
+/// Version like `0.0.7` or `v0.1.0-dev`
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut numbers = version
+        .trim_start_matches('v')
+        .split(['.', '-'])
+        .map(|n| n.parse::<u64>());
+    Some((
+        numbers.next()?.ok()?,
+        numbers.next()?.ok()?,
+        numbers.next()?.ok()?,
+    ))
+}
+
+/// Refuses a Bitcoin Core too old for spaced, which otherwise fails with RPC errors
+async fn check_bitcoind_version(rpc: &ChainRpc, network: &ExtendedNetwork) -> Result<(), String> {
+    // an unreachable node is reported by spaced once it starts
+    let Ok(info) = rpc.call("getnetworkinfo", json!([])).await else {
+        return Ok(());
+    };
+    let Some(version) = info["version"].as_u64() else {
+        return Ok(());
+    };
+    let min = match network {
+        ExtendedNetwork::Testnet4 => MIN_TESTNET4_BITCOIND_VERSION,
+        _ => MIN_BITCOIND_VERSION,
+    };
+    if version < min {
+        return Err(format!(
+            "Bitcoin Core {}.{} is too old, Akron needs {}.{} or later on {}. Update the node \
+             and connect again",
+            version / 10000,
+            version / 100 % 100,
+            min / 10000,
+            min / 100 % 100,
+            network
+        ));
+    }
+    Ok(())
+}
+
+/// Refuses a spaced older than the wallet's RPC calls need and warns about a newer one,
+/// versions that aren't reported are let through
+async fn check_spaced_version(rpc: &HttpClient) -> Result<Option<String>, String> {
+    use jsonrpsee::core::client::ClientT;
+    let Ok(info) = rpc
+        .request::<serde_json::Value, _>("getserverinfo", Vec::<serde_json::Value>::new())
+        .await
+    else {
+        return Ok(None);
+    };
+    let Some(reported) = info["version"].as_str() else {
+        return Ok(None);
+    };
+    let Some(version) = parse_version(reported) else {
+        return Ok(None);
+    };
+    let (major, minor, patch) = SPACED_VERSIONS.start;
+    if version < SPACED_VERSIONS.start {
+        return Err(format!(
+            "spaced {} is too old, Akron needs {}.{}.{} or later. Update spaced and connect again",
+            reported, major, minor, patch
+        ));
+    }
+    if version >= SPACED_VERSIONS.end {
+        return Ok(Some(format!(
+            "spaced {} is newer than the versions this Akron works with, update Akron if \
+             wallet actions fail",
+            reported
+        )));
+    }
+    Ok(None)
+}
+
+/// Client of the spaced at `url` logging in with `auth`, calls taking longer than
+/// `request_timeout` fail
+fn rpc_client(
+    url: &str,
+    auth: &str,
+    request_timeout: std::time::Duration,
+) -> Result<HttpClient, ClientError> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "Authorization",
+        HeaderValue::from_str(&format!("Basic {}", auth))
+            .map_err(|e| ClientError::Custom(e.to_string()))?,
+    );
+    HttpClientBuilder::default()
+        .set_headers(headers)
+        .request_timeout(request_timeout)
+        .build(url)
+}
+
+/// Whether `url` points at this machine
+fn is_local_url(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    match url.host_str() {
+        Some("localhost") => true,
+        Some(host) => host
+            .trim_matches(['[', ']'])
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback()),
+        None => false,
+    }
+}
+
+/// Opens a connection to the server of `url` and closes it again, jsonrpsee has no
+/// timeout of its own for connecting
+async fn probe_connect(url: &str, timeout: std::time::Duration) -> Result<(), String> {
+    let target = reqwest::Url::parse(url).map_err(|e| e.to_string())?;
+    let host = target.host_str().unwrap_or_default().to_string();
+    let port = target.port_or_known_default().unwrap_or(80);
+    match tokio::time::timeout(timeout, tokio::net::TcpStream::connect((host, port))).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(format!("Could not connect to {}: {}", url, e)),
+        Err(_) => Err(format!(
+            "Could not connect to {} within {} seconds",
+            url,
+            timeout.as_secs()
+        )),
+    }
+}
+
+/// Fails when another program listens on the port a service is about to be started on
+fn check_port(port: u16, service: &str) -> Result<(), String> {
+    match std::net::TcpListener::bind(("127.0.0.1", port)) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!(
+            "Port {} for {} is already used by another program",
+            port, service
+        )),
+    }
+}
+
+/// Spaced run by Akron on `port`
+fn local_endpoint(port: u16) -> Vec<(String, String)> {
+    let url = format!("http://127.0.0.1:{}", port);
+    vec![(url.clone(), url)]
+}
+
+/// Local address tunneling to `url` through the proxy and over TLS for https, until
+/// `shutdown`. Plain https URLs without custom TLS settings are used as they are
+async fn tunneled_url(
+    url: &str,
+    proxy: Option<&ProxyConfig>,
+    ca_cert: Option<&str>,
+    cert_fingerprint: Option<&str>,
+    shutdown: &tokio::sync::broadcast::Sender<()>,
+) -> Result<String, String> {
+    let target = reqwest::Url::parse(url).map_err(|e| e.to_string())?;
+    let host = target.host_str().unwrap_or_default().to_string();
+    let port = target.port_or_known_default().unwrap_or(80);
+    let addr = match (target.scheme(), proxy) {
+        ("https", _) if proxy.is_some() || ca_cert.is_some() || cert_fingerprint.is_some() => {
+            tls::forward(
+                tls::client_config(ca_cert, cert_fingerprint)?,
+                proxy.map(ProxyConfig::addr),
+                host,
+                port,
+                shutdown.subscribe(),
+            )
+            .await
+        }
+        ("http", Some(proxy)) => {
+            proxy::forward(proxy.addr(), host, port, shutdown.subscribe()).await
+        }
+        ("http" | "https", _) => return Ok(url.to_string()),
+        (scheme, _) => return Err(format!("Unsupported URL scheme {}", scheme)),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(format!("http://{}{}", addr, target.path()))
+}
+
+/// The spaced service when it's run by Akron, kept around to restart it
+#[derive(Debug, Clone)]
+struct SpacesService {
+    akron: Arc<Akron>,
+    args: Vec<String>,
+}
+
+impl SpacesService {
+    async fn stop(&self) -> ClientResult<()> {
+        self.akron
+            .shutdown(ServiceKind::Spaces)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn stop_all(&self) {
+        _ = self.stop().await;
+        _ = self.akron.shutdown(ServiceKind::Yuki).await;
+        _ = self.akron.shutdown(ServiceKind::Bitcoind).await;
+        _ = self.akron.shutdown(ServiceKind::Mirror).await;
+    }
+
+    async fn start(&self, client: &HttpClient) -> ClientResult<()> {
+        self.akron
+            .start(ServiceKind::Spaces, self.args.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        Backoff::START
+            .retry(|| client.get_server_info(), |_| true)
+            .await
+            .map(|_| ())
+            .map_err(|_| "spaced didn't respond after restarting".to_string())
+    }
 }
 
 pub type ClientResult<T> = Result<T, String>;
 
-fn map_result<T>(result: Result<T, ClientError>) -> ClientResult<T> {
-    result.map_err(|e| match e {
+/// Error returned for requests spaced rejects with 401, the login has to be entered again
+pub const UNAUTHORIZED_ERROR: &str = "Spaced rejected the login";
+
+fn is_unauthorized(e: &ClientError) -> bool {
+    matches!(e, ClientError::Transport(e) if e.to_string().contains("401"))
+}
+
+/// Errors of requests that may go through when tried again, which only read calls are.
+/// Calls that change something aren't retried, they may have gone through already
+fn is_transient(e: &ClientError) -> bool {
+    matches!(
+        e,
+        ClientError::Transport(_) | ClientError::RequestTimeout | ClientError::RestartNeeded(_)
+    ) && !is_unauthorized(e)
+}
+
+fn map_error(e: ClientError) -> String {
+    match e {
+        e if is_unauthorized(&e) => UNAUTHORIZED_ERROR.to_string(),
         ClientError::Call(e) => e.message().to_string(),
         _ => e.to_string(),
+    }
+}
+
+fn map_result<T>(result: Result<T, ClientError>) -> ClientResult<T> {
+    result.map_err(map_error)
+}
+
+/// Ways to restore a wallet spaced can't recover on its own, it only derives BIP86
+/// account 0 without a passphrase and scans from spaces activation. Wallets using any
+/// of these are imported as a descriptor instead, see [`wallet_export_from_mnemonic`]
+#[derive(Debug, Clone, Default)]
+pub struct RestoreOptions {
+    pub passphrase: String,
+    /// Scanning starts here but never before spaces activation
+    pub birthday: Option<u32>,
+    /// Account level path, e.g. `m/86'/0'/1'`
+    pub account_path: Option<DerivationPath>,
+}
+
+impl RestoreOptions {
+    fn is_default(&self) -> bool {
+        self.passphrase.is_empty() && self.birthday.is_none() && self.account_path.is_none()
+    }
+}
+
+/// Builds the taproot descriptor spaced derives from a mnemonic with the changes
+/// requested in `options`. Account 0 comes from the BIP86 template spaced derives its
+/// wallets with, the template has no other accounts so those are written out the same
+/// way
+fn wallet_export_from_mnemonic(
+    network: ExtendedNetwork,
+    label: &str,
+    mnemonic: &str,
+    options: &RestoreOptions,
+) -> ClientResult<WalletExport> {
+    use spaces_wallet::{
+        bdk_wallet::{
+            keys::bip39::Mnemonic,
+            template::{Bip86, DescriptorTemplate},
+            KeychainKind,
+        },
+        bitcoin::{bip32::Xpriv, Network},
+    };
+
+    let mnemonic = Mnemonic::parse(mnemonic).map_err(|e| e.to_string())?;
+    let (bitcoin_network, coin_type, blockheight) = match network {
+        ExtendedNetwork::Mainnet => (Network::Bitcoin, 0, ChainAnchor::MAINNET().height),
+        ExtendedNetwork::Testnet4 => (Network::Testnet, 1, ChainAnchor::TESTNET4().height),
+        ExtendedNetwork::Testnet => (Network::Testnet, 1, 0),
+        ExtendedNetwork::Signet => (Network::Signet, 1, 0),
+        ExtendedNetwork::Regtest => (Network::Regtest, 1, 0),
+    };
+    let xpriv = Xpriv::new_master(bitcoin_network, &mnemonic.to_seed(&options.passphrase))
+        .map_err(|e| e.to_string())?;
+    let descriptor = match &options.account_path {
+        Some(path) if path.to_string() != format!("86'/{}'/0'", coin_type) => {
+            format!("tr({}/{}/0/*)", xpriv, path)
+        }
+        _ => {
+            let (descriptor, keys, _) = Bip86(xpriv, KeychainKind::External)
+                .build(bitcoin_network)
+                .map_err(|e| e.to_string())?;
+            descriptor.to_string_with_secret(&keys)
+        }
+    };
+    Ok(WalletExport {
+        descriptor,
+        blockheight: options.birthday.map_or(blockheight, |b| b.max(blockheight)),
+        label: label.to_string(),
     })
 }
 
+/// Addresses looked at to find the index of an address, past a gap this long spaced
+/// wouldn't hand out a new one anyway
+const MAX_ADDRESS_INDEX: u32 = 10_000;
+
+/// Index at which the public `descriptor` derives `address`. Spaces addresses are taproot
+/// addresses with their own prefix, so only the witness programs are compared
+pub fn address_index(descriptor: &str, address: &str) -> Option<u32> {
+    use spaces_wallet::{
+        bdk_wallet::miniscript::{Descriptor, DescriptorPublicKey},
+        bitcoin::{bech32, secp256k1::Secp256k1},
+    };
+
+    let (_, _, program) = bech32::segwit::decode(address).ok()?;
+    let (descriptor, _) =
+        Descriptor::<DescriptorPublicKey>::parse_descriptor(&Secp256k1::new(), descriptor).ok()?;
+    (0..MAX_ADDRESS_INDEX).find(|index| {
+        descriptor
+            .at_derivation_index(*index)
+            .is_ok_and(|derived| derived.script_pubkey().as_bytes().get(2..) == Some(&program[..]))
+    })
+}
+
+/// `label`, or `label` with a number appended when a wallet in `existing` has it
+pub fn unused_wallet_label(label: &str, existing: &[String]) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => label.to_string(),
+            n => format!("{}-{}", label, n),
+        })
+        .find(|label| !existing.contains(label))
+        .unwrap()
+}
+
+/// Watch-only versions of an exported descriptor, spaced only exports the receive
+/// descriptor so the change one is derived from it
+fn public_descriptors(descriptor: &str) -> ClientResult<Vec<String>> {
+    use spaces_wallet::{
+        bdk_wallet::miniscript::{Descriptor, DescriptorPublicKey},
+        bitcoin::secp256k1::Secp256k1,
+    };
+
+    let secp = Secp256k1::new();
+    let descriptor = descriptor.split('#').next().unwrap_or_default();
+    let mut descriptors = vec![descriptor.to_string()];
+    if let Some(prefix) = descriptor.strip_suffix("/0/*)") {
+        descriptors.push(format!("{}/1/*)", prefix));
+    }
+    descriptors
+        .iter()
+        .map(|descriptor| {
+            Descriptor::<DescriptorPublicKey>::parse_descriptor(&secp, descriptor)
+                .map(|(descriptor, _)| descriptor.to_string())
+                .map_err(|e| e.to_string())
+        })
+        .collect()
+}
+
+/// Where spaced run from the command line keeps the wallets of `network`, when there's
+/// such a directory on this computer
+pub fn spaced_cli_wallets_dir(network: ExtendedNetwork) -> Option<std::path::PathBuf> {
+    let dir = directories::ProjectDirs::from("", "", "spaces")?
+        .data_dir()
+        .join(network.to_string())
+        .join("wallets");
+    dir.is_dir().then_some(dir)
+}
+
+fn wallet_dir(wallets_dir: &std::path::Path, wallet: &str) -> ClientResult<std::path::PathBuf> {
+    if wallet.is_empty() || wallet.starts_with('.') || wallet.contains(['/', '\\']) {
+        return Err(format!("Invalid wallet name: {}", wallet));
+    }
+    Ok(wallets_dir.join(wallet))
+}
+
+async fn remove_wallet_dir(wallets_dir: &std::path::Path, wallet: &str) -> ClientResult<()> {
+    tokio::fs::remove_dir_all(wallet_dir(wallets_dir, wallet)?)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Where a wallet directory is kept during a rescan, next to the wallets directory so
+/// spaced doesn't list it
+fn rescan_dir(wallets_dir: &std::path::Path, wallet: &str) -> std::path::PathBuf {
+    wallets_dir.with_file_name(format!("rescan-{}", wallet))
+}
+
+/// Outcome of importing the wallets of spaced run from the command line
+#[derive(Debug, Clone, Default)]
+pub struct CliWalletsImport {
+    /// Labels of the wallets imported
+    pub imported: Vec<String>,
+    /// Wallets Akron already has with the same descriptor
+    pub existing: Vec<String>,
+    /// Old and new label of the wallets imported under a new label, theirs was taken
+    pub renamed: Vec<(String, String)>,
+    /// Wallet files that couldn't be imported, with the reason
+    pub skipped: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct WalletResult<T> {
     pub label: String,
     pub result: Result<T, String>,
 }
 
+/// Wallet call that can be batched with others, see [`Client::wallet_calls`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WalletCall {
+    Info,
+    Balance,
+    Spaces,
+    /// Latest transactions, as many as given
+    Transactions(usize),
+}
+
+/// Answer to a [`WalletCall`]
+#[derive(Debug, Clone)]
+pub enum WalletUpdate {
+    Info(WalletResult<WalletInfoWithProgress>),
+    Balance(WalletResult<Balance>),
+    Spaces(WalletResult<ListSpacesResponse>),
+    Transactions(WalletResult<Vec<TxInfo>>),
+}
+
+impl WalletCall {
+    fn method(&self) -> &'static str {
+        match self {
+            WalletCall::Info => "walletgetinfo",
+            WalletCall::Balance => "walletgetbalance",
+            WalletCall::Spaces => "walletlistspaces",
+            WalletCall::Transactions(_) => "walletlisttransactions",
+        }
+    }
+
+    fn params(&self, wallet: &str) -> Result<ArrayParams, serde_json::Error> {
+        let mut params = ArrayParams::new();
+        params.insert(wallet)?;
+        if let WalletCall::Transactions(count) = self {
+            params.insert(count)?;
+            params.insert(0)?;
+        }
+        Ok(params)
+    }
+
+    /// Answer from the JSON result of the call
+    fn update(&self, label: String, result: Result<serde_json::Value, String>) -> WalletUpdate {
+        fn parse<T: serde::de::DeserializeOwned>(
+            label: String,
+            result: Result<serde_json::Value, String>,
+        ) -> WalletResult<T> {
+            WalletResult {
+                label,
+                result: result
+                    .and_then(|value| serde_json::from_value(value).map_err(|e| e.to_string())),
+            }
+        }
+        match self {
+            WalletCall::Info => WalletUpdate::Info(parse(label, result)),
+            WalletCall::Balance => WalletUpdate::Balance(parse(label, result)),
+            WalletCall::Spaces => WalletUpdate::Spaces(parse(label, result)),
+            WalletCall::Transactions(_) => WalletUpdate::Transactions(parse(label, result)),
+        }
+    }
+
+    /// Makes the call on its own
+    async fn call(self, client: &HttpClient, wallet: String) -> WalletUpdate {
+        let result = Backoff::RPC
+            .retry(
+                || client.request(self.method(), self.params(&wallet).unwrap_or_default()),
+                is_transient,
+            )
+            .await
+            .map_err(map_error);
+        self.update(wallet, result)
+    }
+}
+
+/// Makes the calls in a single JSON-RPC batch, answers in the same order
+async fn batch_wallet_calls(
+    client: &HttpClient,
+    calls: &[(String, WalletCall)],
+) -> Result<Vec<WalletUpdate>, ClientError> {
+    let mut batch = BatchRequestBuilder::new();
+    for (wallet, call) in calls {
+        batch.insert(call.method(), call.params(wallet)?)?;
+    }
+    let responses = client.batch_request::<serde_json::Value>(batch).await?;
+    Ok(calls
+        .iter()
+        .zip(responses)
+        .map(|((wallet, call), response)| {
+            call.update(
+                wallet.clone(),
+                response.map_err(|e| e.message().to_string()),
+            )
+        })
+        .collect())
+}
+
 fn map_wallet_result<T>((label, result): (String, Result<T, ClientError>)) -> WalletResult<T> {
     WalletResult {
         label,
@@ -63,6 +956,25 @@ fn map_wallet_result<T>((label, result): (String, Result<T, ClientError>)) -> Wa
     }
 }
 
+/// Owned spaces are renewed when they expire within this many blocks, about a month
+pub const AUTO_RENEW_BLOCKS: u32 = 30 * 144;
+
+/// Renewing a space is transferring it to the same wallet
+pub(crate) fn renew_request(slabel: &SLabel, fee_rate: Option<FeeRate>) -> RpcWalletTxBuilder {
+    RpcWalletTxBuilder {
+        bidouts: None,
+        requests: vec![RpcWalletRequest::Transfer(TransferSpacesParams {
+            spaces: vec![slabel.to_string()],
+            to: None,
+        })],
+        fee_rate,
+        dust: None,
+        force: false,
+        confirmed_only: false,
+        skip_tx_check: false,
+    }
+}
+
 fn random_password() -> String {
     use rand::{
         distributions::Alphanumeric,
@@ -75,24 +987,450 @@ fn random_password() -> String {
         .collect::<String>()
 }
 
-impl Client {
+/// RPC port yuki serves spaced on by default
+const YUKI_RPC_PORT: u16 = 8225;
+
+/// RPC port of the bitcoind run by Akron, away from the default one so it doesn't clash
+/// with a node the user runs
+const MANAGED_BITCOIND_RPC_PORT: u16 = 8226;
+
+/// Ports of the services Akron runs for the backend
+pub fn service_ports(backend: &ConfigBackend) -> Vec<u16> {
+    match backend {
+        ConfigBackend::Akrond {
+            network,
+            spaced_rpc_port,
+            yuki_rpc_port,
+            ..
+        } => vec![
+            spaced_rpc_port.unwrap_or_else(|| default_spaces_rpc_port(network)),
+            yuki_rpc_port.unwrap_or_else(|| network_port(YUKI_RPC_PORT, network)),
+        ],
+        ConfigBackend::ManagedBitcoind { network, .. } => vec![
+            default_spaces_rpc_port(network),
+            network_port(MANAGED_BITCOIND_RPC_PORT, network),
+        ],
+        ConfigBackend::Bitcoind { network, .. } => vec![default_spaces_rpc_port(network)],
+        ConfigBackend::Spaced { .. } => Vec::new(),
+    }
+}
+
+/// Moves a service port per network, the way bitcoind does, so sessions on different
+/// networks can run side by side
+fn network_port(port: u16, network: &ExtendedNetwork) -> u16 {
+    match network {
+        ExtendedNetwork::Mainnet => port,
+        ExtendedNetwork::Testnet => port + 10_000,
+        ExtendedNetwork::Regtest => port + 20_000,
+        ExtendedNetwork::Signet => port + 30_000,
+        ExtendedNetwork::Testnet4 => port + 40_000,
+    }
+}
+
+/// Compact block filters yuki syncs from on mainnet
+pub const FILTERS_ENDPOINT: &str = "https://checkpoint.akron.io/";
+
+/// Blocks kept by the bitcoind run by Akron, in MiB. Spaced reads the blocks while bitcoind
+/// syncs, so this leaves it room to fall behind before they are pruned
+const MANAGED_BITCOIND_PRUNE_MIB: u32 = 10_000;
+
+fn bitcoind_chain(network: &ExtendedNetwork) -> &'static str {
+    match network {
+        ExtendedNetwork::Mainnet => "main",
+        ExtendedNetwork::Testnet => "test",
+        ExtendedNetwork::Testnet4 => "testnet4",
+        ExtendedNetwork::Signet => "signet",
+        ExtendedNetwork::Regtest => "regtest",
+    }
+}
+
+/// How long spaced has to stay at the same tip before the databases are compacted
+const COMPACTION_IDLE_CHECK: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub enum CompactionProgress {
+    Compacting {
+        wallet: String,
+        done: usize,
+        total: usize,
+    },
+    Done(Result<String, String>),
+}
+
+/// Parts of the data directory shown in the storage panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageKind {
+    Bitcoind,
+    Yuki,
+    Spaces,
+    Wallets,
+    Logs,
+    /// Served filters and unfinished checkpoint downloads
+    Caches,
+}
+
+impl std::fmt::Display for StorageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Bitcoind => "Bitcoin node",
+            Self::Yuki => "Compact filters (yuki)",
+            Self::Spaces => "Spaces database",
+            Self::Wallets => "Wallets",
+            Self::Logs => "Logs",
+            Self::Caches => "Caches",
+        })
+    }
+}
+
+fn is_cache_file(path: &std::path::Path) -> bool {
+    path.to_str()
+        .is_some_and(|path| path.ends_with(".part") || path.ends_with(".part.info"))
+}
+
+/// Adds the sizes of the files in `dir` to `usage`, logs, caches and wallets found in it
+/// are counted apart
+fn add_dir_usage(dir: &std::path::Path, kind: StorageKind, usage: &mut [(StorageKind, u64)]) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let path = entry.path();
+        if metadata.is_dir() {
+            let kind = if kind == StorageKind::Spaces && entry.file_name() == "wallets" {
+                StorageKind::Wallets
+            } else {
+                kind
+            };
+            add_dir_usage(&path, kind, usage);
+            continue;
+        }
+        let kind = if path.extension().is_some_and(|ext| ext == "log") {
+            StorageKind::Logs
+        } else if is_cache_file(&path) {
+            StorageKind::Caches
+        } else {
+            kind
+        };
+        if let Some((_, bytes)) = usage.iter_mut().find(|(k, _)| *k == kind) {
+            *bytes += metadata.len();
+        }
+    }
+}
+
+/// Directories of the services run by Akron with the kind of data they hold
+fn storage_dirs(
+    data_dir: &std::path::Path,
+    backend_config: &ConfigBackend,
+) -> Vec<(StorageKind, std::path::PathBuf)> {
+    let network = backend_config.network().to_string();
+    let data_dir = backend_config.services_dir(data_dir);
+    let spaces = (StorageKind::Spaces, data_dir.join("spaces").join(&network));
+    match backend_config {
+        ConfigBackend::Akrond { .. } => vec![
+            (StorageKind::Yuki, data_dir.join("yuki").join(&network)),
+            spaces,
+            (StorageKind::Caches, data_dir.join("filters")),
+        ],
+        ConfigBackend::ManagedBitcoind { .. } => {
+            vec![(StorageKind::Bitcoind, data_dir.join("bitcoind")), spaces]
+        }
+        ConfigBackend::Bitcoind { .. } => vec![spaces],
+        ConfigBackend::Spaced { .. } => Vec::new(),
+    }
+}
+
+/// Removes files in `dir` matching `filter`, log files are truncated instead as the
+/// services keep them open
+fn clear_files(
+    dir: &std::path::Path,
+    filter: &impl Fn(&std::path::Path) -> bool,
+) -> std::io::Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            clear_files(&path, filter)?;
+        } else if !filter(&path) {
+            continue;
+        } else if path.extension().is_some_and(|ext| ext == "log") {
+            std::fs::File::create(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes everything in `dir` except the wallets
+async fn remove_dir_contents(dir: &std::path::Path) -> std::io::Result<()> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_name() == "wallets" {
+            continue;
+        }
+        if entry.file_type().await?.is_dir() {
+            tokio::fs::remove_dir_all(entry.path()).await?;
+        } else {
+            tokio::fs::remove_file(entry.path()).await?;
+        }
+    }
+    Ok(())
+}
+
+impl Client {
+    /// Time since the checkpoint of an Akrond backend was loaded
+    pub async fn checkpoint_age(
+        data_dir: std::path::PathBuf,
+        backend_config: ConfigBackend,
+    ) -> Option<std::time::Duration> {
+        let checkpoint = backend_config
+            .services_dir(&data_dir)
+            .join("spaces")
+            .join(backend_config.network().to_string())
+            .join("akron.checkpoint.json");
+        tokio::fs::metadata(checkpoint)
+            .await
+            .ok()?
+            .modified()
+            .ok()?
+            .elapsed()
+            .ok()
+    }
+
+    /// Disk space used by the services run by Akron, computed off the UI thread
+    pub async fn storage_usage(
+        data_dir: std::path::PathBuf,
+        backend_config: ConfigBackend,
+    ) -> Vec<(StorageKind, u64)> {
+        tokio::task::spawn_blocking(move || {
+            let dirs = storage_dirs(&data_dir, &backend_config);
+            let mut usage: Vec<_> = dirs.iter().map(|(kind, _)| (*kind, 0)).collect();
+            if !dirs.is_empty() {
+                usage.retain(|(kind, _)| *kind != StorageKind::Caches);
+                usage.extend([
+                    (StorageKind::Wallets, 0),
+                    (StorageKind::Logs, 0),
+                    (StorageKind::Caches, 0),
+                ]);
+            }
+            for (kind, dir) in &dirs {
+                add_dir_usage(dir, *kind, &mut usage);
+            }
+            usage
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    /// Clears the logs or caches of the services run by Akron
+    pub async fn clear_storage(
+        data_dir: std::path::PathBuf,
+        backend_config: ConfigBackend,
+        kind: StorageKind,
+    ) -> Result<(), String> {
+        tokio::task::spawn_blocking(move || {
+            for (dir_kind, dir) in storage_dirs(&data_dir, &backend_config) {
+                let result = match kind {
+                    StorageKind::Logs => clear_files(&dir, &|path| {
+                        path.extension().is_some_and(|ext| ext == "log")
+                    }),
+                    StorageKind::Caches if dir_kind == StorageKind::Caches => {
+                        clear_files(&dir, &|_| true)
+                    }
+                    StorageKind::Caches => clear_files(&dir, &is_cache_file),
+                    _ => return Err(format!("{} can't be cleared", kind)),
+                };
+                result.map_err(|e| format!("Could not clear {}: {}", dir.display(), e))?;
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    /// Removes the chain data of the services run by Akron so they are rebuilt on the next
+    /// start, wallets are kept. The services must not be running
+    pub async fn remove_chain_data(
+        data_dir: &std::path::Path,
+        backend_config: &ConfigBackend,
+    ) -> Result<(), String> {
+        let network = backend_config.network().to_string();
+        let data_dir = backend_config.services_dir(data_dir);
+        let dirs = match backend_config {
+            ConfigBackend::Akrond { .. } => vec![
+                data_dir.join("spaces").join(&network),
+                data_dir.join("yuki").join(&network),
+            ],
+            ConfigBackend::ManagedBitcoind { .. } | ConfigBackend::Bitcoind { .. } => {
+                vec![data_dir.join("spaces").join(&network)]
+            }
+            ConfigBackend::Spaced { .. } => Vec::new(),
+        };
+        for dir in dirs {
+            remove_dir_contents(&dir)
+                .await
+                .map_err(|e| format!("Could not remove {}: {}", dir.display(), e))?;
+        }
+        Ok(())
+    }
+
+    /// Checks that a remote backend answers with the given login and is on the expected
+    /// network, without connecting to it
+    pub async fn test_backend(backend_config: ConfigBackend) -> Result<String, String> {
+        match backend_config {
+            ConfigBackend::Akrond { .. } | ConfigBackend::ManagedBitcoind { .. } => {
+                Ok("Run by Akron, nothing to test".to_string())
+            }
+            ConfigBackend::Bitcoind {
+                network,
+                url,
+                user,
+                password,
+                cookie,
+                ..
+            } => {
+                let (user, password) = match cookie {
+                    Some(cookie) => read_cookie(&cookie).await?,
+                    None => (user, password),
+                };
+                let local = is_local_url(&url);
+                let timeouts = RpcTimeouts::default();
+                let mut request = reqwest::Client::builder()
+                    .connect_timeout(timeouts.connect(local))
+                    .timeout(timeouts.request(local))
+                    .build()
+                    .map_err(|e| e.to_string())?
+                    .post(&url)
+                    .body(
+                    r#"{"jsonrpc":"1.0","id":"akron","method":"getblockchaininfo","params":[]}"#,
+                );
+                if !user.is_empty() {
+                    request = request.basic_auth(user, Some(password));
+                }
+                let response = request
+                    .send()
+                    .await
+                    .map_err(|e| format!("Not reachable: {}", e))?;
+                if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+                    return Err("Authentication failed, check the login".to_string());
+                }
+                let body = response.text().await.map_err(|e| e.to_string())?;
+                let info: serde_json::Value = serde_json::from_str(&body)
+                    .map_err(|_| "Not a bitcoind JSON-RPC server".to_string())?;
+                let chain = info["result"]["chain"].as_str().unwrap_or_default();
+                if chain != bitcoind_chain(&network) {
+                    return Err(format!("Wrong network, the node is on {}", chain));
+                }
+                Ok(format!(
+                    "Bitcoin node is reachable, synced to block {}",
+                    info["result"]["blocks"]
+                ))
+            }
+            ConfigBackend::Spaced {
+                network,
+                url,
+                user,
+                password,
+                ca_cert,
+                cert_fingerprint,
+                timeouts,
+                ..
+            } => {
+                let local = is_local_url(&url);
+                probe_connect(&url, timeouts.connect(local)).await?;
+                // tunnels close with `shutdown` when the test is done
+                let shutdown = tokio::sync::broadcast::Sender::new(1);
+                let url = tunneled_url(
+                    &url,
+                    None,
+                    ca_cert.as_deref(),
+                    cert_fingerprint.as_deref(),
+                    &shutdown,
+                )
+                .await?;
+                let client = rpc_client(
+                    &url,
+                    &auth_token_from_creds(&user, &password),
+                    timeouts.request(local),
+                )
+                .map_err(|e| e.to_string())?;
+                let info = client.get_server_info().await.map_err(|e| {
+                    if is_unauthorized(&e) {
+                        "Authentication failed, check the login".to_string()
+                    } else {
+                        format!("Not reachable: {}", map_error(e))
+                    }
+                })?;
+                if info.network != network.to_string() {
+                    return Err(format!("Wrong network, spaced is on {}", info.network));
+                }
+                Ok(format!(
+                    "Spaced is reachable, synced to block {}",
+                    info.tip.height
+                ))
+            }
+        }
+    }
+
     pub async fn create(
         data_dir: std::path::PathBuf,
         mut backend_config: ConfigBackend,
+        proxy: Option<ProxyConfig>,
+        checkpoint_progress: Option<tokio::sync::broadcast::Sender<CheckpointProgress>>,
+        capture_logs: bool,
     ) -> Result<(Self, ConfigBackend), String> {
         let mut logs = None;
-        // TODO: move this as a command line flag --no-capture-logs (uses stdout instead)
-        const CAPTURE_LOGS: bool = true;
-        let (spaces_rpc_url, spaces_user, spaces_password, shutdown) = match &mut backend_config {
+        let mut wallets_dir = None;
+        let mut checkpoint_warning = None;
+        let mut spaces_service = None;
+        let mut chain_rpc = None;
+        let services_dir = backend_config.services_dir(&data_dir);
+        // remote nodes reached through the proxy are never considered local
+        let (timeouts, local) = match &backend_config {
+            ConfigBackend::Spaced { url, timeouts, .. } => (
+                *timeouts,
+                is_local_url(url) && !proxy.as_ref().is_some_and(|p| p.spaced),
+            ),
+            _ => (RpcTimeouts::default(), true),
+        };
+        let (spaces_rpc_urls, spaces_user, spaces_password, shutdown) = match &mut backend_config {
             ConfigBackend::Akrond {
                 network,
                 prune_point,
                 spaced_password,
+                checkpoint_mirrors,
+                filters_endpoints,
+                serve_peers,
+                spaced_rpc_port,
+                yuki_rpc_port,
+                ..
             } => {
-                let (akron, shutdown) = Akron::create(CAPTURE_LOGS);
+                let spaced_rpc_port =
+                    spaced_rpc_port.unwrap_or_else(|| default_spaces_rpc_port(network));
+                let yuki_rpc_port =
+                    yuki_rpc_port.unwrap_or_else(|| network_port(YUKI_RPC_PORT, network));
+                check_port(yuki_rpc_port, "yuki")?;
+                check_port(spaced_rpc_port, "spaced")?;
+                let (akron, shutdown) = Akron::create(capture_logs);
                 logs = akron.subscribe_logs();
-                let yuki_data_dir = data_dir.join("yuki");
-                let spaces_data_dir = data_dir.join("spaces");
+                let yuki_data_dir = services_dir.join("yuki");
+                let spaces_data_dir = services_dir.join("spaces");
+                wallets_dir = Some(spaces_data_dir.join(network.to_string()).join("wallets"));
+                let mut filters_endpoints = filters_endpoints.clone();
+                if filters_endpoints.is_empty() {
+                    filters_endpoints.push(FILTERS_ENDPOINT.to_string());
+                }
                 let mut yuki_args: Vec<String> = [
                     "--chain",
                     &network.to_string(),
@@ -106,11 +1444,12 @@ impl Client {
                     *spaced_password = Some(random_password());
                 };
                 let password = spaced_password.as_ref().unwrap().to_string();
+                let yuki_rpc_url = format!("http://127.0.0.1:{}", yuki_rpc_port);
                 let spaces_args: Vec<String> = [
                     "--chain",
                     &network.to_string(),
                     "--bitcoin-rpc-url",
-                    "http://127.0.0.1:8225",
+                    &yuki_rpc_url,
                     "--rpc-user",
                     "akron",
                     "--rpc-password",
@@ -118,28 +1457,63 @@ impl Client {
                     "--data-dir",
                     spaces_data_dir.to_str().unwrap(),
                     "--bitcoin-rpc-light",
+                    "--rpc-port",
+                    &spaced_rpc_port.to_string(),
                 ]
                 .iter()
                 .map(|s| s.to_string())
                 .collect();
-                if prune_point.is_none() {
+                yuki_args.push("--rpc-port".to_string());
+                yuki_args.push(yuki_rpc_port.to_string());
+                if prune_point.is_none() && *network == ExtendedNetwork::Testnet4 {
+                    *prune_point = Some(ChainAnchor::TESTNET4());
+                }
+                // the checkpoint is downloaded while the filters endpoints are probed
+                let load_checkpoint = async {
+                    if prune_point.is_some() || *network != ExtendedNetwork::Mainnet {
+                        return Ok(None);
+                    }
+                    let mirrors: Vec<String> = akrond::CHECKPOINT_MIRRORS
+                        .iter()
+                        .map(|url| url.to_string())
+                        .collect();
+                    akron
+                        .load_checkpoint(
+                            &mirrors,
+                            checkpoint_mirrors,
+                            &spaces_data_dir.join(network.to_string()),
+                            checkpoint_progress,
+                            proxy.as_ref().filter(|p| p.chain).map(ProxyConfig::url),
+                        )
+                        .await
+                        .map(Some)
+                        .map_err(|e| e.to_string())
+                };
+                let chain_proxy = proxy.as_ref().filter(|p| p.chain);
+                let fastest_endpoint = async {
                     match network {
                         ExtendedNetwork::Mainnet => {
-                            let checkpoint = akron
-                                .load_checkpoint(
-                                    "https://checkpoint.akron.io/protocol.sdb",
-                                    &spaces_data_dir.join(network.to_string()),
-                                    None,
-                                )
-                                .await
-                                .map_err(|e| e.to_string())?;
-
-                            *prune_point = Some(checkpoint.block);
+                            akrond::fastest_endpoint(
+                                &filters_endpoints,
+                                chain_proxy.map(ProxyConfig::url),
+                            )
+                            .await
                         }
-                        ExtendedNetwork::Testnet4 => *prune_point = Some(ChainAnchor::TESTNET4()),
-                        _ => {}
+                        _ => None,
                     }
+                };
+                let (checkpoint, fastest_endpoint) =
+                    tokio::join!(load_checkpoint, fastest_endpoint);
+                if let Some(checkpoint) = checkpoint? {
+                    *prune_point = Some(checkpoint.block);
                 }
+                checkpoint_warning = tokio::fs::read_to_string(
+                    spaces_data_dir
+                        .join(network.to_string())
+                        .join(akrond::CHECKPOINT_WARNING_FILE),
+                )
+                .await
+                .ok();
                 if let Some(prune_point) = prune_point {
                     yuki_args.push("--prune-point".to_string());
                     yuki_args.push(format!(
@@ -149,10 +1523,28 @@ impl Client {
                     ));
                 }
 
+                // yuki has no proxy option, its filters and broadcast endpoints are reached
+                // through local tunnels instead. Its peer connections are made directly
+                let tunnels = &shutdown;
+                let yuki_endpoint = |url: String| async move {
+                    match chain_proxy {
+                        Some(_) => tunneled_url(&url, chain_proxy, None, None, tunnels).await,
+                        None => Ok(url),
+                    }
+                };
                 match network {
                     ExtendedNetwork::Mainnet => {
+                        // yuki takes a single endpoint and keeps it while it runs, the fastest
+                        // one is chosen again on the next start
+                        filters_endpoints.swap(0, fastest_endpoint.unwrap_or_default());
+                        if let Some(logs) = logs.as_ref() {
+                            _ = logs.send(LogLine::new(format!(
+                                "Using filters endpoint {}",
+                                filters_endpoints[0]
+                            )));
+                        }
                         yuki_args.push("--filters-endpoint".to_string());
-                        yuki_args.push("https://checkpoint.akron.io/".to_string());
+                        yuki_args.push(yuki_endpoint(filters_endpoints[0].clone()).await?);
 
                         // Optional: used for a quick acceptance test
                         // TODO: add option in settings to skip mempool acceptance tests
@@ -161,35 +1553,156 @@ impl Client {
                         // Works exactly like https://mempool.space/api/tx, which we can't
                         // unfortunately use, because it doesn't support specifying
                         // `maxburnamount` flag, so any OP_RETURN with non-zero burn will not work
-                        yuki_args.push("https://broadcastmempoolcheck.akron.io".to_string());
+                        yuki_args.push(
+                            yuki_endpoint("https://broadcastmempoolcheck.akron.io".to_string())
+                                .await?,
+                        );
                     }
                     ExtendedNetwork::Testnet4 => {
                         yuki_args.push("--broadcast-endpoint".to_string());
                         yuki_args.push(
-                            "https://testnet4.broadcastmempoolcheck.akron.io/testnet4".to_string(),
+                            yuki_endpoint(
+                                "https://testnet4.broadcastmempoolcheck.akron.io/testnet4"
+                                    .to_string(),
+                            )
+                            .await?,
                         );
                     }
                     _ => {}
                 }
 
-                if let Err(e) = akron.start(ServiceKind::Yuki, yuki_args).await {
+                // spaced retries until yuki answers, so both start at once
+                let mut services = vec![
+                    (ServiceKind::Yuki, yuki_args),
+                    (ServiceKind::Spaces, spaces_args.clone()),
+                ];
+                if let Some(bind) = serve_peers
+                    .as_ref()
+                    .filter(|_| *network == ExtendedNetwork::Mainnet)
+                {
+                    let mut mirror_args = vec![
+                        "--bind".to_string(),
+                        bind.clone(),
+                        "--checkpoint-dir".to_string(),
+                        services_dir
+                            .join("checkpoint")
+                            .to_str()
+                            .unwrap()
+                            .to_string(),
+                        "--filters-cache".to_string(),
+                        services_dir.join("filters").to_str().unwrap().to_string(),
+                    ];
+                    for endpoint in filters_endpoints {
+                        mirror_args.push("--filters-upstream".to_string());
+                        mirror_args.push(endpoint);
+                    }
+                    services.push((ServiceKind::Mirror, mirror_args));
+                }
+                if let Err(e) = akron.start_all(services).await {
                     let _ = shutdown.send(());
                     return Err(e.to_string());
                 }
+                spaces_service = Some(SpacesService {
+                    akron: Arc::new(akron),
+                    args: spaces_args,
+                });
+                chain_rpc = Some(ChainRpc {
+                    url: yuki_rpc_url,
+                    user: String::new(),
+                    password: String::new(),
+                    cookie: None,
+                    bitcoind: false,
+                });
+                (
+                    local_endpoint(spaced_rpc_port),
+                    "akron".to_string(),
+                    password,
+                    Some(shutdown),
+                )
+            }
+            ConfigBackend::ManagedBitcoind {
+                network,
+                rpc_password,
+                spaced_password,
+            } => {
+                let (akron, shutdown) = Akron::create(capture_logs);
+                logs = akron.subscribe_logs();
+                let bitcoind_data_dir = data_dir.join("bitcoind");
+                tokio::fs::create_dir_all(&bitcoind_data_dir)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let spaces_data_dir = data_dir.join("spaces");
+                let network_string = network.to_string();
+                wallets_dir = Some(spaces_data_dir.join(&network_string).join("wallets"));
+                if rpc_password.is_none() {
+                    *rpc_password = Some(random_password());
+                };
+                if spaced_password.is_none() {
+                    *spaced_password = Some(random_password());
+                };
+                let rpc_password = rpc_password.as_ref().unwrap().to_string();
+                let spaces_password = spaced_password.as_ref().unwrap().to_string();
+                let bitcoind_rpc_port = network_port(MANAGED_BITCOIND_RPC_PORT, network);
+                check_port(bitcoind_rpc_port, "bitcoind")?;
+                check_port(default_spaces_rpc_port(network), "spaced")?;
+                let bitcoind_args: Vec<String> = vec![
+                    format!("-chain={}", bitcoind_chain(network)),
+                    format!("-datadir={}", bitcoind_data_dir.to_str().unwrap()),
+                    format!("-prune={}", MANAGED_BITCOIND_PRUNE_MIB),
+                    "-server=1".to_string(),
+                    "-listen=0".to_string(),
+                    "-rpcbind=127.0.0.1".to_string(),
+                    "-rpcallowip=127.0.0.1".to_string(),
+                    format!("-rpcport={}", bitcoind_rpc_port),
+                    "-rpcuser=akron".to_string(),
+                    format!("-rpcpassword={}", rpc_password),
+                ];
+                let bitcoind_url = format!("http://127.0.0.1:{}", bitcoind_rpc_port);
+                let spaces_args: Vec<String> = [
+                    "--chain",
+                    &network_string,
+                    "--data-dir",
+                    spaces_data_dir.to_str().unwrap(),
+                    "--bitcoin-rpc-url",
+                    &bitcoind_url,
+                    "--bitcoin-rpc-user",
+                    "akron",
+                    "--bitcoin-rpc-password",
+                    &rpc_password,
+                    "--rpc-user",
+                    "akron",
+                    "--rpc-password",
+                    &spaces_password,
+                ]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+                // spaced retries until bitcoind answers, so both start at once
                 if let Err(e) = akron
-                    .start(
-                        ServiceKind::Spaces,
-                        spaces_args.iter().map(|s| s.to_string()).collect(),
-                    )
+                    .start_all(vec![
+                        (ServiceKind::Bitcoind, bitcoind_args),
+                        (ServiceKind::Spaces, spaces_args.clone()),
+                    ])
                     .await
                 {
                     let _ = shutdown.send(());
                     return Err(e.to_string());
                 }
+                spaces_service = Some(SpacesService {
+                    akron: Arc::new(akron),
+                    args: spaces_args,
+                });
+                chain_rpc = Some(ChainRpc {
+                    url: bitcoind_url,
+                    user: "akron".to_string(),
+                    password: rpc_password,
+                    cookie: None,
+                    bitcoind: true,
+                });
                 (
-                    format!("http://127.0.0.1:{}", default_spaces_rpc_port(network)),
+                    local_endpoint(default_spaces_rpc_port(network)),
                     "akron".to_string(),
-                    password,
+                    spaces_password,
                     Some(shutdown),
                 )
             }
@@ -198,12 +1711,23 @@ impl Client {
                 url,
                 user,
                 password,
+                cookie,
                 spaced_password,
             } => {
-                let (akron, shutdown) = Akron::create(CAPTURE_LOGS);
+                check_port(default_spaces_rpc_port(network), "spaced")?;
+                let bitcoind = ChainRpc {
+                    url: url.clone(),
+                    user: user.clone(),
+                    password: password.clone(),
+                    cookie: cookie.clone(),
+                    bitcoind: true,
+                };
+                check_bitcoind_version(&bitcoind, network).await?;
+                let (akron, shutdown) = Akron::create(capture_logs);
                 logs = akron.subscribe_logs();
                 let spaces_data_dir = data_dir.join("spaces");
                 let network_string = network.to_string();
+                wallets_dir = Some(spaces_data_dir.join(&network_string).join("wallets"));
                 if spaced_password.is_none() {
                     *spaced_password = Some(random_password());
                 };
@@ -220,7 +1744,9 @@ impl Client {
                     "--rpc-password",
                     &spaces_password,
                 ];
-                if !user.is_empty() {
+                if let Some(cookie) = cookie {
+                    spaces_args.extend_from_slice(&["--bitcoin-rpc-cookie", cookie]);
+                } else if !user.is_empty() {
                     spaces_args.extend_from_slice(&[
                         "--bitcoin-rpc-user",
                         user,
@@ -228,18 +1754,18 @@ impl Client {
                         password,
                     ]);
                 }
-                if let Err(e) = akron
-                    .start(
-                        ServiceKind::Spaces,
-                        spaces_args.iter().map(|s| s.to_string()).collect(),
-                    )
-                    .await
-                {
+                let spaces_args: Vec<String> = spaces_args.iter().map(|s| s.to_string()).collect();
+                if let Err(e) = akron.start(ServiceKind::Spaces, spaces_args.clone()).await {
                     let _ = shutdown.send(());
                     return Err(e.to_string());
                 }
+                spaces_service = Some(SpacesService {
+                    akron: Arc::new(akron),
+                    args: spaces_args,
+                });
+                chain_rpc = Some(bitcoind);
                 (
-                    format!("http://127.0.0.1:{}", default_spaces_rpc_port(network)),
+                    local_endpoint(default_spaces_rpc_port(network)),
                     "akron".to_string(),
                     spaces_password,
                     Some(shutdown),
@@ -247,86 +1773,236 @@ impl Client {
             }
             ConfigBackend::Spaced {
                 url,
+                fallback_urls,
                 user,
                 password,
+                ca_cert,
+                cert_fingerprint,
                 ..
-            } => (
-                url.to_string(),
-                user.to_string(),
-                password.to_string(),
-                None,
-            ),
+            } => {
+                let proxy = proxy.as_ref().filter(|p| p.spaced);
+                if proxy.is_none() {
+                    // fails fast when none of the nodes can be reached
+                    let mut error = None;
+                    for url in std::iter::once(&*url).chain(fallback_urls.iter()) {
+                        match probe_connect(url, timeouts.connect(local)).await {
+                            Ok(()) => {
+                                error = None;
+                                break;
+                            }
+                            Err(e) => {
+                                error.get_or_insert(e);
+                            }
+                        }
+                    }
+                    if let Some(error) = error {
+                        return Err(error);
+                    }
+                }
+                let shutdown = tokio::sync::broadcast::Sender::new(1);
+                let mut urls = Vec::new();
+                for url in std::iter::once(&*url).chain(fallback_urls.iter()) {
+                    let rpc_url = tunneled_url(
+                        url,
+                        proxy,
+                        ca_cert.as_deref(),
+                        cert_fingerprint.as_deref(),
+                        &shutdown,
+                    )
+                    .await?;
+                    urls.push((url.to_string(), rpc_url));
+                }
+                if !fallback_urls.is_empty() {
+                    // shows the failovers in the logs bar
+                    logs = Some(tokio::sync::broadcast::Sender::new(100));
+                }
+                (urls, user.to_string(), password.to_string(), Some(shutdown))
+            }
+        };
+        let auth = auth_token_from_creds(&spaces_user, &spaces_password);
+        let clients = spaces_rpc_urls
+            .into_iter()
+            .map(|(label, url)| {
+                rpc_client(&url, &auth, timeouts.request(local)).map(|client| (label, client))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        // spaced run by Akron is built along with the wallet
+        let version_warning = match backend_config {
+            ConfigBackend::Spaced { .. } => check_spaced_version(&clients[0].1).await?,
+            _ => None,
         };
-        let client = http_client_with_auth(
-            &spaces_rpc_url,
-            &auth_token_from_creds(&spaces_user, &spaces_password),
-        )
-        .map_err(|e| e.to_string())?;
         Ok((
             Self {
                 id: rand::random(),
-                client,
+                endpoints: Arc::new(Endpoints {
+                    clients,
+                    current: Default::default(),
+                    logs: logs.clone(),
+                    batch_unsupported: Default::default(),
+                    spaces: Default::default(),
+                    in_flight: Default::default(),
+                }),
                 shutdown,
                 logs,
+                wallets_dir,
+                spaces_service,
+                network: backend_config.network(),
+                checkpoint_warning,
+                version_warning,
+                chain_rpc,
             },
             backend_config,
         ))
     }
 
+    pub(crate) fn rpc(&self) -> HttpClient {
+        self.endpoints.current()
+    }
+
     pub fn get_server_info(&self) -> Task<ClientResult<ServerInfo>> {
-        let client = self.client.clone();
-        Task::perform(async move { client.get_server_info().await }, map_result)
+        let endpoints = self.endpoints.clone();
+        Task::perform(async move { endpoints.get_server_info().await }, map_result)
+    }
+
+    /// Endpoint requests go to, it changes when the current one stops answering and the
+    /// wallets have to be loaded on the new one
+    pub fn endpoint(&self) -> usize {
+        self.endpoints.current_index()
+    }
+
+    /// Status of the services, spaced and the Bitcoin node it syncs from
+    pub fn node_status(&self) -> Task<NodeStatus> {
+        let endpoints = self.endpoints.clone();
+        let akron = self.spaces_service.as_ref().map(|s| s.akron.clone());
+        let chain_rpc = self.chain_rpc.clone();
+        Task::future(async move {
+            let services = match akron {
+                Some(akron) => akron.status().await.unwrap_or_default(),
+                None => Vec::new(),
+            };
+            let spaced = map_result(endpoints.get_server_info().await);
+            let chain = match chain_rpc {
+                Some(chain_rpc) => Some(chain_rpc.status().await),
+                None => None,
+            };
+            NodeStatus {
+                services,
+                spaced,
+                chain,
+            }
+        })
+    }
+
+    /// Stops the service and starts it again the way it was started
+    pub fn restart_service(&self, kind: ServiceKind) -> Task<ClientResult<()>> {
+        let akron = self.spaces_service.as_ref().map(|s| s.akron.clone());
+        Task::future(async move {
+            match akron {
+                Some(akron) => akron.restart(kind).await.map_err(|e| e.to_string()),
+                None => Err("The services are not run by Akron".to_string()),
+            }
+        })
+    }
+
+    /// Manages the peers of the Bitcoin Core node spaced syncs from
+    pub fn peer_action(&self, action: PeerAction) -> Task<ClientResult<()>> {
+        let chain_rpc = self.chain_rpc.clone();
+        Task::future(async move {
+            match chain_rpc {
+                Some(chain_rpc) => chain_rpc.peer_action(action).await,
+                None => Err("The Bitcoin node is not run by Akron".to_string()),
+            }
+        })
     }
 
     pub fn get_space_info(
         &self,
         slabel: SLabel,
     ) -> Task<ClientResult<(SLabel, Option<FullSpaceOut>)>> {
-        let client = self.client.clone();
+        let endpoints = self.endpoints.clone();
         Task::perform(
             async move {
-                use spaces_client::store::Sha256;
-                use spaces_protocol::hasher::KeyHasher;
-                let hash = hex::encode(Sha256::hash(slabel.as_ref()));
-                let result = client.get_space(&hash).await;
+                let result = endpoints.get_space(&slabel).await;
                 result.map(|r| (slabel, r))
             },
             map_result,
         )
     }
 
-    pub fn list_wallets(&self) -> Task<ClientResult<Vec<String>>> {
-        let client = self.client.clone();
-        Task::perform(async move { client.list_wallets().await }, map_result)
+    pub fn verify_listing(&self, listing: Listing) -> Task<ClientResult<()>> {
+        let client = self.rpc();
+        Task::perform(
+            async move {
+                Backoff::RPC
+                    .retry(|| client.verify_listing(listing.clone()), is_transient)
+                    .await
+            },
+            map_result,
+        )
     }
 
-    pub fn create_wallet(&self, wallet: String) -> Task<WalletResult<String>> {
-        let client = self.client.clone();
+    pub fn list_wallets(&self) -> Task<ClientResult<Vec<String>>> {
+        let client = self.rpc();
         Task::perform(
             async move {
-                let result = client.wallet_create(&wallet).await;
-                (wallet, result)
+                Backoff::RPC
+                    .retry(|| client.list_wallets(), is_transient)
+                    .await
             },
-            map_wallet_result,
+            map_result,
         )
     }
 
-    pub fn restore_wallet(&self, wallet: String, mnemonic: String) -> Task<WalletResult<()>> {
-        let client = self.client.clone();
+    pub fn create_wallet(&self, wallet: String) -> Task<WalletResult<String>> {
+        let client = self.rpc();
         Task::perform(
             async move {
-                let result = client.wallet_recover(&wallet, mnemonic).await;
+                let result = client.wallet_create(&wallet).await;
                 (wallet, result)
             },
             map_wallet_result,
         )
     }
 
+    pub fn restore_wallet(
+        &self,
+        wallet: String,
+        mnemonic: String,
+        options: RestoreOptions,
+    ) -> Task<WalletResult<()>> {
+        let client = self.rpc();
+        if options.is_default() {
+            return Task::perform(
+                async move {
+                    let result = client.wallet_recover(&wallet, mnemonic).await;
+                    (wallet, result)
+                },
+                map_wallet_result,
+            );
+        }
+        match wallet_export_from_mnemonic(self.network, &wallet, &mnemonic, &options) {
+            Ok(export) => Task::perform(
+                async move {
+                    let result = client.wallet_import(export).await;
+                    (wallet, result)
+                },
+                map_wallet_result,
+            ),
+            Err(err) => Task::done(WalletResult {
+                label: wallet,
+                result: Err(err),
+            }),
+        }
+    }
+
     pub fn load_wallet(&self, wallet: String) -> Task<WalletResult<()>> {
-        let client = self.client.clone();
+        let client = self.rpc();
         Task::perform(
             async move {
-                let result = client.wallet_load(&wallet).await;
+                let result = Backoff::RPC
+                    .retry(|| client.wallet_load(&wallet), is_transient)
+                    .await;
                 (wallet, result)
             },
             map_wallet_result,
@@ -334,7 +2010,7 @@ impl Client {
     }
 
     pub fn export_wallet(&self, wallet: String) -> Task<WalletResult<String>> {
-        let client = self.client.clone();
+        let client = self.rpc();
         Task::perform(
             async move {
                 let result = client.wallet_export(&wallet).await;
@@ -344,11 +2020,42 @@ impl Client {
         )
     }
 
+    /// Exports of `wallets` with their private keys, for the app backup
+    pub fn export_wallets(&self, wallets: Vec<String>) -> Task<ClientResult<Vec<String>>> {
+        let client = self.rpc();
+        Task::perform(
+            async move {
+                let mut exports = Vec::with_capacity(wallets.len());
+                for wallet in wallets {
+                    let export = client.wallet_export(&wallet).await.map_err(map_error)?;
+                    exports.push(export.to_string());
+                }
+                Ok(exports)
+            },
+            |result| result,
+        )
+    }
+
+    pub fn export_public_descriptors(&self, wallet: String) -> Task<WalletResult<Vec<String>>> {
+        let client = self.rpc();
+        Task::perform(
+            async move {
+                let result = client.wallet_export(&wallet).await;
+                (wallet, result)
+            },
+            |(label, result)| WalletResult {
+                label,
+                result: map_result(result)
+                    .and_then(|export| public_descriptors(&export.descriptor)),
+            },
+        )
+    }
+
     pub fn import_wallet(&self, wallet_string: &str) -> Task<Result<String, String>> {
         let wallet_export: Result<WalletExport, _> = std::str::FromStr::from_str(wallet_string);
         match wallet_export {
             Ok(wallet_export) => {
-                let client = self.client.clone();
+                let client = self.rpc();
                 Task::perform(
                     async move {
                         let label = wallet_export.label.clone();
@@ -362,51 +2069,280 @@ impl Client {
         }
     }
 
-    pub fn get_wallet_info(&self, wallet: String) -> Task<WalletResult<WalletInfoWithProgress>> {
-        let client = self.client.clone();
+    /// Imports the wallets of an app backup, skipping the ones spaced already has
+    pub fn import_wallets(&self, wallet_strings: Vec<String>) -> Task<ClientResult<()>> {
+        let client = self.rpc();
         Task::perform(
             async move {
-                let result = client.wallet_get_info(&wallet).await;
-                (wallet, result)
+                let existing = client.list_wallets().await.map_err(map_error)?;
+                for wallet_string in wallet_strings {
+                    let wallet_export = wallet_string
+                        .parse::<WalletExport>()
+                        .map_err(|e| e.to_string())?;
+                    if existing.contains(&wallet_export.label) {
+                        continue;
+                    }
+                    client
+                        .wallet_import(wallet_export)
+                        .await
+                        .map_err(map_error)?;
+                }
+                Ok(())
             },
-            map_wallet_result,
+            |result| result,
         )
     }
 
-    pub fn get_wallet_balance(&self, wallet: String) -> Task<WalletResult<Balance>> {
-        let client = self.client.clone();
+    /// Imports the wallets of spaced run from the command line on this computer, so they
+    /// don't have to be restored from their mnemonics. A wallet whose label is taken by
+    /// another wallet is imported under a new label, unreadable wallet files are skipped
+    pub fn import_cli_wallets(&self) -> Task<ClientResult<CliWalletsImport>> {
+        let Some(dir) = spaced_cli_wallets_dir(self.network) else {
+            return Task::done(Err(
+                "No wallets of spaced were found on this computer".to_string()
+            ));
+        };
+        let client = self.rpc();
         Task::perform(
             async move {
-                let result = client.wallet_get_balance(&wallet).await;
-                (wallet, result)
+                let mut existing = client.list_wallets().await.map_err(map_error)?;
+                let mut import = CliWalletsImport::default();
+                let mut entries = tokio::fs::read_dir(&dir).await.map_err(|e| e.to_string())?;
+                while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+                    // spaced keeps the export of each wallet next to its database
+                    let path = entry.path().join("wallet.json");
+                    let Ok(contents) = tokio::fs::read_to_string(&path).await else {
+                        continue;
+                    };
+                    let mut wallet_export = match contents.parse::<WalletExport>() {
+                        Ok(wallet_export) => wallet_export,
+                        Err(e) => {
+                            import.skipped.push(format!("{}: {}", path.display(), e));
+                            continue;
+                        }
+                    };
+                    if existing.contains(&wallet_export.label) {
+                        match client.wallet_export(&wallet_export.label).await {
+                            Ok(current) if current.descriptor == wallet_export.descriptor => {
+                                import.existing.push(wallet_export.label);
+                                continue;
+                            }
+                            Ok(_) => {}
+                            Err(e) => {
+                                import.skipped.push(format!(
+                                    "{}: could not compare with wallet {}: {}",
+                                    path.display(),
+                                    wallet_export.label,
+                                    map_error(e)
+                                ));
+                                continue;
+                            }
+                        }
+                        let label =
+                            unused_wallet_label(&format!("{}-cli", wallet_export.label), &existing);
+                        import.renamed.push((
+                            std::mem::replace(&mut wallet_export.label, label.clone()),
+                            label,
+                        ));
+                    }
+                    let label = wallet_export.label.clone();
+                    client
+                        .wallet_import(wallet_export)
+                        .await
+                        .map_err(map_error)?;
+                    existing.push(label.clone());
+                    import.imported.push(label);
+                }
+                Ok(import)
             },
-            map_wallet_result,
+            |result| result,
         )
     }
 
-    pub fn get_wallet_spaces(&self, wallet: String) -> Task<WalletResult<ListSpacesResponse>> {
-        let client = self.client.clone();
+    /// Imports the wallet under a new label and removes the old wallet directory once the
+    /// import went through. spaced has no rename call so this only works for wallets
+    /// managed by Akron, and it's restarted to drop the old wallet, unloading every wallet
+    pub fn rename_wallet(&self, wallet: String, new_name: String) -> Task<ClientResult<String>> {
+        let (Some(wallets_dir), Some(spaces_service)) =
+            (self.wallets_dir.clone(), self.spaces_service.clone())
+        else {
+            return Task::done(Err(
+                "Renaming wallets is not supported with a remote spaced backend".to_string(),
+            ));
+        };
+        let client = self.rpc();
         Task::perform(
             async move {
-                let result = client.wallet_list_spaces(&wallet).await;
-                (wallet, result)
+                let mut export = client.wallet_export(&wallet).await.map_err(map_error)?;
+                export.label = new_name.clone();
+                client.wallet_import(export).await.map_err(map_error)?;
+                spaces_service.stop().await?;
+                let removed = remove_wallet_dir(&wallets_dir, &wallet).await;
+                spaces_service.start(&client).await?;
+                removed?;
+                Ok(new_name)
             },
-            map_wallet_result,
+            |result| result,
+        )
+    }
+
+    /// Removes the wallet directory, spaced is restarted first to drop the loaded wallet
+    /// which unloads every wallet
+    pub fn delete_wallet(&self, wallet: String) -> Task<ClientResult<()>> {
+        let (Some(wallets_dir), Some(spaces_service)) =
+            (self.wallets_dir.clone(), self.spaces_service.clone())
+        else {
+            return Task::done(Err(
+                "Deleting wallets is not supported with a remote spaced backend".to_string(),
+            ));
+        };
+        let client = self.rpc();
+        Task::perform(
+            async move {
+                spaces_service.stop().await?;
+                let removed = remove_wallet_dir(&wallets_dir, &wallet).await;
+                spaces_service.start(&client).await?;
+                removed
+            },
+            |result| result,
         )
     }
 
+    /// Scans the wallet again from `height`, or from its birthday. spaced has no rescan
+    /// call so it's stopped to drop the loaded wallet, which is then moved aside and
+    /// imported again. The old directory is only removed once the import went through,
+    /// it's put back otherwise. Only works for wallets managed by Akron
+    pub fn rescan_wallet(&self, wallet: String, height: Option<u32>) -> Task<ClientResult<()>> {
+        let (Some(wallets_dir), Some(spaces_service)) =
+            (self.wallets_dir.clone(), self.spaces_service.clone())
+        else {
+            return Task::done(Err(
+                "Rescanning wallets is not supported with a remote spaced backend".to_string(),
+            ));
+        };
+        let client = self.rpc();
+        Task::perform(
+            async move {
+                let mut export = client.wallet_export(&wallet).await.map_err(map_error)?;
+                if let Some(height) = height {
+                    export.blockheight = height;
+                }
+                let dir = wallet_dir(&wallets_dir, &wallet)?;
+                let aside = rescan_dir(&wallets_dir, &wallet);
+                spaces_service.stop().await?;
+                let moved = tokio::fs::rename(&dir, &aside).await;
+                let started = spaces_service.start(&client).await;
+                let imported = match (moved, started) {
+                    (Err(e), _) => return Err(e.to_string()),
+                    (Ok(()), Ok(())) => client.wallet_import(export).await.map_err(map_error),
+                    (Ok(()), Err(e)) => Err(e),
+                };
+                if imported.is_ok() {
+                    _ = tokio::fs::remove_dir_all(&aside).await;
+                    return Ok(());
+                }
+                // the wallet goes back as it was before the rescan
+                _ = spaces_service.stop().await;
+                _ = tokio::fs::remove_dir_all(&dir).await;
+                let restored = tokio::fs::rename(&aside, &dir).await;
+                spaces_service.start(&client).await?;
+                restored.map_err(|e| e.to_string())?;
+                imported
+            },
+            |result| result,
+        )
+    }
+
+    /// Makes the wallet calls of a tick in one round trip, or one by one when spaced
+    /// doesn't take batched requests
+    pub fn wallet_calls(&self, calls: Vec<(String, WalletCall)>) -> Task<Vec<WalletUpdate>> {
+        Task::future(self.endpoints.clone().wallet_calls(calls))
+    }
+
+    /// A single wallet call, `answer` picking its result out of the update
+    fn wallet_call<T: Send + 'static>(
+        &self,
+        wallet: String,
+        call: WalletCall,
+        answer: fn(WalletUpdate) -> Option<WalletResult<T>>,
+    ) -> Task<WalletResult<T>> {
+        let endpoints = self.endpoints.clone();
+        Task::future(async move {
+            let update = endpoints.wallet_calls(vec![(wallet.clone(), call)]).await;
+            update
+                .into_iter()
+                .next()
+                .and_then(answer)
+                .unwrap_or_else(|| WalletResult {
+                    label: wallet,
+                    result: Err("No answer to the call".to_string()),
+                })
+        })
+    }
+
+    pub fn get_wallet_info(&self, wallet: String) -> Task<WalletResult<WalletInfoWithProgress>> {
+        self.wallet_call(wallet, WalletCall::Info, |update| match update {
+            WalletUpdate::Info(result) => Some(result),
+            _ => None,
+        })
+    }
+
+    /// Calls any spaced method for the RPC console, `params` being a JSON array or object,
+    /// and answers the pretty printed result
+    pub fn raw_request(&self, method: String, params: String) -> Task<ClientResult<String>> {
+        use jsonrpsee::core::params::ObjectParams;
+        let client = self.rpc();
+        Task::perform(
+            async move {
+                let params = match params.trim() {
+                    "" => serde_json::Value::Array(Vec::new()),
+                    params => serde_json::from_str(params)
+                        .map_err(|e| format!("Invalid params: {}", e))?,
+                };
+                let result: serde_json::Value = match params {
+                    serde_json::Value::Array(params) => client.request(&method, params).await,
+                    serde_json::Value::Object(params) => {
+                        let mut object = ObjectParams::new();
+                        for (name, value) in params {
+                            object.insert(&name, value).map_err(|e| e.to_string())?;
+                        }
+                        client.request(&method, object).await
+                    }
+                    _ => return Err("Params must be a JSON array or object".to_string()),
+                }
+                .map_err(map_error)?;
+                serde_json::to_string_pretty(&result).map_err(|e| e.to_string())
+            },
+            |result| result,
+        )
+    }
+
+    pub fn get_wallet_balance(&self, wallet: String) -> Task<WalletResult<Balance>> {
+        self.wallet_call(wallet, WalletCall::Balance, |update| match update {
+            WalletUpdate::Balance(result) => Some(result),
+            _ => None,
+        })
+    }
+
+    pub fn get_wallet_spaces(&self, wallet: String) -> Task<WalletResult<ListSpacesResponse>> {
+        self.wallet_call(wallet, WalletCall::Spaces, |update| match update {
+            WalletUpdate::Spaces(result) => Some(result),
+            _ => None,
+        })
+    }
+
     pub fn get_wallet_transactions(
         &self,
         wallet: String,
         count: usize,
     ) -> Task<WalletResult<Vec<TxInfo>>> {
-        let client = self.client.clone();
-        Task::perform(
-            async move {
-                let result = client.wallet_list_transactions(&wallet, count, 0).await;
-                (wallet, result)
+        self.wallet_call(
+            wallet,
+            WalletCall::Transactions(count),
+            |update| match update {
+                WalletUpdate::Transactions(result) => Some(result),
+                _ => None,
             },
-            map_wallet_result,
         )
     }
 
@@ -415,7 +2351,7 @@ impl Client {
         wallet: String,
         address_kind: AddressKind,
     ) -> Task<WalletResult<(AddressKind, String)>> {
-        let client = self.client.clone();
+        let client = self.rpc();
         Task::perform(
             async move {
                 let result = client.wallet_get_new_address(&wallet, address_kind).await;
@@ -432,7 +2368,7 @@ impl Client {
         amount: Amount,
         fee_rate: Option<FeeRate>,
     ) -> Task<WalletResult<WalletResponse>> {
-        let client = self.client.clone();
+        let client = self.rpc();
         Task::perform(
             async move {
                 let result = client
@@ -467,7 +2403,7 @@ impl Client {
     ) -> Task<WalletResult<WalletResponse>> {
         let name = slabel.to_string();
         let amount = amount.to_sat();
-        let client = self.client.clone();
+        let client = self.rpc();
         Task::perform(
             async move {
                 let result = client
@@ -499,7 +2435,7 @@ impl Client {
     ) -> Task<WalletResult<WalletResponse>> {
         let name = slabel.to_string();
         let amount = amount.to_sat();
-        let client = self.client.clone();
+        let client = self.rpc();
         Task::perform(
             async move {
                 let result = client
@@ -529,7 +2465,7 @@ impl Client {
         fee_rate: Option<FeeRate>,
     ) -> Task<WalletResult<WalletResponse>> {
         let name = slabel.to_string();
-        let client = self.client.clone();
+        let client = self.rpc();
         Task::perform(
             async move {
                 let result = client
@@ -561,26 +2497,11 @@ impl Client {
         slabel: SLabel,
         fee_rate: Option<FeeRate>,
     ) -> Task<WalletResult<WalletResponse>> {
-        let name = slabel.to_string();
-        let client = self.client.clone();
+        let client = self.rpc();
         Task::perform(
             async move {
                 let result = client
-                    .wallet_send_request(
-                        &wallet,
-                        RpcWalletTxBuilder {
-                            bidouts: None,
-                            requests: vec![RpcWalletRequest::Transfer(TransferSpacesParams {
-                                spaces: vec![name],
-                                to: None,
-                            })],
-                            fee_rate,
-                            dust: None,
-                            force: false,
-                            confirmed_only: false,
-                            skip_tx_check: false,
-                        },
-                    )
+                    .wallet_send_request(&wallet, renew_request(&slabel, fee_rate))
                     .await;
                 (wallet, result)
             },
@@ -596,7 +2517,7 @@ impl Client {
         fee_rate: Option<FeeRate>,
     ) -> Task<WalletResult<WalletResponse>> {
         let name = slabel.to_string();
-        let client = self.client.clone();
+        let client = self.rpc();
         Task::perform(
             async move {
                 let result = client
@@ -628,7 +2549,7 @@ impl Client {
         txid: Txid,
         fee_rate: FeeRate,
     ) -> Task<WalletResult<WalletResponse>> {
-        let client = self.client.clone();
+        let client = self.rpc();
         Task::perform(
             async move {
                 let result = client.wallet_bump_fee(&wallet, txid, fee_rate, false).await;
@@ -644,7 +2565,7 @@ impl Client {
         listing: Listing,
         fee_rate: Option<FeeRate>,
     ) -> Task<WalletResult<WalletResponse>> {
-        let client = self.client.clone();
+        let client = self.rpc();
         Task::perform(
             async move {
                 let result = client.wallet_buy(&wallet, listing, fee_rate, false).await;
@@ -660,7 +2581,7 @@ impl Client {
         slabel: SLabel,
         price: Amount,
     ) -> Task<WalletResult<Listing>> {
-        let client = self.client.clone();
+        let client = self.rpc();
         let space = slabel.to_string();
         let amount = price.to_sat();
         Task::perform(
@@ -679,7 +2600,7 @@ impl Client {
         event: NostrEvent,
     ) -> Task<WalletResult<NostrEvent>> {
         let space = slabel.to_string();
-        let client = self.client.clone();
+        let client = self.rpc();
         Task::perform(
             async move {
                 let result = client.wallet_sign_event(&wallet, &space, event).await;
@@ -689,7 +2610,156 @@ impl Client {
         )
     }
 
-    pub fn logs_subscription(&self) -> Subscription<String> {
+    /// Writes a checkpoint of the local spaces database to `destination`. spaced has to be
+    /// fully synced, it's stopped while the database is copied which unloads the wallets
+    pub fn export_checkpoint(&self, destination: std::path::PathBuf) -> Task<ClientResult<String>> {
+        let (Some(wallets_dir), Some(spaces_service)) =
+            (self.wallets_dir.clone(), self.spaces_service.clone())
+        else {
+            return Task::done(Err(
+                "Checkpoints can only be exported from a spaced run by Akron".to_string(),
+            ));
+        };
+        let db_path = wallets_dir.with_file_name("protocol.sdb");
+        let client = self.rpc();
+        Task::perform(
+            async move {
+                let info = client.get_server_info().await.map_err(map_error)?;
+                if !info.ready || info.tip.height < info.chain.headers {
+                    return Err("Spaced has to be fully synced to export a checkpoint".to_string());
+                }
+                spaces_service.stop().await?;
+                let exported = Akron::export_checkpoint(&db_path, &destination).await;
+                spaces_service.start(&client).await?;
+                let anchor = exported.map_err(|e| format!("{:#}", e))?;
+                Ok(format!(
+                    "Exported the checkpoint at block {} to {}",
+                    anchor.block.height,
+                    destination.display()
+                ))
+            },
+            |result| result,
+        )
+    }
+
+    /// Compacts the wallet databases with spaced paused, reporting each wallet as it's
+    /// done. Waits until spaced is idle, synced with no new block since the last check, so
+    /// no wallet is being updated. Stopping spaced unloads the wallets
+    pub fn compact_databases(&self) -> Task<CompactionProgress> {
+        let (Some(wallets_dir), Some(spaces_service)) =
+            (self.wallets_dir.clone(), self.spaces_service.clone())
+        else {
+            return Task::done(CompactionProgress::Done(Err(
+                "Databases can only be compacted for a spaced run by Akron".to_string(),
+            )));
+        };
+        let client = self.rpc();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let result = async {
+                let mut synced_tip = None;
+                loop {
+                    let info = client.get_server_info().await.map_err(map_error)?;
+                    let synced = info.ready && info.tip.height >= info.chain.headers;
+                    if synced && synced_tip == Some(info.tip.height) {
+                        break;
+                    }
+                    synced_tip = synced.then_some(info.tip.height);
+                    tokio::time::sleep(COMPACTION_IDLE_CHECK).await;
+                }
+                let mut databases = Vec::new();
+                let mut wallets = tokio::fs::read_dir(&wallets_dir)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                while let Ok(Some(wallet)) = wallets.next_entry().await {
+                    let Ok(mut files) = tokio::fs::read_dir(wallet.path()).await else {
+                        continue;
+                    };
+                    while let Ok(Some(file)) = files.next_entry().await {
+                        if file.path().extension().is_some_and(|ext| ext == "db") {
+                            databases.push((
+                                wallet.file_name().to_string_lossy().to_string(),
+                                file.path(),
+                            ));
+                        }
+                    }
+                }
+                spaces_service.stop().await?;
+                let mut freed = 0;
+                let mut result = Ok(());
+                for (done, (wallet, path)) in databases.iter().enumerate() {
+                    _ = tx.send(CompactionProgress::Compacting {
+                        wallet: wallet.clone(),
+                        done,
+                        total: databases.len(),
+                    });
+                    let path = path.clone();
+                    let compacted = tokio::task::spawn_blocking(move || {
+                        let before = std::fs::metadata(&path).map_or(0, |m| m.len());
+                        spaces_wallet::bdk_wallet::rusqlite::Connection::open(&path)?
+                            .execute_batch("VACUUM")?;
+                        let after = std::fs::metadata(&path).map_or(before, |m| m.len());
+                        Ok::<_, spaces_wallet::bdk_wallet::rusqlite::Error>(
+                            before.saturating_sub(after),
+                        )
+                    })
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|r| r.map_err(|e| format!("Could not compact {}: {}", wallet, e)));
+                    match compacted {
+                        Ok(bytes) => freed += bytes,
+                        Err(e) => {
+                            result = Err(e);
+                            break;
+                        }
+                    }
+                }
+                spaces_service.start(&client).await?;
+                result?;
+                Ok(format!(
+                    "Compacted {} wallet databases, freed {}",
+                    databases.len(),
+                    crate::helpers::format_bytes(freed)
+                ))
+            }
+            .await;
+            _ = tx.send(CompactionProgress::Done(result));
+        });
+        Task::run(
+            tokio_stream::wrappers::UnboundedReceiverStream::new(rx),
+            |progress| progress,
+        )
+    }
+
+    pub fn checkpoint_warning(&self) -> Option<&str> {
+        self.checkpoint_warning.as_deref()
+    }
+
+    pub fn version_warning(&self) -> Option<&str> {
+        self.version_warning.as_deref()
+    }
+
+    /// Whether spaced is run by Akron rather than a remote backend
+    pub fn is_managed(&self) -> bool {
+        self.spaces_service.is_some()
+    }
+
+    /// Stops the services run by Akron and waits for them to exit
+    pub fn stop_services(&self) -> Task<()> {
+        let Some(spaces_service) = self.spaces_service.clone() else {
+            return Task::none();
+        };
+        Task::future(async move { spaces_service.stop_all().await })
+    }
+
+    /// Same as `stop_services`, without the UI runtime
+    pub async fn shutdown_services(&self) {
+        if let Some(spaces_service) = self.spaces_service.as_ref() {
+            spaces_service.stop_all().await;
+        }
+    }
+
+    pub fn logs_subscription(&self) -> Subscription<LogLine> {
         if let Some(sender) = &self.logs {
             let stream = BroadcastStream::new(sender.subscribe()).filter_map(|result| result.ok());
             Subscription::run_with_id(format!("client_logs_{}", self.id), stream)
@@ -697,6 +2767,17 @@ impl Client {
             Subscription::none()
         }
     }
+
+    /// Services akrond restarted on its own after they crashed
+    pub fn restarts_subscription(&self) -> Subscription<ServiceKind> {
+        if let Some(spaces_service) = &self.spaces_service {
+            let sender = spaces_service.akron.subscribe_restarts();
+            let stream = BroadcastStream::new(sender.subscribe()).filter_map(|result| result.ok());
+            Subscription::run_with_id(format!("client_restarts_{}", self.id), stream)
+        } else {
+            Subscription::none()
+        }
+    }
 }
 
 impl Drop for Client {
@@ -0,0 +1,147 @@
+use iced::{
+    widget::{button, column, row, scrollable, text},
+    Element, Fill,
+};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::{
+    diagnostics,
+    widget::{
+        base::modal,
+        form::STANDARD_PADDING,
+        text::{text_big, text_monospace},
+    },
+};
+
+/// Report of the last crash in the data directory, shown on the next launch
+const CRASH_REPORT: &str = "crash-report.txt";
+/// Report already shown, kept until the next crash
+const SEEN_CRASH_REPORT: &str = "crash-report.seen.txt";
+/// Log lines included in a report
+const LOG_LINES_KEPT: usize = 200;
+
+static RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Keeps the line for the report of a crash
+pub fn record_log(line: String) {
+    let mut logs = RECENT_LOGS.lock().unwrap();
+    if logs.len() == LOG_LINES_KEPT {
+        logs.pop_front();
+    }
+    logs.push_back(line);
+}
+
+/// Writes a crash report to the data directory when a thread panics, before the usual
+/// message on stderr
+pub fn install(data_dir: PathBuf) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let mut report = String::from("Akron crashed\n\n");
+        for (name, version) in diagnostics::versions() {
+            report.push_str(&format!("{}: {}\n", name, version));
+        }
+        report.push_str(&format!(
+            "\nThread {} {}\n\n{}\n",
+            std::thread::current().name().unwrap_or("unnamed"),
+            info,
+            std::backtrace::Backtrace::force_capture()
+        ));
+        // the panic may have happened while the logs were locked
+        if let Ok(logs) = RECENT_LOGS.try_lock() {
+            report.push_str("\nLast log lines:\n");
+            for line in logs.iter() {
+                report.push_str(line);
+                report.push('\n');
+            }
+        }
+        if std::fs::write(data_dir.join(CRASH_REPORT), report).is_ok() {
+            eprintln!(
+                "Crash report written to {}",
+                data_dir.join(CRASH_REPORT).display()
+            );
+        }
+        previous(info);
+    }));
+}
+
+/// Crash report left by the last run
+#[derive(Debug)]
+pub struct Report {
+    path: PathBuf,
+    contents: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Open,
+    Copy,
+    Dismiss,
+}
+
+impl Report {
+    /// Report of a crash not shown yet
+    pub fn load(data_dir: &Path) -> Option<Self> {
+        let path = data_dir.join(CRASH_REPORT);
+        let contents = std::fs::read_to_string(&path).ok()?;
+        Some(Self { path, contents })
+    }
+
+    /// Opens the report in the default text editor
+    pub fn open(&self) {
+        #[cfg(target_os = "windows")]
+        let command = std::process::Command::new("cmd")
+            .args(["/C", "start", ""])
+            .arg(&self.path)
+            .spawn();
+        #[cfg(target_os = "macos")]
+        let command = std::process::Command::new("open").arg(&self.path).spawn();
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        let command = std::process::Command::new("xdg-open")
+            .arg(&self.path)
+            .spawn();
+        if let Err(e) = command {
+            eprintln!("Could not open {}: {}", self.path.display(), e);
+        }
+    }
+
+    pub fn contents(&self) -> &str {
+        &self.contents
+    }
+
+    /// Keeps the report aside so it isn't shown again
+    pub fn dismiss(self) {
+        _ = std::fs::rename(&self.path, self.path.with_file_name(SEEN_CRASH_REPORT));
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let action = |label: &'static str, message: Message| {
+            button(text(label))
+                .style(button::secondary)
+                .padding(STANDARD_PADDING)
+                .on_press(message)
+        };
+        modal(
+            column![
+                text_big("Akron closed unexpectedly"),
+                text(format!(
+                    "A crash report was saved to {}. Attaching it to a bug report helps fix \
+                     the problem.",
+                    self.path.display()
+                )),
+                scrollable(text_monospace(&self.contents).size(12))
+                    .height(250)
+                    .width(Fill),
+                row![
+                    action("Open", Message::Open),
+                    action("Copy", Message::Copy),
+                    action("Dismiss", Message::Dismiss),
+                ]
+                .spacing(10),
+            ]
+            .spacing(20)
+            .padding(30),
+        )
+    }
+}
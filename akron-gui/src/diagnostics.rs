@@ -0,0 +1,242 @@
+use jsonrpsee::http_client::HttpClient;
+use serde_json::Value;
+use spaces_client::rpc::RpcClient;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::{
+    automation::automation_addr, client::FILTERS_ENDPOINT, sign_request::SIGN_REQUEST_ADDR,
+};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Clock differences above this are reported as a problem
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: String,
+    pub result: Result<String, String>,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, result: Result<String, String>) -> Self {
+        Self {
+            name: name.into(),
+            result,
+        }
+    }
+}
+
+/// Version of the app and of the services, which are built into it
+pub fn versions() -> Vec<(&'static str, String)> {
+    vec![
+        ("Akron", env!("CARGO_PKG_VERSION").to_string()),
+        ("akrond, yuki and spaced", akrond::VERSION.to_string()),
+        (
+            "System",
+            format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        ),
+    ]
+}
+
+async fn check_backend(rpc: &HttpClient) -> Result<String, String> {
+    let started = Instant::now();
+    let info = rpc.get_server_info().await.map_err(|e| e.to_string())?;
+    Ok(format!(
+        "{} at block {} of {}, answered in {} ms",
+        info.network,
+        info.tip.height,
+        info.chain.headers,
+        started.elapsed().as_millis()
+    ))
+}
+
+/// Whether Akron itself listens on the sign requests port, by sending it a request it
+/// rejects in its own way
+async fn check_sign_request_port() -> Result<String, String> {
+    let Ok(stream) = tokio::net::TcpStream::connect(SIGN_REQUEST_ADDR).await else {
+        return Err(format!("Nothing listens on {}", SIGN_REQUEST_ADDR));
+    };
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    let answered = async {
+        writer.write_all(b"{}\n").await?;
+        BufReader::new(reader).read_line(&mut line).await
+    };
+    let _ = tokio::time::timeout(PROBE_TIMEOUT, answered).await;
+    match serde_json::from_str::<Value>(&line) {
+        Ok(response) if response.get("error").is_some() => {
+            Ok(format!("Akron listens on {}", SIGN_REQUEST_ADDR))
+        }
+        _ => Err(format!(
+            "{} is used by another program, sign requests from other apps won't arrive",
+            SIGN_REQUEST_ADDR
+        )),
+    }
+}
+
+async fn check_automation_port(port: u16) -> Result<String, String> {
+    let addr = automation_addr(port);
+    let response = reqwest::Client::new()
+        .post(format!("http://{}", addr))
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await
+        .map_err(|_| format!("Nothing listens on {}", addr))?;
+    let body = response.text().await.unwrap_or_default();
+    let body: Value = serde_json::from_str(&body).unwrap_or_default();
+    if body.get("jsonrpc").is_some() {
+        Ok(format!("Akron listens on {}", addr))
+    } else {
+        Err(format!("{} is used by another program", addr))
+    }
+}
+
+/// Seconds since the epoch of an HTTP date, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+fn parse_http_date(date: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let mut parts = date.split_whitespace().skip(1);
+    let day: i64 = parts.next()?.parse().ok()?;
+    let month = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as i64 + 1;
+    let year: i64 = parts.next()?.parse().ok()?;
+    let mut time = parts.next()?.split(':').map(|n| n.parse::<i64>().ok());
+    let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+    let days = akrond::days_from_civil(year, month, day);
+    Some(days * 86400 + hours * 3600 + minutes * 60 + seconds)
+}
+
+/// Fetches the endpoint, returning how long it took and the server's clock
+async fn probe(url: &str) -> Result<(Duration, Option<i64>), String> {
+    let started = Instant::now();
+    let response = crate::proxy::http_client()
+        .get(url)
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status().is_server_error() {
+        return Err(format!("Answered {}", response.status()));
+    }
+    let date = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .and_then(|date| date.to_str().ok())
+        .and_then(parse_http_date);
+    Ok((started.elapsed(), date))
+}
+
+/// Runs the checks, `filters_endpoints` are the ones yuki syncs from. The clock is
+/// compared with theirs, or with the default endpoint's without any. `automation` is the
+/// port of the automation API when it's enabled
+pub async fn run(
+    rpc: HttpClient,
+    filters_endpoints: Vec<String>,
+    automation: Option<u16>,
+) -> Vec<Check> {
+    let mut checks = vec![
+        Check::new("Backend", check_backend(&rpc).await),
+        Check::new("Sign requests port", check_sign_request_port().await),
+    ];
+    if let Some(port) = automation {
+        checks.push(Check::new(
+            "Automation API port",
+            check_automation_port(port).await,
+        ));
+    }
+    let mut server_time = None;
+    for endpoint in &filters_endpoints {
+        let result = probe(endpoint).await.map(|(elapsed, date)| {
+            server_time = server_time.or(date);
+            format!("{} answered in {} ms", endpoint, elapsed.as_millis())
+        });
+        checks.push(Check::new("Filters endpoint", result));
+    }
+    if filters_endpoints.is_empty() {
+        server_time = probe(FILTERS_ENDPOINT)
+            .await
+            .ok()
+            .and_then(|(_, date)| date);
+    }
+    let clock = match server_time {
+        Some(server_time) => {
+            let now = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs() as i64);
+            let skew = now - server_time;
+            if skew.unsigned_abs() > MAX_CLOCK_SKEW.as_secs() {
+                Err(format!(
+                    "The clock is {} seconds {}, set the system time",
+                    skew.abs(),
+                    if skew > 0 { "ahead" } else { "behind" }
+                ))
+            } else {
+                Ok(format!(
+                    "Within {} seconds of the endpoint's",
+                    skew.abs().max(1)
+                ))
+            }
+        }
+        None => Err("No endpoint answered with its time".to_string()),
+    };
+    checks.push(Check::new("Clock", clock));
+    checks
+}
+
+/// Plain text report of the results, with `private` values, e.g. paths, URLs and wallet
+/// names, left out so it can be shared
+pub fn report(checks: &[Check], private: &[String]) -> String {
+    let mut report = String::from("Akron diagnostics\n\n");
+    for (name, version) in versions() {
+        report.push_str(&format!("{}: {}\n", name, version));
+    }
+    report.push('\n');
+    for check in checks {
+        let (status, details) = match &check.result {
+            Ok(details) => ("OK", details),
+            Err(details) => ("FAILED", details),
+        };
+        report.push_str(&format!("[{}] {}: {}\n", status, check.name, details));
+    }
+    private
+        .iter()
+        .filter(|value| !value.is_empty())
+        .fold(report, |report, value| {
+            report.replace(value.as_str(), "<redacted>")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_dates() {
+        assert_eq!(
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(784_111_777)
+        );
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(
+            parse_http_date("Thu, 29 Feb 2024 12:00:00 GMT"),
+            Some(1_709_208_000)
+        );
+    }
+
+    #[test]
+    fn invalid_http_dates() {
+        for date in [
+            "",
+            "Sun, 06 Nov 1994",
+            "Sun, 06 Foo 1994 08:49:37 GMT",
+            "Sun, xx Nov 1994 08:49:37 GMT",
+            "Sun, 06 Nov 1994 08:49 GMT",
+            "Sun, 06 Nov 1994 08:49:xx GMT",
+        ] {
+            assert_eq!(parse_http_date(date), None, "{}", date);
+        }
+    }
+}
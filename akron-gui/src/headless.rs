@@ -0,0 +1,325 @@
+use crate::automation;
+use crate::client::{renew_request, Client, Covenant, FeeRate, SLabel, AUTO_RENEW_BLOCKS};
+use crate::pages::main::watcher::Watcher;
+use crate::webhooks::Delivery;
+use crate::widget::fee_rate;
+use crate::Config;
+use serde_json::{json, Value};
+use spaces_client::rpc::RpcClient;
+use spaces_client::wallets::WalletStatus;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, Mutex};
+
+/// Control socket address when `--control` isn't given, only loopback addresses are
+/// allowed
+pub const DEFAULT_CONTROL_ADDR: &str = "127.0.0.1:7227";
+
+/// File in the data directory holding the token of the control socket, a new one is
+/// written on every start
+pub const CONTROL_TOKEN_FILE: &str = "control.token";
+
+/// Wallets are checked for events this often, as the app does for the webhooks
+const WATCH_INTERVAL: Duration = Duration::from_secs(30);
+const RENEW_INTERVAL: Duration = Duration::from_secs(600);
+/// Latest transactions of each wallet looked at for confirmations
+const TRANSACTIONS_WATCHED: usize = 50;
+const CONTROL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Wallets watched without the UI, the current one and the ones kept loaded next to it
+fn watched_wallets(config: &Config) -> Vec<String> {
+    let mut wallets: Vec<String> = config.wallet.iter().cloned().collect();
+    for wallet in &config.loaded_wallets {
+        if !wallets.contains(wallet) {
+            wallets.push(wallet.clone());
+        }
+    }
+    wallets
+}
+
+/// Renews the expiring spaces of the watched wallets with auto renew enabled at the fee
+/// rate preset of each wallet, returning the renewed ones. The rate is left to the
+/// backend if the recommended rates can't be fetched
+async fn auto_renew(client: &Client, config: &Config) -> Result<Vec<String>, String> {
+    let rpc = client.rpc();
+    let info = rpc.get_server_info().await.map_err(|e| e.to_string())?;
+    if !info.ready || info.tip.height < info.chain.headers {
+        return Ok(Vec::new());
+    }
+    let wallets: Vec<_> = watched_wallets(config)
+        .into_iter()
+        .map(|wallet| (config.load_wallet_config(&wallet), wallet))
+        .filter(|(wallet_config, _)| wallet_config.auto_renew)
+        .collect();
+    if wallets.is_empty() {
+        return Ok(Vec::new());
+    }
+    let fee_rates = fee_rate::fetch_fee_rates()
+        .await
+        .inspect_err(|e| eprintln!("{}", e))
+        .ok();
+    let mut renewed = Vec::new();
+    for (wallet_config, wallet) in wallets {
+        let fee_rate = fee_rates
+            .as_ref()
+            .and_then(|rates| wallet_config.fee_rate.preset_fee_rate(rates))
+            .and_then(|rate| FeeRate::from_sat_per_vb(rate as _));
+        let synced = rpc
+            .wallet_get_info(&wallet)
+            .await
+            .is_ok_and(|info| matches!(info.sync.status, WalletStatus::Complete));
+        if !synced {
+            continue;
+        }
+        let spaces = rpc
+            .wallet_list_spaces(&wallet)
+            .await
+            .map_err(|e| e.to_string())?;
+        let expiring: Vec<SLabel> = spaces
+            .owned
+            .into_iter()
+            .filter_map(|out| out.spaceout.space)
+            .filter(|space| {
+                !spaces.pending.contains(&space.name)
+                    && matches!(space.covenant, Covenant::Transfer { expire_height, .. }
+                        if expire_height <= info.tip.height + AUTO_RENEW_BLOCKS)
+            })
+            .map(|space| space.name)
+            .collect();
+        for slabel in expiring {
+            match rpc
+                .wallet_send_request(&wallet, renew_request(&slabel, fee_rate))
+                .await
+            {
+                Ok(_) => {
+                    println!("Renewed {} from wallet {}", slabel, wallet);
+                    renewed.push(slabel.to_string());
+                }
+                Err(e) => eprintln!("Could not renew {} from wallet {}: {}", slabel, wallet, e),
+            }
+        }
+    }
+    Ok(renewed)
+}
+
+/// Server side of the control socket, shared by the connections
+struct Control {
+    client: Client,
+    config: Mutex<Config>,
+    token: String,
+    stop: broadcast::Sender<()>,
+}
+
+/// Answers one JSON request per line, `{"token": "...", "method": "status" | "renew" |
+/// "stop"}`. The token is the one in the control token file of the data directory, the
+/// connection is closed on a wrong one
+async fn handle_control(stream: TcpStream, control: Arc<Control>) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        let request = serde_json::from_str::<Value>(&line).unwrap_or_default();
+        let authorized = request
+            .get("token")
+            .and_then(Value::as_str)
+            .is_some_and(|token| automation::token_matches(token, &control.token));
+        if !authorized {
+            writer
+                .write_all(format!("{}\n", json!({ "error": "Unauthorized" })).as_bytes())
+                .await?;
+            break;
+        }
+        let method = request.get("method").and_then(Value::as_str);
+        let response = match method {
+            Some("status") => match control.client.rpc().get_server_info().await {
+                Ok(info) => json!({ "result": {
+                    "network": info.network,
+                    "tip": info.tip.height,
+                    "headers": info.chain.headers,
+                    "ready": info.ready,
+                    "wallets": watched_wallets(&*control.config.lock().await),
+                }}),
+                Err(e) => json!({ "error": e.to_string() }),
+            },
+            Some("renew") => {
+                let config = control.config.lock().await.clone();
+                match auto_renew(&control.client, &config).await {
+                    Ok(renewed) => json!({ "result": renewed }),
+                    Err(e) => json!({ "error": e }),
+                }
+            }
+            Some("stop") => {
+                _ = control.stop.send(());
+                json!({ "result": null })
+            }
+            _ => json!({ "error": "Unknown method" }),
+        };
+        writer
+            .write_all(format!("{}\n", response).as_bytes())
+            .await?;
+        if method == Some("stop") {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Looks for new events in the watched wallets, printing them and posting them to the
+/// webhooks configured for them. Failed posts wait in `queue` for their retry
+async fn watch(client: &Client, config: &Config, watcher: &mut Watcher, queue: &mut Vec<Delivery>) {
+    let rpc = client.rpc();
+    if let Ok(info) = rpc.get_server_info().await {
+        for wallet in watched_wallets(config) {
+            let mut events = Vec::new();
+            if let Ok(spaces) = rpc.wallet_list_spaces(&wallet).await {
+                events.extend(watcher.spaces(&wallet, info.tip.height, &spaces));
+            }
+            if let Ok(transactions) = rpc
+                .wallet_list_transactions(&wallet, TRANSACTIONS_WATCHED, 0)
+                .await
+            {
+                events.extend(watcher.transactions(&wallet, &transactions));
+            }
+            for event in events {
+                let value = json!(event);
+                println!("{}: {}", event.name(), value);
+                queue.extend(Delivery::for_event(&config.webhooks, event.name(), &value));
+            }
+        }
+    }
+    let (due, waiting): (Vec<_>, Vec<_>) = std::mem::take(queue)
+        .into_iter()
+        .partition(Delivery::is_due);
+    *queue = waiting;
+    for delivery in due {
+        if let Err((retry, err)) = delivery.send().await {
+            eprintln!("{}", err);
+            queue.extend(retry);
+        }
+    }
+}
+
+/// Asks Akron running headless with the data directory to stop, through its control
+/// socket at `control_addr`
+pub fn stop(data_dir: &Path, control_addr: &str) -> Result<(), String> {
+    use std::io::{BufRead, Write};
+    let token = std::fs::read_to_string(data_dir.join(CONTROL_TOKEN_FILE))
+        .map_err(|e| format!("Could not read the control token: {}", e))?;
+    let addr: SocketAddr = control_addr
+        .parse()
+        .map_err(|_| format!("Invalid control address {}", control_addr))?;
+    let mut stream = std::net::TcpStream::connect_timeout(&addr, CONTROL_TIMEOUT)
+        .map_err(|e| format!("Could not connect to {}: {}", addr, e))?;
+    stream
+        .set_read_timeout(Some(CONTROL_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    writeln!(
+        stream,
+        "{}",
+        json!({ "token": token.trim(), "method": "stop" })
+    )
+    .map_err(|e| e.to_string())?;
+    let mut reply = String::new();
+    std::io::BufReader::new(stream)
+        .read_line(&mut reply)
+        .map_err(|e| e.to_string())?;
+    let reply: Value = serde_json::from_str(&reply).unwrap_or_default();
+    match reply.get("error") {
+        Some(error) => Err(format!("Could not stop Akron: {}", error)),
+        None => Ok(()),
+    }
+}
+
+/// Runs the configured backend and watches the wallets without the UI until stopped
+/// through the control socket or with Ctrl-C
+pub fn run(mut config: Config, control_addr: &str) -> Result<(), String> {
+    let backend_config = config
+        .backend
+        .clone()
+        .ok_or_else(|| "No backend configured, set one up in the app first".to_string())?;
+    let control_addr: SocketAddr = control_addr
+        .parse()
+        .map_err(|_| format!("Invalid control address {}", control_addr))?;
+    // the socket can stop the services and spend from the wallets
+    if !control_addr.ip().is_loopback() {
+        return Err(format!(
+            "The control socket can only listen on a loopback address, not {}",
+            control_addr
+        ));
+    }
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+    runtime.block_on(async move {
+        let listener = TcpListener::bind(control_addr)
+            .await
+            .map_err(|e| format!("Could not listen on {}: {}", control_addr, e))?;
+        let token_path = config.data_dir().join(CONTROL_TOKEN_FILE);
+        let token = automation::write_token(&token_path)
+            .map_err(|e| format!("Could not write {}: {}", token_path.display(), e))?;
+        let (client, backend_config) = Client::create(
+            config.data_dir().to_path_buf(),
+            backend_config,
+            config.proxy.clone(),
+            None,
+            false,
+        )
+        .await?;
+        config.backend = Some(backend_config);
+        config.save();
+        for wallet in watched_wallets(&config) {
+            if let Err(e) = client.rpc().wallet_load(&wallet).await {
+                eprintln!("Could not load wallet {}: {}", wallet, e);
+            }
+        }
+        println!(
+            "Running headless, control socket on {} with the token in {}",
+            control_addr,
+            token_path.display()
+        );
+
+        let (stop, mut stopped) = broadcast::channel(1);
+        let control = Arc::new(Control {
+            client,
+            config: Mutex::new(config),
+            token,
+            stop,
+        });
+        let mut watcher = Watcher::default();
+        let mut webhook_queue = Vec::new();
+        let mut watch_tick = tokio::time::interval(WATCH_INTERVAL);
+        let mut renew_tick = tokio::time::interval(RENEW_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = watch_tick.tick() => {
+                    let config = control.config.lock().await.clone();
+                    watch(&control.client, &config, &mut watcher, &mut webhook_queue).await;
+                }
+                _ = renew_tick.tick() => {
+                    let config = control.config.lock().await.clone();
+                    if let Err(e) = auto_renew(&control.client, &config).await {
+                        eprintln!("Auto renew failed: {}", e);
+                    }
+                }
+                Ok((stream, _)) = listener.accept() => {
+                    let control = control.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_control(stream, control).await {
+                            eprintln!("Control connection failed: {}", e);
+                        }
+                    });
+                }
+                _ = tokio::signal::ctrl_c() => break,
+                _ = stopped.recv() => break,
+            }
+        }
+        println!("Stopping services");
+        control.client.shutdown_services().await;
+        _ = std::fs::remove_file(&token_path);
+        Ok(())
+    })
+}
@@ -1,3 +1,4 @@
+use spaces_client::config::ExtendedNetwork;
 pub use spaces_protocol::slabel::SLabel;
 pub use spaces_wallet::{
     bitcoin::{Amount, FeeRate},
@@ -78,6 +79,16 @@ pub fn format_amount_number(mut n: u64) -> String {
     result
 }
 
+/// Size in MB, or GB from 1000 MB
+pub fn format_bytes(bytes: u64) -> String {
+    let mb = bytes as f64 / 1_000_000.0;
+    if mb < 1000.0 {
+        format!("{:.1} MB", mb)
+    } else {
+        format!("{:.2} GB", mb / 1000.0)
+    }
+}
+
 pub fn format_amount(amount: crate::helpers::Amount) -> String {
     format_amount_number(amount.to_sat())
 }
@@ -132,3 +143,36 @@ pub fn height_to_past_est(block_height: u32, tip_height: u32) -> String {
     let days = (remaining_blocks + 72) / 144;
     format!("{} days ago", days)
 }
+
+/// Days since 1970-01-01 of a `YYYY-MM-DD` date
+fn days_from_date(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-');
+    let y: i64 = parts.next()?.parse().ok()?;
+    let m: i64 = parts.next()?.parse().ok()?;
+    let d: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return None;
+    }
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146_097 + doe - 719_468)
+}
+
+/// Accepts a block height or a `YYYY-MM-DD` date, dates are estimated from a known block
+/// and moved a week earlier to account for block time variance
+pub fn height_from_str(s: &str, network: ExtendedNetwork) -> Option<u32> {
+    if let Ok(height) = s.parse() {
+        return Some(height);
+    }
+    let (reference_height, reference_time): (i64, i64) = match network {
+        ExtendedNetwork::Mainnet => (840_000, 1_713_571_767),
+        ExtendedNetwork::Testnet4 => (0, 1_714_777_860),
+        _ => return None,
+    };
+    let time = days_from_date(s)? * 86_400;
+    let height = reference_height + (time - reference_time) / 600 - 7 * 144;
+    Some(height.clamp(0, u32::MAX as i64) as u32)
+}
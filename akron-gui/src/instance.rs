@@ -0,0 +1,141 @@
+use iced::futures::{SinkExt, Stream};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Locked by the instance using the data directory for as long as it runs, the OS
+/// releases the lock when it exits
+const LOCK_FILE: &str = "akron.lock";
+/// Address the instance using the data directory answers focus requests on, kept in it
+const INSTANCE_FILE: &str = "akron.instance";
+const FOCUS_REQUEST: &str = "focus";
+/// Answer to a focus request, tells Akron apart from another program on a reused port
+const FOCUS_REPLY: &str = "akron";
+/// Answer of Akron running headless, followed by the address of its control socket
+const HEADLESS_REPLY: &str = "akron-headless";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Requests to show the window, taken by the subscription of the GUI
+static FOCUS_REQUESTS: Mutex<Option<mpsc::UnboundedReceiver<()>>> = Mutex::new(None);
+/// Lock of the claimed data directory, held until the process exits
+static LOCK: OnceLock<File> = OnceLock::new();
+
+/// Why the data directory couldn't be claimed
+#[derive(Debug)]
+pub enum ClaimError {
+    /// Akron runs headless with it, e.g. as the background service, with the address of
+    /// its control socket
+    Headless(String),
+    Other(String),
+}
+
+impl std::fmt::Display for ClaimError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Headless(_) => f.write_str("Akron is running in the background"),
+            Self::Other(e) => f.write_str(e),
+        }
+    }
+}
+
+/// Asks the instance at the address in `path` to show its window, returning its answer
+fn focus_running(path: &Path) -> Option<String> {
+    let focus = || -> std::io::Result<String> {
+        let addr = std::fs::read_to_string(path)?
+            .trim()
+            .parse()
+            .map_err(std::io::Error::other)?;
+        let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+        stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+        writeln!(stream, "{}", FOCUS_REQUEST)?;
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        Ok(reply.trim().to_string())
+    };
+    focus().ok()
+}
+
+/// Claims the data directory for this process by locking it, so the services aren't
+/// started twice. When another Akron already uses it, that one is asked to show its
+/// window instead. `headless_control` is the control socket of a headless run, given to
+/// the app when it's opened on the same data directory
+pub fn claim(data_dir: &Path, headless_control: Option<&str>) -> Result<(), ClaimError> {
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(data_dir.join(LOCK_FILE))
+        .map_err(|e| ClaimError::Other(e.to_string()))?;
+    match lock.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            // may not answer yet when it was just started
+            let reply = focus_running(&data_dir.join(INSTANCE_FILE));
+            return Err(
+                match reply
+                    .as_deref()
+                    .and_then(|reply| reply.strip_prefix(HEADLESS_REPLY))
+                {
+                    Some(control) => ClaimError::Headless(control.trim().to_string()),
+                    None => ClaimError::Other(format!(
+                        "Akron is already running with the data directory {}",
+                        data_dir.display()
+                    )),
+                },
+            );
+        }
+        Err(TryLockError::Error(e)) => {
+            return Err(ClaimError::Other(format!(
+                "Could not lock {}: {}",
+                data_dir.display(),
+                e
+            )))
+        }
+    }
+    _ = LOCK.set(lock);
+
+    let path = data_dir.join(INSTANCE_FILE);
+    let reply = match headless_control {
+        Some(control) => format!("{} {}", HEADLESS_REPLY, control),
+        None => FOCUS_REPLY.to_string(),
+    };
+    let listener =
+        TcpListener::bind("127.0.0.1:0").map_err(|e| ClaimError::Other(e.to_string()))?;
+    let addr = listener
+        .local_addr()
+        .map_err(|e| ClaimError::Other(e.to_string()))?;
+    std::fs::write(&path, addr.to_string()).map_err(|e| ClaimError::Other(e.to_string()))?;
+
+    // answered on a thread of its own, so headless runs are found as well
+    let (sender, receiver) = mpsc::unbounded_channel();
+    *FOCUS_REQUESTS.lock().unwrap() = Some(receiver);
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            _ = stream.set_read_timeout(Some(CONNECT_TIMEOUT));
+            let mut line = String::new();
+            let read = BufReader::new(Read::by_ref(&mut stream).take(64)).read_line(&mut line);
+            if read.is_ok() && line.trim() == FOCUS_REQUEST {
+                _ = writeln!(stream, "{}", reply);
+                _ = sender.send(());
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Fires whenever another launch with the same data directory asks to be shown
+pub fn focus_requests() -> impl Stream<Item = ()> {
+    iced::stream::channel(1, |mut output| async move {
+        let requests = FOCUS_REQUESTS.lock().unwrap().take();
+        let Some(mut requests) = requests else {
+            return;
+        };
+        while requests.recv().await.is_some() {
+            _ = output.send(()).await;
+        }
+    })
+}
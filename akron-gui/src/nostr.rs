@@ -0,0 +1,362 @@
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use spaces_wallet::bitcoin::{
+    hashes::{sha256, Hash},
+    secp256k1::{schnorr::Signature, Message, Secp256k1, XOnlyPublicKey},
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    client_async_tls, connect_async, tungstenite::Message as WsMessage, MaybeTlsStream,
+    WebSocketStream,
+};
+
+use crate::proxy;
+
+pub use spaces_wallet::nostr::NostrEvent;
+
+pub const DEFAULT_RELAYS: [&str; 3] = [
+    "wss://relay.damus.io",
+    "wss://nos.lol",
+    "wss://relay.primal.net",
+];
+
+/// Parameterized replaceable event kind used for space listings,
+/// the `d` tag holds the space name so a newer listing replaces the old one
+pub const LISTING_KIND: u32 = 30_889;
+
+/// Regular event kind used for offers and counter-offers on a space,
+/// the `s` tag holds the space name and `e`/`p` point at the offer being countered
+pub const OFFER_KIND: u32 = 1_889;
+
+/// Ephemeral event kind used to sign free-text messages with a space key,
+/// relays don't store these so a signed message can't be replayed as a note
+pub const MESSAGE_KIND: u32 = 21_889;
+
+const RELAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn is_relay_url(s: &str) -> bool {
+    (s.starts_with("wss://") || s.starts_with("ws://")) && s.len() > 6
+}
+
+pub fn event_tag<'a>(event: &'a Value, name: &str) -> Option<&'a str> {
+    event
+        .get("tags")?
+        .as_array()?
+        .iter()
+        .filter_map(|tag| tag.as_array())
+        .find(|tag| tag.first().and_then(Value::as_str) == Some(name))
+        .and_then(|tag| tag.get(1))
+        .and_then(Value::as_str)
+}
+
+/// Builds an unsigned event, the public key is filled in by the wallet when signing
+pub fn unsigned_event(kind: u32, content: String, tags: Vec<Vec<String>>) -> NostrEvent {
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    serde_json::from_value(json!({
+        "pubkey": "",
+        "created_at": created_at,
+        "kind": kind,
+        "tags": tags,
+        "content": content,
+    }))
+    .expect("valid event")
+}
+
+pub fn listing_event(space: &str, price: u64, content: String) -> NostrEvent {
+    unsigned_event(
+        LISTING_KIND,
+        content,
+        vec![
+            vec!["d".to_string(), space.to_string()],
+            vec!["price".to_string(), price.to_string()],
+        ],
+    )
+}
+
+pub fn listings_filter() -> Value {
+    json!({ "kinds": [LISTING_KIND], "limit": 200 })
+}
+
+pub fn message_event(space: &str, message: String) -> NostrEvent {
+    unsigned_event(
+        MESSAGE_KIND,
+        message,
+        vec![vec!["space".to_string(), space.to_string()]],
+    )
+}
+
+/// Builds a kind-0 metadata event, `profile` holds the fields to publish
+pub fn profile_event(profile: serde_json::Map<String, Value>) -> NostrEvent {
+    unsigned_event(0, Value::Object(profile).to_string(), Vec::new())
+}
+
+/// Replaceable event kind of Akron listing the keys a space authorizes to post on its
+/// behalf, each `key` tag holds a public key and the conditions of the authorization,
+/// e.g. `created_at<1767225600`. It's not NIP-26 delegation, other clients don't know
+/// the kind and events of the keys don't carry a proof. Publishing a new list without a
+/// key revokes it
+pub const AUTHORIZED_KEYS_KIND: u32 = 10_889;
+
+pub fn authorized_keys_event(keys: &[(String, String)]) -> NostrEvent {
+    unsigned_event(
+        AUTHORIZED_KEYS_KIND,
+        String::new(),
+        keys.iter()
+            .map(|(pubkey, conditions)| vec!["key".to_string(), pubkey.clone(), conditions.clone()])
+            .collect(),
+    )
+}
+
+pub fn authorized_keys_from_event(event: &Value) -> Vec<(String, String)> {
+    event
+        .get("tags")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| {
+            let tag = tag.as_array()?;
+            if tag.first()?.as_str()? != "key" {
+                return None;
+            }
+            Some((
+                tag.get(1)?.as_str()?.to_string(),
+                tag.get(2)
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Accepts a hex or `npub` encoded public key and returns it as hex
+pub fn parse_pubkey(s: &str) -> Option<String> {
+    let bytes = if s.starts_with("npub1") {
+        let (hrp, data) = spaces_wallet::bitcoin::bech32::decode(s).ok()?;
+        if hrp.as_str() != "npub" {
+            return None;
+        }
+        data
+    } else {
+        hex::decode(s).ok()?
+    };
+    XOnlyPublicKey::from_slice(&bytes)
+        .ok()
+        .map(|pubkey| pubkey.to_string())
+}
+
+/// Newest replaceable event of `kind` published by `pubkey`
+pub fn latest_filter(pubkey: &str, kind: u32) -> Value {
+    json!({ "kinds": [kind], "authors": [pubkey], "limit": 1 })
+}
+
+/// Builds an offer for `space` signed by the `from` space, `reply_to` is the id and
+/// public key of the offer being countered
+pub fn offer_event(
+    space: &str,
+    price: u64,
+    from: &str,
+    reply_to: Option<(&str, &str)>,
+) -> NostrEvent {
+    let mut tags = vec![
+        vec!["s".to_string(), space.to_string()],
+        vec!["price".to_string(), price.to_string()],
+        vec!["from".to_string(), from.to_string()],
+    ];
+    if let Some((id, pubkey)) = reply_to {
+        tags.push(vec!["e".to_string(), id.to_string()]);
+        tags.push(vec!["p".to_string(), pubkey.to_string()]);
+    }
+    unsigned_event(OFFER_KIND, String::new(), tags)
+}
+
+/// Offers made on `spaces` and counter-offers addressed to `pubkeys`
+pub fn offers_filters(spaces: &[String], pubkeys: &[String]) -> Vec<Value> {
+    vec![
+        json!({ "kinds": [OFFER_KIND], "#s": spaces, "limit": 200 }),
+        json!({ "kinds": [OFFER_KIND], "#p": pubkeys, "limit": 200 }),
+    ]
+}
+
+fn event_hash(event: &Value) -> Option<[u8; 32]> {
+    let serialized = json!([
+        0,
+        event.get("pubkey")?,
+        event.get("created_at")?,
+        event.get("kind")?,
+        event.get("tags")?,
+        event.get("content")?,
+    ])
+    .to_string();
+    Some(sha256::Hash::hash(serialized.as_bytes()).to_byte_array())
+}
+
+/// Checks that the event id matches its contents and that it's signed by `pubkey`
+pub fn verify_event(event: &Value) -> bool {
+    let verify = || -> Option<bool> {
+        let hash = event_hash(event)?;
+        if event.get("id")?.as_str()? != hex::encode(hash) {
+            return Some(false);
+        }
+        let pubkey =
+            XOnlyPublicKey::from_slice(&hex::decode(event.get("pubkey")?.as_str()?).ok()?).ok()?;
+        let sig = Signature::from_slice(&hex::decode(event.get("sig")?.as_str()?).ok()?).ok()?;
+        Some(
+            Secp256k1::verification_only()
+                .verify_schnorr(&sig, &Message::from_digest(hash), &pubkey)
+                .is_ok(),
+        )
+    };
+    verify().unwrap_or(false)
+}
+
+/// Queries the relays with the given filters and returns verified events, newest first.
+/// Fails when no relay answered, an empty list means the relays have no such events
+pub async fn fetch_events(relays: Vec<String>, filters: Vec<Value>) -> Result<Vec<Value>, String> {
+    let results = futures_util::future::join_all(
+        relays
+            .iter()
+            .map(|relay| fetch_from_relay(relay.as_str(), &filters)),
+    )
+    .await;
+
+    let mut answered = Vec::new();
+    let mut errors = Vec::new();
+    for (relay, result) in relays.iter().zip(results) {
+        match result {
+            Ok(events) => answered.push(events),
+            Err(err) => errors.push(format!("{}: {}", relay, err)),
+        }
+    }
+    if answered.is_empty() {
+        return Err(if errors.is_empty() {
+            "No relays configured".to_string()
+        } else {
+            format!("No relay answered. {}", errors.join(", "))
+        });
+    }
+
+    let mut events: Vec<Value> = Vec::new();
+    for event in answered.into_iter().flatten() {
+        if !verify_event(&event) || events.iter().any(|e| e.get("id") == event.get("id")) {
+            continue;
+        }
+        events.push(event);
+    }
+    events.sort_by_key(|event| {
+        std::cmp::Reverse(event.get("created_at").and_then(Value::as_u64).unwrap_or(0))
+    });
+    Ok(events)
+}
+
+/// Opens a websocket to the relay, through the proxy for third party services when set
+async fn connect_relay(relay: &str) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, String> {
+    let Some(proxy) = proxy::web_proxy_addr() else {
+        let (ws, _) = connect_async(relay).await.map_err(|e| e.to_string())?;
+        return Ok(ws);
+    };
+    let url = reqwest::Url::parse(relay).map_err(|e| e.to_string())?;
+    let host = url.host_str().ok_or("Relay URL has no host")?;
+    let port = url
+        .port()
+        .unwrap_or(if url.scheme() == "wss" { 443 } else { 80 });
+    let stream = proxy::socks5_connect(&proxy, host, port)
+        .await
+        .map_err(|e| format!("Proxy: {}", e))?;
+    let (ws, _) = client_async_tls(relay, stream)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(ws)
+}
+
+async fn fetch_from_relay(relay: &str, filters: &[Value]) -> Result<Vec<Value>, String> {
+    tokio::time::timeout(RELAY_TIMEOUT, async {
+        let mut ws = connect_relay(relay).await?;
+        let subscription = "akron";
+        let mut request = vec![json!("REQ"), json!(subscription)];
+        request.extend(filters.iter().cloned());
+        ws.send(WsMessage::text(Value::Array(request).to_string()))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut events = Vec::new();
+        while let Some(message) = ws.next().await {
+            let WsMessage::Text(text) = message.map_err(|e| e.to_string())? else {
+                continue;
+            };
+            let Ok(value) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+            match value.get(0).and_then(Value::as_str) {
+                Some("EVENT") => {
+                    if let Some(event) = value.get(2) {
+                        events.push(event.clone());
+                    }
+                }
+                Some("EOSE") | Some("CLOSED") => break,
+                _ => {}
+            }
+        }
+        let _ = ws
+            .send(WsMessage::text(json!(["CLOSE", subscription]).to_string()))
+            .await;
+        let _ = ws.close(None).await;
+        Ok(events)
+    })
+    .await
+    .map_err(|_| format!("{}: timed out", relay))?
+}
+
+pub type PublishResult = (String, Result<(), String>);
+
+/// Sends the event to every relay and collects the per-relay outcome
+pub async fn publish_event(relays: Vec<String>, event: NostrEvent) -> Vec<PublishResult> {
+    let event = serde_json::to_value(&event).expect("valid event");
+    futures_util::future::join_all(relays.into_iter().map(|relay| {
+        let event = event.clone();
+        async move {
+            let result = publish_to_relay(&relay, &event).await;
+            (relay, result)
+        }
+    }))
+    .await
+}
+
+async fn publish_to_relay(relay: &str, event: &Value) -> Result<(), String> {
+    tokio::time::timeout(RELAY_TIMEOUT, async {
+        let mut ws = connect_relay(relay).await?;
+        ws.send(WsMessage::text(json!(["EVENT", event]).to_string()))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut result = Err("Connection closed by relay".to_string());
+        while let Some(message) = ws.next().await {
+            let WsMessage::Text(text) = message.map_err(|e| e.to_string())? else {
+                continue;
+            };
+            let Ok(value) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+            if value.get(0).and_then(Value::as_str) == Some("OK") && value.get(1) == event.get("id")
+            {
+                result = if value.get(2).and_then(Value::as_bool) == Some(true) {
+                    Ok(())
+                } else {
+                    Err(value
+                        .get(3)
+                        .and_then(Value::as_str)
+                        .unwrap_or("Rejected by relay")
+                        .to_string())
+                };
+                break;
+            }
+        }
+        let _ = ws.close(None).await;
+        result
+    })
+    .await
+    .map_err(|_| "Timed out".to_string())?
+}
@@ -0,0 +1,148 @@
+use crate::widget::{
+    base::base_container,
+    form::{submit_button, text_input},
+    text::{error_block, text_big, text_bold, text_monospace},
+};
+use iced::{
+    widget::{button, column, row, text, Column},
+    Center, Element, Fill, Shrink,
+};
+
+/// Calls kept in the console, newest first
+const HISTORY_SIZE: usize = 20;
+
+#[derive(Debug, Clone)]
+struct Call {
+    method: String,
+    params: String,
+    result: Result<String, String>,
+}
+
+#[derive(Debug, Default)]
+pub struct State {
+    method: String,
+    params: String,
+    running: bool,
+    history: Vec<Call>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    MethodInput(String),
+    ParamsInput(String),
+    SendPress,
+    Response(String, String, Result<String, String>),
+    ReusePress(usize),
+    ClearPress,
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    None,
+    Request { method: String, params: String },
+}
+
+impl State {
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::MethodInput(method) => {
+                if !method.contains(char::is_whitespace) {
+                    self.method = method;
+                }
+                Action::None
+            }
+            Message::ParamsInput(params) => {
+                self.params = params;
+                Action::None
+            }
+            Message::SendPress => {
+                self.running = true;
+                Action::Request {
+                    method: self.method.clone(),
+                    params: self.params.clone(),
+                }
+            }
+            Message::Response(method, params, result) => {
+                self.running = false;
+                self.history.insert(
+                    0,
+                    Call {
+                        method,
+                        params,
+                        result,
+                    },
+                );
+                self.history.truncate(HISTORY_SIZE);
+                Action::None
+            }
+            Message::ReusePress(index) => {
+                if let Some(call) = self.history.get(index) {
+                    self.method = call.method.clone();
+                    self.params = call.params.clone();
+                }
+                Action::None
+            }
+            Message::ClearPress => {
+                self.history.clear();
+                Action::None
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        base_container(
+            column![
+                text_big("RPC console"),
+                text(
+                    "Calls go straight to spaced, wallet methods take the wallet name as their \
+                     first parameter. Be careful, nothing is asked before sending.",
+                )
+                .size(14),
+                row![
+                    text_input("getserverinfo", &self.method)
+                        .width(250)
+                        .on_input(Message::MethodInput),
+                    text_input("[] or {} of JSON params", &self.params)
+                        .width(Fill)
+                        .on_input(Message::ParamsInput)
+                        .on_submit_maybe(
+                            (!self.method.is_empty() && !self.running)
+                                .then_some(Message::SendPress)
+                        ),
+                    submit_button(
+                        text(if self.running { "Sending" } else { "Send" }).align_x(Center),
+                        (!self.method.is_empty() && !self.running).then_some(Message::SendPress),
+                    )
+                    .width(Shrink),
+                ]
+                .spacing(10),
+            ]
+            .push_maybe((!self.history.is_empty()).then(|| {
+                button(text("Clear").size(14))
+                    .style(button::text)
+                    .on_press(Message::ClearPress)
+            }))
+            .push(
+                Column::with_children(self.history.iter().enumerate().map(|(index, call)| {
+                    column![
+                        row![
+                            text_bold(format!("{} {}", call.method, call.params)).width(Fill),
+                            button(text("Reuse").size(14))
+                                .style(button::text)
+                                .on_press(Message::ReusePress(index)),
+                        ]
+                        .align_y(Center),
+                        match &call.result {
+                            Ok(result) => text_monospace(result).size(14).into(),
+                            Err(err) => error_block(Some(err)),
+                        },
+                    ]
+                    .spacing(10)
+                    .into()
+                }))
+                .spacing(30),
+            )
+            .spacing(20),
+        )
+    }
+}
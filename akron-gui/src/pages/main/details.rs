@@ -0,0 +1,148 @@
+use crate::{
+    client::*,
+    widget::{
+        base::base_container,
+        icon::{button_icon, Icon},
+        text::{text_big, text_bold, text_monospace},
+    },
+};
+use iced::{
+    widget::{button, column, row, text, Column},
+    Center, Element, Fill,
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Default)]
+pub struct State {
+    /// Index of the next address of each receive descriptor, found from the address
+    /// spaced hands out
+    next_indices: HashMap<String, u32>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    BackPress,
+    CopyPress(String),
+    /// Descriptor with the index of its next address, `None` when it wasn't found
+    NextIndex(String, Option<u32>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    ShowSettings,
+    WriteClipboard(String),
+    None,
+}
+
+/// Path of the keys in a descriptor taken from its key origin,
+/// e.g. `m/86'/0'/0'/0/*` for `tr([fingerprint/86'/0'/0']xpub.../0/*)`
+fn derivation_path(descriptor: &str) -> Option<String> {
+    let (origin, rest) = descriptor.split_once('[')?.1.split_once(']')?;
+    let account = origin.split_once('/').map_or("", |(_, path)| path);
+    let children: String = rest
+        .chars()
+        .skip_while(|c| *c != '/')
+        .take_while(|c| *c != ')')
+        .collect();
+    Some(format!("m/{}{}", account, children))
+}
+
+fn descriptor_kind(descriptor: &DescriptorInfo) -> &'static str {
+    match (descriptor.spaces, descriptor.internal) {
+        (true, false) => "Spaces receive",
+        (true, true) => "Spaces change",
+        (false, false) => "Coins receive",
+        (false, true) => "Coins change",
+    }
+}
+
+fn info_row<'a>(label: &'a str, value: String) -> Element<'a, Message> {
+    row![text_bold(label).width(200), text(value)].into()
+}
+
+impl State {
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::BackPress => Action::ShowSettings,
+            Message::CopyPress(s) => Action::WriteClipboard(s),
+            Message::NextIndex(descriptor, index) => {
+                match index {
+                    Some(index) => self.next_indices.insert(descriptor, index),
+                    None => self.next_indices.remove(&descriptor),
+                };
+                Action::None
+            }
+        }
+    }
+
+    pub fn view<'a>(
+        &self,
+        label: &'a str,
+        info: Option<&'a WalletInfoWithProgress>,
+        server_info: Option<&'a ServerInfo>,
+    ) -> Element<'a, Message> {
+        let header = row![
+            button_icon(Icon::ChevronLeft)
+                .style(button::text)
+                .on_press(Message::BackPress),
+            text_big(label),
+        ]
+        .align_y(Center);
+
+        let Some(info) = info.map(|info| &info.info) else {
+            return base_container(column![header, text("Loading")].spacing(40)).into();
+        };
+
+        base_container(
+            column![
+                header,
+                column![
+                    text_big("Sync"),
+                    info_row("Birthday", info.start_block.to_string()),
+                    info_row("Wallet tip", info.tip.to_string()),
+                ]
+                .push_maybe(server_info.map(|server_info| {
+                    column![
+                        info_row("Spaces tip", server_info.tip.height.to_string()),
+                        info_row("Chain blocks", server_info.chain.blocks.to_string()),
+                        info_row("Chain headers", server_info.chain.headers.to_string()),
+                    ]
+                    .spacing(10)
+                }))
+                .spacing(10),
+                column![
+                    text_big("Public descriptors"),
+                    Column::with_children(info.descriptors.iter().map(|descriptor| {
+                        column![
+                            row![text_bold(descriptor_kind(descriptor))]
+                                .push_maybe(
+                                    derivation_path(&descriptor.descriptor)
+                                        .map(|path| text_monospace(path).size(14))
+                                )
+                                .push_maybe(
+                                    self.next_indices
+                                        .get(&descriptor.descriptor)
+                                        .map(|index| text(format!("Next address index {}", index)))
+                                )
+                                .spacing(20)
+                                .align_y(Center),
+                            row![
+                                text_monospace(&descriptor.descriptor).size(12).width(Fill),
+                                button_icon(Icon::Copy)
+                                    .on_press(Message::CopyPress(descriptor.descriptor.clone())),
+                            ]
+                            .spacing(5)
+                            .align_y(Center),
+                        ]
+                        .spacing(5)
+                        .into()
+                    }))
+                    .spacing(20),
+                ]
+                .spacing(10),
+            ]
+            .spacing(40),
+        )
+        .into()
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{
+    diagnostics::{versions, Check},
+    widget::{
+        base::base_container,
+        form::STANDARD_PADDING,
+        icon::{button_icon, text_icon, Icon},
+        text::{error_block, text_big, text_bold},
+    },
+};
+use iced::{
+    widget::{button, column, row, text, Column},
+    Center, Element, Fill,
+};
+
+#[derive(Debug, Default)]
+pub struct State {
+    checks: Option<Vec<Check>>,
+    running: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    BackPress,
+    RunPress,
+    Finished(Vec<Check>),
+    CopyReportPress,
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    None,
+    ShowSettings,
+    Run,
+    CopyReport(Vec<Check>),
+}
+
+impl State {
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::BackPress => Action::ShowSettings,
+            Message::RunPress => {
+                self.running = true;
+                Action::Run
+            }
+            Message::Finished(checks) => {
+                self.running = false;
+                self.checks = Some(checks);
+                Action::None
+            }
+            Message::CopyReportPress => match self.checks.as_ref() {
+                Some(checks) => Action::CopyReport(checks.clone()),
+                None => Action::None,
+            },
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        base_container(
+            column![
+                row![
+                    button_icon(Icon::ChevronLeft)
+                        .style(button::text)
+                        .on_press(Message::BackPress),
+                    text_big("Diagnostics"),
+                ]
+                .align_y(Center),
+                Column::with_children(versions().into_iter().map(|(name, version)| {
+                    row![text_bold(name).width(250), text(version)].into()
+                }))
+                .spacing(10),
+                row![
+                    button(
+                        text(if self.running {
+                            "Running checks"
+                        } else {
+                            "Run checks"
+                        })
+                        .align_x(Center)
+                        .width(Fill)
+                    )
+                    .style(button::secondary)
+                    .padding(STANDARD_PADDING)
+                    .width(Fill)
+                    .on_press_maybe((!self.running).then_some(Message::RunPress)),
+                    button(text("Copy report").align_x(Center).width(Fill))
+                        .style(button::secondary)
+                        .padding(STANDARD_PADDING)
+                        .width(Fill)
+                        .on_press_maybe(self.checks.as_ref().map(|_| Message::CopyReportPress)),
+                ]
+                .spacing(10),
+            ]
+            .push_maybe(self.checks.as_ref().map(|checks| {
+                Column::with_children(checks.iter().map(|check| {
+                    column![row![
+                        text_icon(match check.result {
+                            Ok(_) => Icon::CircleDot,
+                            Err(_) => Icon::Circle,
+                        }),
+                        text_bold(&check.name)
+                    ]
+                    .spacing(10)
+                    .align_y(Center)]
+                    .push(match &check.result {
+                        Ok(details) => text(details).size(14).into(),
+                        Err(err) => error_block(Some(err)),
+                    })
+                    .spacing(5)
+                    .into()
+                }))
+                .spacing(20)
+            }))
+            .push(
+                text(
+                    "The copied report leaves out paths, addresses of your nodes and wallet \
+                     names so it can be shared when asking for help.",
+                )
+                .size(14),
+            )
+            .spacing(40),
+        )
+    }
+}
@@ -1,4 +1,6 @@
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::ops::Range;
 use std::str::FromStr;
 
 use crate::widget::base::{base_container, result_column};
@@ -18,15 +20,209 @@ use iced::border::rounded;
 use iced::{
     widget::{
         button, center, column, container, horizontal_rule, horizontal_space, row, scrollable,
-        text, Column, Row,
+        text, Column, Row, Space,
     },
     Center, Color, Element, Fill, FillPortion, Padding, Theme,
 };
 
+/// Height of a transaction row, fixed so the rows out of view can be left out and replaced
+/// by space of the same height
+const TX_ROW_HEIGHT: f32 = 84.0;
+/// Added to rows showing a label
+const TX_LABEL_HEIGHT: f32 = 23.0;
+const TX_ROW_SPACING: f32 = 10.0;
+/// Rows built above and below the viewport, so scrolling doesn't show them appearing
+const TX_ROW_OVERSCAN: usize = 5;
+
+/// Rows of the list in view when scrolled by `offset`, with the height of the rows left out
+/// before and after them
+fn visible_rows(heights: &[f32], offset: f32, viewport: f32) -> (Range<usize>, f32, f32) {
+    let mut top = 0.0;
+    let mut start = heights.len();
+    let mut end = heights.len();
+    for (i, height) in heights.iter().enumerate() {
+        if start == heights.len() && top + height >= offset {
+            start = i;
+        }
+        if top > offset + viewport {
+            end = i;
+            break;
+        }
+        top += height + TX_ROW_SPACING;
+    }
+    let rows = start.saturating_sub(TX_ROW_OVERSCAN)..(end + TX_ROW_OVERSCAN).min(heights.len());
+    // the column adds the spacing between the space and the rows
+    let space = |heights: &[f32]| {
+        (heights.iter().map(|h| h + TX_ROW_SPACING).sum::<f32>() - TX_ROW_SPACING).max(0.0)
+    };
+    let above = space(&heights[..rows.start]);
+    let below = space(&heights[rows.end..]);
+    (rows, above, below)
+}
+
+/// Amounts, block and events of `transaction`, also shown in a window of its own
+pub fn transaction_info<'a>(transaction: &'a TxInfo, tip_height: u32) -> Element<'a, Message> {
+    let event_row_with_space =
+        |action: &'static str, space: &'a str, amount: Option<Amount>| -> Row<'a, Message> {
+            let slabel = SLabel::from_str(space).unwrap();
+            row![
+                text(action),
+                button(text_monospace(space))
+                    .on_press(Message::SpacePress(slabel))
+                    .style(button::text)
+                    .padding(0)
+            ]
+            .push_maybe(amount.map(|amount| text(format_amount(amount))))
+        };
+
+    let event_row_with_string =
+        |action: &'static str, s: String| -> Row<'a, Message> { row![text(action), text(s)] };
+
+    let events_rows: Vec<Element<'a, Message>> = transaction
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            TxEvent {
+                kind: TxEventKind::Commit,
+                space,
+                ..
+            } => Some(event_row_with_space(
+                "Commit",
+                space.as_ref().unwrap(),
+                None,
+            )),
+            TxEvent {
+                kind: TxEventKind::Bidout,
+                details,
+                ..
+            } => Some(event_row_with_string(
+                "Bidout",
+                BidoutEventDetails::deserialize(details.as_ref().unwrap())
+                    .unwrap()
+                    .count
+                    .to_string(),
+            )),
+            TxEvent {
+                kind: TxEventKind::Open,
+                space,
+                details,
+                ..
+            } => Some(event_row_with_space(
+                "Open",
+                space.as_ref().unwrap(),
+                Some(
+                    OpenEventDetails::deserialize(details.as_ref().unwrap())
+                        .unwrap()
+                        .initial_bid,
+                ),
+            )),
+            TxEvent {
+                kind: TxEventKind::Bid,
+                space,
+                details,
+                ..
+            } => Some(event_row_with_space(
+                "Bid",
+                space.as_ref().unwrap(),
+                Some(
+                    BidEventDetails::deserialize(details.as_ref().unwrap())
+                        .unwrap()
+                        .current_bid,
+                ),
+            )),
+            TxEvent {
+                kind: TxEventKind::Register,
+                space,
+                ..
+            } => Some(event_row_with_space(
+                "Register",
+                space.as_ref().unwrap(),
+                None,
+            )),
+            TxEvent {
+                kind: TxEventKind::Transfer,
+                space,
+                ..
+            } => Some(event_row_with_space(
+                "Transfer",
+                space.as_ref().unwrap(),
+                None,
+            )),
+            TxEvent {
+                kind: TxEventKind::Renew,
+                space,
+                ..
+            } => Some(event_row_with_space("Renew", space.as_ref().unwrap(), None)),
+            TxEvent {
+                kind: TxEventKind::Send,
+                ..
+            } => Some(event_row_with_string(
+                "Send",
+                format_amount(
+                    SendEventDetails::deserialize(event.details.as_ref().unwrap())
+                        .unwrap()
+                        .amount,
+                ),
+            )),
+            TxEvent {
+                kind: TxEventKind::Buy,
+                space,
+                ..
+            } => Some(event_row_with_space("Buy", space.as_ref().unwrap(), None)),
+            TxEvent {
+                kind: TxEventKind::FeeBump,
+                ..
+            } => Some(event_row_with_string("Bump fee", String::new())),
+            _ => None,
+        })
+        .map(|row| row.spacing(10).into())
+        .collect();
+
+    container(
+        column![
+            text_bold("Info"),
+            text(format!("Sent: {}", format_amount(transaction.sent))),
+            text(format!("Received: {}", format_amount(transaction.received))),
+        ]
+        .push_maybe(
+            transaction
+                .fee
+                .map(|fee| text(format!("Fee: {}", format_amount(fee)))),
+        )
+        .push_maybe(transaction.block_height.map(|block_height| {
+            text(format!(
+                "Block: {} ({})",
+                block_height,
+                height_to_past_est(block_height, tip_height)
+            ))
+        }))
+        .push_maybe(if events_rows.is_empty() {
+            None
+        } else {
+            Some(text_bold("Events"))
+        })
+        .extend(events_rows)
+        .spacing(10)
+        .width(Fill),
+    )
+    .style(|t: &Theme| {
+        let t = t.extended_palette();
+        container::Style {
+            border: rounded(8).color(t.secondary.base.color).width(1),
+            ..container::Style::default()
+        }
+    })
+    .padding(40)
+    .into()
+}
+
 #[derive(Debug)]
 pub struct State {
     txid: Option<Txid>,
     transactions_limit: usize,
+    /// Scroll offset and height of the transactions list
+    txs_offset: f32,
+    txs_viewport: f32,
     fee_rate: String,
     error: Option<String>,
     tx_result: Option<TxResultWidget>,
@@ -37,6 +233,8 @@ impl Default for State {
         Self {
             txid: None,
             transactions_limit: 10,
+            txs_offset: 0.0,
+            txs_viewport: 1000.0,
             fee_rate: String::new(),
             error: None,
             tx_result: None,
@@ -49,8 +247,9 @@ pub enum Message {
     BackPress,
     TxidPress(Txid),
     CopyTxidPress(Txid),
+    DetachPress(Txid),
     SpacePress(SLabel),
-    TxsListScrolled(f32, usize),
+    TxsListScrolled(scrollable::Viewport, usize),
     FeeRateInput(String),
     BumpFeeSubmit,
     BumpFeeResult(Result<WalletResponse, String>),
@@ -62,6 +261,7 @@ pub enum Action {
     None,
     WriteClipboard(String),
     ShowSpace { slabel: SLabel },
+    Detach(Txid),
     GetTransactions,
     BumpFee { txid: Txid, fee_rate: FeeRate },
 }
@@ -94,7 +294,11 @@ impl State {
             }
             Message::SpacePress(slabel) => Action::ShowSpace { slabel },
             Message::CopyTxidPress(txid) => Action::WriteClipboard(txid.to_string()),
-            Message::TxsListScrolled(percentage, count) => {
+            Message::DetachPress(txid) => Action::Detach(txid),
+            Message::TxsListScrolled(viewport, count) => {
+                self.txs_offset = viewport.absolute_offset().y;
+                self.txs_viewport = viewport.bounds().height;
+                let percentage = viewport.relative_offset().y;
                 if percentage > 0.8 && count >= self.transactions_limit {
                     self.transactions_limit += (percentage * count as f32) as usize;
                     Action::GetTransactions
@@ -139,128 +343,10 @@ impl State {
         tip_height: u32,
         balance: Option<Amount>,
         transactions: &'a [TxInfo],
+        labels: &'a HashMap<Txid, String>,
     ) -> Element<'a, Message> {
         if let Some(txid) = self.txid.as_ref() {
             if let Some(transaction) = transactions.iter().find(|tx| &tx.txid == txid) {
-                let event_row_with_space = |action: &'static str,
-                                            space: &'a str,
-                                            amount: Option<Amount>|
-                 -> Row<'a, Message> {
-                    let slabel = SLabel::from_str(space).unwrap();
-                    row![
-                        text(action),
-                        button(text_monospace(space))
-                            .on_press(Message::SpacePress(slabel))
-                            .style(button::text)
-                            .padding(0)
-                    ]
-                    .push_maybe(amount.map(|amount| text(format_amount(amount))))
-                };
-
-                let event_row_with_string = |action: &'static str, s: String| -> Row<'a, Message> {
-                    row![text(action), text(s)]
-                };
-
-                let events_rows: Vec<Element<'a, Message>> = transaction
-                    .events
-                    .iter()
-                    .filter_map(|event| match event {
-                        TxEvent {
-                            kind: TxEventKind::Commit,
-                            space,
-                            ..
-                        } => Some(event_row_with_space(
-                            "Commit",
-                            space.as_ref().unwrap(),
-                            None,
-                        )),
-                        TxEvent {
-                            kind: TxEventKind::Bidout,
-                            details,
-                            ..
-                        } => Some(event_row_with_string(
-                            "Bidout",
-                            BidoutEventDetails::deserialize(details.as_ref().unwrap())
-                                .unwrap()
-                                .count
-                                .to_string(),
-                        )),
-                        TxEvent {
-                            kind: TxEventKind::Open,
-                            space,
-                            details,
-                            ..
-                        } => Some(event_row_with_space(
-                            "Open",
-                            space.as_ref().unwrap(),
-                            Some(
-                                OpenEventDetails::deserialize(details.as_ref().unwrap())
-                                    .unwrap()
-                                    .initial_bid,
-                            ),
-                        )),
-                        TxEvent {
-                            kind: TxEventKind::Bid,
-                            space,
-                            details,
-                            ..
-                        } => Some(event_row_with_space(
-                            "Bid",
-                            space.as_ref().unwrap(),
-                            Some(
-                                BidEventDetails::deserialize(details.as_ref().unwrap())
-                                    .unwrap()
-                                    .current_bid,
-                            ),
-                        )),
-                        TxEvent {
-                            kind: TxEventKind::Register,
-                            space,
-                            ..
-                        } => Some(event_row_with_space(
-                            "Register",
-                            space.as_ref().unwrap(),
-                            None,
-                        )),
-                        TxEvent {
-                            kind: TxEventKind::Transfer,
-                            space,
-                            ..
-                        } => Some(event_row_with_space(
-                            "Transfer",
-                            space.as_ref().unwrap(),
-                            None,
-                        )),
-                        TxEvent {
-                            kind: TxEventKind::Renew,
-                            space,
-                            ..
-                        } => Some(event_row_with_space("Renew", space.as_ref().unwrap(), None)),
-                        TxEvent {
-                            kind: TxEventKind::Send,
-                            ..
-                        } => Some(event_row_with_string(
-                            "Send",
-                            format_amount(
-                                SendEventDetails::deserialize(event.details.as_ref().unwrap())
-                                    .unwrap()
-                                    .amount,
-                            ),
-                        )),
-                        TxEvent {
-                            kind: TxEventKind::Buy,
-                            space,
-                            ..
-                        } => Some(event_row_with_space("Buy", space.as_ref().unwrap(), None)),
-                        TxEvent {
-                            kind: TxEventKind::FeeBump,
-                            ..
-                        } => Some(event_row_with_string("Bump fee", String::new())),
-                        _ => None,
-                    })
-                    .map(|row| row.spacing(10).into())
-                    .collect();
-
                 column![
                     row![
                         button(text_icon(Icon::ChevronLeft).size(20))
@@ -274,6 +360,10 @@ impl State {
                         button_icon(Icon::Copy)
                             .style(button::text)
                             .on_press(Message::CopyTxidPress(*txid)),
+                        horizontal_space(),
+                        button(text("Open in window").size(14))
+                            .style(button::text)
+                            .on_press(Message::DetachPress(*txid)),
                     ]
                     .padding(Padding {
                         top: 20.0,
@@ -286,44 +376,7 @@ impl State {
                     horizontal_rule(3),
                     base_container(
                         column![
-                            container(
-                                column![
-                                    text_bold("Info"),
-                                    text(format!("Sent: {}", format_amount(transaction.sent))),
-                                    text(format!(
-                                        "Received: {}",
-                                        format_amount(transaction.received)
-                                    )),
-                                ]
-                                .push_maybe(
-                                    transaction.fee.map(|fee| {
-                                        text(format!("Fee: {}", format_amount(fee)))
-                                    })
-                                )
-                                .push_maybe(transaction.block_height.map(|block_height| text(
-                                    format!(
-                                        "Block: {} ({})",
-                                        block_height,
-                                        height_to_past_est(block_height, tip_height)
-                                    )
-                                )))
-                                .push_maybe(if events_rows.is_empty() {
-                                    None
-                                } else {
-                                    Some(text_bold("Events"))
-                                })
-                                .extend(events_rows.into_iter())
-                                .spacing(10)
-                                .width(Fill),
-                            )
-                            .style(|t: &Theme| {
-                                let t = t.extended_palette();
-                                container::Style {
-                                    border: rounded(8).color(t.secondary.base.color).width(1),
-                                    ..container::Style::default()
-                                }
-                            })
-                            .padding(40),
+                            transaction_info(transaction, tip_height),
                             if transaction.block_height.is_some() {
                                 column![]
                             } else {
@@ -387,58 +440,84 @@ impl State {
                         let element: Element<'a, Message> = if transactions.is_empty() {
                             center(text("No transactions yet")).into()
                         } else {
+                            let heights: Vec<f32> = transactions
+                                .iter()
+                                .map(|transaction| {
+                                    if labels.contains_key(&transaction.txid) {
+                                        TX_ROW_HEIGHT + TX_LABEL_HEIGHT
+                                    } else {
+                                        TX_ROW_HEIGHT
+                                    }
+                                })
+                                .collect();
+                            let (rows, above, below) =
+                                visible_rows(&heights, self.txs_offset, self.txs_viewport);
                             scrollable(
-                                Column::from_iter(transactions.iter().map(|transaction| {
-                                    let block_height = transaction.block_height;
-                                    let txid = transaction.txid;
-                                    let txid_string = txid.to_string();
-                                    let event = transaction
-                                        .events
-                                        .iter()
-                                        .find(|event| event.space.is_some());
-                                    let bumped = transaction
-                                        .events
-                                        .iter()
-                                        .any(|event| event.kind == TxEventKind::FeeBump);
+                                Column::new()
+                                    .push_maybe((above > 0.0).then(|| Space::with_height(above)))
+                                    .extend(
+                                        transactions[rows.clone()].iter().zip(&heights[rows]).map(
+                                            |(transaction, &row_height)| {
+                                                let block_height = transaction.block_height;
+                                                let txid = transaction.txid;
+                                                let txid_string = txid.to_string();
+                                                let event = transaction
+                                                    .events
+                                                    .iter()
+                                                    .find(|event| event.space.is_some());
+                                                let bumped =
+                                                    transaction.events.iter().any(|event| {
+                                                        event.kind == TxEventKind::FeeBump
+                                                    });
 
-                                    let tx_data_without_event = || -> Row<'a, Message> {
-                                        let diff = transaction.received.to_sat() as i64
-                                            - transaction.sent.to_sat() as i64;
-                                        row![
-                                            horizontal_space(),
-                                            if diff >= 0 {
-                                                text(format!(
-                                                    "+{}",
-                                                    format_amount_number(diff as u64)
-                                                ))
-                                                .style(move |theme: &Theme| text::Style {
-                                                    color: Some(
-                                                        theme
-                                                            .extended_palette()
-                                                            .success
-                                                            .strong
-                                                            .color,
-                                                    ),
-                                                })
-                                            } else {
-                                                text(format!(
-                                                    "-{}",
-                                                    format_amount_number(-diff as u64)
-                                                ))
-                                                .style(move |theme: &Theme| text::Style {
-                                                    color: Some(
-                                                        theme
-                                                            .extended_palette()
-                                                            .danger
-                                                            .strong
-                                                            .color,
-                                                    ),
-                                                })
-                                            }
-                                        ]
-                                    };
+                                                let tx_data_without_event =
+                                                    || -> Row<'a, Message> {
+                                                        let diff = transaction.received.to_sat()
+                                                            as i64
+                                                            - transaction.sent.to_sat() as i64;
+                                                        row![
+                                                            horizontal_space(),
+                                                            if diff >= 0 {
+                                                                text(format!(
+                                                                    "+{}",
+                                                                    format_amount_number(
+                                                                        diff as u64
+                                                                    )
+                                                                ))
+                                                                .style(move |theme: &Theme| {
+                                                                    text::Style {
+                                                                        color: Some(
+                                                                            theme
+                                                                                .extended_palette()
+                                                                                .success
+                                                                                .strong
+                                                                                .color,
+                                                                        ),
+                                                                    }
+                                                                })
+                                                            } else {
+                                                                text(format!(
+                                                                    "-{}",
+                                                                    format_amount_number(
+                                                                        -diff as u64
+                                                                    )
+                                                                ))
+                                                                .style(move |theme: &Theme| {
+                                                                    text::Style {
+                                                                        color: Some(
+                                                                            theme
+                                                                                .extended_palette()
+                                                                                .danger
+                                                                                .strong
+                                                                                .color,
+                                                                        ),
+                                                                    }
+                                                                })
+                                                            }
+                                                        ]
+                                                    };
 
-                                    let tx_data_with_event =
+                                                let tx_data_with_event =
                                     |action: &'static str,
                                      space: &'a str,
                                      amount: Option<Amount>|
@@ -459,9 +538,9 @@ impl State {
                                         .align_y(Center)
                                     };
 
-                                    container(
-                                        column![
-                                            row![
+                                                container(
+                                                    column![
+                                                        row![
                                                 container(
                                                     button(
                                                         Row::new()
@@ -557,33 +636,39 @@ impl State {
                                                 }
                                                 .width(FillPortion(4)),
                                             ],
-                                            match block_height {
-                                                Some(block_height) => text_small(
-                                                    height_to_past_est(block_height, tip_height),
-                                                ),
-                                                None => text_small("Unconfirmed"),
+                                                        match block_height {
+                                                            Some(block_height) =>
+                                                                text_small(height_to_past_est(
+                                                                    block_height,
+                                                                    tip_height
+                                                                ),),
+                                                            None => text_small("Unconfirmed"),
+                                                        },
+                                                    ]
+                                                    .push_maybe(labels.get(&txid).map(text_small))
+                                                    .spacing(5),
+                                                )
+                                                .style(|_t: &Theme| container::Style {
+                                                    background: Some(
+                                                        Color::from_rgb8(0xFC, 0xFD, 0xFE).into(),
+                                                    ),
+                                                    border: rounded(8)
+                                                        .width(1)
+                                                        .color(Color::from_rgb8(0xDD, 0xE3, 0xEA)),
+                                                    ..container::Style::default()
+                                                })
+                                                .padding(STANDARD_PADDING)
+                                                .height(row_height)
+                                                .into()
                                             },
-                                        ]
-                                        .spacing(5),
+                                        ),
                                     )
-                                    .style(|_t: &Theme| container::Style {
-                                        background: Some(Color::from_rgb8(0xFC, 0xFD, 0xFE).into()),
-                                        border: rounded(8)
-                                            .width(1)
-                                            .color(Color::from_rgb8(0xDD, 0xE3, 0xEA)),
-                                        ..container::Style::default()
-                                    })
+                                    .push_maybe((below > 0.0).then(|| Space::with_height(below)))
                                     .padding(STANDARD_PADDING)
-                                    .into()
-                                }))
-                                .padding(STANDARD_PADDING)
-                                .spacing(10),
+                                    .spacing(TX_ROW_SPACING),
                             )
                             .on_scroll(|viewport| {
-                                Message::TxsListScrolled(
-                                    viewport.relative_offset().y,
-                                    transactions.len(),
-                                )
+                                Message::TxsListScrolled(viewport, transactions.len())
                             })
                             .height(Fill)
                             .into()
@@ -601,3 +686,40 @@ impl State {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visible_rows_of_the_viewport() {
+        let heights = [TX_ROW_HEIGHT; 100];
+        assert_eq!(visible_rows(&heights, 0.0, 500.0), (0..11, 0.0, 8356.0));
+        assert_eq!(
+            visible_rows(&heights, 4700.0, 500.0),
+            (45..61, 4220.0, 3656.0)
+        );
+        assert_eq!(
+            visible_rows(&heights, 100_000.0, 500.0),
+            (95..100, 8920.0, 0.0)
+        );
+        assert_eq!(visible_rows(&[], 0.0, 500.0), (0..0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn visible_rows_keep_the_total_height() {
+        let heights: Vec<f32> = (0..50)
+            .map(|i| TX_ROW_HEIGHT + if i % 3 == 0 { TX_LABEL_HEIGHT } else { 0.0 })
+            .collect();
+        let total = heights.iter().sum::<f32>() + TX_ROW_SPACING * 49.0;
+        for offset in [0.0, 333.0, 2000.0, 4000.0] {
+            let (rows, above, below) = visible_rows(&heights, offset, 600.0);
+            let shown = heights[rows.clone()].iter().sum::<f32>();
+            // spacing between the shown rows, and between them and the space around them
+            let gaps = (rows.len() - 1) as f32
+                + f32::from(u8::from(rows.start > 0))
+                + f32::from(u8::from(rows.end < heights.len()));
+            assert_eq!(above + shown + gaps * TX_ROW_SPACING + below, total);
+        }
+    }
+}
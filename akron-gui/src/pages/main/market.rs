@@ -1,41 +1,189 @@
+use super::state::{PriceHistory, SaleRecord, SpacesCollection};
 use crate::widget::base::{base_container, result_column};
+use crate::widget::form::{submit_button, text_input, STANDARD_PADDING};
 use crate::widget::tx_result::{TxListMessage, TxResultWidget};
 use crate::{
+    client::*,
     helpers::*,
+    nostr,
     widget::{
         form::Form,
         icon::{button_icon, Icon},
         tabs::TabsRow,
-        text::{text_big, text_monospace},
+        text::{text_big, text_monospace, text_semibold, text_small},
     },
 };
 use iced::{
-    widget::{column, container, row, text_editor},
-    Border, Element, Fill, Theme,
+    border::rounded,
+    widget::{
+        center, column, container, horizontal_space, qr_code, row, text, text_editor, Column,
+    },
+    Border, Center, Color, Element, Fill, Shrink, Theme,
 };
 use spaces_client::wallets::WalletResponse;
 use spaces_wallet::bdk_wallet::serde_json;
+use std::str::FromStr;
+
+#[derive(Debug, Clone)]
+pub struct BrowseListing {
+    space: String,
+    price: u64,
+    seller: String,
+    listing: Listing,
+}
+
+impl BrowseListing {
+    fn from_event(event: &serde_json::Value) -> Option<Self> {
+        let content = event.get("content")?.as_str()?;
+        let value: serde_json::Value = serde_json::from_str(content).ok()?;
+        let space = value.get("space")?.as_str()?.to_string();
+        if nostr::event_tag(event, "d") != Some(space.as_str()) {
+            return None;
+        }
+        // the seller is an address, the card shortens it by byte offsets
+        let seller = value.get("seller")?.as_str()?;
+        if seller.is_empty() || !seller.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        Some(Self {
+            price: value.get("price")?.as_u64()?,
+            seller: seller.to_string(),
+            listing: listing_from_str(content)?,
+            space,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Offer {
+    id: String,
+    pubkey: String,
+    space: String,
+    price: u64,
+    from: String,
+    counter: bool,
+}
+
+impl Offer {
+    fn from_event(event: &serde_json::Value) -> Option<Self> {
+        if event.get("kind")?.as_u64()? != nostr::OFFER_KIND as u64 {
+            return None;
+        }
+        Some(Self {
+            id: event.get("id")?.as_str()?.to_string(),
+            pubkey: event.get("pubkey")?.as_str()?.to_string(),
+            space: nostr::event_tag(event, "s")?.to_string(),
+            price: nostr::event_tag(event, "price")?.parse().ok()?,
+            from: nostr::event_tag(event, "from")?.to_string(),
+            counter: nostr::event_tag(event, "e").is_some(),
+        })
+    }
+
+    /// Whether the event is signed by the key of the `from` space, `None` until the space
+    /// was looked up
+    fn signed_by_from(&self, spaces: &SpacesCollection) -> Option<bool> {
+        let Ok(from) = SLabel::from_str(&self.from) else {
+            return Some(false);
+        };
+        match spaces.get_covenant(&from)? {
+            None => Some(false),
+            Some(_) => Some(spaces.get_outpoint(&from).is_some_and(|(_, public_key)| {
+                public_key.is_some_and(|pk| pk.to_string() == self.pubkey)
+            })),
+        }
+    }
+}
+
+/// Spaces the offers were made from, to check the offers are signed by them
+fn offer_senders(offers: &[Offer]) -> Vec<SLabel> {
+    let mut senders: Vec<SLabel> = Vec::new();
+    for offer in offers {
+        if let Ok(from) = SLabel::from_str(&offer.from) {
+            if !senders.contains(&from) {
+                senders.push(from);
+            }
+        }
+    }
+    senders
+}
+
+#[derive(Debug, Default)]
+pub struct BrowseState {
+    listings: Vec<BrowseListing>,
+    loading: bool,
+    selected: Option<usize>,
+    error: Option<String>,
+    tx_result: Option<TxResultWidget>,
+}
 
 #[derive(Debug, Default)]
 pub struct BuyState {
     listing: text_editor::Content,
+    verified: Option<(String, Option<Result<(), String>>)>,
+    url: String,
+    loading: bool,
     fee_rate: String,
     error: Option<String>,
     tx_result: Option<TxResultWidget>,
 }
 
+/// The listing JSON in a single QR code, pasting the scanned text in the buy screen
+/// imports it like a copied listing
+#[derive(Debug)]
+pub struct ListingQr {
+    data: String,
+    code: qr_code::Data,
+}
+
+impl ListingQr {
+    /// `None` when the listing doesn't fit in a QR code
+    fn new(listing: &Listing) -> Option<Self> {
+        let data = serde_json::to_string(listing).unwrap();
+        let code = qr_code::Data::new(&data).ok()?;
+        Some(Self { data, code })
+    }
+
+    fn to_svg(&self) -> String {
+        qrcode::QrCode::new(self.data.as_bytes())
+            .unwrap()
+            .render::<qrcode::render::svg::Color>()
+            .min_dimensions(400, 400)
+            .build()
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct SellState {
     space: Option<SLabel>,
     price: String,
-    listing: Option<String>,
+    listing: Option<(Listing, String)>,
+    qr: Option<ListingQr>,
+    show_qr: bool,
+    publishing: bool,
+    published: Vec<nostr::PublishResult>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct OffersState {
+    space: String,
+    price: String,
+    signer: Option<SLabel>,
+    offers: Vec<Offer>,
+    loading: bool,
+    sending: bool,
+    counter: Option<(usize, String)>,
+    published: Vec<nostr::PublishResult>,
+    saved: bool,
     error: Option<String>,
 }
 
 #[derive(Debug)]
 pub enum State {
+    Browse(BrowseState),
     Buy(BuyState),
     Sell(SellState),
+    Offers(OffersState),
 }
 
 impl Default for State {
@@ -46,9 +194,19 @@ impl Default for State {
 
 #[derive(Debug, Clone)]
 pub enum Message {
+    BrowseTabPress,
+    RefreshPress,
+    ListingsFetched(Result<Vec<serde_json::Value>, String>),
+    BrowseBuyPress(usize),
+    BrowseOfferPress(usize),
     BuyTabPress,
     SellTabPress,
     ListingAction(text_editor::Action),
+    ListingVerified(String, Result<(), String>),
+    ListingUrlInput(String),
+    FetchListingPress,
+    LoadListingPress,
+    ListingLoaded(Result<Option<String>, String>),
     SLabelSelect(SLabel),
     PriceInput(String),
     BuySubmit,
@@ -56,19 +214,103 @@ pub enum Message {
     SellSubmit,
     SellResult(Result<Listing, String>),
     CopyPress,
+    QrPress,
+    SaveQrPress,
+    QrSaved(Result<(), String>),
+    PublishPress,
+    PublishResult(Result<Vec<nostr::PublishResult>, String>),
     TxResult(TxListMessage),
+    OffersTabPress,
+    OffersFetched(Result<Vec<serde_json::Value>, String>),
+    OfferSpaceInput(String),
+    OfferPriceInput(String),
+    OfferSignerSelect(SLabel),
+    SendOfferPress,
+    SaveOfferPress,
+    LoadOfferPress,
+    OfferFileLoaded(Result<Option<serde_json::Value>, String>),
+    OfferSent(Result<Vec<nostr::PublishResult>, String>),
+    OfferSaved(Result<(), String>),
+    AcceptOfferPress(usize, bool),
+    CounterPress(usize),
+    CounterPriceInput(String),
+    CounterSubmit(SLabel),
 }
 
 #[derive(Debug, Clone)]
 pub enum Action {
     None,
-    Buy { listing: Listing },
-    Sell { slabel: SLabel, price: Amount },
+    FetchListings,
+    FetchOffers,
+    SendOffer {
+        signer: SLabel,
+        event: nostr::NostrEvent,
+        to_file: bool,
+    },
+    LoadOfferFile,
+    /// Looks the spaces up, offers are only shown once signed by the space they're from
+    GetSpacesInfo(Vec<SLabel>),
+    Buy {
+        listing: Listing,
+    },
+    FetchListing(String),
+    LoadListingFile,
+    VerifyListing {
+        key: String,
+        slabel: SLabel,
+        listing: Listing,
+    },
+    Sell {
+        slabel: SLabel,
+        price: Amount,
+    },
+    PublishListing {
+        slabel: SLabel,
+        listing: Listing,
+    },
     WriteClipboard(String),
+    SaveQr(String),
+    Purchased {
+        record: SaleRecord,
+    },
     ShowTransactions,
 }
 
+impl BuyState {
+    fn verify_listing(&mut self) -> Action {
+        let Some(listing) = listing_from_str(&self.listing.text()) else {
+            self.verified = None;
+            return Action::None;
+        };
+        let key = serde_json::to_string(&listing).unwrap();
+        if self.verified.as_ref().is_some_and(|(k, _)| k == &key) {
+            return Action::None;
+        }
+        match SLabel::from_str(&listing.space) {
+            Ok(slabel) => {
+                self.verified = Some((key.clone(), None));
+                Action::VerifyListing {
+                    key,
+                    slabel,
+                    listing,
+                }
+            }
+            Err(_) => {
+                self.verified = Some((key, Some(Err("Invalid space name".to_string()))));
+                Action::None
+            }
+        }
+    }
+}
+
 impl State {
+    fn as_browse(&mut self) -> &mut BrowseState {
+        match self {
+            Self::Browse(state) => state,
+            _ => panic!("Expected Browse state"),
+        }
+    }
+
     fn as_buy(&mut self) -> &mut BuyState {
         match self {
             Self::Buy(state) => state,
@@ -83,15 +325,75 @@ impl State {
         }
     }
 
+    fn as_offers(&mut self) -> &mut OffersState {
+        match self {
+            Self::Offers(state) => state,
+            _ => panic!("Expected Offers state"),
+        }
+    }
+
     pub fn update(&mut self, message: Message) -> Action {
         match self {
+            Self::Browse(state) => {
+                state.error = None;
+                state.tx_result = None;
+            }
             Self::Buy(state) => {
                 state.error = None;
                 state.tx_result = None;
             }
             Self::Sell(state) => state.error = None,
+            Self::Offers(state) => state.error = None,
         }
         match message {
+            Message::BrowseTabPress => {
+                *self = Self::Browse(BrowseState {
+                    loading: true,
+                    ..Default::default()
+                });
+                Action::FetchListings
+            }
+            Message::RefreshPress => {
+                let state = self.as_browse();
+                if state.loading {
+                    return Action::None;
+                }
+                state.loading = true;
+                Action::FetchListings
+            }
+            Message::ListingsFetched(result) => {
+                if let Self::Browse(state) = self {
+                    state.loading = false;
+                    state.selected = None;
+                    match result {
+                        Ok(events) => {
+                            state.listings = events
+                                .iter()
+                                .filter_map(BrowseListing::from_event)
+                                .collect()
+                        }
+                        Err(err) => state.error = Some(err),
+                    }
+                }
+                Action::None
+            }
+            Message::BrowseBuyPress(index) => {
+                let state = self.as_browse();
+                state.selected = Some(index);
+                Action::Buy {
+                    listing: state.listings[index].listing.clone(),
+                }
+            }
+            Message::BrowseOfferPress(index) => {
+                let listing = &self.as_browse().listings[index];
+                *self = Self::Offers(OffersState {
+                    space: listing.space.clone(),
+                    price: listing.price.to_string(),
+                    loading: true,
+                    ..Default::default()
+                });
+                Action::FetchOffers
+            }
             Message::BuyTabPress => {
                 *self = Self::Buy(Default::default());
                 Action::None
@@ -101,7 +403,48 @@ impl State {
                 Action::None
             }
             Message::ListingAction(action) => {
-                self.as_buy().listing.perform(action);
+                let state = self.as_buy();
+                state.listing.perform(action);
+                state.verify_listing()
+            }
+            Message::ListingUrlInput(url) => {
+                self.as_buy().url = url;
+                Action::None
+            }
+            Message::FetchListingPress => {
+                let state = self.as_buy();
+                state.loading = true;
+                Action::FetchListing(state.url.clone())
+            }
+            Message::LoadListingPress => {
+                self.as_buy().loading = true;
+                Action::LoadListingFile
+            }
+            Message::ListingLoaded(result) => {
+                let Self::Buy(state) = self else {
+                    return Action::None;
+                };
+                state.loading = false;
+                match result {
+                    Ok(Some(content)) => {
+                        state.listing = text_editor::Content::with_text(content.trim());
+                        state.verify_listing()
+                    }
+                    Ok(None) => Action::None,
+                    Err(err) => {
+                        state.error = Some(err);
+                        Action::None
+                    }
+                }
+            }
+            Message::ListingVerified(key, result) => {
+                if let Self::Buy(state) = self {
+                    if let Some((k, verified)) = state.verified.as_mut() {
+                        if *k == key {
+                            *verified = Some(result);
+                        }
+                    }
+                }
                 Action::None
             }
             Message::SLabelSelect(slabel) => {
@@ -114,24 +457,47 @@ impl State {
                 }
                 Action::None
             }
-            Message::BuySubmit => {
-                let state = self.as_buy();
-                Action::Buy {
-                    listing: listing_from_str(&state.listing.text()).unwrap(),
-                }
-            }
+            Message::BuySubmit => match self {
+                Self::Browse(state) => Action::Buy {
+                    listing: state.listings[state.selected.unwrap()].listing.clone(),
+                },
+                _ => Action::Buy {
+                    listing: listing_from_str(&self.as_buy().listing.text()).unwrap(),
+                },
+            },
             Message::BuyResult(Ok(w)) => {
                 if w.result.iter().any(|r| r.error.is_some()) {
-                    if let State::Buy(buy_state) = self {
-                        buy_state.tx_result = Some(TxResultWidget::new(w));
+                    match self {
+                        Self::Browse(state) => state.tx_result = Some(TxResultWidget::new(w)),
+                        Self::Buy(state) => state.tx_result = Some(TxResultWidget::new(w)),
+                        Self::Sell(_) | Self::Offers(_) => {}
                     }
                     return Action::None;
                 }
-                Action::ShowTransactions
+                let listing = match self {
+                    Self::Browse(state) => {
+                        state.selected.map(|i| state.listings[i].listing.clone())
+                    }
+                    Self::Buy(state) => listing_from_str(&state.listing.text()),
+                    Self::Sell(_) | Self::Offers(_) => None,
+                };
+                match (listing, w.result.first()) {
+                    (Some(listing), Some(tx)) => Action::Purchased {
+                        record: SaleRecord {
+                            space: listing.space,
+                            price: listing.price,
+                            txid: tx.txid,
+                            block_height: None,
+                        },
+                    },
+                    _ => Action::ShowTransactions,
+                }
             }
             Message::BuyResult(Err(err)) => {
-                if let Self::Buy(state) = self {
-                    state.error = Some(err);
+                match self {
+                    Self::Browse(state) => state.error = Some(err),
+                    Self::Buy(state) => state.error = Some(err),
+                    Self::Sell(_) | Self::Offers(_) => {}
                 }
                 Action::None
             }
@@ -144,7 +510,10 @@ impl State {
             }
             Message::SellResult(Ok(value)) => {
                 if let Self::Sell(state) = self {
-                    state.listing = Some(serde_json::to_string_pretty(&value).unwrap());
+                    let listing_json = serde_json::to_string_pretty(&value).unwrap();
+                    state.qr = ListingQr::new(&value);
+                    state.listing = Some((value, listing_json));
+                    state.published = Vec::new();
                 }
                 Action::None
             }
@@ -154,26 +523,277 @@ impl State {
                 }
                 Action::None
             }
-            Message::CopyPress => Action::WriteClipboard(self.as_sell().listing.clone().unwrap()),
+            Message::CopyPress => {
+                Action::WriteClipboard(self.as_sell().listing.as_ref().unwrap().1.clone())
+            }
+            Message::QrPress => {
+                let state = self.as_sell();
+                state.show_qr = !state.show_qr;
+                Action::None
+            }
+            Message::SaveQrPress => Action::SaveQr(self.as_sell().qr.as_ref().unwrap().to_svg()),
+            Message::QrSaved(result) => {
+                if let (Self::Sell(state), Err(err)) = (self, result) {
+                    state.error = Some(err);
+                }
+                Action::None
+            }
+            Message::PublishPress => {
+                let state = self.as_sell();
+                state.publishing = true;
+                state.published = Vec::new();
+                let (listing, _) = state.listing.as_ref().unwrap();
+                Action::PublishListing {
+                    slabel: SLabel::from_str(&listing.space).unwrap(),
+                    listing: listing.clone(),
+                }
+            }
+            Message::PublishResult(result) => {
+                if let Self::Sell(state) = self {
+                    state.publishing = false;
+                    match result {
+                        Ok(published) => state.published = published,
+                        Err(err) => state.error = Some(err),
+                    }
+                }
+                Action::None
+            }
             Message::TxResult(msg) => {
-                if let Self::Buy(state) = self {
-                    if let Some(tx_result) = &mut state.tx_result {
-                        tx_result.update(msg);
+                let tx_result = match self {
+                    Self::Browse(state) => state.tx_result.as_mut(),
+                    Self::Buy(state) => state.tx_result.as_mut(),
+                    Self::Sell(_) | Self::Offers(_) => None,
+                };
+                if let Some(tx_result) = tx_result {
+                    tx_result.update(msg);
+                }
+                Action::None
+            }
+            Message::OffersTabPress => {
+                *self = Self::Offers(OffersState {
+                    loading: true,
+                    ..Default::default()
+                });
+                Action::FetchOffers
+            }
+            Message::OffersFetched(result) => {
+                if let Self::Offers(state) = self {
+                    state.loading = false;
+                    state.counter = None;
+                    match result {
+                        Ok(events) => {
+                            state.offers = events.iter().filter_map(Offer::from_event).collect();
+                            return Action::GetSpacesInfo(offer_senders(&state.offers));
+                        }
+                        Err(err) => state.error = Some(err),
+                    }
+                }
+                Action::None
+            }
+            Message::OfferSpaceInput(space) => {
+                self.as_offers().space = space;
+                Action::None
+            }
+            Message::OfferPriceInput(price) => {
+                if is_amount_input(&price) {
+                    self.as_offers().price = price;
+                }
+                Action::None
+            }
+            Message::OfferSignerSelect(slabel) => {
+                self.as_offers().signer = Some(slabel);
+                Action::None
+            }
+            Message::SendOfferPress | Message::SaveOfferPress => {
+                let to_file = matches!(message, Message::SaveOfferPress);
+                let state = self.as_offers();
+                let signer = state.signer.clone().unwrap();
+                let space = SLabel::from_str(&state.space).unwrap();
+                let price = amount_from_str(&state.price).unwrap().to_sat();
+                state.sending = true;
+                state.published = Vec::new();
+                state.saved = false;
+                Action::SendOffer {
+                    event: nostr::offer_event(&space.to_string(), price, &signer.to_string(), None),
+                    signer,
+                    to_file,
+                }
+            }
+            Message::LoadOfferPress => Action::LoadOfferFile,
+            Message::OfferFileLoaded(Ok(Some(event))) => {
+                if let Self::Offers(state) = self {
+                    match Offer::from_event(&event).filter(|_| nostr::verify_event(&event)) {
+                        Some(offer) => {
+                            let senders = offer_senders(std::slice::from_ref(&offer));
+                            if !state.offers.iter().any(|o| o.id == offer.id) {
+                                state.offers.insert(0, offer);
+                            }
+                            return Action::GetSpacesInfo(senders);
+                        }
+                        None => state.error = Some("File is not a signed offer".to_string()),
+                    }
+                }
+                Action::None
+            }
+            Message::OfferFileLoaded(Ok(None)) => Action::None,
+            Message::OfferFileLoaded(Err(err)) => {
+                if let Self::Offers(state) = self {
+                    state.error = Some(err);
+                }
+                Action::None
+            }
+            Message::OfferSent(result) => {
+                if let Self::Offers(state) = self {
+                    state.sending = false;
+                    match result {
+                        Ok(published) => {
+                            state.published = published;
+                            state.counter = None;
+                        }
+                        Err(err) => state.error = Some(err),
+                    }
+                }
+                Action::None
+            }
+            Message::OfferSaved(result) => {
+                if let Self::Offers(state) = self {
+                    state.sending = false;
+                    match result {
+                        Ok(()) => state.saved = true,
+                        Err(err) => state.error = Some(err),
+                    }
+                }
+                Action::None
+            }
+            Message::AcceptOfferPress(index, owned) => {
+                let offer = self.as_offers().offers[index].clone();
+                if owned {
+                    let slabel = SLabel::from_str(&offer.space).unwrap();
+                    *self = Self::Sell(SellState {
+                        space: Some(slabel.clone()),
+                        price: offer.price.to_string(),
+                        ..Default::default()
+                    });
+                    Action::Sell {
+                        slabel,
+                        price: Amount::from_sat(offer.price),
+                    }
+                } else {
+                    let state = self.as_offers();
+                    state.space = offer.space;
+                    state.price = offer.price.to_string();
+                    Action::None
+                }
+            }
+            Message::CounterPress(index) => {
+                self.as_offers().counter = Some((index, String::new()));
+                Action::None
+            }
+            Message::CounterPriceInput(price) => {
+                if is_amount_input(&price) {
+                    if let Some((_, counter_price)) = self.as_offers().counter.as_mut() {
+                        *counter_price = price;
                     }
                 }
                 Action::None
             }
+            Message::CounterSubmit(signer) => {
+                let state = self.as_offers();
+                let (index, price) = state.counter.as_ref().unwrap();
+                let offer = &state.offers[*index];
+                let event = nostr::offer_event(
+                    &offer.space,
+                    amount_from_str(price).unwrap().to_sat(),
+                    &signer.to_string(),
+                    Some((&offer.id, &offer.pubkey)),
+                );
+                state.sending = true;
+                state.published = Vec::new();
+                state.saved = false;
+                Action::SendOffer {
+                    signer,
+                    event,
+                    to_file: false,
+                }
+            }
         }
     }
 
-    pub fn view<'a>(&'a self, owned_spaces: &'a Vec<SLabel>) -> Element<'a, Message> {
+    pub fn view<'a>(
+        &'a self,
+        owned_spaces: &'a Vec<SLabel>,
+        spaces: &'a SpacesCollection,
+        price_history: &'a PriceHistory,
+    ) -> Element<'a, Message> {
         base_container(
             column![
                 TabsRow::new()
+                    .add_tab(
+                        "Browse",
+                        matches!(self, Self::Browse(_)),
+                        Message::BrowseTabPress,
+                    )
                     .add_tab("Buy", matches!(self, Self::Buy(_)), Message::BuyTabPress,)
-                    .add_tab("Sell", matches!(self, Self::Sell(_)), Message::SellTabPress,),
+                    .add_tab("Sell", matches!(self, Self::Sell(_)), Message::SellTabPress,)
+                    .add_tab(
+                        "Offers",
+                        matches!(self, Self::Offers(_)),
+                        Message::OffersTabPress,
+                    ),
                 match self {
+                    Self::Browse(state) => {
+                        column![
+                            row![
+                                text_big("Listings"),
+                                horizontal_space(),
+                                submit_button(
+                                    text("Refresh").align_x(Center),
+                                    (!state.loading).then_some(Message::RefreshPress),
+                                )
+                                .padding([5, 10])
+                                .width(Shrink),
+                            ]
+                            .align_y(Center),
+                            result_column(
+                                state.error.as_ref(),
+                                state
+                                    .tx_result
+                                    .as_ref()
+                                    .map(|tx| TxResultWidget::view(tx).map(Message::TxResult)),
+                                [if state.loading {
+                                    center(text("Loading listings from relays")).into()
+                                } else if state.listings.is_empty() {
+                                    center(text("No listings found")).into()
+                                } else {
+                                    Column::with_children(state.listings.iter().enumerate().map(
+                                        |(index, listing)| {
+                                            listing_card(
+                                                index,
+                                                listing,
+                                                price_history.last(&listing.space),
+                                            )
+                                        },
+                                    ))
+                                    .spacing(10)
+                                    .into()
+                                }]
+                            )
+                            .spacing(40),
+                        ]
+                        .spacing(40)
+                    }
                     Self::Buy(state) => {
+                        let listing = listing_from_str(&state.listing.text());
+                        let slabel = listing
+                            .as_ref()
+                            .and_then(|listing| SLabel::from_str(&listing.space).ok());
+                        let covenant = slabel.as_ref().and_then(|s| spaces.get_covenant(s));
+                        let signature = state.verified.as_ref().and_then(|(_, v)| v.as_ref());
+                        let registered = matches!(covenant, Some(Some(Covenant::Transfer { .. })));
+                        let listing_text = state.listing.text();
+                        let parse_error = (!listing_text.trim().is_empty())
+                            .then(|| serde_json::from_str::<Listing>(&listing_text).err())
+                            .flatten();
                         column![
                             text_big("Buy space"),
                             result_column(
@@ -182,22 +802,61 @@ impl State {
                                     .tx_result
                                     .as_ref()
                                     .map(|tx| TxResultWidget::view(tx).map(Message::TxResult)),
-                                [Form::new(
-                                    "Buy",
-                                    (listing_from_str(&state.listing.text()).is_some()
-                                        && fee_rate_from_str(&state.fee_rate).is_some())
-                                    .then_some(Message::BuySubmit)
-                                )
-                                .add_text_editor(
-                                    "Listing",
-                                    "JSON",
-                                    &state.listing,
-                                    Message::ListingAction
-                                )
-                                .into()]
+                                [
+                                    row![
+                                        text_input("https://", &state.url)
+                                            .width(Fill)
+                                            .on_input(Message::ListingUrlInput),
+                                        submit_button(
+                                            text("Fetch").align_x(Center),
+                                            (!state.loading
+                                                && (state.url.starts_with("https://")
+                                                    || state.url.starts_with("http://")))
+                                            .then_some(Message::FetchListingPress),
+                                        )
+                                        .width(Shrink),
+                                        submit_button(
+                                            text("Load file").align_x(Center),
+                                            (!state.loading).then_some(Message::LoadListingPress),
+                                        )
+                                        .width(Shrink),
+                                    ]
+                                    .spacing(10)
+                                    .into(),
+                                    Form::new(
+                                        "Buy",
+                                        (registered
+                                            && signature.is_some_and(|r| r.is_ok())
+                                            && fee_rate_from_str(&state.fee_rate).is_some())
+                                        .then_some(Message::BuySubmit)
+                                    )
+                                    .add_text_editor(
+                                        "Listing",
+                                        "JSON",
+                                        &state.listing,
+                                        Message::ListingAction
+                                    )
+                                    .into(),
+                                ]
                             )
                             .spacing(40),
                         ]
+                        .push_maybe(parse_error.map(|err| {
+                            text_small(format!("Invalid listing: {}", err)).style(text::danger)
+                        }))
+                        .push_maybe(listing.map(|listing| {
+                            let last_sale = price_history.last(&listing.space);
+                            listing_preview(
+                                listing,
+                                last_sale,
+                                slabel
+                                    .as_ref()
+                                    .and_then(|s| spaces.get_outpoint(s))
+                                    .map(|(outpoint, _)| *outpoint),
+                                covenant,
+                                signature,
+                            )
+                        }))
                         .spacing(40)
                     }
                     Self::Sell(state) => {
@@ -222,7 +881,7 @@ impl State {
                                 .into(),]
                             ),
                         ]
-                        .push_maybe(state.listing.as_ref().map(|listing| {
+                        .push_maybe(state.listing.as_ref().map(|(_, listing)| {
                             container(row![
                                 text_monospace(listing).width(Fill),
                                 button_icon(Icon::Copy).on_press(Message::CopyPress)
@@ -239,6 +898,168 @@ impl State {
                                     })
                             })
                         }))
+                        .push_maybe(state.qr.as_ref().map(|qr| {
+                            column![row![
+                                submit_button(
+                                    text(if state.show_qr {
+                                        "Hide QR code"
+                                    } else {
+                                        "Show QR code"
+                                    })
+                                    .width(Fill)
+                                    .align_x(Center),
+                                    Some(Message::QrPress),
+                                ),
+                                submit_button(
+                                    text("Save QR code").width(Fill).align_x(Center),
+                                    Some(Message::SaveQrPress),
+                                ),
+                            ]
+                            .spacing(10)]
+                            .push_maybe(state.show_qr.then(|| {
+                                container(qr_code(&qr.code).cell_size(5))
+                                    .align_x(Center)
+                                    .width(Fill)
+                            }))
+                            .align_x(Center)
+                            .spacing(20)
+                        }))
+                        .push_maybe(state.listing.as_ref().map(|_| {
+                            column![submit_button(
+                                text(if state.publishing {
+                                    "Publishing"
+                                } else {
+                                    "Publish to relays"
+                                })
+                                .width(Fill)
+                                .align_x(Center),
+                                (!state.publishing).then_some(Message::PublishPress),
+                            )]
+                            .extend(state.published.iter().map(|(relay, result)| {
+                                row![
+                                    text_monospace(relay).width(Fill),
+                                    match result {
+                                        Ok(()) => text_small("Published").style(text::success),
+                                        Err(err) => text_small(err).style(text::danger),
+                                    }
+                                ]
+                                .spacing(10)
+                                .into()
+                            }))
+                            .spacing(10)
+                        }))
+                        .spacing(40)
+                    }
+                    Self::Offers(state) => {
+                        let can_send = !state.sending
+                            && state.signer.is_some()
+                            && SLabel::from_str(&state.space).is_ok()
+                            && amount_from_str(&state.price).is_some();
+                        column![
+                            text_big("Make an offer"),
+                            result_column(
+                                state.error.as_ref(),
+                                None,
+                                [
+                                    Form::new(
+                                        "Send to relays",
+                                        can_send.then_some(Message::SendOfferPress),
+                                    )
+                                    .add_text_input(
+                                        "Space",
+                                        "@space",
+                                        &state.space,
+                                        Message::OfferSpaceInput,
+                                    )
+                                    .add_text_input(
+                                        "Price",
+                                        "sat",
+                                        &state.price,
+                                        Message::OfferPriceInput,
+                                    )
+                                    .add_pick_list(
+                                        "Sign as",
+                                        owned_spaces.as_slice(),
+                                        state.signer.as_ref(),
+                                        Message::OfferSignerSelect,
+                                    )
+                                    .into(),
+                                    submit_button(
+                                        text("Save to file").width(Fill).align_x(Center),
+                                        can_send.then_some(Message::SaveOfferPress),
+                                    )
+                                    .into(),
+                                ]
+                            )
+                            .spacing(10),
+                        ]
+                        .push_maybe(
+                            state
+                                .saved
+                                .then(|| text("Offer saved").style(text::success)),
+                        )
+                        .extend(state.published.iter().map(|(relay, result)| {
+                            row![
+                                text_monospace(relay).width(Fill),
+                                match result {
+                                    Ok(()) => text_small("Sent").style(text::success),
+                                    Err(err) => text_small(err).style(text::danger),
+                                }
+                            ]
+                            .spacing(10)
+                            .into()
+                        }))
+                        .push(
+                            row![
+                                text_big("Received offers"),
+                                horizontal_space(),
+                                submit_button(
+                                    text("Load file").align_x(Center),
+                                    Some(Message::LoadOfferPress),
+                                )
+                                .padding([5, 10])
+                                .width(Shrink),
+                            ]
+                            .align_y(Center)
+                            .spacing(10),
+                        )
+                        .push(if state.loading {
+                            Element::from(center(text("Loading offers from relays")))
+                        } else if state.offers.is_empty() {
+                            center(text("No offers found")).into()
+                        } else {
+                            Column::with_children(state.offers.iter().enumerate().filter_map(
+                                |(index, offer)| {
+                                    // offers claiming to be from a space they're not signed by
+                                    let verified = match offer.signed_by_from(spaces) {
+                                        Some(false) => return None,
+                                        Some(true) => true,
+                                        None => false,
+                                    };
+                                    let owned =
+                                        owned_spaces.iter().any(|s| s.to_string() == offer.space);
+                                    let signer = if owned {
+                                        SLabel::from_str(&offer.space).ok()
+                                    } else {
+                                        state.signer.clone()
+                                    };
+                                    Some(offer_card(
+                                        index,
+                                        offer,
+                                        verified,
+                                        owned,
+                                        state
+                                            .counter
+                                            .as_ref()
+                                            .filter(|(i, _)| *i == index)
+                                            .map(|(_, price)| price.as_str()),
+                                        signer.filter(|_| !state.sending),
+                                    ))
+                                },
+                            ))
+                            .spacing(10)
+                            .into()
+                        })
                         .spacing(40)
                     }
                 }
@@ -249,3 +1070,174 @@ impl State {
         .into()
     }
 }
+
+fn listing_card<'a>(
+    index: usize,
+    listing: &'a BrowseListing,
+    last_sale: Option<&SaleRecord>,
+) -> Element<'a, Message> {
+    container(
+        row![
+            column![
+                text_semibold(&listing.space).size(20),
+                text_small(format!(
+                    "Seller {} .. {}",
+                    &listing.seller[..listing.seller.len().min(10)],
+                    &listing.seller[listing.seller.len().saturating_sub(6)..],
+                )),
+            ]
+            .push_maybe(last_sale.map(|sale| {
+                text_small(format!(
+                    "Last sold for {}",
+                    format_amount_number(sale.price)
+                ))
+            }))
+            .spacing(5)
+            .width(Fill),
+            text_semibold(format_amount_number(listing.price)),
+            submit_button(
+                text("Offer").align_x(Center),
+                Some(Message::BrowseOfferPress(index))
+            )
+            .width(Shrink),
+            submit_button(
+                text("Buy").align_x(Center),
+                Some(Message::BrowseBuyPress(index))
+            )
+            .width(Shrink),
+        ]
+        .align_y(Center)
+        .spacing(20),
+    )
+    .style(|_t: &Theme| container::Style {
+        background: Some(Color::from_rgb8(0xFC, 0xFD, 0xFE).into()),
+        border: rounded(8)
+            .width(1)
+            .color(Color::from_rgb8(0xDD, 0xE3, 0xEA)),
+        ..container::Style::default()
+    })
+    .padding(STANDARD_PADDING)
+    .into()
+}
+
+fn offer_card<'a>(
+    index: usize,
+    offer: &'a Offer,
+    verified: bool,
+    owned: bool,
+    counter_price: Option<&'a str>,
+    signer: Option<SLabel>,
+) -> Element<'a, Message> {
+    container(
+        column![row![
+            column![
+                text_semibold(&offer.space).size(20),
+                text_small(format!(
+                    "{} from {}{}",
+                    if offer.counter {
+                        "Counter-offer"
+                    } else {
+                        "Offer"
+                    },
+                    offer.from,
+                    if verified { "" } else { ", checking signer" }
+                )),
+            ]
+            .spacing(5)
+            .width(Fill),
+            text_semibold(format_amount_number(offer.price)),
+            submit_button(
+                text("Counter").align_x(Center),
+                verified.then_some(Message::CounterPress(index))
+            )
+            .width(Shrink),
+            submit_button(
+                text("Accept").align_x(Center),
+                verified.then_some(Message::AcceptOfferPress(index, owned))
+            )
+            .width(Shrink),
+        ]
+        .align_y(Center)
+        .spacing(20)]
+        .push_maybe(counter_price.map(|price| {
+            row![
+                text_input("sat", price)
+                    .width(Fill)
+                    .on_input(Message::CounterPriceInput),
+                submit_button(
+                    text("Send counter-offer").align_x(Center),
+                    signer
+                        .filter(|_| amount_from_str(price).is_some())
+                        .map(Message::CounterSubmit),
+                )
+                .width(Shrink),
+            ]
+            .spacing(20)
+        }))
+        .spacing(20),
+    )
+    .style(|_t: &Theme| container::Style {
+        background: Some(Color::from_rgb8(0xFC, 0xFD, 0xFE).into()),
+        border: rounded(8)
+            .width(1)
+            .color(Color::from_rgb8(0xDD, 0xE3, 0xEA)),
+        ..container::Style::default()
+    })
+    .padding(STANDARD_PADDING)
+    .into()
+}
+
+fn listing_preview<'a>(
+    listing: Listing,
+    last_sale: Option<&SaleRecord>,
+    outpoint: Option<OutPoint>,
+    covenant: Option<Option<&Covenant>>,
+    signature: Option<&'a Result<(), String>>,
+) -> Element<'a, Message> {
+    let ownership = match covenant {
+        None => text("Checking"),
+        Some(None) => text("Space not found").style(text::danger),
+        Some(Some(Covenant::Transfer { expire_height, .. })) => {
+            text(format!("Registered, expires at block {}", expire_height)).style(text::success)
+        }
+        Some(Some(_)) => text("Space is not registered").style(text::danger),
+    };
+    let signature = match signature {
+        None => text("Checking"),
+        Some(Ok(())) => text("Valid").style(text::success),
+        Some(Err(err)) => text(err).style(text::danger),
+    };
+    let row_item = |label: &'a str, value: Element<'a, Message>| {
+        row![text_small(label).width(120), value].align_y(Center)
+    };
+    container(
+        column![
+            row_item("Space", text_semibold(listing.space).into()),
+            row_item("Price", text(format_amount_number(listing.price)).into()),
+            row_item(
+                "Last sale",
+                text(
+                    last_sale.map_or("-".to_string(), |sale| { format_amount_number(sale.price) })
+                )
+                .into(),
+            ),
+            row_item("Seller", text_monospace(listing.seller).into()),
+            row_item(
+                "Outpoint",
+                text_monospace(outpoint.map_or("-".to_string(), |o| o.to_string())).into(),
+            ),
+            row_item("Ownership", ownership.into()),
+            row_item("Signature", signature.into()),
+        ]
+        .spacing(10),
+    )
+    .style(|_t: &Theme| container::Style {
+        background: Some(Color::from_rgb8(0xFC, 0xFD, 0xFE).into()),
+        border: rounded(8)
+            .width(1)
+            .color(Color::from_rgb8(0xDD, 0xE3, 0xEA)),
+        ..container::Style::default()
+    })
+    .padding(STANDARD_PADDING)
+    .into()
+}
@@ -1,31 +1,65 @@
+mod console;
+mod details;
+mod diagnostics;
 mod home;
 mod market;
+mod node;
 mod receive;
 mod send;
 mod settings;
 mod sign;
 mod spaces;
 mod state;
+mod watchdog;
+pub mod watcher;
 
 use iced::{
-    clipboard, time,
+    clipboard, keyboard, time,
     widget::{
-        button, center, column, container, progress_bar, row, text, vertical_rule, vertical_space,
-        Column, Stack,
+        button, center, column, container, progress_bar, row, text, text_input, vertical_rule,
+        vertical_space, Column, Row, Space, Stack,
     },
     Center, Color, Element, Fill, Font, Padding, Subscription, Task, Theme,
 };
-use ringbuffer::{ConstGenericRingBuffer, RingBuffer};
+use ringbuffer::{AllocRingBuffer, RingBuffer};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use crate::{
+    app_backup::AppBackup,
+    automation::{self, AutomationRequest, Call},
+    background,
+    backoff::Backoff,
     client::*,
+    crash,
+    helpers::format_amount,
+    nostr,
+    plugins::{self, Plugin},
+    proxy,
+    sign_request::{self, SignRequest},
+    update,
+    webhooks::Delivery,
     widget::{
+        base::{base_container, modal},
         fee_rate::{FeeRateMessage, FeeRateSelector},
         icon::{text_icon, Icon},
-        text::text_small,
+        qr_code::QrMatrix,
+        text::{error_block, text_big, text_monospace, text_small},
     },
-    Config,
+    Config, ConfigBackend, ExtendedNetwork, ProxyConfig, WalletConfig,
 };
+
+/// Age of the checkpoint in days before the chain data is rebuilt from a newer one
+pub const PRUNE_REFRESH_DAYS: u32 = 90;
+/// Log lines kept for the logs bar
+const LOG_SCROLLBACK: usize = 5000;
+/// Most recent matching lines the expanded logs bar shows
+const LOGS_SHOWN: usize = 500;
+/// Listings are a few hundred bytes, larger responses aren't read
+const MAX_LISTING_SIZE: usize = 64 * 1024;
+const LISTING_TIMEOUT: Duration = Duration::from_secs(30);
 use iced::widget::button::Status;
 use iced::widget::{horizontal_rule, scrollable, stack};
 
@@ -38,6 +72,41 @@ enum Screen {
     Market,
     Sign,
     Settings,
+    WalletDetails,
+    Diagnostics,
+    Console,
+    Node,
+    Plugin(usize),
+}
+
+impl Screen {
+    /// Name the screen is kept by for the next launch, screens about the moment such as
+    /// diagnostics have none
+    fn name(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Home => "home",
+            Self::Send => "send",
+            Self::Receive => "receive",
+            Self::Spaces => "spaces",
+            Self::Market => "market",
+            Self::Sign => "sign",
+            Self::Settings => "settings",
+            Self::Console => "console",
+            Self::Node => "node",
+            Self::WalletDetails | Self::Diagnostics | Self::Plugin(_) => return None,
+        })
+    }
+}
+
+/// Server info requests failing in a row show the backend offline
+const OFFLINE_AFTER_FAILURES: u32 = 3;
+
+/// Backend not answering, ticks are skipped until it's tried again
+#[derive(Debug)]
+struct Unreachable {
+    failures: u32,
+    retry_at: Instant,
+    error: String,
 }
 
 #[derive(Debug)]
@@ -46,8 +115,13 @@ pub struct State {
     client: Client,
     screen: Screen,
     tip_height: u32,
+    server_info: Option<ServerInfo>,
+    unreachable: Option<Unreachable>,
     wallets: state::WalletsCollection,
+    wallet_config: Option<(String, WalletConfig)>,
+    auto_renewing: Vec<SLabel>,
     spaces: state::SpacesCollection,
+    price_history: state::PriceHistory,
     home_screen: home::State,
     send_screen: send::State,
     receive_screen: receive::State,
@@ -55,11 +129,50 @@ pub struct State {
     market_screen: market::State,
     sign_screen: sign::State,
     settings_screen: settings::State,
-    log_buffer: ConstGenericRingBuffer<String, 100>,
+    details_screen: details::State,
+    console_screen: console::State,
+    diagnostics_screen: diagnostics::State,
+    node_screen: node::State,
+    sync_watchdog: watchdog::SyncWatchdog,
+    /// Spaced endpoint the wallets were loaded on
+    endpoint: usize,
+    log_buffer: AllocRingBuffer<LogLine>,
+    /// Service whose lines the expanded logs show, all when `None`
+    log_filter: Option<ServiceKind>,
+    log_search: String,
+    /// Least severe lines shown
+    log_level: LogLevel,
+    /// Lines received while the logs are paused, added once they resume
+    log_paused: Option<AllocRingBuffer<LogLine>>,
+    /// Id and label of every open session, for the switcher
+    sessions: Vec<(usize, String)>,
+    session_id: usize,
     logs_expanded: bool,
     fee_rate_selector: FeeRateSelector,
     fee_rate: Option<FeeRate>,
     fee_rate_confirmed_message: Option<Message>,
+    sign_requests: Vec<SignRequest>,
+    sign_request_signing: bool,
+    /// Login typed in the prompt shown when spaced answers 401
+    reauth: Option<(String, String)>,
+    plugins: Vec<Plugin>,
+    /// Lines of the open plugin screen
+    plugin_screen: Option<Result<Vec<String>, String>>,
+    /// Transaction labels from plugins, and the transactions they were asked about
+    transaction_labels: HashMap<Txid, String>,
+    decorated_transactions: HashSet<Txid>,
+    /// Transactions of each wallet plugins were told about
+    hooked_transactions: HashMap<String, HashSet<Txid>>,
+    watcher: watcher::Watcher,
+    /// Webhook posts waiting to be sent or retried
+    webhook_queue: Vec<Delivery>,
+}
+
+/// Part of a session shown in a window of its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detached {
+    Logs,
+    Transaction(Txid),
 }
 
 #[derive(Debug, Clone)]
@@ -73,22 +186,45 @@ pub enum Route {
     Market,
     Sign,
     Settings,
+    WalletDetails,
+    Diagnostics,
+    Console,
+    Node,
+    Plugin(usize),
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Tick,
     ToggleLogs,
-    LogReceived(String),
+    DetachLogsPress,
+    LogReceived(LogLine),
+    LogFilterSelect(Option<ServiceKind>),
+    LogSearchInput(String),
+    LogLevelSelect(LogLevel),
+    LogPauseToggle,
+    LogsCopyPress,
+    LogsSavePress,
+    LogsSaved(Result<(), String>),
     NavigateTo(Route),
     ServerInfo(ClientResult<ServerInfo>),
+    RetryBackendPress,
+    StallRestartPress(ServiceKind),
+    StallRestarted(ServiceKind, ClientResult<()>),
+    /// akrond restarted the service after it crashed
+    ServiceRestarted(ServiceKind),
     ListWallets(ClientResult<Vec<String>>),
     WalletLoad(WalletResult<()>),
+    /// Wallet loaded again after spaced was restarted
+    WalletReloaded(WalletResult<()>),
     WalletInfo(WalletResult<WalletInfoWithProgress>),
     WalletBalance(WalletResult<Balance>),
     WalletSpaces(WalletResult<ListSpacesResponse>),
     WalletTransactions(WalletResult<Vec<TxInfo>>),
     WalletAddress(WalletResult<(AddressKind, String)>),
+    /// QR code of the address of a wallet
+    AddressQrCode(String, String, Result<QrMatrix, String>),
+    AutoRenewed(SLabel, WalletResult<WalletResponse>),
     SpaceInfo(ClientResult<(SLabel, Option<FullSpaceOut>)>),
     HomeScreen(home::Message),
     SendScreen(send::Message),
@@ -97,52 +233,241 @@ pub enum Message {
     MarketScreen(market::Message),
     SignScreen(sign::Message),
     SettingsScreen(settings::Message),
+    DetailsScreen(details::Message),
+    ConsoleScreen(console::Message),
+    DiagnosticsScreen(diagnostics::Message),
+    NodeScreen(node::Message),
+
+    // Wallet switcher
+    WalletSwitch(String),
+    WalletShortcut(usize),
+    WalletUnload(String),
+
+    // Session switcher
+    SessionSwitch(usize),
+    SessionOpen,
+    SessionClose(usize),
 
     // Fee rate modal
     ShowFeeRateModal,
     FeeRateSelector(FeeRateMessage),
     FeeRateConfirmed(u32),
+
+    // External signing requests
+    SignRequestReceived(SignRequest),
+    SignRequestApprove,
+    SignRequestReject,
+    SignRequestSigned(Result<NostrEvent, String>),
+    AutomationRequestReceived(AutomationRequest),
+    PluginScreen(usize, Result<serde_json::Value, String>),
+    TransactionLabels(Result<serde_json::Value, String>),
+    WatchTick,
+    WebhookSent(Result<(), (Option<Delivery>, String)>),
+    AutomationRequestDone(AutomationRequest, Result<serde_json::Value, String>),
+
+    // Re-authentication prompt
+    ReauthUserInput(String),
+    ReauthPasswordInput(String),
+    ReauthSubmit,
+    ReauthChangeBackend,
+
+    /// Checks whether the checkpoint is due for a refresh
+    PruneRefreshCheck,
+    CheckpointAge(Option<std::time::Duration>),
+
+    /// Services stopped to reconnect, removing the chain data first if `rebuild`
+    ServicesStopped {
+        rebuild: bool,
+    },
 }
 
 pub enum Action {
     Return(Config),
+    RebuildChainData(Config),
+    Reconnect(Config),
+    SwitchSession(usize),
+    OpenSession,
+    CloseSession(usize),
+    Detach(Detached),
     Task(Task<Message>),
 }
 
 impl State {
     pub fn run(config: Config, client: Client) -> (Self, Task<Message>) {
+        let price_history = state::PriceHistory::load(config.data_dir().join("price_history.json"));
+        let plugins = plugins::load(config.data_dir());
+        let settings_screen = settings::State::new(
+            config.proxy.as_ref(),
+            background::is_installed(config.data_dir()),
+            config.automation_port,
+        );
         let state = Self {
             config,
             client,
             screen: Screen::Home,
             tip_height: 0,
+            server_info: None,
+            unreachable: None,
             wallets: Default::default(),
+            wallet_config: None,
+            auto_renewing: Vec::new(),
             spaces: Default::default(),
+            price_history,
             home_screen: Default::default(),
             send_screen: Default::default(),
             receive_screen: Default::default(),
             spaces_screen: Default::default(),
             market_screen: Default::default(),
             sign_screen: Default::default(),
-            settings_screen: Default::default(),
-            log_buffer: Default::default(),
+            settings_screen,
+            details_screen: Default::default(),
+            console_screen: Default::default(),
+            diagnostics_screen: Default::default(),
+            node_screen: Default::default(),
+            sync_watchdog: Default::default(),
+            endpoint: 0,
+            log_buffer: AllocRingBuffer::new(LOG_SCROLLBACK),
+            log_filter: None,
+            log_search: String::new(),
+            log_level: LogLevel::Info,
+            log_paused: None,
+            sessions: Vec::new(),
+            session_id: 0,
             logs_expanded: false,
             fee_rate_selector: Default::default(),
             fee_rate: None,
             fee_rate_confirmed_message: None,
+            sign_requests: Vec::new(),
+            sign_request_signing: false,
+            reauth: None,
+            plugins,
+            plugin_screen: None,
+            transaction_labels: HashMap::new(),
+            decorated_transactions: HashSet::new(),
+            hooked_transactions: HashMap::new(),
+            watcher: Default::default(),
+            webhook_queue: Vec::new(),
+        };
+        let mut tasks = vec![state.get_server_info(), state.list_wallets()];
+        if state.config.check_updates {
+            tasks.push(check_update());
+        }
+        (state, Task::batch(tasks))
+    }
+
+    /// Name of the screen and whether the logs bar is expanded, kept for the next launch
+    pub fn ui_state(&self) -> (Option<&'static str>, bool) {
+        (self.screen.name(), self.logs_expanded)
+    }
+
+    /// Shows the screen and logs bar the app was left with
+    pub fn restore_ui(&mut self, screen: Option<&str>, logs_expanded: bool) -> Task<Message> {
+        self.logs_expanded = logs_expanded;
+        let route = match screen {
+            Some("send") => Route::Send,
+            Some("receive") => Route::Receive,
+            Some("spaces") => Route::Spaces,
+            Some("market") => Route::Market,
+            Some("sign") => Route::Sign,
+            Some("settings") => Route::Settings,
+            Some("console") if self.config.rpc_console => Route::Console,
+            Some("node") => Route::Node,
+            _ => return Task::none(),
         };
-        let task = Task::batch([state.get_server_info(), state.list_wallets()]);
-        (state, task)
+        self.navigate_to(route)
+    }
+
+    /// Label of the session in the switcher
+    pub fn session_label(&self) -> String {
+        match &self.config.profile {
+            Some(profile) => profile.clone(),
+            None => self.config.backend.as_ref().unwrap().network().to_string(),
+        }
+    }
+
+    /// Backend of the session, to tell which services it runs
+    pub fn backend(&self) -> Option<&ConfigBackend> {
+        self.config.backend.as_ref()
+    }
+
+    pub fn set_sessions(&mut self, session_id: usize, sessions: Vec<(usize, String)>) {
+        self.session_id = session_id;
+        self.sessions = sessions;
+    }
+
+    /// Passes `event` to the plugins hooked to it, their errors go to the logs
+    fn run_plugin_hooks(&self, event: &'static str, params: serde_json::Value) -> Task<Message> {
+        Task::batch(
+            self.plugins
+                .iter()
+                .filter(|plugin| plugin.has_hook(event))
+                .map(|plugin| {
+                    let mut params = params.clone();
+                    params["event"] = json!(event);
+                    Task::future(plugin.clone().call("hook", params)).then(|result| match result {
+                        Ok(_) => Task::none(),
+                        Err(err) => Task::done(Message::LogReceived(LogLine::error(err))),
+                    })
+                }),
+        )
+    }
+
+    /// Queues the webhook posts for the events and sends the ones that are due
+    fn fire_webhooks(&mut self, events: Vec<watcher::WalletEvent>) -> Task<Message> {
+        for event in events {
+            let value = json!(event);
+            self.webhook_queue.extend(Delivery::for_event(
+                &self.config.webhooks,
+                event.name(),
+                &value,
+            ));
+        }
+        let (due, waiting) = std::mem::take(&mut self.webhook_queue)
+            .into_iter()
+            .partition(Delivery::is_due);
+        self.webhook_queue = waiting;
+        Task::batch(
+            due.into_iter()
+                .map(|delivery: Delivery| Task::perform(delivery.send(), Message::WebhookSent)),
+        )
     }
 
     fn get_server_info(&self) -> Task<Message> {
         self.client.get_server_info().map(Message::ServerInfo)
     }
 
+    fn node_status(&self) -> Task<Message> {
+        self.client
+            .node_status()
+            .map(|status| Message::NodeScreen(node::Message::StatusLoaded(status)))
+    }
+
     fn list_wallets(&self) -> Task<Message> {
         self.client.list_wallets().map(Message::ListWallets)
     }
 
+    /// Loads the wallets that were loaded again, restarting spaced unloads all of them
+    fn reload_wallets(&self) -> Task<Message> {
+        Task::batch(self.wallets.get_loaded().into_iter().map(|w| {
+            self.client
+                .load_wallet(w.label.clone())
+                .map(Message::WalletReloaded)
+        }))
+    }
+
+    fn wallet_calls(&self, calls: Vec<(String, WalletCall)>) -> Task<Message> {
+        self.client.wallet_calls(calls).then(|updates| {
+            Task::batch(updates.into_iter().map(|update| {
+                Task::done(match update {
+                    WalletUpdate::Info(result) => Message::WalletInfo(result),
+                    WalletUpdate::Balance(result) => Message::WalletBalance(result),
+                    WalletUpdate::Spaces(result) => Message::WalletSpaces(result),
+                    WalletUpdate::Transactions(result) => Message::WalletTransactions(result),
+                })
+            }))
+        })
+    }
+
     fn get_wallet_info(&self) -> Task<Message> {
         if let Some(wallet) = self.wallets.get_current() {
             self.client
@@ -186,6 +511,48 @@ impl State {
         }
     }
 
+    /// Looks for the index of the next address of each receive descriptor of the current
+    /// wallet, spaced doesn't report them
+    fn find_next_indices(&self) -> Task<Message> {
+        let Some(wallet) = self.wallets.get_current() else {
+            return Task::none();
+        };
+        let Some(info) = wallet.state.info.as_ref() else {
+            return Task::none();
+        };
+        Task::batch(
+            info.info
+                .descriptors
+                .iter()
+                .filter(|descriptor| !descriptor.internal)
+                .filter_map(|descriptor| {
+                    let address = match descriptor.spaces {
+                        true => wallet.state.space_address.as_ref()?,
+                        false => wallet.state.coin_address.as_ref()?,
+                    };
+                    let (descriptor, address) =
+                        (descriptor.descriptor.clone(), address.as_str().to_string());
+                    Some(Task::perform(
+                        async move {
+                            let index = {
+                                let descriptor = descriptor.clone();
+                                tokio::task::spawn_blocking(move || {
+                                    address_index(&descriptor, &address)
+                                })
+                                .await
+                                .ok()
+                                .flatten()
+                            };
+                            (descriptor, index)
+                        },
+                        |(descriptor, index)| {
+                            Message::DetailsScreen(details::Message::NextIndex(descriptor, index))
+                        },
+                    ))
+                }),
+        )
+    }
+
     fn get_wallet_address(&self, address_kind: AddressKind) -> Task<Message> {
         if let Some(wallet) = self.wallets.get_current() {
             self.client
@@ -196,6 +563,78 @@ impl State {
         }
     }
 
+    fn load_wallet_config(&mut self) {
+        let Some(label) = self.wallets.get_current().map(|w| w.label.clone()) else {
+            return;
+        };
+        if self
+            .wallet_config
+            .as_ref()
+            .is_some_and(|(l, _)| l == &label)
+        {
+            return;
+        }
+        let wallet_config = self.config.load_wallet_config(&label);
+        self.fee_rate_selector
+            .set_default_option(wallet_config.fee_rate);
+        self.auto_renewing.clear();
+        self.wallet_config = Some((label, wallet_config));
+    }
+
+    fn get_watchlist_info(&self) -> Task<Message> {
+        Task::batch(
+            self.wallet_config
+                .iter()
+                .flat_map(|(_, c)| c.watchlist.iter())
+                .map(|slabel| self.get_space_info(slabel.clone())),
+        )
+    }
+
+    /// Renews owned spaces of the current wallet that are close to expiring
+    fn auto_renew(&mut self) -> Task<Message> {
+        let (Some(wallet), Some((_, wallet_config))) =
+            (self.wallets.get_current(), self.wallet_config.as_ref())
+        else {
+            return Task::none();
+        };
+        if !wallet_config.auto_renew || !wallet.is_synced() || self.tip_height == 0 {
+            return Task::none();
+        }
+        let expiring: Vec<SLabel> = wallet
+            .state
+            .owned_spaces
+            .iter()
+            .filter(|slabel| {
+                !self.auto_renewing.contains(slabel)
+                    && !wallet.state.pending_spaces.contains(slabel)
+                    && matches!(
+                        self.spaces.get_covenant(slabel),
+                        Some(Some(Covenant::Transfer { expire_height, .. }))
+                            if *expire_height <= self.tip_height + AUTO_RENEW_BLOCKS
+                    )
+            })
+            .cloned()
+            .collect();
+        let label = wallet.label.clone();
+        self.auto_renewing.extend(expiring.iter().cloned());
+        Task::batch(expiring.into_iter().map(|slabel| {
+            self.client
+                .renew_space(label.clone(), slabel.clone(), None)
+                .map(move |r| Message::AutoRenewed(slabel.clone(), r))
+        }))
+    }
+
+    fn set_current_wallet(&mut self, name: String) -> Task<Message> {
+        self.wallets.set_current(&name);
+        self.load_wallet_config();
+        if !self.config.loaded_wallets.contains(&name) {
+            self.config.loaded_wallets.push(name.clone());
+        }
+        self.config.wallet = Some(name);
+        self.config.save();
+        self.list_wallets()
+    }
+
     fn get_space_info(&self, slabel: SLabel) -> Task<Message> {
         self.client.get_space_info(slabel).map(Message::SpaceInfo)
     }
@@ -238,7 +677,7 @@ impl State {
                 if let Some(slabel) = self.spaces_screen.get_slabel() {
                     self.get_space_info(slabel)
                 } else {
-                    self.get_wallet_spaces()
+                    Task::batch([self.get_wallet_spaces(), self.get_watchlist_info()])
                 }
             }
             Route::Space(slabel) => {
@@ -256,41 +695,235 @@ impl State {
             }
             Route::Settings => {
                 self.screen = Screen::Settings;
+                if self.client.is_managed() {
+                    Task::done(Message::SettingsScreen(
+                        settings::Message::StorageRefreshPress,
+                    ))
+                } else {
+                    Task::none()
+                }
+            }
+            Route::Diagnostics => {
+                self.screen = Screen::Diagnostics;
+                Task::none()
+            }
+            Route::Console => {
+                self.screen = Screen::Console;
                 Task::none()
             }
+            Route::Node => {
+                self.screen = Screen::Node;
+                self.node_status()
+            }
+            Route::Plugin(index) => {
+                self.screen = Screen::Plugin(index);
+                self.plugin_screen = None;
+                let params = json!({
+                    "network": self.config.backend.as_ref().map(|b| b.network().to_string()),
+                    "wallet": self.wallets.get_current().map(|w| w.label.clone()),
+                });
+                Task::perform(
+                    self.plugins[index].clone().call("screen", params),
+                    move |r| Message::PluginScreen(index, r),
+                )
+            }
+            Route::WalletDetails => {
+                self.screen = Screen::WalletDetails;
+                Task::batch([
+                    self.get_server_info(),
+                    self.get_wallet_info(),
+                    self.get_wallet_address(AddressKind::Coin),
+                    self.get_wallet_address(AddressKind::Space),
+                ])
+            }
         }
     }
 
     pub fn update(&mut self, message: Message) -> Action {
         match message {
             Message::Tick => {
-                let mut tasks = vec![self.get_server_info(), self.get_wallet_info()];
-                match self.screen {
-                    Screen::Home => {
-                        tasks.push(self.get_wallet_balance());
-                        tasks.push(self.get_wallet_transactions());
-                    }
-                    Screen::Spaces => {
-                        tasks.push(self.get_wallet_spaces());
+                if self
+                    .unreachable
+                    .as_ref()
+                    .is_some_and(|unreachable| Instant::now() < unreachable.retry_at)
+                {
+                    return Action::Task(Task::none());
+                }
+                let mut tasks = vec![self.get_server_info()];
+                // wallet calls go out in a single batch
+                let mut calls = Vec::new();
+                let current = self.wallets.get_current().map(|w| w.label.clone());
+                if let Some(current) = current.as_ref() {
+                    calls.push((current.clone(), WalletCall::Info));
+                }
+                for wallet in self.wallets.get_loaded() {
+                    if Some(wallet.label) != current.as_ref() {
+                        calls.push((wallet.label.clone(), WalletCall::Info));
+                        calls.push((wallet.label.clone(), WalletCall::Balance));
+                    }
+                }
+                match (self.screen, current) {
+                    (Screen::Home, Some(current)) => {
+                        calls.push((current.clone(), WalletCall::Balance));
+                        calls.push((
+                            current,
+                            WalletCall::Transactions(self.home_screen.get_transactions_limit()),
+                        ));
+                    }
+                    (Screen::Spaces, current) => {
+                        if let Some(current) = current {
+                            calls.push((current, WalletCall::Spaces));
+                        }
                         if let Some(slabel) = self.spaces_screen.get_slabel() {
                             tasks.push(self.get_space_info(slabel));
+                        } else {
+                            tasks.push(self.get_watchlist_info());
                         }
                     }
                     _ => {}
                 }
+                if !calls.is_empty() {
+                    tasks.push(self.wallet_calls(calls));
+                }
                 Action::Task(Task::batch(tasks))
             }
             Message::LogReceived(log) => {
-                self.log_buffer.push(log);
+                self.push_log(log);
+                Action::Task(Task::none())
+            }
+            Message::LogFilterSelect(filter) => {
+                self.log_filter = filter;
+                Action::Task(Task::none())
+            }
+            Message::LogSearchInput(search) => {
+                self.log_search = search;
+                Action::Task(Task::none())
+            }
+            Message::LogLevelSelect(level) => {
+                self.log_level = level;
+                Action::Task(Task::none())
+            }
+            Message::LogPauseToggle => {
+                match self.log_paused.take() {
+                    Some(pending) => self.log_buffer.extend(pending),
+                    None => self.log_paused = Some(AllocRingBuffer::new(LOG_SCROLLBACK)),
+                }
+                Action::Task(Task::none())
+            }
+            Message::LogsCopyPress => {
+                Action::Task(clipboard::write(self.logs_report(self.visible_logs())))
+            }
+            Message::LogsSavePress => {
+                let report = self.logs_report(self.log_buffer.iter().collect());
+                Action::Task(Task::future(async move {
+                    let file_path = rfd::AsyncFileDialog::new()
+                        .add_filter("Log file", &["log", "txt"])
+                        .set_file_name("akron.log")
+                        .save_file()
+                        .await
+                        .map(|file| file.path().to_path_buf());
+
+                    let result = if let Some(file_path) = file_path {
+                        tokio::fs::write(&file_path, report)
+                            .await
+                            .map_err(|e| e.to_string())
+                    } else {
+                        Ok(())
+                    };
+                    Message::LogsSaved(result)
+                }))
+            }
+            Message::LogsSaved(result) => {
+                if let Err(err) = result {
+                    self.push_log(LogLine::error(format!("Could not save the logs: {}", err)));
+                }
                 Action::Task(Task::none())
             }
             Message::NavigateTo(route) => Action::Task(self.navigate_to(route)),
             Message::ServerInfo(result) => {
-                if let Ok(server_info) = result {
-                    self.tip_height = server_info.chain.headers;
+                let mut task = Task::none();
+                match result {
+                    Ok(server_info) => {
+                        self.unreachable = None;
+                        let endpoint = self.client.endpoint();
+                        if endpoint != self.endpoint {
+                            self.endpoint = endpoint;
+                            task = self.reload_wallets();
+                        }
+                        let previous_tip = self.server_info.as_ref().map(|info| info.tip.height);
+                        if previous_tip.is_some_and(|tip| tip < server_info.tip.height) {
+                            task = self.run_plugin_hooks(
+                                "block",
+                                json!({ "height": server_info.tip.height }),
+                            );
+                        }
+                        self.tip_height = server_info.chain.headers;
+                        let stall = self
+                            .sync_watchdog
+                            .observe(server_info.tip.height, server_info.chain.headers);
+                        if let Some(stall) = stall {
+                            self.push_log(LogLine {
+                                level: LogLevel::Warn,
+                                ..LogLine::new(
+                                    stall
+                                        .message(server_info.tip.height, server_info.chain.headers),
+                                )
+                            });
+                            let service = self
+                                .config
+                                .backend
+                                .as_ref()
+                                .and_then(|backend| stall.service(backend));
+                            if let Some(service) =
+                                service.filter(|_| self.config.restart_stuck_services)
+                            {
+                                task = Task::batch([task, self.restart_stalled(service)]);
+                            }
+                        }
+                        self.server_info = Some(server_info);
+                    }
+                    // Akron's own services can't reject the login it generated
+                    Err(err) if err == UNAUTHORIZED_ERROR && self.reauth.is_none() => {
+                        if let Some(ConfigBackend::Spaced { user, .. }) =
+                            self.config.backend.as_ref()
+                        {
+                            self.reauth = Some((user.clone(), String::new()));
+                        }
+                    }
+                    Err(error) => {
+                        let failures = self.unreachable.as_ref().map_or(0, |u| u.failures) + 1;
+                        self.unreachable = Some(Unreachable {
+                            failures,
+                            retry_at: Instant::now() + Backoff::POLL.delay(failures),
+                            error,
+                        });
+                    }
                 }
+                Action::Task(task)
+            }
+            Message::RetryBackendPress => {
+                if let Some(unreachable) = self.unreachable.as_mut() {
+                    unreachable.retry_at = Instant::now();
+                }
+                Action::Task(Task::done(Message::Tick))
+            }
+            Message::StallRestartPress(service) => Action::Task(self.restart_stalled(service)),
+            Message::StallRestarted(service, result) => {
+                self.push_log(match result {
+                    Ok(()) => {
+                        LogLine::new(format!("Restarted {} as sync was stuck", service.as_str()))
+                    }
+                    Err(e) => {
+                        LogLine::error(format!("Could not restart {}: {}", service.as_str(), e))
+                    }
+                });
                 Action::Task(Task::none())
             }
+            Message::ServiceRestarted(kind) => Action::Task(if kind == ServiceKind::Spaces {
+                self.reload_wallets()
+            } else {
+                Task::none()
+            }),
             Message::ListWallets(result) => Action::Task(match result {
                 Ok(wallets_names) => {
                     self.wallets.set_wallets(&wallets_names);
@@ -299,21 +932,63 @@ impl State {
                             self.wallets.set_current(name);
                         }
                     }
-                    if let Some(wallet) = self.wallets.get_current() {
+                    self.load_wallet_config();
+                    let loaded_count = self.config.loaded_wallets.len();
+                    self.config
+                        .loaded_wallets
+                        .retain(|name| wallets_names.contains(name));
+                    if self.config.loaded_wallets.len() != loaded_count {
+                        self.config.save();
+                    }
+                    let mut tasks: Vec<Task<Message>> = self
+                        .config
+                        .loaded_wallets
+                        .iter()
+                        .filter(|name| self.wallets.load(name))
+                        .map(|name| {
+                            self.client
+                                .load_wallet(name.clone())
+                                .map(Message::WalletLoad)
+                        })
+                        .collect();
+                    tasks.push(if let Some(wallet) = self.wallets.get_current() {
                         self.client
                             .load_wallet(wallet.label.clone())
                             .map(Message::WalletLoad)
                     } else {
                         self.navigate_to(Route::Settings)
-                    }
+                    });
+                    Task::batch(tasks)
                 }
                 Err(_) => self.list_wallets(),
             }),
-            Message::WalletLoad(result) => Action::Task(if result.result.is_ok() {
+            Message::WalletLoad(result) => Action::Task(if result.result.is_err() {
+                Task::none()
+            } else if self
+                .wallets
+                .get_current()
+                .is_some_and(|w| w.label == &result.label)
+            {
                 Task::batch([self.get_wallet_info(), self.navigate_to(Route::Home)])
             } else {
-                Task::none()
+                Task::batch([
+                    self.client
+                        .get_wallet_info(result.label.clone())
+                        .map(Message::WalletInfo),
+                    self.client
+                        .get_wallet_balance(result.label)
+                        .map(Message::WalletBalance),
+                ])
             }),
+            Message::WalletReloaded(WalletResult { label, result }) => {
+                if let Err(e) = result {
+                    self.push_log(LogLine::error(format!(
+                        "Could not load wallet {} again: {}",
+                        label, e
+                    )));
+                }
+                Action::Task(Task::none())
+            }
             Message::WalletInfo(WalletResult {
                 label: wallet,
                 result,
@@ -340,36 +1015,85 @@ impl State {
                 label: wallet,
                 result,
             }) => {
+                let mut tasks = Vec::new();
                 if let Ok(spaces) = result {
+                    if let Some(tip) = self.server_info.as_ref().map(|info| info.tip.height) {
+                        let events = self.watcher.spaces(&wallet, tip, &spaces);
+                        tasks.push(self.fire_webhooks(events));
+                    }
                     if let Some(wallet_state) = self.wallets.get_data_mut(&wallet) {
-                        let mut collect = |spaces: Vec<FullSpaceOut>| -> Vec<SLabel> {
-                            spaces
-                                .into_iter()
-                                .map(|out| {
-                                    let name = out.spaceout.space.as_ref().unwrap().name.clone();
-                                    self.spaces.set(name.clone(), Some(out));
-                                    name
-                                })
-                                .collect()
-                        };
                         wallet_state.pending_spaces = spaces.pending;
-                        wallet_state.winning_spaces = collect(spaces.winning);
-                        wallet_state.outbid_spaces = collect(spaces.outbid);
-                        wallet_state.owned_spaces = collect(spaces.owned);
+                        self.spaces
+                            .sync_list(&mut wallet_state.winning_spaces, spaces.winning);
+                        self.spaces
+                            .sync_list(&mut wallet_state.outbid_spaces, spaces.outbid);
+                        self.spaces
+                            .sync_list(&mut wallet_state.owned_spaces, spaces.owned);
                     }
                 }
+                tasks.push(self.auto_renew());
+                Action::Task(Task::batch(tasks))
+            }
+            Message::AutoRenewed(slabel, WalletResult { result, .. }) => {
+                let error = match result {
+                    Ok(response) => {
+                        response
+                            .result
+                            .into_iter()
+                            .find_map(|r| r.error)
+                            .map(|errors| {
+                                errors
+                                    .into_iter()
+                                    .map(|(k, v)| format!("{}: {}", k, v))
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            })
+                    }
+                    Err(err) => Some(err),
+                };
+                self.push_log(match error {
+                    Some(err) => {
+                        LogLine::error(format!("Auto-renewing {} failed: {}", slabel, err))
+                    }
+                    None => LogLine::new(format!("Auto-renewed {}", slabel)),
+                });
                 Action::Task(Task::none())
             }
             Message::WalletTransactions(WalletResult {
                 label: wallet,
                 result,
             }) => {
+                let mut tasks = Vec::new();
                 if let Ok(transactions) = result {
+                    tasks.push(self.plugin_transaction_tasks(&wallet, &transactions));
+                    let events = self.watcher.transactions(&wallet, &transactions);
+                    tasks.push(self.fire_webhooks(events));
+                    for tx in &transactions {
+                        let Some(space) = tx
+                            .events
+                            .iter()
+                            .find(|event| matches!(event.kind, TxEventKind::Buy))
+                            .and_then(|event| event.space.clone())
+                        else {
+                            continue;
+                        };
+                        let price = tx
+                            .sent
+                            .checked_sub(tx.received)
+                            .and_then(|amount| amount.checked_sub(tx.fee.unwrap_or(Amount::ZERO)))
+                            .unwrap_or(Amount::ZERO);
+                        self.price_history.record(state::SaleRecord {
+                            space,
+                            price: price.to_sat(),
+                            txid: tx.txid,
+                            block_height: tx.block_height,
+                        });
+                    }
                     if let Some(wallet_state) = self.wallets.get_data_mut(&wallet) {
                         wallet_state.transactions = transactions;
                     }
                 }
-                Action::Task(Task::none())
+                Action::Task(Task::batch(tasks))
             }
             Message::WalletAddress(WalletResult {
                 label: wallet,
@@ -377,15 +1101,51 @@ impl State {
             }) => {
                 if let Ok((address_kind, address)) = result {
                     if let Some(wallet_state) = self.wallets.get_data_mut(&wallet) {
-                        let address = Some(state::AddressData::new(address));
-                        match address_kind {
-                            AddressKind::Coin => wallet_state.coin_address = address,
-                            AddressKind::Space => wallet_state.space_address = address,
+                        let current = match address_kind {
+                            AddressKind::Coin => &mut wallet_state.coin_address,
+                            AddressKind::Space => &mut wallet_state.space_address,
+                        };
+                        // an unchanged address keeps its QR code
+                        if current
+                            .as_ref()
+                            .is_none_or(|current| current.as_str() != address)
+                        {
+                            *current = Some(state::AddressData::new(address.clone()));
+                            let qr_code = Task::perform(
+                                state::AddressData::qr_code(address.clone()),
+                                move |qr_code| {
+                                    Message::AddressQrCode(wallet.clone(), address.clone(), qr_code)
+                                },
+                            );
+                            return Action::Task(if self.screen == Screen::WalletDetails {
+                                Task::batch([qr_code, self.find_next_indices()])
+                            } else {
+                                qr_code
+                            });
+                        }
+                        if self.screen == Screen::WalletDetails {
+                            return Action::Task(self.find_next_indices());
                         }
                     }
                 }
                 Action::Task(Task::none())
             }
+            Message::AddressQrCode(wallet, address, qr_code) => {
+                if let Some(wallet_state) = self.wallets.get_data_mut(&wallet) {
+                    // the address may have changed while the code was built
+                    if let Some(data) = [
+                        &mut wallet_state.coin_address,
+                        &mut wallet_state.space_address,
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .find(|data| data.as_str() == address)
+                    {
+                        data.set_qr_code(qr_code);
+                    }
+                }
+                Action::Task(Task::none())
+            }
             Message::SpaceInfo(result) => {
                 if let Ok((slabel, out)) = result {
                     self.spaces.set(slabel, out)
@@ -395,6 +1155,9 @@ impl State {
             Message::HomeScreen(message) => Action::Task(match self.home_screen.update(message) {
                 home::Action::WriteClipboard(s) => clipboard::write(s),
                 home::Action::ShowSpace { slabel } => self.navigate_to(Route::Space(slabel)),
+                home::Action::Detach(txid) => {
+                    return Action::Detach(Detached::Transaction(txid));
+                }
                 home::Action::GetTransactions => self.get_wallet_transactions(),
                 home::Action::BumpFee { txid, fee_rate } => self
                     .client
@@ -452,6 +1215,20 @@ impl State {
                 Action::Task(match self.spaces_screen.update(message) {
                     spaces::Action::WriteClipboard(s) => clipboard::write(s),
                     spaces::Action::GetSpaceInfo { slabel } => self.get_space_info(slabel),
+                    spaces::Action::GetWatchlistInfo => self.get_watchlist_info(),
+                    spaces::Action::ToggleWatch(slabel) => {
+                        if let Some((_, wallet_config)) = self.wallet_config.as_mut() {
+                            if let Some(index) =
+                                wallet_config.watchlist.iter().position(|s| s == &slabel)
+                            {
+                                wallet_config.watchlist.remove(index);
+                            } else {
+                                wallet_config.watchlist.push(slabel);
+                            }
+                            wallet_config.save();
+                        }
+                        Task::none()
+                    }
                     spaces::Action::OpenSpace { slabel, amount } => {
                         if self.fee_rate.is_none() {
                             self.fee_rate_confirmed_message =
@@ -530,6 +1307,44 @@ impl State {
                             )
                             .map(|r| Message::MarketScreen(market::Message::BuyResult(r.result)))
                     }
+                    market::Action::FetchListing(url) => Task::future(async move {
+                        let result = fetch_listing(&url)
+                            .await
+                            .map(Some)
+                            .map_err(|e| format!("Could not fetch listing: {}", e));
+                        Message::MarketScreen(market::Message::ListingLoaded(result))
+                    }),
+                    market::Action::LoadListingFile => Task::future(async move {
+                        let path = rfd::AsyncFileDialog::new()
+                            .add_filter("Listing", &["json", "txt"])
+                            .add_filter("All files", &["*"])
+                            .pick_file()
+                            .await
+                            .map(|file| file.path().to_path_buf());
+
+                        let result = if let Some(path) = path {
+                            tokio::fs::read_to_string(&path)
+                                .await
+                                .map(Some)
+                                .map_err(|err| format!("Failed to read file: {}", err))
+                        } else {
+                            Ok(None)
+                        };
+                        Message::MarketScreen(market::Message::ListingLoaded(result))
+                    }),
+                    market::Action::VerifyListing {
+                        key,
+                        slabel,
+                        listing,
+                    } => Task::batch([
+                        self.get_space_info(slabel),
+                        self.client.verify_listing(listing).map(move |result| {
+                            Message::MarketScreen(market::Message::ListingVerified(
+                                key.clone(),
+                                result,
+                            ))
+                        }),
+                    ]),
                     market::Action::Sell { slabel, price } => self
                         .client
                         .sell_space(
@@ -538,11 +1353,157 @@ impl State {
                             price,
                         )
                         .map(|r| Message::MarketScreen(market::Message::SellResult(r.result))),
+                    market::Action::PublishListing { slabel, listing } => {
+                        let relays = self.config.relays.clone();
+                        let price = listing.price;
+                        let content = serde_json::to_string(&listing).unwrap();
+                        self.client
+                            .sign_event(
+                                self.wallets.get_current().unwrap().label.clone(),
+                                slabel.clone(),
+                                nostr::listing_event(&slabel.to_string(), price, content),
+                            )
+                            .then(move |result| {
+                                let relays = relays.clone();
+                                Task::future(async move {
+                                    let result = match result.result {
+                                        Ok(event) => Ok(nostr::publish_event(relays, event).await),
+                                        Err(err) => Err(err),
+                                    };
+                                    Message::MarketScreen(market::Message::PublishResult(result))
+                                })
+                            })
+                    }
+                    market::Action::FetchListings => Task::perform(
+                        nostr::fetch_events(
+                            self.config.relays.clone(),
+                            vec![nostr::listings_filter()],
+                        ),
+                        |events| Message::MarketScreen(market::Message::ListingsFetched(events)),
+                    ),
+                    market::Action::FetchOffers => {
+                        let owned_spaces = &self.wallets.get_current().unwrap().state.owned_spaces;
+                        let pubkeys: Vec<String> = owned_spaces
+                            .iter()
+                            .filter_map(|slabel| self.spaces.get_outpoint(slabel))
+                            .filter_map(|(_, public_key)| public_key.map(|pk| pk.to_string()))
+                            .collect();
+                        let spaces: Vec<String> =
+                            owned_spaces.iter().map(|s| s.to_string()).collect();
+                        Task::perform(
+                            nostr::fetch_events(
+                                self.config.relays.clone(),
+                                nostr::offers_filters(&spaces, &pubkeys),
+                            ),
+                            |events| Message::MarketScreen(market::Message::OffersFetched(events)),
+                        )
+                    }
+                    market::Action::SendOffer {
+                        signer,
+                        event,
+                        to_file,
+                    } => {
+                        let relays = self.config.relays.clone();
+                        self.client
+                            .sign_event(
+                                self.wallets.get_current().unwrap().label.clone(),
+                                signer,
+                                event,
+                            )
+                            .then(move |result| {
+                                let relays = relays.clone();
+                                let result = result.result;
+                                Task::future(async move {
+                                    if !to_file {
+                                        let result = match result {
+                                            Ok(event) => {
+                                                Ok(nostr::publish_event(relays, event).await)
+                                            }
+                                            Err(err) => Err(err),
+                                        };
+                                        return Message::MarketScreen(market::Message::OfferSent(
+                                            result,
+                                        ));
+                                    }
+                                    let result = match result {
+                                        Ok(event) => {
+                                            let file_path = rfd::AsyncFileDialog::new()
+                                                .add_filter("JSON event", &["json"])
+                                                .add_filter("All files", &["*"])
+                                                .save_file()
+                                                .await
+                                                .map(|file| file.path().to_path_buf());
+
+                                            if let Some(file_path) = file_path {
+                                                let contents = serde_json::to_vec(&event).unwrap();
+                                                tokio::fs::write(&file_path, contents)
+                                                    .await
+                                                    .map_err(|e| e.to_string())
+                                            } else {
+                                                Ok(())
+                                            }
+                                        }
+                                        Err(err) => Err(err),
+                                    };
+                                    Message::MarketScreen(market::Message::OfferSaved(result))
+                                })
+                            })
+                    }
+                    market::Action::LoadOfferFile => Task::future(async move {
+                        let path = rfd::AsyncFileDialog::new()
+                            .add_filter("JSON event", &["json"])
+                            .pick_file()
+                            .await
+                            .map(|file| file.path().to_path_buf());
+
+                        let result = if let Some(path) = path {
+                            match tokio::fs::read_to_string(&path).await {
+                                Ok(content) => serde_json::from_str(&content)
+                                    .map(Some)
+                                    .map_err(|err| format!("Failed to parse JSON: {}", err)),
+                                Err(err) => Err(format!("Failed to read file: {}", err)),
+                            }
+                        } else {
+                            Ok(None)
+                        };
+                        Message::MarketScreen(market::Message::OfferFileLoaded(result))
+                    }),
                     market::Action::WriteClipboard(s) => clipboard::write(s),
+                    market::Action::SaveQr(svg) => Task::future(async move {
+                        let file_path = rfd::AsyncFileDialog::new()
+                            .add_filter("SVG image", &["svg"])
+                            .save_file()
+                            .await
+                            .map(|file| file.path().to_path_buf());
+
+                        let result = match file_path {
+                            Some(file_path) => tokio::fs::write(&file_path, svg)
+                                .await
+                                .map_err(|e| e.to_string()),
+                            None => Ok(()),
+                        };
+                        Message::MarketScreen(market::Message::QrSaved(result))
+                    }),
+                    market::Action::Purchased { record } => {
+                        self.price_history.record(record);
+                        self.navigate_to(Route::Transactions)
+                    }
                     market::Action::ShowTransactions => self.navigate_to(Route::Transactions),
+                    market::Action::GetSpacesInfo(slabels) => Task::batch(
+                        slabels
+                            .into_iter()
+                            .map(|slabel| self.get_space_info(slabel)),
+                    ),
                     market::Action::None => Task::none(),
                 })
             }
+            Message::DetailsScreen(message) => {
+                Action::Task(match self.details_screen.update(message) {
+                    details::Action::ShowSettings => self.navigate_to(Route::Settings),
+                    details::Action::WriteClipboard(s) => clipboard::write(s),
+                    details::Action::None => Task::none(),
+                })
+            }
             Message::SignScreen(message) => Action::Task(match self.sign_screen.update(message) {
                 sign::Action::FilePick => Task::future(async move {
                     let path = rfd::AsyncFileDialog::new()
@@ -564,6 +1525,23 @@ impl State {
                     };
                     Message::SignScreen(sign::Message::EventFileLoaded(result))
                 }),
+                sign::Action::VerifyFilePick => Task::future(async move {
+                    let path = rfd::AsyncFileDialog::new()
+                        .add_filter("JSON", &["json"])
+                        .pick_file()
+                        .await
+                        .map(|file| file.path().to_path_buf());
+
+                    let result = if let Some(path) = path {
+                        tokio::fs::read_to_string(&path)
+                            .await
+                            .map(|content| Some((path.to_string_lossy().to_string(), content)))
+                            .map_err(|err| format!("Failed to read file: {}", err))
+                    } else {
+                        Ok(None)
+                    };
+                    Message::SignScreen(sign::Message::VerifyFileLoaded(result))
+                }),
                 sign::Action::Sign(slabel, event) => self
                     .client
                     .sign_event(
@@ -571,41 +1549,89 @@ impl State {
                         slabel,
                         event,
                     )
-                    .then(|result| {
-                        let result = result.result;
-                        Task::future(async move {
-                            let result = match result {
-                                Ok(event) => {
-                                    let file_path = rfd::AsyncFileDialog::new()
-                                        .add_filter("JSON event", &["json"])
-                                        .add_filter("All files", &["*"])
-                                        .save_file()
-                                        .await
-                                        .map(|file| file.path().to_path_buf());
+                    .map(|r| Message::SignScreen(sign::Message::EventSigned(r.result))),
+                sign::Action::SaveEvent(event) => Task::future(async move {
+                    let file_path = rfd::AsyncFileDialog::new()
+                        .add_filter("JSON event", &["json"])
+                        .add_filter("All files", &["*"])
+                        .save_file()
+                        .await
+                        .map(|file| file.path().to_path_buf());
 
-                                    if let Some(file_path) = file_path {
-                                        use spaces_wallet::bdk_wallet::serde_json;
-                                        let contents = serde_json::to_vec(&event).unwrap();
-                                        tokio::fs::write(&file_path, contents)
-                                            .await
-                                            .map_err(|e| e.to_string())
-                                    } else {
-                                        Ok(())
+                    let result = if let Some(file_path) = file_path {
+                        let contents = serde_json::to_vec(&event).unwrap();
+                        tokio::fs::write(&file_path, contents)
+                            .await
+                            .map_err(|e| e.to_string())
+                    } else {
+                        Ok(())
+                    };
+                    Message::SignScreen(sign::Message::EventFileSaved(result))
+                }),
+                sign::Action::PublishEvent(event) => Task::perform(
+                    nostr::publish_event(self.config.relays.clone(), event),
+                    |published| Message::SignScreen(sign::Message::EventPublished(published)),
+                ),
+                sign::Action::SignMessage(slabel, event) => self
+                    .client
+                    .sign_event(
+                        self.wallets.get_current().unwrap().label.clone(),
+                        slabel,
+                        event,
+                    )
+                    .map(|r| Message::SignScreen(sign::Message::SignResult(r.result))),
+                sign::Action::GetSpaceInfo(slabel) => self.get_space_info(slabel),
+                sign::Action::FetchLatest(slabel, kind) => {
+                    match self
+                        .spaces
+                        .get_outpoint(&slabel)
+                        .and_then(|(_, public_key)| public_key.as_ref())
+                    {
+                        Some(public_key) => Task::perform(
+                            nostr::fetch_events(
+                                self.config.relays.clone(),
+                                vec![nostr::latest_filter(&public_key.to_string(), kind)],
+                            ),
+                            move |events| {
+                                Message::SignScreen(sign::Message::LatestFetched(kind, events))
+                            },
+                        ),
+                        None => Task::done(Message::SignScreen(sign::Message::LatestFetched(
+                            kind,
+                            Err("The key of the space isn't known yet".to_string()),
+                        ))),
+                    }
+                }
+                sign::Action::SignAndPublish(slabel, event) => {
+                    let relays = self.config.relays.clone();
+                    self.client
+                        .sign_event(
+                            self.wallets.get_current().unwrap().label.clone(),
+                            slabel,
+                            event,
+                        )
+                        .then(move |result| {
+                            let relays = relays.clone();
+                            Task::future(async move {
+                                match result.result {
+                                    Ok(event) => {
+                                        Message::SignScreen(sign::Message::EventPublished(
+                                            nostr::publish_event(relays, event).await,
+                                        ))
+                                    }
+                                    Err(err) => {
+                                        Message::SignScreen(sign::Message::EventSigned(Err(err)))
                                     }
                                 }
-                                Err(err) => Err(err),
-                            };
-                            Message::SignScreen(sign::Message::EventFileSaved(result))
+                            })
                         })
-                    }),
+                }
+                sign::Action::WriteClipboard(s) => clipboard::write(s),
                 sign::Action::None => Task::none(),
             }),
             Message::SettingsScreen(message) => match self.settings_screen.update(message) {
                 settings::Action::SetCurrentWallet(name) => {
-                    self.wallets.set_current(&name);
-                    self.config.wallet = Some(name);
-                    self.config.save();
-                    Action::Task(self.list_wallets())
+                    Action::Task(self.set_current_wallet(name))
                 }
                 settings::Action::ExportWallet(wallet_name) => {
                     Action::Task(self.client.export_wallet(wallet_name).then(|result| {
@@ -623,9 +1649,10 @@ impl State {
                                     if let Some(file_path) = file_path {
                                         tokio::fs::write(&file_path, contents)
                                             .await
+                                            .map(|_| true)
                                             .map_err(|e| e.to_string())
                                     } else {
-                                        Ok(())
+                                        Ok(false)
                                     }
                                 }
                                 Err(err) => Err(err),
@@ -634,6 +1661,112 @@ impl State {
                         })
                     }))
                 }
+                settings::Action::ExportEncrypted { wallet, password } => {
+                    Action::Task(self.client.export_wallet(wallet).then(move |result| {
+                        let password = password.clone();
+                        Task::future(async move {
+                            let label = result.label;
+                            let result = match result.result.and_then(|contents| {
+                                let recipient = age::scrypt::Recipient::new(password.into());
+                                age::encrypt_and_armor(&recipient, contents.as_bytes())
+                                    .map_err(|e| e.to_string())
+                            }) {
+                                Ok(encrypted) => {
+                                    let file_path = rfd::AsyncFileDialog::new()
+                                        .set_file_name(format!("{}-private-keys.age", label))
+                                        .add_filter("Encrypted file", &["age"])
+                                        .save_file()
+                                        .await
+                                        .map(|file| file.path().to_path_buf());
+
+                                    if let Some(file_path) = file_path {
+                                        tokio::fs::write(&file_path, encrypted)
+                                            .await
+                                            .map(|_| true)
+                                            .map_err(|e| e.to_string())
+                                    } else {
+                                        Ok(false)
+                                    }
+                                }
+                                Err(err) => Err(err),
+                            };
+                            Message::SettingsScreen(settings::Message::WalletFileSaved(result))
+                        })
+                    }))
+                }
+                settings::Action::ExportAppBackup { password } => {
+                    let wallets = self.wallets.get_wallets().into_iter().cloned().collect();
+                    let config = self.config.clone();
+                    Action::Task(self.client.export_wallets(wallets).then(move |result| {
+                        let config = config.clone();
+                        let password = password.clone();
+                        Task::future(async move {
+                            let result = match result {
+                                // scrypt takes a moment, off the UI thread
+                                Ok(wallets) => tokio::task::spawn_blocking(move || {
+                                    AppBackup::new(&config, wallets).encrypt(password)
+                                })
+                                .await
+                                .map_err(|e| e.to_string())
+                                .and_then(|result| result),
+                                Err(err) => Err(err),
+                            };
+                            let result = match result {
+                                Ok(encrypted) => {
+                                    let file_path = rfd::AsyncFileDialog::new()
+                                        .set_file_name("akron-backup.age")
+                                        .add_filter("Encrypted file", &["age"])
+                                        .save_file()
+                                        .await
+                                        .map(|file| file.path().to_path_buf());
+
+                                    if let Some(file_path) = file_path {
+                                        tokio::fs::write(&file_path, encrypted)
+                                            .await
+                                            .map(|_| true)
+                                            .map_err(|e| e.to_string())
+                                    } else {
+                                        Ok(false)
+                                    }
+                                }
+                                Err(err) => Err(err),
+                            };
+                            Message::SettingsScreen(settings::Message::AppBackupSaved(result))
+                        })
+                    }))
+                }
+                settings::Action::RescanWallet { wallet, height } => {
+                    // restarting spaced unloads every wallet
+                    let current = self.wallets.get_current().map(|w| w.label.clone());
+                    let reload: Vec<Task<Message>> = self
+                        .wallets
+                        .get_loaded()
+                        .into_iter()
+                        .filter(|w| Some(w.label) != current.as_ref())
+                        .map(|w| {
+                            self.client
+                                .load_wallet(w.label.clone())
+                                .map(Message::WalletLoad)
+                        })
+                        .collect();
+                    Action::Task(
+                        self.client
+                            .rescan_wallet(wallet, height)
+                            .map(|r| Message::SettingsScreen(settings::Message::WalletRescanned(r)))
+                            .chain(Task::batch(reload))
+                            .chain(self.list_wallets()),
+                    )
+                }
+                settings::Action::CopyPublicDescriptors(wallet_name) => {
+                    Action::Task(self.client.export_public_descriptors(wallet_name).then(
+                        |result| match result.result {
+                            Ok(descriptors) => clipboard::write(descriptors.join("\n")),
+                            Err(err) => Task::done(Message::SettingsScreen(
+                                settings::Message::DescriptorsCopyFailed(err),
+                            )),
+                        },
+                    ))
+                }
                 settings::Action::CreateWallet(wallet_name) => {
                     self.config.wallet = None;
                     self.wallets.unset_current();
@@ -649,7 +1782,7 @@ impl State {
                 settings::Action::FilePick => Action::Task(
                     Task::future(async move {
                         let result = rfd::AsyncFileDialog::new()
-                            .add_filter("wallet file", &["json"])
+                            .add_filter("wallet file", &["json", "age"])
                             .pick_file()
                             .await;
                         match result {
@@ -659,6 +1792,24 @@ impl State {
                     })
                     .map(|r| Message::SettingsScreen(settings::Message::WalletFileLoaded(r))),
                 ),
+                settings::Action::ImportEncryptedWallet { contents, password } => {
+                    Action::Task(Task::perform(
+                        async move {
+                            // scrypt takes a moment, off the UI thread
+                            tokio::task::spawn_blocking(move || {
+                                let identity = age::scrypt::Identity::new(password.into());
+                                let contents = age::decrypt(&identity, contents.as_bytes())
+                                    .map_err(|e| format!("Could not decrypt the file: {}", e))?;
+                                String::from_utf8(contents).map_err(|e| e.to_string())
+                            })
+                            .await
+                            .map_err(|e| e.to_string())?
+                        },
+                        |result| {
+                            Message::SettingsScreen(settings::Message::WalletFileDecrypted(result))
+                        },
+                    ))
+                }
                 settings::Action::ImportWallet(contents) => {
                     self.config.wallet = None;
                     self.wallets.unset_current();
@@ -673,12 +1824,391 @@ impl State {
                             .chain(self.list_wallets()),
                     )
                 }
+                settings::Action::ImportCliWallets => Action::Task(
+                    self.client
+                        .import_cli_wallets()
+                        .map(|r| Message::SettingsScreen(settings::Message::CliWalletsImported(r)))
+                        .chain(self.list_wallets()),
+                ),
+                settings::Action::RenameWallet { wallet, new_name } => {
+                    self.wallets.unset_current();
+                    self.wallets.unload(&wallet);
+                    Action::Task(
+                        self.client
+                            .rename_wallet(wallet.clone(), new_name)
+                            .map(move |r| {
+                                Message::SettingsScreen(settings::Message::WalletRenamed(
+                                    wallet.clone(),
+                                    r,
+                                ))
+                            })
+                            .chain(self.reload_wallets())
+                            .chain(self.list_wallets()),
+                    )
+                }
+                settings::Action::DeleteWallet(wallet) => {
+                    if self.config.wallet.as_ref() == Some(&wallet) {
+                        self.config.wallet = None;
+                    }
+                    self.config.loaded_wallets.retain(|name| name != &wallet);
+                    self.config.save();
+                    self.wallets.unset_current();
+                    self.wallets.unload(&wallet);
+                    Action::Task(
+                        self.client
+                            .delete_wallet(wallet.clone())
+                            .map(move |r| {
+                                Message::SettingsScreen(settings::Message::WalletDeleted(
+                                    wallet.clone(),
+                                    r,
+                                ))
+                            })
+                            .chain(self.reload_wallets())
+                            .chain(self.list_wallets()),
+                    )
+                }
+                settings::Action::WalletRenamed { wallet, new_name } => {
+                    if self.config.wallet.as_ref() == Some(&wallet) {
+                        self.config.wallet = Some(new_name.clone());
+                    }
+                    for name in self.config.loaded_wallets.iter_mut() {
+                        if name == &wallet {
+                            *name = new_name.clone();
+                        }
+                    }
+                    self.config.save();
+                    self.config.rename_wallet_config(&wallet, &new_name);
+                    Action::Task(self.set_current_wallet(new_name))
+                }
+                settings::Action::WalletDeleted(wallet) => {
+                    self.config.remove_wallet_config(&wallet);
+                    if self
+                        .wallet_config
+                        .as_ref()
+                        .is_some_and(|(label, _)| label == &wallet)
+                    {
+                        self.wallet_config = None;
+                    }
+                    Action::Task(Task::none())
+                }
+                settings::Action::SetFeeRate(option) => {
+                    if let Some((_, wallet_config)) = self.wallet_config.as_mut() {
+                        wallet_config.fee_rate = option;
+                        wallet_config.save();
+                        self.fee_rate_selector.set_default_option(option);
+                    }
+                    Action::Task(Task::none())
+                }
+                settings::Action::SetAutoRenew(auto_renew) => {
+                    if let Some((_, wallet_config)) = self.wallet_config.as_mut() {
+                        wallet_config.auto_renew = auto_renew;
+                        wallet_config.save();
+                    }
+                    Action::Task(Task::none())
+                }
+                settings::Action::AddRelay(relay) => {
+                    if !self.config.relays.contains(&relay) {
+                        self.config.relays.push(relay);
+                        self.config.save();
+                    }
+                    Action::Task(Task::none())
+                }
+                settings::Action::AddWebhook(webhook) => {
+                    self.config.webhooks.push(webhook);
+                    self.config.save();
+                    Action::Task(Task::none())
+                }
+                settings::Action::RemoveWebhook(index) => {
+                    if index < self.config.webhooks.len() {
+                        self.config.webhooks.remove(index);
+                        self.config.save();
+                    }
+                    Action::Task(Task::none())
+                }
+                settings::Action::RemoveRelay(relay) => {
+                    self.config.relays.retain(|r| r != &relay);
+                    self.config.save();
+                    Action::Task(Task::none())
+                }
+                settings::Action::SetProxy(proxy) => {
+                    proxy::set_web_proxy(proxy.as_ref().filter(|p| p.web).map(ProxyConfig::url));
+                    self.config.proxy = proxy;
+                    self.config.save();
+                    Action::Task(Task::none())
+                }
+                settings::Action::RebuildChainData => self.rebuild_chain_data(),
+                settings::Action::SetRpcConsole(enabled) => {
+                    self.config.rpc_console = enabled;
+                    self.config.save();
+                    Action::Task(Task::none())
+                }
+                settings::Action::SetCheckUpdates(enabled) => {
+                    self.config.check_updates = enabled;
+                    self.config.save();
+                    Action::Task(if enabled {
+                        check_update()
+                    } else {
+                        Task::none()
+                    })
+                }
+                settings::Action::CheckUpdate => Action::Task(check_update()),
+                settings::Action::DownloadUpdate(asset) => Action::Task(Task::future(async move {
+                    let path = rfd::AsyncFileDialog::new()
+                        .set_file_name(&asset.name)
+                        .save_file()
+                        .await
+                        .map(|file| file.path().to_path_buf());
+                    let result = match path {
+                        Some(path) => update::download(asset, path).await.map(Some),
+                        None => Ok(None),
+                    };
+                    Message::SettingsScreen(settings::Message::UpdateDownloaded(result))
+                })),
+                settings::Action::InstallService => {
+                    let data_dir = self.config.data_dir().to_path_buf();
+                    Action::Task(Task::future(async move {
+                        let result = background::install(data_dir.clone()).await;
+                        Message::SettingsScreen(settings::Message::ServiceChanged(
+                            background::is_installed(&data_dir),
+                            result,
+                        ))
+                    }))
+                }
+                settings::Action::RemoveService => {
+                    let data_dir = self.config.data_dir().to_path_buf();
+                    Action::Task(Task::future(async move {
+                        let result = background::uninstall(data_dir.clone()).await;
+                        Message::SettingsScreen(settings::Message::ServiceChanged(
+                            background::is_installed(&data_dir),
+                            result,
+                        ))
+                    }))
+                }
+                settings::Action::SaveServiceDefinition => {
+                    let data_dir = self.config.data_dir().to_path_buf();
+                    Action::Task(Task::future(async move {
+                        let installed = background::is_installed(&data_dir);
+                        let result = async {
+                            let definition = background::definition(&data_dir)?;
+                            let file_name = definition
+                                .path
+                                .file_name()
+                                .map(|name| name.to_string_lossy().into_owned())
+                                .unwrap_or_default();
+                            let Some(file) = rfd::AsyncFileDialog::new()
+                                .set_file_name(file_name)
+                                .save_file()
+                                .await
+                            else {
+                                return Ok("No file chosen".to_string());
+                            };
+                            tokio::fs::write(file.path(), definition.contents)
+                                .await
+                                .map_err(|e| e.to_string())?;
+                            Ok(format!("Saved to {}", file.path().display()))
+                        };
+                        Message::SettingsScreen(settings::Message::ServiceChanged(
+                            installed,
+                            result.await,
+                        ))
+                    }))
+                }
+                settings::Action::SetRestartStuckServices(enabled) => {
+                    self.config.restart_stuck_services = enabled;
+                    self.config.save();
+                    Action::Task(Task::none())
+                }
+                settings::Action::SetStdoutLogs(enabled) => {
+                    self.config.stdout_logs = enabled;
+                    self.config.save();
+                    Action::Task(Task::none())
+                }
+                settings::Action::SetAutomation(enabled) => {
+                    self.config.automation_token = enabled.then(automation::generate_token);
+                    self.config.save();
+                    Action::Task(Task::none())
+                }
+                settings::Action::SetAutomationPort(port) => {
+                    self.config.automation_port = port;
+                    self.config.save();
+                    Action::Task(Task::none())
+                }
+                settings::Action::CopyAutomationToken => Action::Task(
+                    self.config
+                        .automation_token
+                        .clone()
+                        .map_or_else(Task::none, clipboard::write),
+                ),
+                settings::Action::SetPruneRefresh(enabled) => {
+                    if let Some(ConfigBackend::Akrond {
+                        prune_refresh_days, ..
+                    }) = self.config.backend.as_mut()
+                    {
+                        *prune_refresh_days = enabled.then_some(PRUNE_REFRESH_DAYS);
+                    }
+                    self.config.save();
+                    Action::Task(Task::none())
+                }
+                settings::Action::SaveProfile(name) => {
+                    self.config.save_profile(name.trim().to_string());
+                    self.config.save();
+                    Action::Task(Task::none())
+                }
+                settings::Action::SwitchProfile(name) => {
+                    if !self.config.switch_profile(&name) {
+                        return Action::Task(Task::none());
+                    }
+                    self.config.save();
+                    Action::Task(
+                        self.client
+                            .stop_services()
+                            .map(|_| Message::ServicesStopped { rebuild: false }),
+                    )
+                }
+                settings::Action::RemoveProfile(name) => {
+                    self.config.remove_profile(&name);
+                    self.config.save();
+                    Action::Task(Task::none())
+                }
+                settings::Action::TestConnection => {
+                    let network = self.config.backend.as_ref().unwrap().network().to_string();
+                    Action::Task(self.client.get_server_info().map(move |result| {
+                        Message::SettingsScreen(settings::Message::ConnectionTested(
+                            result.and_then(|info| {
+                                if info.network != network {
+                                    Err(format!("Wrong network, spaced is on {}", info.network))
+                                } else {
+                                    Ok(format!(
+                                        "Spaced is reachable, synced to block {} of {}",
+                                        info.tip.height, info.chain.headers
+                                    ))
+                                }
+                            }),
+                        ))
+                    }))
+                }
                 settings::Action::ResetBackend => {
                     self.config.remove();
                     Action::Return(self.config.clone())
                 }
+                settings::Action::ExportCheckpoint => {
+                    // where the checkpoint is served from when serving peers
+                    let served_dir = self
+                        .config
+                        .backend
+                        .as_ref()
+                        .unwrap()
+                        .services_dir(self.config.data_dir())
+                        .join("checkpoint");
+                    Action::Task(
+                        Task::future(async move {
+                            _ = tokio::fs::create_dir_all(&served_dir).await;
+                            rfd::AsyncFileDialog::new()
+                                .set_title("Folder for the checkpoint")
+                                .set_directory(served_dir)
+                                .pick_folder()
+                                .await
+                                .map(|folder| folder.path().to_path_buf())
+                        })
+                        .map(|folder| {
+                            Message::SettingsScreen(settings::Message::CheckpointFolderPicked(
+                                folder,
+                            ))
+                        }),
+                    )
+                }
+                settings::Action::ExportCheckpointTo(folder) => Action::Task(
+                    self.client
+                        .export_checkpoint(folder)
+                        .map(|result| {
+                            Message::SettingsScreen(settings::Message::CheckpointExported(result))
+                        })
+                        .chain(self.reload_wallets()),
+                ),
+                settings::Action::MeasureStorage => Action::Task(Task::perform(
+                    Client::storage_usage(
+                        self.config.data_dir().to_path_buf(),
+                        self.config.backend.clone().unwrap(),
+                    ),
+                    |usage| Message::SettingsScreen(settings::Message::StorageMeasured(usage)),
+                )),
+                settings::Action::ClearStorage(kind) => Action::Task(Task::perform(
+                    Client::clear_storage(
+                        self.config.data_dir().to_path_buf(),
+                        self.config.backend.clone().unwrap(),
+                        kind,
+                    ),
+                    |result| Message::SettingsScreen(settings::Message::StorageCleared(result)),
+                )),
+                settings::Action::CompactDatabases => Action::Task(
+                    self.client
+                        .compact_databases()
+                        .map(|progress| {
+                            Message::SettingsScreen(settings::Message::Compaction(progress))
+                        })
+                        .chain(self.reload_wallets()),
+                ),
+                settings::Action::ShowDetails => {
+                    Action::Task(self.navigate_to(Route::WalletDetails))
+                }
+                settings::Action::ShowDiagnostics => {
+                    Action::Task(self.navigate_to(Route::Diagnostics))
+                }
                 settings::Action::None => Action::Task(Task::none()),
             },
+            Message::PruneRefreshCheck => {
+                let data_dir = self.config.data_dir().to_path_buf();
+                let backend_config = self.config.backend.clone().unwrap();
+                Action::Task(Task::perform(
+                    Client::checkpoint_age(data_dir, backend_config),
+                    Message::CheckpointAge,
+                ))
+            }
+            Message::CheckpointAge(age) => {
+                let days = match self.config.backend.as_ref() {
+                    Some(ConfigBackend::Akrond {
+                        prune_refresh_days: Some(days),
+                        ..
+                    }) => *days,
+                    _ => return Action::Task(Task::none()),
+                };
+                // waits for the wallets to be synced, rebuilding interrupts them
+                let synced = self.wallets.get_current().is_none_or(|w| w.is_synced());
+                if !synced || age.is_none_or(|age| age.as_secs() < days as u64 * 24 * 3600) {
+                    return Action::Task(Task::none());
+                }
+                self.push_log(LogLine::new(format!(
+                    "Checkpoint is older than {} days, rebuilding from a newer one",
+                    days
+                )));
+                self.rebuild_chain_data()
+            }
+            Message::ServicesStopped { rebuild } => {
+                if rebuild {
+                    Action::RebuildChainData(self.config.clone())
+                } else {
+                    Action::Reconnect(self.config.clone())
+                }
+            }
+            Message::WalletSwitch(name) => Action::Task(self.set_current_wallet(name)),
+            Message::WalletShortcut(index) => Action::Task(
+                match self
+                    .wallets
+                    .get_loaded()
+                    .get(index)
+                    .map(|w| w.label.clone())
+                {
+                    Some(name) => self.set_current_wallet(name),
+                    None => Task::none(),
+                },
+            ),
+            Message::WalletUnload(name) => {
+                self.wallets.unload(&name);
+                self.config.loaded_wallets.retain(|n| n != &name);
+                self.config.save();
+                Action::Task(Task::none())
+            }
+            Message::DetachLogsPress => Action::Detach(Detached::Logs),
             Message::ToggleLogs => {
                 self.logs_expanded = !self.logs_expanded;
                 Action::Task(Task::none())
@@ -699,6 +2229,230 @@ impl State {
                     _ => task.map(Message::FeeRateSelector),
                 })
             }
+            Message::SignRequestReceived(request) => {
+                self.sign_requests.push(request);
+                Action::Task(Task::none())
+            }
+            Message::SignRequestApprove => {
+                let request = &self.sign_requests[0];
+                let (Some(wallet), Ok(slabel)) =
+                    (self.wallets.get_current(), SLabel::from_str(&request.space))
+                else {
+                    return Action::Task(Task::none());
+                };
+                self.sign_request_signing = true;
+                Action::Task(
+                    self.client
+                        .sign_event(wallet.label.clone(), slabel, request.event.clone())
+                        .map(|r| Message::SignRequestSigned(r.result)),
+                )
+            }
+            Message::SignRequestReject => Action::Task(Task::done(Message::SignRequestSigned(
+                Err("Rejected by user".to_string()),
+            ))),
+            Message::SignRequestSigned(result) => {
+                self.sign_request_signing = false;
+                let request = self.sign_requests.remove(0);
+                Action::Task(Task::future(request.respond(result)).discard())
+            }
+            Message::PluginScreen(index, result) => {
+                if self.screen == Screen::Plugin(index) {
+                    self.plugin_screen = Some(result.and_then(|lines| {
+                        serde_json::from_value(lines).map_err(|e| e.to_string())
+                    }));
+                }
+                Action::Task(Task::none())
+            }
+            Message::TransactionLabels(result) => {
+                match result.and_then(|labels| {
+                    serde_json::from_value::<HashMap<Txid, String>>(labels)
+                        .map_err(|e| e.to_string())
+                }) {
+                    Ok(labels) => self.transaction_labels.extend(labels),
+                    Err(err) => self.push_log(LogLine::error(err)),
+                }
+                Action::Task(Task::none())
+            }
+            Message::DiagnosticsScreen(message) => {
+                Action::Task(match self.diagnostics_screen.update(message) {
+                    diagnostics::Action::None => Task::none(),
+                    diagnostics::Action::ShowSettings => self.navigate_to(Route::Settings),
+                    diagnostics::Action::Run => {
+                        let filters_endpoints = match self.config.backend.as_ref() {
+                            Some(ConfigBackend::Akrond {
+                                network: ExtendedNetwork::Mainnet,
+                                filters_endpoints,
+                                ..
+                            }) if filters_endpoints.is_empty() => {
+                                vec![FILTERS_ENDPOINT.to_string()]
+                            }
+                            Some(ConfigBackend::Akrond {
+                                network: ExtendedNetwork::Mainnet,
+                                filters_endpoints,
+                                ..
+                            }) => filters_endpoints.clone(),
+                            _ => Vec::new(),
+                        };
+                        Task::perform(
+                            crate::diagnostics::run(
+                                self.client.rpc(),
+                                filters_endpoints,
+                                self.config.automation_token.is_some().then(|| {
+                                    self.config
+                                        .automation_port
+                                        .unwrap_or(automation::AUTOMATION_PORT)
+                                }),
+                            ),
+                            |checks| {
+                                Message::DiagnosticsScreen(diagnostics::Message::Finished(checks))
+                            },
+                        )
+                    }
+                    diagnostics::Action::CopyReport(checks) => clipboard::write(
+                        crate::diagnostics::report(&checks, &self.private_values()),
+                    ),
+                })
+            }
+            Message::NodeScreen(message) => Action::Task(match self.node_screen.update(message) {
+                node::Action::None => Task::none(),
+                node::Action::Refresh => self.node_status(),
+                node::Action::RestartService(kind) => {
+                    let restart = self
+                        .client
+                        .restart_service(kind)
+                        .map(|result| Message::NodeScreen(node::Message::RestartDone(result)));
+                    if kind == ServiceKind::Spaces {
+                        restart.chain(self.reload_wallets())
+                    } else {
+                        restart
+                    }
+                }
+                node::Action::Peer(action) => self
+                    .client
+                    .peer_action(action)
+                    .map(|result| Message::NodeScreen(node::Message::PeerActionDone(result))),
+            }),
+            Message::ConsoleScreen(message) => {
+                Action::Task(match self.console_screen.update(message) {
+                    console::Action::None => Task::none(),
+                    console::Action::Request { method, params } => self
+                        .client
+                        .raw_request(method.clone(), params.clone())
+                        .map(move |result| {
+                            Message::ConsoleScreen(console::Message::Response(
+                                method.clone(),
+                                params.clone(),
+                                result,
+                            ))
+                        }),
+                })
+            }
+            Message::WatchTick => {
+                let mut tasks = vec![self.fire_webhooks(Vec::new())];
+                for wallet in self.wallets.get_loaded() {
+                    let wallet = wallet.label.clone();
+                    tasks.push(
+                        self.client
+                            .get_wallet_spaces(wallet.clone())
+                            .map(Message::WalletSpaces),
+                    );
+                    tasks.push(
+                        self.client
+                            .get_wallet_transactions(
+                                wallet,
+                                self.home_screen.get_transactions_limit(),
+                            )
+                            .map(Message::WalletTransactions),
+                    );
+                }
+                Action::Task(Task::batch(tasks))
+            }
+            Message::WebhookSent(result) => {
+                if let Err((retry, err)) = result {
+                    self.push_log(LogLine::error(err));
+                    self.webhook_queue.extend(retry);
+                }
+                Action::Task(Task::none())
+            }
+            Message::AutomationRequestReceived(request) => {
+                if !self
+                    .wallets
+                    .get_loaded()
+                    .iter()
+                    .any(|wallet| *wallet.label == request.wallet)
+                {
+                    let err = format!("Wallet {} is not loaded", request.wallet);
+                    request.respond(Err(err));
+                    return Action::Task(Task::none());
+                }
+                let wallet = request.wallet.clone();
+                let task = match request.call.clone() {
+                    Call::ListSpaces => self
+                        .client
+                        .get_wallet_spaces(wallet)
+                        .map(|r| r.result.map(|spaces| serde_json::json!(spaces))),
+                    Call::Balance => self
+                        .client
+                        .get_wallet_balance(wallet)
+                        .map(|r| r.result.map(|balance| serde_json::json!(balance))),
+                    Call::Bid {
+                        space,
+                        amount,
+                        fee_rate,
+                    } => self
+                        .client
+                        .bid_space(wallet, space, amount, fee_rate)
+                        .map(|r| r.result.map(|response| serde_json::json!(response))),
+                    Call::SignEvent { space, event } => self
+                        .client
+                        .sign_event(wallet, space, event)
+                        .map(|r| r.result.map(|event| serde_json::json!(event))),
+                };
+                Action::Task(
+                    task.map(move |result| Message::AutomationRequestDone(request.clone(), result)),
+                )
+            }
+            Message::AutomationRequestDone(request, result) => {
+                let bid = matches!(request.call, Call::Bid { .. });
+                request.respond(result);
+                Action::Task(if bid {
+                    Task::batch([self.get_wallet_balance(), self.get_wallet_spaces()])
+                } else {
+                    Task::none()
+                })
+            }
+            Message::ReauthUserInput(value) => {
+                if let Some((user, _)) = self.reauth.as_mut() {
+                    *user = value;
+                }
+                Action::Task(Task::none())
+            }
+            Message::ReauthPasswordInput(value) => {
+                if let Some((_, password)) = self.reauth.as_mut() {
+                    *password = value;
+                }
+                Action::Task(Task::none())
+            }
+            Message::ReauthSubmit => {
+                let Some((new_user, new_password)) = self.reauth.take() else {
+                    return Action::Task(Task::none());
+                };
+                if let Some(ConfigBackend::Spaced { user, password, .. }) =
+                    self.config.backend.as_mut()
+                {
+                    *user = new_user;
+                    *password = new_password;
+                }
+                self.config.save();
+                Action::Reconnect(self.config.clone())
+            }
+            Message::ReauthChangeBackend => {
+                self.config.remove();
+                Action::Return(self.config.clone())
+            }
+            Message::SessionSwitch(id) => Action::SwitchSession(id),
+            Message::SessionOpen => Action::OpenSession,
+            Message::SessionClose(id) => Action::CloseSession(id),
             Message::FeeRateConfirmed(fee_rate) => {
                 self.fee_rate = FeeRate::from_sat_per_vb(fee_rate as _);
 
@@ -716,9 +2470,188 @@ impl State {
             content,
             self.fee_rate_selector.view().map(Message::FeeRateSelector)
         ]
+        .push_maybe(
+            self.sign_requests
+                .first()
+                .map(|request| self.sign_request_view(request)),
+        )
+        .push_maybe(
+            self.reauth
+                .as_ref()
+                .map(|(user, password)| reauth_view(user, password)),
+        )
         .into()
     }
 
+    fn sign_request_view<'a>(&'a self, request: &'a SignRequest) -> Element<'a, Message> {
+        let owned = self.wallets.get_current().is_some_and(|wallet| {
+            wallet
+                .state
+                .owned_spaces
+                .iter()
+                .any(|s| s.to_string() == request.space)
+        });
+        let event = serde_json::to_string_pretty(&request.event).unwrap();
+        let content = column![
+            text("Signing request").size(20),
+            text(format!(
+                "An application asks to sign this event with {}",
+                request.space
+            )),
+            scrollable(container(text_monospace(event).size(12)).padding(10)).height(250),
+        ]
+        .push_maybe(
+            request
+                .callback
+                .as_ref()
+                .map(|callback| text_small(format!("The result will be sent to {}", callback))),
+        )
+        .push_maybe((!owned).then(|| {
+            text(format!(
+                "{} is not owned by the current wallet",
+                request.space
+            ))
+            .style(text::danger)
+        }))
+        .push(row![
+            button(text("Reject"))
+                .padding(20)
+                .style(button::text)
+                .on_press_maybe((!self.sign_request_signing).then_some(Message::SignRequestReject)),
+            Space::with_width(Fill),
+            button(text(if self.sign_request_signing {
+                "Signing"
+            } else {
+                "Sign"
+            }))
+            .padding(20)
+            .on_press_maybe(
+                (owned && !self.sign_request_signing).then_some(Message::SignRequestApprove)
+            )
+            .style(|theme: &Theme, status: button::Status| {
+                let mut style = button::primary(theme, status);
+                style.border = style.border.rounded(7);
+                style
+            }),
+        ])
+        .padding(20)
+        .spacing(10);
+
+        modal(content)
+    }
+
+    /// Networks opened side by side, each with its own services and wallets
+    fn session_switcher_view(&self) -> Element<Message> {
+        let mut col = Column::new().spacing(2);
+        for (id, label) in &self.sessions {
+            let is_current = *id == self.session_id;
+            col = col.push(
+                row![button(text(label).size(14))
+                    .style(move |theme: &Theme, status: button::Status| {
+                        let mut style = if is_current {
+                            button::secondary
+                        } else {
+                            button::text
+                        }(theme, status);
+                        style.border = style.border.rounded(4);
+                        style
+                    })
+                    .width(Fill)
+                    .on_press_maybe((!is_current).then_some(Message::SessionSwitch(*id))),]
+                .push_maybe((!is_current).then(|| {
+                    button(text("×").size(14))
+                        .style(button::text)
+                        .on_press(Message::SessionClose(*id))
+                }))
+                .align_y(Center),
+            );
+        }
+        col.push(
+            button(text("New session").size(14))
+                .style(button::text)
+                .width(Fill)
+                .on_press(Message::SessionOpen),
+        )
+        .into()
+    }
+
+    fn wallet_switcher_view(&self) -> Element<Message> {
+        let current = self.wallets.get_current().map(|w| w.label.clone());
+        let loaded = self.wallets.get_loaded();
+        let unloaded: Vec<String> = self
+            .wallets
+            .get_wallets()
+            .into_iter()
+            .filter(|name| !loaded.iter().any(|w| w.label == *name))
+            .cloned()
+            .collect();
+        let balance =
+            |balance: Option<Amount>| text_small(balance.map_or("--".to_string(), format_amount));
+
+        let mut col = Column::new().spacing(2);
+        for (index, wallet) in loaded.iter().enumerate() {
+            let is_current = current.as_ref() == Some(wallet.label);
+            let shortcut = if index < 9 {
+                format!("Ctrl+{}", index + 1)
+            } else {
+                String::new()
+            };
+            col = col.push(
+                row![button(
+                    column![
+                        row![
+                            text(wallet.label).size(14).width(Fill),
+                            text_small(shortcut),
+                        ]
+                        .align_y(Center),
+                        balance(wallet.state.balance),
+                    ]
+                    .spacing(2),
+                )
+                .style(move |theme: &Theme, status: button::Status| {
+                    let mut style = if is_current {
+                        button::secondary
+                    } else {
+                        button::text
+                    }(theme, status);
+                    style.border = style.border.rounded(4);
+                    style
+                })
+                .width(Fill)
+                .on_press_maybe(
+                    (!is_current).then(|| Message::WalletSwitch(wallet.label.clone())),
+                ),]
+                .push_maybe((!is_current).then(|| {
+                    button(text("×").size(14))
+                        .style(button::text)
+                        .on_press(Message::WalletUnload(wallet.label.clone()))
+                }))
+                .align_y(Center),
+            );
+        }
+        if loaded.len() > 1 {
+            col = col.push(
+                container(
+                    column![
+                        text("All wallets").size(14),
+                        balance(self.wallets.get_total_balance()),
+                    ]
+                    .spacing(2),
+                )
+                .padding([5, 10]),
+            );
+        }
+        if !unloaded.is_empty() {
+            col = col.push(
+                iced::widget::pick_list(unloaded, None::<String>, Message::WalletSwitch)
+                    .placeholder("Open wallet")
+                    .text_size(14)
+                    .width(Fill),
+            );
+        }
+        column![col, horizontal_rule(1)].spacing(10).into()
+    }
+
     pub fn main_view(&self) -> Element<Message> {
         let navbar_button = |label, icon: Icon, route: Route, screen: Screen| {
             let button = button(
@@ -750,6 +2683,9 @@ impl State {
             .push(row![
                 // SIDEBAR
                 column![
+                    self.session_switcher_view(),
+                    horizontal_rule(1),
+                    self.wallet_switcher_view(),
                     navbar_button("Home", Icon::Bitcoin, Route::Home, Screen::Home,),
                     navbar_button("Send", Icon::ArrowBigUpDash, Route::Send, Screen::Send,),
                     navbar_button(
@@ -761,14 +2697,42 @@ impl State {
                     navbar_button("Spaces", Icon::AtSign, Route::Spaces, Screen::Spaces,),
                     navbar_button("Market", Icon::Store, Route::Market, Screen::Market,),
                     navbar_button("Sign", Icon::UserRoundPen, Route::Sign, Screen::Sign,),
-                    vertical_space(),
-                    navbar_button(
-                        "Settings",
-                        Icon::Settings,
-                        Route::Settings,
-                        Screen::Settings,
-                    ),
                 ]
+                .extend(
+                    self.plugins
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(index, plugin)| {
+                            Some(navbar_button(
+                                plugin.manifest.screen.as_ref()?,
+                                Icon::Bolt,
+                                Route::Plugin(index),
+                                Screen::Plugin(index),
+                            ))
+                        })
+                        .map(Element::from),
+                )
+                .push(vertical_space())
+                .push(navbar_button(
+                    "Node",
+                    Icon::CircleDot,
+                    Route::Node,
+                    Screen::Node,
+                ))
+                .push_maybe(self.config.rpc_console.then(|| {
+                    navbar_button(
+                        "RPC console",
+                        Icon::ChevronRight,
+                        Route::Console,
+                        Screen::Console,
+                    )
+                }))
+                .push(navbar_button(
+                    "Settings",
+                    Icon::Settings,
+                    Route::Settings,
+                    Screen::Settings,
+                ))
                 .padding(10)
                 .spacing(5)
                 .width(200),
@@ -776,6 +2740,40 @@ impl State {
                 Column::new()
                     .height(Fill)
                     .width(Fill)
+                    .push_maybe(
+                        self.client
+                            .checkpoint_warning()
+                            .map(|warning| error_block(Some(warning))),
+                    )
+                    .push_maybe(
+                        self.client
+                            .version_warning()
+                            .map(|warning| error_block(Some(warning))),
+                    )
+                    .push_maybe(
+                        self.unreachable
+                            .as_ref()
+                            .filter(|unreachable| unreachable.failures >= OFFLINE_AFTER_FAILURES)
+                            .map(|unreachable| {
+                                column![
+                                    error_block(Some(format!(
+                                        "Offline, the backend doesn't answer: {}. Retrying \
+                                         automatically",
+                                        unreachable.error
+                                    ))),
+                                    button(text("Retry now").size(14))
+                                        .style(button::secondary)
+                                        .on_press(Message::RetryBackendPress)
+                                        .padding(Padding::from([5, 10])),
+                                ]
+                                .spacing(10)
+                            }),
+                    )
+                    .push_maybe(
+                        self.sync_watchdog
+                            .stall()
+                            .map(|stall| self.stall_view(stall)),
+                    )
                     .push_maybe(self.wallets.get_current().and_then(|wallet| {
                         if !wallet.is_synced() {
                             Some(
@@ -818,6 +2816,7 @@ impl State {
                                             self.tip_height,
                                             wallet.state.balance,
                                             &wallet.state.transactions,
+                                            &self.transaction_labels,
                                         )
                                         .map(Message::HomeScreen)
                                 } else {
@@ -852,6 +2851,10 @@ impl State {
                                             &wallet.state.winning_spaces,
                                             &wallet.state.outbid_spaces,
                                             &wallet.state.owned_spaces,
+                                            self.wallet_config
+                                                .as_ref()
+                                                .map_or(&[], |(_, c)| &c.watchlist),
+                                            &self.price_history,
                                         )
                                         .map(Message::SpacesScreen)
                                 } else {
@@ -860,7 +2863,11 @@ impl State {
                             Screen::Market =>
                                 if let Some(wallet) = self.wallets.get_current() {
                                     self.market_screen
-                                        .view(wallet.state.owned_spaces.as_ref())
+                                        .view(
+                                            wallet.state.owned_spaces.as_ref(),
+                                            &self.spaces,
+                                            &self.price_history,
+                                        )
                                         .map(Message::MarketScreen)
                                 } else {
                                     center("No wallet loaded").into()
@@ -868,11 +2875,19 @@ impl State {
                             Screen::Sign =>
                                 if let Some(wallet) = self.wallets.get_current() {
                                     self.sign_screen
-                                        .view(&wallet.state.owned_spaces)
+                                        .view(&wallet.state.owned_spaces, &self.spaces)
                                         .map(Message::SignScreen)
                                 } else {
                                     center("No wallet loaded").into()
                                 },
+                            Screen::Diagnostics => self
+                                .diagnostics_screen
+                                .view()
+                                .map(Message::DiagnosticsScreen),
+                            Screen::Console =>
+                                self.console_screen.view().map(Message::ConsoleScreen),
+                            Screen::Node => self.node_screen.view().map(Message::NodeScreen),
+                            Screen::Plugin(index) => self.plugin_view(*index),
                             Screen::Settings => self
                                 .settings_screen
                                 .view(
@@ -880,8 +2895,42 @@ impl State {
                                     self.tip_height,
                                     self.wallets.get_wallets(),
                                     self.wallets.get_current().map(|w| w.label),
+                                    self.wallet_config.as_ref().map(|(_, c)| c),
+                                    &self.config.relays,
+                                    &self.config.webhooks,
+                                    &self.config.profiles,
+                                    self.config.profile.as_ref(),
+                                    self.client.is_managed(),
+                                    match self.config.backend.as_ref() {
+                                        Some(ConfigBackend::Akrond {
+                                            network: ExtendedNetwork::Mainnet,
+                                            prune_refresh_days,
+                                            ..
+                                        }) => Some(prune_refresh_days.is_some()),
+                                        _ => None,
+                                    },
+                                    self.config.stdout_logs,
+                                    self.config.restart_stuck_services,
+                                    self.config.rpc_console,
+                                    self.config.check_updates,
+                                    self.config.automation_token.as_ref(),
+                                    self.config
+                                        .automation_port
+                                        .unwrap_or(automation::AUTOMATION_PORT),
                                 )
                                 .map(Message::SettingsScreen),
+                            Screen::WalletDetails =>
+                                if let Some(wallet) = self.wallets.get_current() {
+                                    self.details_screen
+                                        .view(
+                                            wallet.label,
+                                            wallet.state.info.as_ref(),
+                                            self.server_info.as_ref(),
+                                        )
+                                        .map(Message::DetailsScreen)
+                                } else {
+                                    center("No wallet loaded").into()
+                                },
                         })
                         .height(Fill)
                     )
@@ -890,6 +2939,206 @@ impl State {
             .into()
     }
 
+    /// Restarts the service the sync is stuck on, giving it a new period to progress
+    fn restart_stalled(&mut self, service: ServiceKind) -> Task<Message> {
+        self.sync_watchdog.reset();
+        let restart = self
+            .client
+            .restart_service(service)
+            .map(move |result| Message::StallRestarted(service, result));
+        if service == ServiceKind::Spaces {
+            restart.chain(self.reload_wallets())
+        } else {
+            restart
+        }
+    }
+
+    /// Warning about the stuck sync, with what may help
+    fn stall_view(&self, stall: watchdog::Stall) -> Element<'_, Message> {
+        let (tip, headers) = self
+            .server_info
+            .as_ref()
+            .map_or((0, 0), |info| (info.tip.height, info.chain.headers));
+        let service = self
+            .config
+            .backend
+            .as_ref()
+            .and_then(|backend| stall.service(backend));
+        column![
+            error_block(Some(stall.message(tip, headers))),
+            row![]
+                .push_maybe(service.map(|service| {
+                    button(text(format!("Restart {}", service.as_str())).size(14))
+                        .style(button::secondary)
+                        .on_press(Message::StallRestartPress(service))
+                }))
+                .push(
+                    button(text("Node").size(14))
+                        .style(button::secondary)
+                        .on_press(Message::NavigateTo(Route::Node)),
+                )
+                .spacing(10)
+                .padding(Padding::from([0, 10])),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    /// Adds the line to the logs bar, or holds it back while the logs are paused
+    fn push_log(&mut self, line: LogLine) {
+        crash::record_log(line.to_string());
+        match self.log_paused.as_mut() {
+            Some(pending) => pending.push(line),
+            None => self.log_buffer.push(line),
+        }
+    }
+
+    /// Lines the expanded logs bar shows with the current filters
+    fn visible_logs(&self) -> Vec<&LogLine> {
+        let search = self.log_search.to_lowercase();
+        let lines: Vec<&LogLine> = self
+            .log_buffer
+            .iter()
+            .filter(|line| {
+                (self.log_filter.is_none() || line.service == self.log_filter)
+                    && line.level >= self.log_level
+                    && (search.is_empty() || line.text.to_lowercase().contains(&search))
+            })
+            .collect();
+        lines[lines.len().saturating_sub(LOGS_SHOWN)..].to_vec()
+    }
+
+    /// The lines as one document for bug reports, headed by the versions and network
+    fn logs_report(&self, lines: Vec<&LogLine>) -> String {
+        let mut report = format!(
+            "Akron {}, akrond {}, {}\n\n",
+            env!("CARGO_PKG_VERSION"),
+            akrond::VERSION,
+            self.config
+                .backend
+                .as_ref()
+                .map_or("no backend".to_string(), |b| b.network().to_string()),
+        );
+        for line in lines {
+            report.push_str(&line.to_string());
+            report.push('\n');
+        }
+        report
+    }
+
+    pub fn detached_title(&self, detached: Detached) -> String {
+        match detached {
+            Detached::Logs => format!("Akron logs ({})", self.session_label()),
+            Detached::Transaction(txid) => format!("Akron transaction {}", txid),
+        }
+    }
+
+    pub fn detached_view(&self, detached: Detached) -> Element<Message> {
+        match detached {
+            Detached::Logs => column![self.logs_toolbar(), self.log_lines()]
+                .spacing(10)
+                .padding(10)
+                .into(),
+            Detached::Transaction(txid) => {
+                let transaction = self
+                    .wallets
+                    .get_current()
+                    .and_then(|wallet| wallet.state.transactions.iter().find(|tx| tx.txid == txid));
+                match transaction {
+                    Some(transaction) => scrollable(
+                        container(
+                            home::transaction_info(transaction, self.tip_height)
+                                .map(Message::HomeScreen),
+                        )
+                        .padding(20),
+                    )
+                    .into(),
+                    None => center("Transaction is not found").into(),
+                }
+            }
+        }
+    }
+
+    /// Filters and actions of the expanded logs, also in the logs window
+    fn logs_toolbar(&self) -> Row<Message> {
+        // services that logged something, to show only their lines
+        let mut services: Vec<ServiceKind> = Vec::new();
+        for kind in self.log_buffer.iter().filter_map(|line| line.service) {
+            if !services.contains(&kind) {
+                services.push(kind);
+            }
+        }
+        let filter_button = |label: &'static str, filter: Option<ServiceKind>| {
+            button(text_small(label))
+                .padding([2, 8])
+                .style(if self.log_filter == filter {
+                    button::secondary
+                } else {
+                    button::text
+                })
+                .on_press(Message::LogFilterSelect(filter))
+                .into()
+        };
+        let action_button = |label: &'static str, message: Message| {
+            button(text_small(label))
+                .padding([2, 8])
+                .style(button::text)
+                .on_press(message)
+        };
+        row![text_small("Status: "), filter_button("All", None)]
+            .extend(
+                services
+                    .into_iter()
+                    .map(|kind| filter_button(kind.as_str(), Some(kind))),
+            )
+            .push(
+                text_input("Search", &self.log_search)
+                    .on_input(Message::LogSearchInput)
+                    .size(12)
+                    .padding([2, 8])
+                    .width(200),
+            )
+            .push(
+                iced::widget::pick_list(
+                    LogLevel::ALL,
+                    Some(self.log_level),
+                    Message::LogLevelSelect,
+                )
+                .text_size(12)
+                .padding([2, 8]),
+            )
+            .push(action_button(
+                if self.log_paused.is_some() {
+                    "Resume"
+                } else {
+                    "Pause"
+                },
+                Message::LogPauseToggle,
+            ))
+            .push(action_button("Copy visible", Message::LogsCopyPress))
+            .push(action_button("Save logs to file", Message::LogsSavePress))
+            .spacing(5)
+            .align_y(Center)
+    }
+
+    fn log_lines(&self) -> Element<Message> {
+        scrollable(column(
+            self.visible_logs()
+                .into_iter()
+                .map(|line| {
+                    text_small(line.to_string())
+                        .color(Color::BLACK)
+                        .font(Font::MONOSPACE)
+                        .into()
+                })
+                .collect::<Vec<_>>(),
+        ))
+        .width(Fill)
+        .height(Fill)
+        .anchor_bottom()
+        .into()
+    }
+
     pub fn logs_view(&self) -> Option<Element<Message>> {
         if self.log_buffer.is_empty() {
             return None;
@@ -919,34 +3168,26 @@ impl State {
             })
             .on_press(Message::ToggleLogs);
 
-        let (log_header, logs) = if self.logs_expanded {
+        let (log_header, logs): (Element<Message>, _) = if self.logs_expanded {
             (
-                text_small("Status: "),
-                Some(
-                    container(
-                        scrollable(column(
-                            self.log_buffer
-                                .iter()
-                                .map(|line| {
-                                    text_small(line.clone())
-                                        .color(Color::BLACK)
-                                        .font(Font::MONOSPACE)
-                                        .into()
-                                })
-                                .collect::<Vec<_>>(),
-                        ))
-                        .width(Fill)
-                        .height(Fill)
-                        .anchor_bottom(),
+                self.logs_toolbar()
+                    .push(
+                        button(text_small("Open in window"))
+                            .padding([2, 8])
+                            .style(button::text)
+                            .on_press(Message::DetachLogsPress),
                     )
-                    .padding(Padding {
-                        top: 0.0,
-                        right: 10.0,
-                        bottom: 10.0,
-                        left: 10.0,
-                    })
-                    .height(280)
-                    .width(Fill),
+                    .into(),
+                Some(
+                    container(self.log_lines())
+                        .padding(Padding {
+                            top: 0.0,
+                            right: 10.0,
+                            bottom: 10.0,
+                            left: 10.0,
+                        })
+                        .height(280)
+                        .width(Fill),
                 ),
             )
         } else {
@@ -954,9 +3195,10 @@ impl State {
                 text_small(
                     self.log_buffer
                         .back()
-                        .map(|s| s.to_string())
+                        .map(|line| line.text.clone())
                         .unwrap_or("".to_string()),
-                ),
+                )
+                .into(),
                 None,
             )
         };
@@ -1000,6 +3242,130 @@ impl State {
         Some(view.into())
     }
 
+    /// Stops the services to remove the chain data, the checkpoint is downloaded again
+    /// along with it
+    fn rebuild_chain_data(&mut self) -> Action {
+        if let Some(ConfigBackend::Akrond { prune_point, .. }) = self.config.backend.as_mut() {
+            *prune_point = None;
+        }
+        self.config.save();
+        Action::Task(
+            self.client
+                .stop_services()
+                .map(|_| Message::ServicesStopped { rebuild: true }),
+        )
+    }
+
+    /// Asks the decorating plugins to label the transactions they haven't seen, and
+    /// tells the hooked ones about new transactions
+    fn plugin_transaction_tasks(&mut self, wallet: &str, transactions: &[TxInfo]) -> Task<Message> {
+        let mut tasks = Vec::new();
+        let undecorated: Vec<&TxInfo> = transactions
+            .iter()
+            .filter(|tx| self.decorated_transactions.insert(tx.txid))
+            .collect();
+        if !undecorated.is_empty() {
+            let params = json!({ "wallet": wallet, "transactions": undecorated });
+            tasks.extend(
+                self.plugins
+                    .iter()
+                    .filter(|plugin| plugin.manifest.decorate_transactions)
+                    .map(|plugin| {
+                        Task::perform(
+                            plugin.clone().call("decorate", params.clone()),
+                            Message::TransactionLabels,
+                        )
+                    }),
+            );
+        }
+
+        // the first list of a wallet only sets what's known, and older transactions
+        // showing up while scrolling aren't new
+        let recent_height = self.tip_height.saturating_sub(6);
+        let first_list = !self.hooked_transactions.contains_key(wallet);
+        let known = self
+            .hooked_transactions
+            .entry(wallet.to_string())
+            .or_default();
+        let new: Vec<&TxInfo> = transactions
+            .iter()
+            .filter(|tx| known.insert(tx.txid))
+            .filter(|tx| tx.block_height.is_none_or(|height| height >= recent_height))
+            .collect();
+        if !first_list {
+            for tx in new {
+                tasks.push(self.run_plugin_hooks(
+                    "transaction",
+                    json!({ "wallet": wallet, "transaction": tx }),
+                ));
+            }
+        }
+        Task::batch(tasks)
+    }
+
+    /// Values left out of shared reports: paths, the user's own nodes and endpoints, and
+    /// wallet names
+    fn private_values(&self) -> Vec<String> {
+        let mut values = vec![self.config.data_dir().to_string_lossy().to_string()];
+        values.extend(
+            directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_string_lossy().to_string()),
+        );
+        values.extend(self.wallets.get_wallets().into_iter().cloned());
+        match self.config.backend.as_ref() {
+            Some(ConfigBackend::Akrond {
+                checkpoint_mirrors,
+                filters_endpoints,
+                serve_peers,
+                ..
+            }) => {
+                values.extend(checkpoint_mirrors.iter().cloned());
+                values.extend(filters_endpoints.iter().cloned());
+                values.extend(serve_peers.iter().cloned());
+            }
+            Some(ConfigBackend::Bitcoind { url, user, .. }) => {
+                values.extend([url.clone(), user.clone()]);
+            }
+            Some(ConfigBackend::Spaced {
+                url,
+                fallback_urls,
+                user,
+                ..
+            }) => {
+                values.extend([url.clone(), user.clone()]);
+                values.extend(fallback_urls.iter().cloned());
+            }
+            _ => {}
+        }
+        // short values would blank out common words
+        values.retain(|value| value.len() > 3);
+        values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+        values
+    }
+
+    fn plugin_view(&self, index: usize) -> Element<Message> {
+        let plugin = &self.plugins[index];
+        base_container(
+            column![
+                row![
+                    text_big(plugin.manifest.screen.as_deref().unwrap_or_default()).width(Fill),
+                    button(text("Refresh").size(14))
+                        .style(button::text)
+                        .on_press(Message::NavigateTo(Route::Plugin(index))),
+                ]
+                .align_y(Center),
+                match &self.plugin_screen {
+                    None => Element::from(text("Loading...")),
+                    Some(Ok(lines)) =>
+                        Column::with_children(lines.iter().map(|line| text_monospace(line).into()),)
+                            .spacing(5)
+                            .into(),
+                    Some(Err(err)) => error_block(Some(err)),
+                },
+            ]
+            .spacing(20),
+        )
+    }
+
     pub fn subscription(&self) -> Subscription<Message> {
         let ticks = time::every(
             if self.tip_height != 0 && self.wallets.get_current().is_some_and(|w| w.is_synced()) {
@@ -1012,11 +3378,144 @@ impl State {
 
         let logs = self.client.logs_subscription().map(Message::LogReceived);
 
+        let restarts = self
+            .client
+            .restarts_subscription()
+            .map(Message::ServiceRestarted);
+
         let fee_rate = self
             .fee_rate_selector
             .subscription()
             .map(Message::FeeRateSelector);
 
-        Subscription::batch([ticks, logs, fee_rate])
+        let data_dir = self.config.data_dir().to_path_buf();
+        let sign_requests =
+            Subscription::run_with_id(data_dir.clone(), sign_request::listen(data_dir))
+                .map(Message::SignRequestReceived);
+
+        // wallets are watched in the background only for the webhooks
+        let watch = if self.config.webhooks.is_empty() {
+            Subscription::none()
+        } else {
+            time::every(time::Duration::from_secs(30)).map(|_| Message::WatchTick)
+        };
+
+        // resource usage is sampled this often by akrond
+        let node = if self.screen == Screen::Node {
+            time::every(akrond::SAMPLE_INTERVAL).map(|_| Message::NodeScreen(node::Message::Tick))
+        } else {
+            Subscription::none()
+        };
+
+        let automation = match self.config.automation_token.as_ref() {
+            Some(token) => {
+                let port = self
+                    .config
+                    .automation_port
+                    .unwrap_or(automation::AUTOMATION_PORT);
+                Subscription::run_with_id(
+                    (token.clone(), port),
+                    automation::listen(token.clone(), port),
+                )
+                .map(|result| match result {
+                    Ok(request) => Message::AutomationRequestReceived(request),
+                    Err(err) => Message::SettingsScreen(settings::Message::AutomationFailed(err)),
+                })
+            }
+            None => Subscription::none(),
+        };
+
+        // Ctrl/Cmd + 1..9 switches between loaded wallets
+        let wallet_shortcuts = keyboard::on_key_press(|key, modifiers| match key {
+            keyboard::Key::Character(c) if modifiers.command() => c
+                .parse::<usize>()
+                .ok()
+                .filter(|n| (1..=9).contains(n))
+                .map(|n| Message::WalletShortcut(n - 1)),
+            _ => None,
+        });
+
+        let prune_refresh = match self.config.backend.as_ref() {
+            Some(ConfigBackend::Akrond {
+                prune_refresh_days: Some(_),
+                ..
+            }) => time::every(time::Duration::from_secs(3600)).map(|_| Message::PruneRefreshCheck),
+            _ => Subscription::none(),
+        };
+
+        Subscription::batch([
+            ticks,
+            logs,
+            restarts,
+            fee_rate,
+            sign_requests,
+            automation,
+            watch,
+            node,
+            wallet_shortcuts,
+            prune_refresh,
+        ])
+    }
+}
+
+/// Reads the listing at `url`, giving up on bodies over `MAX_LISTING_SIZE`
+async fn fetch_listing(url: &str) -> Result<String, String> {
+    let mut response = proxy::http_client()
+        .get(url)
+        .timeout(LISTING_TIMEOUT)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?;
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_LISTING_SIZE as u64)
+    {
+        return Err("Listing is too large".to_string());
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        body.extend_from_slice(&chunk);
+        if body.len() > MAX_LISTING_SIZE {
+            return Err("Listing is too large".to_string());
+        }
     }
+    String::from_utf8(body).map_err(|_| "Listing is not valid UTF-8".to_string())
+}
+
+fn check_update() -> Task<Message> {
+    Task::perform(update::check(), |result| {
+        Message::SettingsScreen(settings::Message::UpdateChecked(result))
+    })
+}
+
+fn reauth_view<'a>(user: &'a str, password: &'a str) -> Element<'a, Message> {
+    modal(
+        column![
+            text("Spaced rejected the login").size(20),
+            text("The node no longer accepts the saved login, enter the current one to reconnect."),
+            text_input("User login", user).on_input(Message::ReauthUserInput),
+            text_input("User password", password)
+                .secure(true)
+                .on_input(Message::ReauthPasswordInput)
+                .on_submit(Message::ReauthSubmit),
+            row![
+                button(text("Change backend"))
+                    .padding(20)
+                    .style(button::text)
+                    .on_press(Message::ReauthChangeBackend),
+                Space::with_width(Fill),
+                button(text("Reconnect"))
+                    .padding(20)
+                    .on_press(Message::ReauthSubmit)
+                    .style(|theme: &Theme, status: button::Status| {
+                        let mut style = button::primary(theme, status);
+                        style.border = style.border.rounded(7);
+                        style
+                    }),
+            ],
+        ]
+        .padding(20)
+        .spacing(10),
+    )
 }
@@ -0,0 +1,345 @@
+use crate::{
+    client::{ChainStatus, ClientResult, NodeStatus, Peer, PeerAction, ServiceKind},
+    helpers::format_bytes,
+    widget::{
+        base::base_container,
+        chart::Sparkline,
+        form::{submit_button, text_input, STANDARD_PADDING},
+        icon::{text_icon, Icon},
+        text::{error_block, text_big, text_bold, text_small},
+    },
+};
+use iced::{
+    widget::{button, column, horizontal_space, row, text, Column},
+    Center, Element, Fill, Shrink,
+};
+use std::time::Duration;
+
+#[derive(Debug, Default)]
+pub struct State {
+    status: Option<NodeStatus>,
+    loading: bool,
+    peer_address: String,
+    peer_error: Option<String>,
+    restarting: Option<ServiceKind>,
+    restart_error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Tick,
+    RefreshPress,
+    StatusLoaded(NodeStatus),
+    PeerAddressInput(String),
+    AddPeerPress,
+    DisconnectPress(u64),
+    BanPress(String),
+    PeerActionDone(ClientResult<()>),
+    RestartPress(ServiceKind),
+    RestartDone(ClientResult<()>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    None,
+    Refresh,
+    Peer(PeerAction),
+    RestartService(ServiceKind),
+}
+
+fn format_uptime(uptime: Duration) -> String {
+    let minutes = uptime.as_secs() / 60;
+    match (minutes / 1440, minutes / 60 % 24, minutes % 60) {
+        (0, 0, minutes) => format!("{} min", minutes),
+        (0, hours, minutes) => format!("{} h {} min", hours, minutes),
+        (days, hours, _) => format!("{} d {} h", days, hours),
+    }
+}
+
+fn status_row<'a>(running: bool, name: &'a str, details: String) -> Element<'a, Message> {
+    row![
+        text_icon(if running {
+            Icon::CircleDot
+        } else {
+            Icon::Circle
+        }),
+        text_bold(name).width(200),
+        text(details),
+    ]
+    .spacing(10)
+    .align_y(Center)
+    .into()
+}
+
+fn peer_row(peer: &Peer) -> Element<'_, Message> {
+    let latency = peer.latency.map_or("-".to_string(), |latency| {
+        format!("{} ms", latency.as_millis())
+    });
+    row![
+        text(&peer.address).width(Fill),
+        text(&peer.agent).size(14).width(Fill),
+        text(latency).size(14).width(80),
+        button(text("Disconnect").size(14))
+            .style(button::text)
+            .on_press(Message::DisconnectPress(peer.id)),
+        button(text("Ban").size(14))
+            .style(button::text)
+            .on_press(Message::BanPress(peer.address.clone())),
+    ]
+    .spacing(10)
+    .align_y(Center)
+    .into()
+}
+
+fn service_view(
+    service: &akrond::ServiceStatus,
+    restarting: Option<ServiceKind>,
+) -> Element<'_, Message> {
+    let ready = service.health.as_ref().is_some_and(|health| health.ready);
+    let details = match service.health.as_ref() {
+        _ if service.gave_up => format!("Stopped after {} crashes", service.crashes),
+        _ if !service.running && service.crashes != 0 => {
+            format!("Restarting after {} crashes", service.crashes)
+        }
+        _ if !service.running => "Stopped".to_string(),
+        None => "Not answering".to_string(),
+        Some(health) if !health.ready => "Not ready".to_string(),
+        Some(health) => health
+            .height
+            .map_or("Ready".to_string(), |height| format!("At block {}", height)),
+    };
+    let uptime = format!(", running for {}", format_uptime(service.uptime));
+    let pid = service
+        .pid
+        .map_or(String::new(), |pid| format!(", pid {}", pid));
+    // errors logged by the service come first, then failures of its RPC
+    let last_error = service.last_error.as_ref().or(service
+        .health
+        .as_ref()
+        .and_then(|health| health.last_error.as_ref()));
+    column![row![
+        status_row(
+            service.running && ready,
+            service.kind.as_str(),
+            if service.running {
+                format!("{}{}{}", details, uptime, pid)
+            } else {
+                details
+            },
+        ),
+        horizontal_space(),
+        button(
+            text(if restarting == Some(service.kind) {
+                "Restarting"
+            } else {
+                "Restart"
+            })
+            .size(14)
+        )
+        .style(button::text)
+        .on_press_maybe(
+            restarting
+                .is_none()
+                .then_some(Message::RestartPress(service.kind))
+        ),
+    ]
+    .align_y(Center)]
+    .push_maybe(usage_view(&service.usage))
+    .push_maybe(last_error.map(|err| error_block(Some(format!("Last error: {}", err)))))
+    .spacing(5)
+    .into()
+}
+
+/// Charts of the recent CPU, memory and disk usage of a service, `None` without samples as
+/// usage is only sampled on Linux
+fn usage_view(usage: &[akrond::ResourceSample]) -> Option<Element<'_, Message>> {
+    let last = usage.last()?;
+    let chart = |label: String, values: Vec<f32>| {
+        column![text_small(label), Sparkline::new(values, 150.0, 30.0)].spacing(5)
+    };
+    Some(
+        row![
+            chart(
+                format!("CPU {:.0}%", last.cpu),
+                usage.iter().map(|sample| sample.cpu).collect(),
+            ),
+            chart(
+                format!("Memory {}", format_bytes(last.memory)),
+                usage.iter().map(|sample| sample.memory as f32).collect(),
+            ),
+            chart(
+                format!(
+                    "Disk {}/s",
+                    format_bytes(last.disk_read + last.disk_written)
+                ),
+                usage
+                    .iter()
+                    .map(|sample| (sample.disk_read + sample.disk_written) as f32)
+                    .collect(),
+            ),
+        ]
+        .spacing(20)
+        .into(),
+    )
+}
+
+fn section<'a>(title: &'a str, content: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    column![text_bold(title), content.into()].spacing(10).into()
+}
+
+impl State {
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::Tick => Action::Refresh,
+            Message::RefreshPress => {
+                self.loading = true;
+                Action::Refresh
+            }
+            Message::StatusLoaded(status) => {
+                self.loading = false;
+                self.status = Some(status);
+                Action::None
+            }
+            Message::PeerAddressInput(address) => {
+                if !address.contains(char::is_whitespace) {
+                    self.peer_address = address;
+                }
+                Action::None
+            }
+            Message::AddPeerPress => {
+                Action::Peer(PeerAction::Add(std::mem::take(&mut self.peer_address)))
+            }
+            Message::DisconnectPress(id) => Action::Peer(PeerAction::Disconnect(id)),
+            Message::BanPress(address) => Action::Peer(PeerAction::Ban(address)),
+            Message::PeerActionDone(result) => {
+                self.peer_error = result.err();
+                Action::Refresh
+            }
+            Message::RestartPress(kind) => {
+                self.restarting = Some(kind);
+                self.restart_error = None;
+                Action::RestartService(kind)
+            }
+            Message::RestartDone(result) => {
+                self.restarting = None;
+                self.restart_error = result.err();
+                Action::Refresh
+            }
+        }
+    }
+
+    fn chain_view<'a>(&'a self, chain: &'a ChainStatus) -> Element<'a, Message> {
+        let can_add = !self.peer_address.is_empty();
+        column![
+            status_row(
+                chain.blocks >= chain.headers,
+                "Sync",
+                format!("Block {} of {}", chain.blocks, chain.headers),
+            ),
+            status_row(
+                chain.mempool_txs.is_some(),
+                "Mempool",
+                chain.mempool_txs.map_or("Not reported".to_string(), |txs| {
+                    format!("{} transactions", txs)
+                }),
+            ),
+            status_row(
+                chain.peers.as_ref().is_some_and(|peers| !peers.is_empty()),
+                "Peers",
+                chain
+                    .peers
+                    .as_ref()
+                    .map_or("Only reported by Bitcoin Core".to_string(), |peers| {
+                        peers.len().to_string()
+                    }),
+            ),
+        ]
+        .push_maybe(chain.peers.as_ref().map(|peers| {
+            Column::with_children(peers.iter().map(peer_row))
+                .push(
+                    row![
+                        text_input(
+                            "Address of a peer to add, e.g. 1.2.3.4:8333",
+                            &self.peer_address
+                        )
+                        .on_input(Message::PeerAddressInput)
+                        .on_submit_maybe(can_add.then_some(Message::AddPeerPress)),
+                        submit_button(
+                            text("Add peer").align_x(Center),
+                            can_add.then_some(Message::AddPeerPress),
+                        )
+                        .width(Shrink),
+                    ]
+                    .spacing(10),
+                )
+                .push_maybe(self.peer_error.as_ref().map(|err| error_block(Some(err))))
+                .spacing(10)
+        }))
+        .spacing(10)
+        .into()
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let Some(status) = self.status.as_ref() else {
+            return base_container(column![text_big("Node"), text("Loading")].spacing(40));
+        };
+        base_container(
+            Column::new()
+                .push(
+                    row![
+                        text_big("Node").width(Fill),
+                        button(text(if self.loading {
+                            "Refreshing"
+                        } else {
+                            "Refresh"
+                        }))
+                        .style(button::secondary)
+                        .padding(STANDARD_PADDING)
+                        .on_press_maybe((!self.loading).then_some(Message::RefreshPress)),
+                    ]
+                    .align_y(Center),
+                )
+                .push_maybe((!status.services.is_empty()).then(|| {
+                    section(
+                        "Services",
+                        Column::with_children(
+                            status
+                                .services
+                                .iter()
+                                .map(|service| service_view(service, self.restarting)),
+                        )
+                        .push_maybe(
+                            self.restart_error
+                                .as_ref()
+                                .map(|err| error_block(Some(err))),
+                        )
+                        .spacing(10),
+                    )
+                }))
+                .push(section(
+                    "Spaces",
+                    match &status.spaced {
+                        Ok(info) => status_row(
+                            info.tip.height >= info.chain.headers,
+                            "spaced",
+                            format!(
+                                "{}, block {} of {}",
+                                info.network, info.tip.height, info.chain.headers
+                            ),
+                        ),
+                        Err(err) => error_block(Some(err)),
+                    },
+                ))
+                .push_maybe(status.chain.as_ref().map(|chain| {
+                    section(
+                        "Bitcoin",
+                        match chain {
+                            Ok(chain) => self.chain_view(chain),
+                            Err(err) => error_block(Some(err)),
+                        },
+                    )
+                }))
+                .spacing(40),
+        )
+    }
+}
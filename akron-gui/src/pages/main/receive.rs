@@ -5,15 +5,19 @@ use crate::{
     client::*,
     widget::{
         icon::{button_icon, Icon},
+        qr_code::qr_code,
         tabs::TabsRow,
-        text::{text_big, text_monospace},
+        text::{text_big, text_monospace, text_small},
     },
 };
 use iced::{
-    widget::{column, container, qr_code, row, text},
+    widget::{column, container, row, text},
     Border, Center, Element, Fill, Theme,
 };
 
+/// About the height of the QR code of an address, so the page doesn't jump when it's done
+const QR_CODE_PLACEHOLDER_HEIGHT: f32 = 300.0;
+
 #[derive(Debug)]
 pub struct State(AddressKind);
 
@@ -103,7 +107,15 @@ impl State {
 
                 ]
                 .align_x(Center),
-                container(qr_code(address.as_qr_code()).cell_size(7)).align_x(Center).width(Fill),
+                match address.as_qr_code() {
+                    Some(Ok(matrix)) => container(qr_code(matrix).cell_size(7.0)),
+                    Some(Err(err)) => container(text_small(format!("No QR code: {}", err))),
+                    None => container(text_small("Generating the QR code…"))
+                        .height(QR_CODE_PLACEHOLDER_HEIGHT)
+                        .align_y(Center),
+                }
+                .align_x(Center)
+                .width(Fill),
             ].width(Fill)
             .spacing(40)
         })).width(Fill).spacing(40)
@@ -1,12 +1,19 @@
+use crate::automation::{automation_addr, AUTOMATION_PORT};
+use crate::client::{CliWalletsImport, CompactionProgress, StorageKind};
+use crate::helpers::{format_bytes, height_from_str};
+use crate::nostr;
+use crate::update::{Asset, Release};
 use crate::widget::base::{base_container, result_column};
+use crate::widget::fee_rate::FeeRateOption;
 use crate::widget::form::STANDARD_PADDING;
 use crate::widget::{
     form::{pick_list, submit_button, text_input},
-    text::{text_big, text_bold},
+    text::{error_block, text_big, text_bold, text_monospace},
 };
+use crate::{BackendProfile, ProxyConfig, WalletConfig, Webhook};
 use iced::{
     border::rounded,
-    widget::{button, column, row, text},
+    widget::{button, checkbox, column, horizontal_space, row, text, Column},
     Center, Element, Fill, Shrink, Theme,
 };
 use spaces_client::config::ExtendedNetwork;
@@ -14,21 +21,254 @@ use spaces_client::config::ExtendedNetwork;
 #[derive(Debug, Default)]
 pub struct State {
     new_wallet_name: String,
+    new_relay: String,
+    new_webhook_url: String,
+    new_webhook_event: &'static str,
+    new_webhook_template: String,
+    new_profile_name: String,
+    rename: Option<String>,
+    delete: Option<DeleteState>,
+    sensitive_export: Option<SensitiveExportState>,
+    encrypted_import: Option<EncryptedImportState>,
+    app_backup: Option<AppBackupState>,
+    exporting_app_backup: bool,
+    app_backup_result: Option<Result<String, String>>,
+    importing_cli_wallets: bool,
+    cli_wallets_import: Option<Result<String, String>>,
+    rescan: Option<RescanState>,
+    rebuild_confirmation: bool,
+    auto_renew_confirmation: bool,
+    /// Why the automation API isn't listening, e.g. its port was taken. It stays
+    /// enabled with the same token and is tried again on the next start
+    automation_error: Option<String>,
+    /// Port typed for the automation API, empty for the default one
+    automation_port: String,
+    exporting_checkpoint: bool,
+    checkpoint_export: Option<Result<String, String>>,
+    storage: Option<Vec<(StorageKind, u64)>>,
+    compaction: Option<CompactionProgress>,
+    measuring_storage: bool,
+    proxy: ProxyState,
+    testing_connection: bool,
+    connection_test: Option<Result<String, String>>,
+    checking_update: bool,
+    /// Newer release, `None` when this build is the latest
+    update_check: Option<Result<Option<Release>, String>>,
+    downloading_update: bool,
+    update_download: Option<Result<String, String>>,
+    background_service: bool,
+    changing_background_service: bool,
+    background_service_result: Option<Result<String, String>>,
     error: Option<String>,
 }
 
+#[derive(Debug)]
+struct ProxyState {
+    enabled: bool,
+    host: String,
+    port: String,
+    chain: bool,
+    web: bool,
+    spaced: bool,
+}
+
+impl Default for ProxyState {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "127.0.0.1".to_string(),
+            port: "9050".to_string(),
+            chain: true,
+            web: true,
+            spaced: true,
+        }
+    }
+}
+
+impl ProxyState {
+    fn config(&self) -> Option<Option<ProxyConfig>> {
+        if !self.enabled {
+            return Some(None);
+        }
+        if self.host.is_empty() {
+            return None;
+        }
+        Some(Some(ProxyConfig {
+            host: self.host.clone(),
+            port: self.port.parse().ok()?,
+            chain: self.chain,
+            web: self.web,
+            spaced: self.spaced,
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct RescanState {
+    wallet: String,
+    height: String,
+    running: bool,
+}
+
+#[derive(Debug)]
+struct DeleteState {
+    wallet: String,
+    backed_up: bool,
+    confirmation: String,
+}
+
+#[derive(Debug)]
+struct SensitiveExportState {
+    wallet: String,
+    password: String,
+    confirmation: String,
+    /// Asking to confirm the export without encryption
+    plaintext: bool,
+}
+
+/// Wallet file encrypted by the export, waiting for its password
+#[derive(Debug)]
+struct EncryptedImportState {
+    contents: String,
+    password: String,
+    decrypting: bool,
+}
+
+#[derive(Debug)]
+struct AppBackupState {
+    password: String,
+    confirmation: String,
+}
+
+const ALL_EVENTS: &str = "all events";
+
+const WEBHOOK_EVENTS: [&str; 5] = [
+    ALL_EVENTS,
+    super::watcher::EVENT_NAMES[0],
+    super::watcher::EVENT_NAMES[1],
+    super::watcher::EVENT_NAMES[2],
+    super::watcher::EVENT_NAMES[3],
+];
+
+/// Shortest password accepted for an encrypted private key export or app backup
+const MIN_EXPORT_PASSWORD_LENGTH: usize = 8;
+
+/// First line of an armored age file, as written by the encrypted export
+const AGE_ARMOR_HEADER: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+
 #[derive(Debug, Clone)]
 pub enum Message {
     WalletSelect(String),
-    ExportWalletPress(String),
+    CopyDescriptorsPress(String),
     NewWalletInput(String),
     CreateWalletPress,
     ImportWalletPress,
+    ImportCliWalletsPress,
+    /// Labels of the wallets imported from spaced
+    CliWalletsImported(Result<CliWalletsImport, String>),
+    FeeRateSelect(FeeRateOption),
+    AutoRenewToggle(bool),
+    AutoRenewCancelPress,
+    AutoRenewConfirmPress,
+    PruneRefreshToggle(bool),
+    StdoutLogsToggle(bool),
+    RestartStuckServicesToggle(bool),
+    RpcConsoleToggle(bool),
+    CheckUpdatesToggle(bool),
+    CheckUpdatePress,
+    UpdateChecked(Result<Option<Release>, String>),
+    DownloadUpdatePress(Asset),
+    /// Path the release was saved to, `None` when no file was chosen
+    UpdateDownloaded(Result<Option<std::path::PathBuf>, String>),
+    InstallServicePress,
+    RemoveServicePress,
+    SaveServicePress,
+    /// Whether the service is installed afterwards, with the outcome
+    ServiceChanged(bool, Result<String, String>),
+    AutomationToggle(bool),
+    /// The automation API couldn't listen
+    AutomationFailed(String),
+    AutomationPortInput(String),
+    AutomationPortSavePress(Option<u16>),
+    CopyAutomationTokenPress,
+    RelayInput(String),
+    AddRelayPress,
+    RemoveRelayPress(String),
+    WebhookUrlInput(String),
+    WebhookEventSelect(&'static str),
+    WebhookTemplateInput(String),
+    AddWebhookPress,
+    RemoveWebhookPress(usize),
     ResetBackendPress,
-    WalletFileSaved(Result<(), String>),
+    ProfileNameInput(String),
+    SaveProfilePress,
+    SwitchProfilePress(String),
+    RemoveProfilePress(String),
+    TestConnectionPress,
+    ConnectionTested(Result<String, String>),
+    RebuildPress,
+    ExportCheckpointPress,
+    /// `None` when no folder was chosen
+    CheckpointFolderPicked(Option<std::path::PathBuf>),
+    CheckpointExported(Result<String, String>),
+    RebuildCancelPress,
+    RebuildConfirmPress,
+    StorageRefreshPress,
+    StorageMeasured(Vec<(StorageKind, u64)>),
+    StorageClearPress(StorageKind),
+    StorageCleared(Result<(), String>),
+    CompactPress,
+    Compaction(CompactionProgress),
+    RenamePress(String),
+    RenameInput(String),
+    RenameCancelPress,
+    RenameConfirmPress(String),
+    DeletePress(String),
+    DeleteBackupPress,
+    DeleteConfirmationInput(String),
+    DeleteCancelPress,
+    DeleteConfirmPress,
+    SensitiveExportPress(String),
+    SensitivePasswordInput(String),
+    SensitiveConfirmationInput(String),
+    SensitiveExportCancelPress,
+    SensitiveExportConfirmPress,
+    PlaintextExportPress,
+    PlaintextExportCancelPress,
+    PlaintextExportConfirmPress,
+    EncryptedImportPasswordInput(String),
+    EncryptedImportCancelPress,
+    EncryptedImportConfirmPress,
+    WalletFileDecrypted(Result<String, String>),
+    AppBackupPress,
+    AppBackupPasswordInput(String),
+    AppBackupConfirmationInput(String),
+    AppBackupCancelPress,
+    AppBackupConfirmPress,
+    /// Whether the backup was saved, `false` when no file was chosen
+    AppBackupSaved(Result<bool, String>),
+    RescanPress(String),
+    DetailsPress,
+    DiagnosticsPress,
+    RescanHeightInput(String),
+    RescanCancelPress,
+    RescanConfirmPress(Option<u32>),
+    ProxyToggle(bool),
+    ProxyHostInput(String),
+    ProxyPortInput(String),
+    ProxyChainToggle(bool),
+    ProxyWebToggle(bool),
+    ProxySpacedToggle(bool),
+    ProxySavePress(Option<ProxyConfig>),
+    WalletFileSaved(Result<bool, String>),
     WalletCreated(Result<String, String>),
     WalletFileLoaded(Option<String>),
     WalletFileImported(Result<(), String>),
+    /// Old name of the wallet with the outcome
+    WalletRenamed(String, Result<String, String>),
+    WalletDeleted(String, Result<(), String>),
+    DescriptorsCopyFailed(String),
+    WalletRescanned(Result<(), String>),
 }
 
 #[derive(Debug, Clone)]
@@ -36,40 +276,669 @@ pub enum Action {
     None,
     SetCurrentWallet(String),
     ExportWallet(String),
+    ExportEncrypted { wallet: String, password: String },
+    ImportEncryptedWallet { contents: String, password: String },
+    ExportAppBackup { password: String },
+    CopyPublicDescriptors(String),
     CreateWallet(String),
     FilePick,
     ImportWallet(String),
+    ImportCliWallets,
+    RenameWallet { wallet: String, new_name: String },
+    DeleteWallet(String),
+    WalletRenamed { wallet: String, new_name: String },
+    WalletDeleted(String),
+    RescanWallet { wallet: String, height: Option<u32> },
+    ShowDetails,
+    ShowDiagnostics,
+    SetFeeRate(FeeRateOption),
+    SetAutoRenew(bool),
+    AddRelay(String),
+    RemoveRelay(String),
+    AddWebhook(Webhook),
+    RemoveWebhook(usize),
+    SetProxy(Option<ProxyConfig>),
+    RebuildChainData,
+    SetPruneRefresh(bool),
+    SetStdoutLogs(bool),
+    SetRestartStuckServices(bool),
+    SetRpcConsole(bool),
+    SetCheckUpdates(bool),
+    CheckUpdate,
+    DownloadUpdate(Asset),
+    InstallService,
+    RemoveService,
+    SaveServiceDefinition,
+    SetAutomation(bool),
+    SetAutomationPort(Option<u16>),
+    CopyAutomationToken,
+    ExportCheckpoint,
+    ExportCheckpointTo(std::path::PathBuf),
+    MeasureStorage,
+    ClearStorage(StorageKind),
+    CompactDatabases,
+    SaveProfile(String),
+    SwitchProfile(String),
+    RemoveProfile(String),
+    TestConnection,
     ResetBackend,
 }
 
+fn danger_button_style(t: &Theme, status: button::Status) -> button::Style {
+    let mut style = button::danger(t, status);
+    let p = t.extended_palette();
+    if matches!(status, button::Status::Active) {
+        style.background = Some(p.danger.weak.color.into());
+    }
+    style.border = rounded(7);
+    style
+}
+
+fn is_wallet_name(s: &str) -> bool {
+    s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn is_export_password_valid(password: &str, confirmation: &str) -> bool {
+    password.len() >= MIN_EXPORT_PASSWORD_LENGTH && password == confirmation
+}
+
 impl State {
+    pub fn new(
+        proxy: Option<&ProxyConfig>,
+        background_service: bool,
+        automation_port: Option<u16>,
+    ) -> Self {
+        Self {
+            background_service,
+            automation_port: automation_port
+                .map(|port| port.to_string())
+                .unwrap_or_default(),
+            proxy: proxy
+                .map(|proxy| ProxyState {
+                    enabled: true,
+                    host: proxy.host.clone(),
+                    port: proxy.port.to_string(),
+                    chain: proxy.chain,
+                    web: proxy.web,
+                    spaced: proxy.spaced,
+                })
+                .unwrap_or_default(),
+            new_webhook_event: ALL_EVENTS,
+            ..Default::default()
+        }
+    }
+
     pub fn update(&mut self, message: Message) -> Action {
         self.error = None;
         match message {
             Message::WalletSelect(w) => Action::SetCurrentWallet(w),
-            Message::ExportWalletPress(w) => Action::ExportWallet(w),
+            Message::CopyDescriptorsPress(w) => Action::CopyPublicDescriptors(w),
             Message::NewWalletInput(w) => {
-                if w.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                if is_wallet_name(&w) {
                     self.new_wallet_name = w;
                 }
                 Action::None
             }
             Message::CreateWalletPress => Action::CreateWallet(self.new_wallet_name.to_string()),
             Message::ImportWalletPress => Action::FilePick,
+            Message::ImportCliWalletsPress => {
+                self.importing_cli_wallets = true;
+                self.cli_wallets_import = None;
+                Action::ImportCliWallets
+            }
+            Message::CliWalletsImported(result) => {
+                self.importing_cli_wallets = false;
+                self.cli_wallets_import = Some(result.map(|import| {
+                    let mut lines = Vec::new();
+                    if import.imported.is_empty() && import.skipped.is_empty() {
+                        lines.push("Every wallet of spaced is already in Akron".to_string());
+                    }
+                    if !import.imported.is_empty() {
+                        lines.push(format!("Imported {}", import.imported.join(", ")));
+                    }
+                    for (old, new) in &import.renamed {
+                        lines.push(format!(
+                            "Akron has another wallet named {}, it was imported as {}",
+                            old, new
+                        ));
+                    }
+                    for skipped in &import.skipped {
+                        lines.push(format!("Skipped {}", skipped));
+                    }
+                    lines.join("\n")
+                }));
+                Action::None
+            }
+            Message::FeeRateSelect(option) => Action::SetFeeRate(option),
+            // renewing spends from the wallet, so it's only turned on once confirmed
+            Message::AutoRenewToggle(true) => {
+                self.auto_renew_confirmation = true;
+                Action::None
+            }
+            Message::AutoRenewToggle(false) => Action::SetAutoRenew(false),
+            Message::AutoRenewCancelPress => {
+                self.auto_renew_confirmation = false;
+                Action::None
+            }
+            Message::AutoRenewConfirmPress => {
+                self.auto_renew_confirmation = false;
+                Action::SetAutoRenew(true)
+            }
+            Message::PruneRefreshToggle(enabled) => Action::SetPruneRefresh(enabled),
+            Message::StdoutLogsToggle(enabled) => Action::SetStdoutLogs(enabled),
+            Message::RestartStuckServicesToggle(enabled) => {
+                Action::SetRestartStuckServices(enabled)
+            }
+            Message::RpcConsoleToggle(enabled) => Action::SetRpcConsole(enabled),
+            Message::CheckUpdatesToggle(enabled) => {
+                if enabled {
+                    self.checking_update = true;
+                }
+                Action::SetCheckUpdates(enabled)
+            }
+            Message::CheckUpdatePress => {
+                self.checking_update = true;
+                Action::CheckUpdate
+            }
+            Message::UpdateChecked(result) => {
+                self.checking_update = false;
+                self.update_check = Some(result);
+                Action::None
+            }
+            Message::DownloadUpdatePress(asset) => {
+                self.downloading_update = true;
+                self.update_download = None;
+                Action::DownloadUpdate(asset)
+            }
+            Message::UpdateDownloaded(result) => {
+                self.downloading_update = false;
+                self.update_download = result
+                    .map(|path| path.map(|path| format!("Saved to {}", path.display())))
+                    .transpose();
+                Action::None
+            }
+            Message::InstallServicePress => {
+                self.changing_background_service = true;
+                self.background_service_result = None;
+                Action::InstallService
+            }
+            Message::RemoveServicePress => {
+                self.changing_background_service = true;
+                self.background_service_result = None;
+                Action::RemoveService
+            }
+            Message::SaveServicePress => {
+                self.background_service_result = None;
+                Action::SaveServiceDefinition
+            }
+            Message::ServiceChanged(installed, result) => {
+                self.changing_background_service = false;
+                self.background_service = installed;
+                self.background_service_result = Some(result);
+                Action::None
+            }
+            Message::AutomationToggle(enabled) => {
+                self.automation_error = None;
+                Action::SetAutomation(enabled)
+            }
+            Message::AutomationFailed(err) => {
+                self.automation_error = Some(err);
+                Action::None
+            }
+            Message::AutomationPortInput(port) => {
+                if port.len() <= 5 && port.chars().all(|c| c.is_ascii_digit()) {
+                    self.automation_port = port;
+                }
+                Action::None
+            }
+            Message::AutomationPortSavePress(port) => {
+                self.automation_error = None;
+                Action::SetAutomationPort(port)
+            }
+            Message::CopyAutomationTokenPress => Action::CopyAutomationToken,
+            Message::RelayInput(relay) => {
+                if !relay.contains(char::is_whitespace) {
+                    self.new_relay = relay;
+                }
+                Action::None
+            }
+            Message::AddRelayPress => Action::AddRelay(std::mem::take(&mut self.new_relay)),
+            Message::RemoveRelayPress(relay) => Action::RemoveRelay(relay),
+            Message::WebhookUrlInput(url) => {
+                if !url.contains(char::is_whitespace) {
+                    self.new_webhook_url = url;
+                }
+                Action::None
+            }
+            Message::WebhookEventSelect(event) => {
+                self.new_webhook_event = event;
+                Action::None
+            }
+            Message::WebhookTemplateInput(template) => {
+                self.new_webhook_template = template;
+                Action::None
+            }
+            Message::AddWebhookPress => {
+                let event = std::mem::replace(&mut self.new_webhook_event, ALL_EVENTS);
+                let template = std::mem::take(&mut self.new_webhook_template);
+                Action::AddWebhook(Webhook {
+                    url: std::mem::take(&mut self.new_webhook_url),
+                    events: (event != ALL_EVENTS)
+                        .then(|| event.to_string())
+                        .into_iter()
+                        .collect(),
+                    template: (!template.trim().is_empty()).then_some(template),
+                })
+            }
+            Message::RemoveWebhookPress(index) => Action::RemoveWebhook(index),
             Message::ResetBackendPress => Action::ResetBackend,
-            Message::WalletFileSaved(result) | Message::WalletFileImported(result) => {
-                if let Err(err) = result {
+            Message::ProfileNameInput(name) => {
+                self.new_profile_name = name;
+                Action::None
+            }
+            Message::SaveProfilePress => {
+                Action::SaveProfile(std::mem::take(&mut self.new_profile_name))
+            }
+            Message::SwitchProfilePress(name) => Action::SwitchProfile(name),
+            Message::RemoveProfilePress(name) => Action::RemoveProfile(name),
+            Message::TestConnectionPress => {
+                self.testing_connection = true;
+                self.connection_test = None;
+                Action::TestConnection
+            }
+            Message::ConnectionTested(result) => {
+                self.testing_connection = false;
+                self.connection_test = Some(result);
+                Action::None
+            }
+            Message::ProxyToggle(enabled) => {
+                self.proxy.enabled = enabled;
+                Action::None
+            }
+            Message::ProxyHostInput(host) => {
+                self.proxy.host = host;
+                Action::None
+            }
+            Message::ProxyPortInput(port) => {
+                if port.chars().all(|c| c.is_ascii_digit()) {
+                    self.proxy.port = port;
+                }
+                Action::None
+            }
+            Message::ProxyChainToggle(chain) => {
+                self.proxy.chain = chain;
+                Action::None
+            }
+            Message::ProxyWebToggle(web) => {
+                self.proxy.web = web;
+                Action::None
+            }
+            Message::ProxySpacedToggle(spaced) => {
+                self.proxy.spaced = spaced;
+                Action::None
+            }
+            Message::ProxySavePress(proxy) => Action::SetProxy(proxy),
+            Message::RebuildPress => {
+                self.rebuild_confirmation = true;
+                Action::None
+            }
+            Message::RebuildCancelPress => {
+                self.rebuild_confirmation = false;
+                Action::None
+            }
+            Message::RebuildConfirmPress => {
+                self.rebuild_confirmation = false;
+                Action::RebuildChainData
+            }
+            Message::ExportCheckpointPress => {
+                self.exporting_checkpoint = true;
+                self.checkpoint_export = None;
+                Action::ExportCheckpoint
+            }
+            Message::CheckpointFolderPicked(folder) => match folder {
+                Some(folder) => Action::ExportCheckpointTo(folder),
+                None => {
+                    self.exporting_checkpoint = false;
+                    Action::None
+                }
+            },
+            Message::CheckpointExported(result) => {
+                self.exporting_checkpoint = false;
+                self.checkpoint_export = Some(result);
+                Action::None
+            }
+            Message::StorageRefreshPress => {
+                self.measuring_storage = true;
+                Action::MeasureStorage
+            }
+            Message::StorageMeasured(usage) => {
+                self.measuring_storage = false;
+                self.storage = Some(usage);
+                Action::None
+            }
+            Message::StorageClearPress(kind) => {
+                self.measuring_storage = true;
+                Action::ClearStorage(kind)
+            }
+            Message::StorageCleared(result) => match result {
+                Ok(()) => Action::MeasureStorage,
+                Err(err) => {
+                    self.measuring_storage = false;
                     self.error = Some(err);
+                    Action::None
+                }
+            },
+            Message::CompactPress => {
+                self.compaction = Some(CompactionProgress::Compacting {
+                    wallet: String::new(),
+                    done: 0,
+                    total: 0,
+                });
+                Action::CompactDatabases
+            }
+            Message::Compaction(progress) => {
+                let done = matches!(progress, CompactionProgress::Done(_));
+                self.compaction = Some(progress);
+                if done {
+                    self.measuring_storage = true;
+                    Action::MeasureStorage
+                } else {
+                    Action::None
+                }
+            }
+            Message::RenamePress(w) => {
+                self.delete = None;
+                self.sensitive_export = None;
+                self.rescan = None;
+                self.rename = Some(w);
+                Action::None
+            }
+            Message::RenameInput(w) => {
+                if is_wallet_name(&w) {
+                    self.rename = Some(w);
+                }
+                Action::None
+            }
+            Message::RenameCancelPress => {
+                self.rename = None;
+                Action::None
+            }
+            Message::RenameConfirmPress(wallet) => match self.rename.take() {
+                Some(new_name) => Action::RenameWallet { wallet, new_name },
+                None => Action::None,
+            },
+            Message::DeletePress(wallet) => {
+                self.rename = None;
+                self.sensitive_export = None;
+                self.rescan = None;
+                self.delete = Some(DeleteState {
+                    wallet,
+                    backed_up: false,
+                    confirmation: String::new(),
+                });
+                Action::None
+            }
+            Message::DeleteBackupPress => {
+                // the delete confirmation comes back once the export is saved or canceled
+                if let Some(delete) = &self.delete {
+                    self.sensitive_export = Some(SensitiveExportState {
+                        wallet: delete.wallet.clone(),
+                        password: String::new(),
+                        confirmation: String::new(),
+                        plaintext: false,
+                    });
+                }
+                Action::None
+            }
+            Message::DeleteConfirmationInput(confirmation) => {
+                if let Some(delete) = &mut self.delete {
+                    delete.confirmation = confirmation;
+                }
+                Action::None
+            }
+            Message::DeleteCancelPress => {
+                self.delete = None;
+                Action::None
+            }
+            Message::DeleteConfirmPress => match self.delete.take() {
+                Some(delete) if delete.backed_up && delete.confirmation == delete.wallet => {
+                    Action::DeleteWallet(delete.wallet)
+                }
+                delete => {
+                    self.delete = delete;
+                    Action::None
+                }
+            },
+            Message::SensitiveExportPress(wallet) => {
+                self.rename = None;
+                self.delete = None;
+                self.rescan = None;
+                self.sensitive_export = Some(SensitiveExportState {
+                    wallet,
+                    password: String::new(),
+                    confirmation: String::new(),
+                    plaintext: false,
+                });
+                Action::None
+            }
+            Message::PlaintextExportPress => {
+                if let Some(export) = &mut self.sensitive_export {
+                    export.plaintext = true;
+                }
+                Action::None
+            }
+            Message::PlaintextExportCancelPress => {
+                if let Some(export) = &mut self.sensitive_export {
+                    export.plaintext = false;
+                }
+                Action::None
+            }
+            Message::PlaintextExportConfirmPress => match &self.sensitive_export {
+                Some(export) if export.plaintext => Action::ExportWallet(export.wallet.clone()),
+                _ => Action::None,
+            },
+            Message::EncryptedImportPasswordInput(password) => {
+                if let Some(import) = &mut self.encrypted_import {
+                    import.password = password;
                 }
                 Action::None
             }
-            Message::WalletFileLoaded(contents) => {
-                if let Some(contents) = contents {
+            Message::EncryptedImportCancelPress => {
+                self.encrypted_import = None;
+                Action::None
+            }
+            Message::EncryptedImportConfirmPress => match &mut self.encrypted_import {
+                Some(import) if !import.decrypting && !import.password.is_empty() => {
+                    import.decrypting = true;
+                    Action::ImportEncryptedWallet {
+                        contents: import.contents.clone(),
+                        password: import.password.clone(),
+                    }
+                }
+                _ => Action::None,
+            },
+            Message::WalletFileDecrypted(result) => match result {
+                Ok(contents) => {
+                    self.encrypted_import = None;
                     Action::ImportWallet(contents)
-                } else {
+                }
+                Err(err) => {
+                    if let Some(import) = &mut self.encrypted_import {
+                        import.decrypting = false;
+                    }
+                    self.error = Some(err);
+                    Action::None
+                }
+            },
+            Message::SensitivePasswordInput(password) => {
+                if let Some(export) = &mut self.sensitive_export {
+                    export.password = password;
+                }
+                Action::None
+            }
+            Message::SensitiveConfirmationInput(confirmation) => {
+                if let Some(export) = &mut self.sensitive_export {
+                    export.confirmation = confirmation;
+                }
+                Action::None
+            }
+            Message::SensitiveExportCancelPress => {
+                self.sensitive_export = None;
+                Action::None
+            }
+            Message::SensitiveExportConfirmPress => match &self.sensitive_export {
+                Some(export)
+                    if is_export_password_valid(&export.password, &export.confirmation) =>
+                {
+                    Action::ExportEncrypted {
+                        wallet: export.wallet.clone(),
+                        password: export.password.clone(),
+                    }
+                }
+                _ => Action::None,
+            },
+            Message::AppBackupPress => {
+                self.app_backup_result = None;
+                self.app_backup = Some(AppBackupState {
+                    password: String::new(),
+                    confirmation: String::new(),
+                });
+                Action::None
+            }
+            Message::AppBackupPasswordInput(password) => {
+                if let Some(backup) = &mut self.app_backup {
+                    backup.password = password;
+                }
+                Action::None
+            }
+            Message::AppBackupConfirmationInput(confirmation) => {
+                if let Some(backup) = &mut self.app_backup {
+                    backup.confirmation = confirmation;
+                }
+                Action::None
+            }
+            Message::AppBackupCancelPress => {
+                self.app_backup = None;
+                Action::None
+            }
+            Message::AppBackupConfirmPress => match &self.app_backup {
+                Some(backup)
+                    if !self.exporting_app_backup
+                        && is_export_password_valid(&backup.password, &backup.confirmation) =>
+                {
+                    self.exporting_app_backup = true;
+                    Action::ExportAppBackup {
+                        password: backup.password.clone(),
+                    }
+                }
+                _ => Action::None,
+            },
+            Message::AppBackupSaved(result) => {
+                self.exporting_app_backup = false;
+                match result {
+                    Ok(true) => {
+                        self.app_backup = None;
+                        self.app_backup_result = Some(Ok("App backup saved".to_string()));
+                    }
+                    Ok(false) => {}
+                    Err(err) => self.app_backup_result = Some(Err(err)),
+                }
+                Action::None
+            }
+            Message::RescanPress(wallet) => {
+                self.rename = None;
+                self.delete = None;
+                self.sensitive_export = None;
+                self.rescan = Some(RescanState {
+                    wallet,
+                    height: String::new(),
+                    running: false,
+                });
+                Action::None
+            }
+            Message::DetailsPress => Action::ShowDetails,
+            Message::DiagnosticsPress => Action::ShowDiagnostics,
+            Message::RescanHeightInput(height) => {
+                if let Some(rescan) = &mut self.rescan {
+                    if height.chars().all(|c| c.is_ascii_digit() || c == '-') {
+                        rescan.height = height;
+                    }
+                }
+                Action::None
+            }
+            Message::RescanCancelPress => {
+                if self.rescan.as_ref().is_some_and(|rescan| !rescan.running) {
+                    self.rescan = None;
+                }
+                Action::None
+            }
+            Message::RescanConfirmPress(height) => match &mut self.rescan {
+                Some(rescan) if !rescan.running => {
+                    rescan.running = true;
+                    Action::RescanWallet {
+                        wallet: rescan.wallet.clone(),
+                        height,
+                    }
+                }
+                _ => Action::None,
+            },
+            Message::WalletRescanned(result) => {
+                self.rescan = None;
+                if let Err(err) = result {
+                    self.error = Some(err);
+                }
+                Action::None
+            }
+            Message::WalletFileSaved(result) => {
+                match result {
+                    Ok(saved) => {
+                        if let Some(delete) = &mut self.delete {
+                            delete.backed_up |= saved;
+                        }
+                        if saved {
+                            self.sensitive_export = None;
+                        }
+                    }
+                    Err(err) => self.error = Some(err),
+                }
+                Action::None
+            }
+            Message::WalletFileImported(result) => {
+                if let Err(err) = result {
+                    self.error = Some(err);
+                }
+                Action::None
+            }
+            Message::WalletDeleted(wallet, result) => match result {
+                Ok(()) => Action::WalletDeleted(wallet),
+                Err(err) => {
+                    self.error = Some(err);
                     Action::None
                 }
+            },
+            Message::WalletRenamed(wallet, result) => match result {
+                Ok(new_name) => Action::WalletRenamed { wallet, new_name },
+                Err(err) => {
+                    self.error = Some(err);
+                    Action::None
+                }
+            },
+            Message::DescriptorsCopyFailed(err) => {
+                self.error = Some(err);
+                Action::None
             }
+            Message::WalletFileLoaded(contents) => match contents {
+                Some(contents) if contents.trim_start().starts_with(AGE_ARMOR_HEADER) => {
+                    self.encrypted_import = Some(EncryptedImportState {
+                        contents,
+                        password: String::new(),
+                        decrypting: false,
+                    });
+                    Action::None
+                }
+                Some(contents) => Action::ImportWallet(contents),
+                None => Action::None,
+            },
             Message::WalletCreated(result) => {
                 if let Err(err) = result {
                     self.error = Some(err);
@@ -81,85 +950,1046 @@ impl State {
         }
     }
 
+    /// Saves the typed port when it's valid and not the one listened on
+    fn automation_port_save(&self, current: u16) -> Option<Message> {
+        let port = match self.automation_port.as_str() {
+            "" => None,
+            port => Some(port.parse::<u16>().ok().filter(|port| *port != 0)?),
+        };
+        (port.unwrap_or(AUTOMATION_PORT) != current)
+            .then_some(Message::AutomationPortSavePress(port))
+    }
+
+    fn proxy_view(&self) -> Element<Message> {
+        let proxy = &self.proxy;
+        let save = submit_button(
+            text("Save").align_x(Center),
+            proxy.config().map(Message::ProxySavePress),
+        )
+        .width(Shrink);
+        if !proxy.enabled {
+            return column![
+                checkbox("Use a SOCKS5 proxy (e.g. Tor)", false).on_toggle(Message::ProxyToggle),
+                save,
+            ]
+            .spacing(20)
+            .into();
+        }
+        column![
+            checkbox("Use a SOCKS5 proxy (e.g. Tor)", true).on_toggle(Message::ProxyToggle),
+            row![
+                text_input("127.0.0.1", &proxy.host)
+                    .width(Fill)
+                    .on_input(Message::ProxyHostInput),
+                text_input("9050", &proxy.port)
+                    .width(100)
+                    .on_input(Message::ProxyPortInput),
+            ]
+            .spacing(10),
+            checkbox(
+                "Checkpoint downloads, block filters and transaction broadcasts",
+                proxy.chain
+            )
+            .on_toggle(Message::ProxyChainToggle),
+            checkbox("Fee rates, listings and signing callbacks", proxy.web)
+                .on_toggle(Message::ProxyWebToggle),
+            checkbox("Remote spaced connection", proxy.spaced)
+                .on_toggle(Message::ProxySpacedToggle),
+            text(
+                "Bitcoin peer connections of the built-in node aren't proxied. Changes to \
+                 chain data and spaced connections apply after restarting.",
+            )
+            .size(14),
+            save,
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    fn webhooks_view<'a>(&'a self, webhooks: &'a [Webhook]) -> Element<'a, Message> {
+        let valid_url = self.new_webhook_url.starts_with("https://")
+            || self.new_webhook_url.starts_with("http://");
+        column![
+            Column::with_children(webhooks.iter().enumerate().map(|(index, webhook)| {
+                row![
+                    column![
+                        text_monospace(&webhook.url),
+                        text(if webhook.events.is_empty() {
+                            ALL_EVENTS.to_string()
+                        } else {
+                            webhook.events.join(", ")
+                        })
+                        .size(14),
+                    ]
+                    .width(Fill),
+                    button(text("Remove").size(14))
+                        .style(button::text)
+                        .on_press(Message::RemoveWebhookPress(index)),
+                ]
+                .align_y(Center)
+                .into()
+            }))
+            .spacing(10),
+            row![
+                text_input("https://", &self.new_webhook_url)
+                    .width(Fill)
+                    .on_input(Message::WebhookUrlInput),
+                pick_list(
+                    WEBHOOK_EVENTS,
+                    Some(self.new_webhook_event),
+                    Message::WebhookEventSelect
+                )
+                .width(220),
+            ]
+            .spacing(10),
+            row![
+                text_input(
+                    "Body, e.g. {\"text\": \"{event} {space}\"}, the event as JSON if empty",
+                    &self.new_webhook_template
+                )
+                .width(Fill)
+                .on_input(Message::WebhookTemplateInput),
+                submit_button(
+                    text("Add").align_x(Center),
+                    valid_url.then_some(Message::AddWebhookPress),
+                )
+                .width(Shrink),
+            ]
+            .spacing(20),
+            text(
+                "Posted to when a transaction confirms, you're outbid, a space can be \
+                 registered or a listing sells. Failed posts are retried a few times.",
+            )
+            .size(14),
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    fn profiles_view<'a>(
+        &'a self,
+        profiles: &'a [BackendProfile],
+        active_profile: Option<&'a String>,
+    ) -> Element<'a, Message> {
+        column![
+            Column::with_children(profiles.iter().map(|profile| {
+                let active = active_profile == Some(&profile.name);
+                row![
+                    if active {
+                        text_bold(&profile.name)
+                    } else {
+                        text(&profile.name)
+                    }
+                    .width(Fill),
+                    text(profile.backend.network().to_string()).size(14),
+                    button(text("Switch").size(14))
+                        .style(button::text)
+                        .on_press_maybe(
+                            (!active).then(|| Message::SwitchProfilePress(profile.name.clone()))
+                        ),
+                    button(text("Remove").size(14))
+                        .style(button::text)
+                        .on_press(Message::RemoveProfilePress(profile.name.clone())),
+                ]
+                .spacing(10)
+                .align_y(Center)
+                .into()
+            }))
+            .spacing(10),
+            row![
+                text_input("home node", &self.new_profile_name)
+                    .width(Fill)
+                    .on_input(Message::ProfileNameInput),
+                submit_button(
+                    text("Save current").align_x(Center),
+                    (!self.new_profile_name.trim().is_empty()).then_some(Message::SaveProfilePress),
+                )
+                .width(Shrink),
+            ]
+            .spacing(20),
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    fn auto_renew_view(&self, auto_renew: bool) -> Element<Message> {
+        let toggle = checkbox(
+            "Renew spaces automatically when they expire within 30 days",
+            auto_renew,
+        )
+        .on_toggle(Message::AutoRenewToggle);
+        if !self.auto_renew_confirmation {
+            return toggle.into();
+        }
+        column![
+            toggle,
+            text(
+                "Akron will send a renewal from this wallet, paying its fee, for every space \
+                 expiring within 30 days. It does so while the app is open and when it runs \
+                 in the background.",
+            ),
+            row![
+                button(text("Cancel").align_x(Center).width(Fill))
+                    .style(button::secondary)
+                    .padding(STANDARD_PADDING)
+                    .width(Fill)
+                    .on_press(Message::AutoRenewCancelPress),
+                button(text("Renew automatically").align_x(Center).width(Fill))
+                    .style(button::primary)
+                    .padding(STANDARD_PADDING)
+                    .width(Fill)
+                    .on_press(Message::AutoRenewConfirmPress),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn rebuild_view(&self) -> Element<Message> {
+        if !self.rebuild_confirmation {
+            return button(text("Rebuild chain data").align_x(Center).width(Fill))
+                .style(button::secondary)
+                .padding(STANDARD_PADDING)
+                .width(Fill)
+                .on_press(Message::RebuildPress)
+                .into();
+        }
+        column![
+            text(
+                "Removes the local chain and spaces databases and syncs them again from \
+                 scratch. Use it if the databases got corrupted. Wallets are kept but \
+                 can't be used until the sync is done.",
+            ),
+            row![
+                button(text("Cancel").align_x(Center).width(Fill))
+                    .style(button::secondary)
+                    .padding(STANDARD_PADDING)
+                    .width(Fill)
+                    .on_press(Message::RebuildCancelPress),
+                button(text("Rebuild").align_x(Center).width(Fill))
+                    .style(danger_button_style)
+                    .padding(STANDARD_PADDING)
+                    .width(Fill)
+                    .on_press(Message::RebuildConfirmPress),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn storage_view(&self) -> Element<Message> {
+        let storage = self.storage.as_deref().unwrap_or_default();
+        let total: u64 = storage.iter().map(|(_, bytes)| bytes).sum();
+        column![text_bold("Disk usage")]
+            .extend(storage.iter().map(|(kind, bytes)| {
+                let action = match kind {
+                    StorageKind::Yuki | StorageKind::Spaces => {
+                        Some(("Rebuild", Message::RebuildPress))
+                    }
+                    StorageKind::Logs | StorageKind::Caches => {
+                        Some(("Clear", Message::StorageClearPress(*kind)))
+                    }
+                    StorageKind::Bitcoind | StorageKind::Wallets => None,
+                };
+                row![
+                    text(kind.to_string()).width(Fill),
+                    text_monospace(format_bytes(*bytes)),
+                ]
+                .push_maybe(action.map(|(label, message)| {
+                    button(text(label).size(14))
+                        .style(button::text)
+                        .on_press_maybe((!self.measuring_storage).then_some(message))
+                }))
+                .spacing(10)
+                .align_y(Center)
+                .into()
+            }))
+            .push(
+                row![
+                    text_bold("Total").width(Fill),
+                    text_monospace(format_bytes(total)),
+                    button(
+                        text(if self.measuring_storage {
+                            "Measuring"
+                        } else {
+                            "Refresh"
+                        })
+                        .size(14)
+                    )
+                    .style(button::text)
+                    .on_press_maybe(
+                        (!self.measuring_storage).then_some(Message::StorageRefreshPress)
+                    ),
+                ]
+                .spacing(10)
+                .align_y(Center),
+            )
+            .spacing(10)
+            .into()
+    }
+
+    fn compact_view(&self) -> Element<Message> {
+        let compacting = matches!(self.compaction, Some(CompactionProgress::Compacting { .. }));
+        column![
+            button(
+                text(if compacting {
+                    "Compacting wallet databases"
+                } else {
+                    "Compact wallet databases"
+                })
+                .align_x(Center)
+                .width(Fill)
+            )
+            .style(button::secondary)
+            .padding(STANDARD_PADDING)
+            .width(Fill)
+            .on_press_maybe((!compacting).then_some(Message::CompactPress)),
+            text(
+                "Reclaims the space left by deleted data in the wallet databases. spaced is \
+                 paused while it runs, so it waits until the node is synced and no block came \
+                 in for a while. The spaces database only grows, clearing it in Storage \
+                 downloads a fresh checkpoint instead.",
+            ),
+        ]
+        .push_maybe(self.compaction.as_ref().map(|progress| match progress {
+            CompactionProgress::Compacting { total: 0, .. } => text("Waiting for spaced").into(),
+            CompactionProgress::Compacting {
+                wallet,
+                done,
+                total,
+            } => text(format!("Compacting {} ({}/{})", wallet, done + 1, total)).into(),
+            CompactionProgress::Done(Ok(summary)) => text(summary).into(),
+            CompactionProgress::Done(Err(err)) => error_block(Some(err)),
+        }))
+        .spacing(10)
+        .into()
+    }
+
+    fn export_checkpoint_view(&self) -> Element<Message> {
+        column![
+            button(
+                text(if self.exporting_checkpoint {
+                    "Exporting checkpoint"
+                } else {
+                    "Export checkpoint"
+                })
+                .align_x(Center)
+                .width(Fill)
+            )
+            .style(button::secondary)
+            .padding(STANDARD_PADDING)
+            .width(Fill)
+            .on_press_maybe((!self.exporting_checkpoint).then_some(Message::ExportCheckpointPress)),
+            text(
+                "Saves the spaces database of this node with its anchors to a folder, so \
+                 other installs can start from it instead of a hosted checkpoint. spaced is \
+                 paused while it's copied. The suggested folder is the one served to peers \
+                 when that's enabled.",
+            ),
+        ]
+        .push_maybe(self.checkpoint_export.as_ref().map(|result| match result {
+            Ok(summary) => text(summary).into(),
+            Err(err) => error_block(Some(err)),
+        }))
+        .spacing(10)
+        .into()
+    }
+
+    fn encrypted_import_view<'a>(
+        &'a self,
+        import: &'a EncryptedImportState,
+    ) -> Element<'a, Message> {
+        row![
+            text_input("Password of the wallet file", &import.password)
+                .secure(true)
+                .width(Fill)
+                .on_input(Message::EncryptedImportPasswordInput)
+                .on_submit(Message::EncryptedImportConfirmPress),
+            row![
+                submit_button(
+                    text(if import.decrypting {
+                        "Decrypting"
+                    } else {
+                        "Import"
+                    })
+                    .align_x(Center),
+                    (!import.decrypting && !import.password.is_empty())
+                        .then_some(Message::EncryptedImportConfirmPress),
+                ),
+                button(text("Cancel").align_x(Center))
+                    .style(button::text)
+                    .padding(STANDARD_PADDING)
+                    .on_press_maybe(
+                        (!import.decrypting).then_some(Message::EncryptedImportCancelPress)
+                    ),
+            ]
+            .spacing(5),
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    fn cli_wallets_view(&self) -> Element<Message> {
+        column![row![
+            text("Wallets of spaced run from the command line on this computer").width(Fill),
+            button(text(if self.importing_cli_wallets {
+                "Importing"
+            } else {
+                "Import from spaced"
+            }))
+            .style(button::secondary)
+            .on_press_maybe(
+                (!self.importing_cli_wallets).then_some(Message::ImportCliWalletsPress)
+            ),
+        ]
+        .spacing(20)
+        .align_y(Center)]
+        .push_maybe(self.cli_wallets_import.as_ref().map(|result| match result {
+            Ok(summary) => text(summary).into(),
+            Err(err) => error_block(Some(err)),
+        }))
+        .spacing(10)
+        .into()
+    }
+
+    fn app_backup_view(&self) -> Element<Message> {
+        let description = text(
+            "Saves the settings, the wallets with their private keys and the wallet \
+             settings to one encrypted file. Restore it from the first screen of Akron on \
+             another computer to carry on there.",
+        );
+        let result = self.app_backup_result.as_ref().map(|result| match result {
+            Ok(summary) => text(summary).into(),
+            Err(err) => error_block(Some(err)),
+        });
+        let Some(backup) = &self.app_backup else {
+            return column![
+                button(text("Export app backup").align_x(Center).width(Fill))
+                    .style(button::secondary)
+                    .padding(STANDARD_PADDING)
+                    .width(Fill)
+                    .on_press(Message::AppBackupPress),
+                description,
+            ]
+            .push_maybe(result)
+            .spacing(10)
+            .into();
+        };
+        column![
+            description,
+            text_input("Password", &backup.password)
+                .secure(true)
+                .on_input(Message::AppBackupPasswordInput),
+            text_input("Confirm password", &backup.confirmation)
+                .secure(true)
+                .on_input(Message::AppBackupConfirmationInput),
+            text(format!(
+                "At least {} characters. Anyone with the file and the password can take the \
+                 funds, the password can't be recovered.",
+                MIN_EXPORT_PASSWORD_LENGTH
+            ))
+            .size(14),
+            row![
+                button(text("Cancel").align_x(Center).width(Fill))
+                    .style(button::secondary)
+                    .padding(STANDARD_PADDING)
+                    .width(Fill)
+                    .on_press(Message::AppBackupCancelPress),
+                button(
+                    text(if self.exporting_app_backup {
+                        "Exporting app backup"
+                    } else {
+                        "Export app backup"
+                    })
+                    .align_x(Center)
+                    .width(Fill)
+                )
+                .style(danger_button_style)
+                .padding(STANDARD_PADDING)
+                .width(Fill)
+                .on_press_maybe(
+                    (!self.exporting_app_backup
+                        && is_export_password_valid(&backup.password, &backup.confirmation))
+                    .then_some(Message::AppBackupConfirmPress)
+                ),
+            ]
+            .spacing(10),
+        ]
+        .push_maybe(result)
+        .spacing(10)
+        .into()
+    }
+
+    fn background_service_view(&self) -> Element<Message> {
+        let (label, message) = if self.background_service {
+            ("Remove background service", Message::RemoveServicePress)
+        } else {
+            ("Install background service", Message::InstallServicePress)
+        };
+        column![
+            row![
+                button(text(label).align_x(Center).width(Fill))
+                    .style(button::secondary)
+                    .padding(STANDARD_PADDING)
+                    .width(Fill)
+                    .on_press_maybe((!self.changing_background_service).then_some(message)),
+                button(text("Save definition").align_x(Center).width(Fill))
+                    .style(button::secondary)
+                    .padding(STANDARD_PADDING)
+                    .width(Fill)
+                    .on_press(Message::SaveServicePress),
+            ]
+            .spacing(10),
+            text(
+                "Runs Akron without its window from the next login, so spaces are renewed \
+                 and notifications are sent while it's closed. The service and this window \
+                 share the data directory, only one of them runs at a time. On Linux, \
+                 `loginctl enable-linger` starts it at boot before anyone logs in.",
+            ),
+        ]
+        .push_maybe(
+            self.background_service_result
+                .as_ref()
+                .map(|result| match result {
+                    Ok(summary) => text(summary).into(),
+                    Err(err) => error_block(Some(err)),
+                }),
+        )
+        .spacing(10)
+        .into()
+    }
+
+    fn rescan_view<'a>(
+        &'a self,
+        rescan: &'a RescanState,
+        network: ExtendedNetwork,
+    ) -> Element<'a, Message> {
+        let height = if rescan.height.is_empty() {
+            Some(None)
+        } else {
+            height_from_str(&rescan.height, network).map(Some)
+        };
+        column![
+            text(
+                "Scans the wallet again to find missing transactions. spaced is restarted \
+                 and the progress is shown in the sync bar. Leave the height empty to start \
+                 from the wallet birthday.",
+            ),
+            text_input("Start height or date (YYYY-MM-DD)", &rescan.height)
+                .on_input_maybe((!rescan.running).then_some(Message::RescanHeightInput)),
+        ]
+        .push_maybe(
+            height
+                .is_none()
+                .then(|| text("Enter a block height or a date like 2025-01-31").size(14)),
+        )
+        .push_maybe(rescan.running.then(|| text("Restarting spaced…").size(14)))
+        .push(
+            row![
+                button(text("Cancel").align_x(Center).width(Fill))
+                    .style(button::secondary)
+                    .padding(STANDARD_PADDING)
+                    .width(Fill)
+                    .on_press_maybe((!rescan.running).then_some(Message::RescanCancelPress)),
+                submit_button(
+                    text("Rescan").align_x(Center).width(Fill),
+                    height
+                        .filter(|_| !rescan.running)
+                        .map(Message::RescanConfirmPress),
+                ),
+            ]
+            .spacing(10),
+        )
+        .spacing(10)
+        .into()
+    }
+
+    fn manage_view<'a>(
+        &'a self,
+        wallet_name: &'a String,
+        network: ExtendedNetwork,
+    ) -> Element<'a, Message> {
+        if let Some(rescan) = &self.rescan {
+            return self.rescan_view(rescan, network);
+        }
+
+        if let Some(new_name) = &self.rename {
+            return row![
+                text_input(wallet_name, new_name)
+                    .width(Fill)
+                    .on_input(Message::RenameInput),
+                row![
+                    submit_button(
+                        text("Rename").align_x(Center),
+                        (!new_name.is_empty() && new_name != wallet_name)
+                            .then(|| Message::RenameConfirmPress(wallet_name.clone())),
+                    ),
+                    button(text("Cancel").align_x(Center))
+                        .style(button::text)
+                        .padding(STANDARD_PADDING)
+                        .on_press(Message::RenameCancelPress),
+                ]
+                .spacing(5),
+            ]
+            .spacing(20)
+            .into();
+        }
+
+        if let Some(export) = self.sensitive_export.as_ref().filter(|e| e.plaintext) {
+            return column![
+                text(format!("Export {} without encryption", export.wallet)).style(text::danger),
+                text(
+                    "Writes the private keys of this wallet to a file in plain text. Anyone \
+                     or any program that can read the file, including cloud backups, can take \
+                     the funds. Only use it to import the wallet somewhere else right away.",
+                ),
+                row![
+                    button(text("Back").align_x(Center).width(Fill))
+                        .style(button::secondary)
+                        .padding(STANDARD_PADDING)
+                        .width(Fill)
+                        .on_press(Message::PlaintextExportCancelPress),
+                    button(text("Export unencrypted keys").align_x(Center).width(Fill))
+                        .style(danger_button_style)
+                        .padding(STANDARD_PADDING)
+                        .width(Fill)
+                        .on_press(Message::PlaintextExportConfirmPress),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10)
+            .into();
+        }
+
+        if let Some(export) = &self.sensitive_export {
+            return column![
+                text(format!("Export {}", export.wallet)).style(text::danger),
+                text(
+                    "Writes the private keys of this wallet to a file encrypted with the \
+                     password below. Anyone with the file and the password can take the \
+                     funds. Import reads it back with the password.",
+                ),
+                text_input("Password", &export.password)
+                    .secure(true)
+                    .on_input(Message::SensitivePasswordInput),
+                text_input("Confirm password", &export.confirmation)
+                    .secure(true)
+                    .on_input(Message::SensitiveConfirmationInput),
+                text(format!(
+                    "At least {} characters. The file can be decrypted with any age \
+                     compatible tool, the password can't be recovered.",
+                    MIN_EXPORT_PASSWORD_LENGTH
+                ))
+                .size(14),
+                row![
+                    button(text("Cancel").align_x(Center).width(Fill))
+                        .style(button::secondary)
+                        .padding(STANDARD_PADDING)
+                        .width(Fill)
+                        .on_press(Message::SensitiveExportCancelPress),
+                    button(text("Export encrypted keys").align_x(Center).width(Fill))
+                        .style(danger_button_style)
+                        .padding(STANDARD_PADDING)
+                        .width(Fill)
+                        .on_press_maybe(
+                            is_export_password_valid(&export.password, &export.confirmation)
+                                .then_some(Message::SensitiveExportConfirmPress)
+                        ),
+                ]
+                .spacing(10),
+                button(text("Export without encryption").size(14))
+                    .style(button::text)
+                    .on_press(Message::PlaintextExportPress),
+            ]
+            .spacing(10)
+            .into();
+        }
+
+        match &self.delete {
+            Some(delete) => column![
+                text(format!(
+                    "Deleting removes the wallet \"{}\" from this computer. \
+                     Coins and spaces can only be recovered from a backup.",
+                    delete.wallet
+                )),
+                if delete.backed_up {
+                    Element::from(text("Backup saved").style(text::success))
+                } else {
+                    submit_button(
+                        text("Save backup").align_x(Center),
+                        Some(Message::DeleteBackupPress),
+                    )
+                    .width(Fill)
+                    .into()
+                },
+                text_input(&delete.wallet, &delete.confirmation)
+                    .on_input_maybe(delete.backed_up.then_some(Message::DeleteConfirmationInput)),
+                text("Type the wallet name to confirm").size(14),
+                row![
+                    button(text("Cancel").align_x(Center).width(Fill))
+                        .style(button::secondary)
+                        .padding(STANDARD_PADDING)
+                        .width(Fill)
+                        .on_press(Message::DeleteCancelPress),
+                    button(text("Delete wallet").align_x(Center).width(Fill))
+                        .style(danger_button_style)
+                        .padding(STANDARD_PADDING)
+                        .width(Fill)
+                        .on_press_maybe(
+                            (delete.backed_up && delete.confirmation == delete.wallet)
+                                .then_some(Message::DeleteConfirmPress)
+                        ),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10)
+            .into(),
+            None => row![
+                button(text("Rename").size(14))
+                    .style(button::text)
+                    .on_press(Message::RenamePress(wallet_name.clone())),
+                button(text("Delete").size(14))
+                    .style(button::text)
+                    .on_press(Message::DeletePress(wallet_name.clone())),
+                button(text("Details").size(14))
+                    .style(button::text)
+                    .on_press(Message::DetailsPress),
+                button(text("Rescan").size(14))
+                    .style(button::text)
+                    .on_press(Message::RescanPress(wallet_name.clone())),
+                horizontal_space(),
+                button(text("Copy public descriptors").size(14))
+                    .style(button::text)
+                    .on_press(Message::CopyDescriptorsPress(wallet_name.clone())),
+            ]
+            .spacing(10)
+            .into(),
+        }
+    }
+
+    /// Banner of a newer release with its notes and download
+    fn update_view(&self) -> Option<Element<'_, Message>> {
+        let Some(Ok(Some(release))) = self.update_check.as_ref() else {
+            return None;
+        };
+        Some(
+            column![
+                text_big(format!("Akron {} is available", release.version)),
+                text(&release.notes),
+                match release.asset.as_ref() {
+                    Some(asset) => Element::from(
+                        submit_button(
+                            text(if self.downloading_update {
+                                "Downloading and verifying"
+                            } else {
+                                "Download"
+                            })
+                            .align_x(Center)
+                            .width(Fill),
+                            (!self.downloading_update)
+                                .then(|| Message::DownloadUpdatePress(asset.clone())),
+                        )
+                        .width(Fill),
+                    ),
+                    None => text(format!("Download it from {}", release.url)).into(),
+                },
+            ]
+            .push_maybe(self.update_download.as_ref().map(|result| match result {
+                Ok(saved) => text(saved).into(),
+                Err(err) => error_block(Some(err)),
+            }))
+            .spacing(10)
+            .into(),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn view<'a>(
         &'a self,
         network: ExtendedNetwork,
         tip_height: u32,
         wallets_names: Vec<&'a String>,
         wallet_name: Option<&'a String>,
+        wallet_config: Option<&'a WalletConfig>,
+        relays: &'a [String],
+        webhooks: &'a [Webhook],
+        profiles: &'a [BackendProfile],
+        active_profile: Option<&'a String>,
+        managed_backend: bool,
+        prune_refresh: Option<bool>,
+        stdout_logs: bool,
+        restart_stuck_services: bool,
+        rpc_console: bool,
+        check_updates: bool,
+        automation_token: Option<&'a String>,
+        automation_port: u16,
     ) -> Element<'a, Message> {
         base_container(
-            column![
-                column![
-                    text_big("Wallet"),
-                    result_column(
-                        self.error.as_ref(),
-                        None,
-                        [
+            Column::new()
+                .push_maybe(self.update_view())
+                .push(
+                    column![
+                        column![
+                            text_big("Wallet"),
+                            result_column(
+                                self.error.as_ref(),
+                                None,
+                                [
+                                    Some(
+                                        row![
+                                            pick_list(wallets_names, wallet_name, |w| {
+                                                Message::WalletSelect(w.to_string())
+                                            })
+                                            .width(Fill),
+                                            submit_button(
+                                                "Export",
+                                                wallet_name.map(|w| {
+                                                    Message::SensitiveExportPress(w.to_string())
+                                                })
+                                            )
+                                            .width(Shrink),
+                                        ]
+                                        .spacing(20)
+                                        .into(),
+                                    ),
+                                    wallet_name.map(|w| self.manage_view(w, network)),
+                                    Some(
+                                        row![
+                                            text_input("default", &self.new_wallet_name)
+                                                .width(Fill)
+                                                .on_input(Message::NewWalletInput),
+                                            row![
+                                                submit_button(
+                                                    text("Create").align_x(Center),
+                                                    if self.new_wallet_name.is_empty() {
+                                                        None
+                                                    } else {
+                                                        Some(Message::CreateWalletPress)
+                                                    }
+                                                ),
+                                                submit_button(
+                                                    text("Import").align_x(Center),
+                                                    Some(Message::ImportWalletPress)
+                                                ),
+                                            ]
+                                            .spacing(5)
+                                        ]
+                                        .spacing(20)
+                                        .into(),
+                                    ),
+                                    self.encrypted_import
+                                        .as_ref()
+                                        .map(|import| self.encrypted_import_view(import)),
+                                    Some(self.cli_wallets_view()),
+                                ]
+                                .into_iter()
+                                .flatten()
+                            )
+                            .spacing(40),
+                        ]
+                        .spacing(40),
+                        Column::new().push_maybe(wallet_config.map(|wallet_config| {
+                            column![
+                                text_big("Wallet preferences"),
+                                column![
+                                    text_bold("Default fee rate"),
+                                    pick_list(
+                                        FeeRateOption::PRESETS,
+                                        Some(wallet_config.fee_rate),
+                                        Message::FeeRateSelect
+                                    ),
+                                ]
+                                .spacing(10),
+                                self.auto_renew_view(wallet_config.auto_renew),
+                            ]
+                            .spacing(20)
+                        })),
+                        column![
+                            text_big("Nostr relays"),
+                            Column::with_children(relays.iter().map(|relay| {
+                                row![
+                                    text_monospace(relay).width(Fill),
+                                    button(text("Remove").size(14))
+                                        .style(button::text)
+                                        .on_press(Message::RemoveRelayPress(relay.clone())),
+                                ]
+                                .align_y(Center)
+                                .into()
+                            }))
+                            .spacing(10),
                             row![
-                                pick_list(wallets_names, wallet_name, |w| {
-                                    Message::WalletSelect(w.to_string())
-                                })
-                                .width(Fill),
+                                text_input("wss://", &self.new_relay)
+                                    .width(Fill)
+                                    .on_input(Message::RelayInput),
                                 submit_button(
-                                    "Export",
-                                    wallet_name.map(|w| Message::ExportWalletPress(w.to_string()))
+                                    text("Add").align_x(Center),
+                                    nostr::is_relay_url(&self.new_relay)
+                                        .then_some(Message::AddRelayPress),
                                 )
                                 .width(Shrink),
                             ]
-                            .spacing(20)
-                            .into(),
-                            row![
-                                text_input("default", &self.new_wallet_name)
-                                    .width(Fill)
-                                    .on_input(Message::NewWalletInput),
+                            .spacing(20),
+                        ]
+                        .spacing(20),
+                        column![text_big("Proxy"), self.proxy_view()].spacing(20),
+                        column![text_big("Webhooks"), self.webhooks_view(webhooks)].spacing(20),
+                        column![
+                    text_big("Automation API"),
+                    checkbox(
+                        "Let scripts list spaces, check the balance, bid and sign events with \
+                         the loaded wallets",
+                        automation_token.is_some(),
+                    )
+                    .on_toggle(Message::AutomationToggle),
+                ]
+                        .push_maybe(
+                            self.automation_error
+                                .clone()
+                                .map(|err| error_block(Some(err)))
+                        )
+                        .push_maybe(automation_token.map(|token| {
+                            column![
                                 row![
-                                    submit_button(
-                                        text("Create").align_x(Center),
-                                        if self.new_wallet_name.is_empty() {
-                                            None
-                                        } else {
-                                            Some(Message::CreateWalletPress)
-                                        }
-                                    ),
-                                    submit_button(
-                                        text("Import").align_x(Center),
-                                        Some(Message::ImportWalletPress)
-                                    ),
+                                    text_bold("Address: "),
+                                    text_monospace(format!(
+                                        "http://{}",
+                                        automation_addr(automation_port)
+                                    ))
+                                    .width(Fill),
+                                    text_input("Default port", &self.automation_port)
+                                        .width(100)
+                                        .on_input(Message::AutomationPortInput),
+                                    button(text("Save port").size(14))
+                                        .style(button::text)
+                                        .on_press_maybe(self.automation_port_save(automation_port)),
+                                ]
+                                .align_y(Center),
+                                row![
+                                    text_bold("Token: "),
+                                    text_monospace(token).width(Fill),
+                                    button(text("Copy").size(14))
+                                        .style(button::text)
+                                        .on_press(Message::CopyAutomationTokenPress),
                                 ]
-                                .spacing(5)
+                                .align_y(Center),
                             ]
-                            .spacing(20)
-                            .into()
+                            .spacing(10)
+                        }))
+                        .spacing(20),
+                        column![text_big("App backup"), self.app_backup_view()].spacing(20),
+                        column![
+                            text_big("Backend profiles"),
+                            self.profiles_view(profiles, active_profile)
                         ]
+                        .spacing(20),
+                        column![
+                            text_big("Backend"),
+                            column![
+                                row![text_bold("Network: "), text(network.to_string()),],
+                                row![text_bold("Block height: "), text(tip_height.to_string()),],
+                            ]
+                            .spacing(20),
+                        ]
+                        .push(
+                            column![row![
+                                button(text("Test connection").align_x(Center).width(Fill))
+                                    .style(button::secondary)
+                                    .padding(STANDARD_PADDING)
+                                    .width(Fill)
+                                    .on_press_maybe(
+                                        (!self.testing_connection)
+                                            .then_some(Message::TestConnectionPress)
+                                    ),
+                                button(text("Diagnostics").align_x(Center).width(Fill))
+                                    .style(button::secondary)
+                                    .padding(STANDARD_PADDING)
+                                    .width(Fill)
+                                    .on_press(Message::DiagnosticsPress),
+                            ]
+                            .spacing(10)]
+                            .push_maybe(self.connection_test.as_ref().map(|result| match result {
+                                Ok(summary) => text(summary).into(),
+                                Err(err) => error_block(Some(err)),
+                            }))
+                            .spacing(10)
+                        )
+                        .push_maybe(prune_refresh.map(|enabled| {
+                            checkbox(
+                                format!(
+                            "Rebuild from a newer checkpoint every {} days to keep the chain \
+                             data small",
+                            super::PRUNE_REFRESH_DAYS
+                        ),
+                                enabled,
+                            )
+                            .on_toggle(Message::PruneRefreshToggle)
+                        }))
+                        .push_maybe(managed_backend.then(|| {
+                            checkbox(
+                        "Print service logs to the terminal instead of the logs bar, from the \
+                         next start",
+                        stdout_logs,
                     )
+                    .on_toggle(Message::StdoutLogsToggle)
+                        }))
+                        .push_maybe(managed_backend.then(|| {
+                            checkbox(
+                                "Restart a service automatically when the sync is stuck on it",
+                                restart_stuck_services,
+                            )
+                            .on_toggle(Message::RestartStuckServicesToggle)
+                        }))
+                        .push(
+                            checkbox(
+                                "Show the RPC console to call spaced methods directly",
+                                rpc_console,
+                            )
+                            .on_toggle(Message::RpcConsoleToggle),
+                        )
+                        .push(
+                            column![row![
+                                checkbox("Check GitHub for new releases on start", check_updates)
+                                    .on_toggle(Message::CheckUpdatesToggle)
+                                    .width(Fill),
+                                button(text(if self.checking_update {
+                                    "Checking"
+                                } else {
+                                    "Check now"
+                                }))
+                                .style(button::secondary)
+                                .on_press_maybe(
+                                    (!self.checking_update).then_some(Message::CheckUpdatePress)
+                                ),
+                            ]
+                            .align_y(Center)]
+                            .push_maybe(self.update_check.as_ref().and_then(
+                                |result| match result {
+                                    Ok(None) => Some(text("Akron is up to date").into()),
+                                    Ok(Some(_)) => None,
+                                    Err(err) => Some(error_block(Some(err))),
+                                }
+                            ))
+                            .spacing(10),
+                        )
+                        .push(self.background_service_view())
+                        .push_maybe(managed_backend.then(|| self.storage_view()))
+                        .push_maybe(managed_backend.then(|| self.compact_view()))
+                        .push_maybe(managed_backend.then(|| self.export_checkpoint_view()))
+                        .push_maybe(managed_backend.then(|| self.rebuild_view()))
+                        .push(
+                            button(text("Reset backend settings").align_x(Center).width(Fill))
+                                .on_press(Message::ResetBackendPress)
+                                .style(danger_button_style)
+                                .padding(STANDARD_PADDING)
+                                .width(Fill),
+                        )
+                        .spacing(40)
+                    ]
                     .spacing(40),
-                ]
+                )
                 .spacing(40),
-                column![
-                    text_big("Backend"),
-                    column![
-                        row![text_bold("Network: "), text(network.to_string()),],
-                        row![text_bold("Block height: "), text(tip_height.to_string()),],
-                    ]
-                    .spacing(20),
-                    button(text("Reset backend settings").align_x(Center).width(Fill))
-                        .on_press(Message::ResetBackendPress)
-                        .style(|t: &Theme, status: button::Status| {
-                            let mut style = button::danger(t, status);
-                            let p = t.extended_palette();
-                            if matches!(status, button::Status::Active) {
-                                style.background = Some(p.danger.weak.color.into());
-                            }
-                            style.border = rounded(7);
-                            style
-                        })
-                        .padding(STANDARD_PADDING)
-                        .width(Fill),
-                ]
-                .spacing(40)
-            ]
-            .spacing(40),
         )
         .into()
     }
@@ -1,46 +1,194 @@
+use super::state::SpacesCollection;
 use crate::widget::base::{base_container, result_column};
 use crate::{
     client::*,
-    widget::{form::Form, text::text_big},
+    nostr,
+    widget::{
+        form::{submit_button, Form},
+        icon::{button_icon, Icon},
+        tabs::TabsRow,
+        text::{text_big, text_monospace, text_small},
+    },
 };
-use iced::{widget::column, Element};
+use iced::{
+    widget::{button, column, container, row, text, text_editor, Column},
+    Border, Center, Element, Fill, Theme,
+};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Tab {
+    #[default]
+    Event,
+    Message,
+    Profile,
+    AuthorizedKeys,
+    Verify,
+}
+
+/// Portable bundle of a message signed with a space key
+#[derive(Debug, Serialize, Deserialize)]
+struct SignedMessage {
+    space: String,
+    message: String,
+    event: NostrEvent,
+}
+
+#[derive(Debug)]
+struct Verification {
+    slabel: SLabel,
+    pubkey: String,
+    signature_valid: bool,
+}
 
 #[derive(Debug, Default)]
 pub struct State {
+    tab: Tab,
     slabel: Option<SLabel>,
     event: Option<(String, NostrEvent)>,
+    signed_event: Option<NostrEvent>,
+    publishing: bool,
+    published: Vec<nostr::PublishResult>,
+    message: text_editor::Content,
+    signed_message: Option<String>,
+    verify_input: text_editor::Content,
+    verify_path: String,
+    verify_space: String,
+    verification: Option<Verification>,
+    profile: serde_json::Map<String, serde_json::Value>,
+    profile_name: String,
+    profile_about: String,
+    profile_picture: String,
+    authorized_keys: Vec<(String, String)>,
+    key_input: String,
+    key_days: String,
+    loading: bool,
+    /// Whether a relay answered for the published profile or keys, publishing without
+    /// them would replace what was published with only the fields shown here
+    fetched: bool,
     error: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
+    TabPress(Tab),
     SLabelSelect(SLabel),
     PathPress,
     SignSubmit,
     EventFileLoaded(Result<Option<(String, NostrEvent)>, String>),
+    EventSigned(Result<NostrEvent, String>),
+    SaveEventPress,
     EventFileSaved(Result<(), String>),
+    PublishEventPress,
+    EventPublished(Vec<nostr::PublishResult>),
+    TextAction(text_editor::Action),
+    SignMessageSubmit,
+    SignResult(Result<NostrEvent, String>),
+    CopyPress,
+    VerifyAction(text_editor::Action),
+    VerifyPathPress,
+    VerifyFileLoaded(Result<Option<(String, String)>, String>),
+    VerifySpaceInput(String),
+    VerifySubmit,
+    LatestFetched(u32, Result<Vec<serde_json::Value>, String>),
+    ProfileNameInput(String),
+    ProfileAboutInput(String),
+    ProfilePictureInput(String),
+    ProfileSubmit,
+    KeyInput(String),
+    KeyDaysInput(String),
+    AuthorizeKeySubmit,
+    RevokeKeyPress(usize),
 }
 
 #[derive(Debug, Clone)]
 pub enum Action {
     None,
     FilePick,
+    VerifyFilePick,
     Sign(SLabel, NostrEvent),
+    SaveEvent(NostrEvent),
+    PublishEvent(NostrEvent),
+    SignAndPublish(SLabel, NostrEvent),
+    SignMessage(SLabel, NostrEvent),
+    GetSpaceInfo(SLabel),
+    FetchLatest(SLabel, u32),
+    WriteClipboard(String),
 }
 
 impl State {
+    fn fetch_latest(&mut self) -> Action {
+        let kind = match self.tab {
+            Tab::Profile => 0,
+            Tab::AuthorizedKeys => nostr::AUTHORIZED_KEYS_KIND,
+            _ => return Action::None,
+        };
+        match &self.slabel {
+            Some(slabel) => {
+                self.loading = true;
+                self.fetched = false;
+                Action::FetchLatest(slabel.clone(), kind)
+            }
+            None => Action::None,
+        }
+    }
+
+    fn publish_authorized_keys(&mut self) -> Action {
+        self.publishing = true;
+        self.published = Vec::new();
+        Action::SignAndPublish(
+            self.slabel.as_ref().unwrap().clone(),
+            nostr::authorized_keys_event(&self.authorized_keys),
+        )
+    }
+
     pub fn update(&mut self, message: Message) -> Action {
         self.error = None;
         match message {
+            Message::TabPress(tab) => {
+                self.tab = tab;
+                self.published = Vec::new();
+                self.fetch_latest()
+            }
             Message::SLabelSelect(slabel) => {
                 self.slabel = Some(slabel);
-                Action::None
+                self.published = Vec::new();
+                self.fetch_latest()
             }
             Message::PathPress => Action::FilePick,
-            Message::SignSubmit => Action::Sign(
-                self.slabel.as_ref().unwrap().clone(),
-                self.event.as_ref().unwrap().1.clone(),
-            ),
+            Message::SignSubmit => {
+                self.signed_event = None;
+                self.published = Vec::new();
+                Action::Sign(
+                    self.slabel.as_ref().unwrap().clone(),
+                    self.event.as_ref().unwrap().1.clone(),
+                )
+            }
+            Message::EventSigned(Ok(event)) => {
+                self.signed_event = Some(event);
+                Action::None
+            }
+            Message::EventSigned(Err(err)) => {
+                self.publishing = false;
+                self.error = Some(err);
+                if self.tab == Tab::AuthorizedKeys {
+                    // the local list was already changed, reload the published one
+                    return self.fetch_latest();
+                }
+                Action::None
+            }
+            Message::SaveEventPress => Action::SaveEvent(self.signed_event.clone().unwrap()),
+            Message::PublishEventPress => {
+                self.publishing = true;
+                self.published = Vec::new();
+                Action::PublishEvent(self.signed_event.clone().unwrap())
+            }
+            Message::EventPublished(published) => {
+                self.publishing = false;
+                self.published = published;
+                Action::None
+            }
             Message::EventFileLoaded(result) => {
                 match result {
                     Ok(Some(event_file)) => {
@@ -57,36 +205,550 @@ impl State {
                 }
                 Action::None
             }
+            Message::TextAction(action) => {
+                self.message.perform(action);
+                Action::None
+            }
+            Message::SignMessageSubmit => {
+                let slabel = self.slabel.as_ref().unwrap().clone();
+                self.signed_message = None;
+                let event = nostr::message_event(&slabel.to_string(), self.message.text());
+                Action::SignMessage(slabel, event)
+            }
+            Message::SignResult(Ok(event)) => {
+                let signed_message = SignedMessage {
+                    space: self.slabel.as_ref().unwrap().to_string(),
+                    message: event.content.clone(),
+                    event,
+                };
+                self.signed_message = Some(serde_json::to_string_pretty(&signed_message).unwrap());
+                Action::None
+            }
+            Message::SignResult(Err(err)) => {
+                self.error = Some(err);
+                Action::None
+            }
+            Message::CopyPress => Action::WriteClipboard(self.signed_message.clone().unwrap()),
+            Message::VerifyAction(action) => {
+                self.verify_input.perform(action);
+                self.verification = None;
+                Action::None
+            }
+            Message::LatestFetched(_, Err(err)) => {
+                self.loading = false;
+                self.error = Some(format!(
+                    "Could not load what the space published, try again before publishing. {}",
+                    err
+                ));
+                Action::None
+            }
+            Message::LatestFetched(kind, Ok(events)) if kind == nostr::AUTHORIZED_KEYS_KIND => {
+                self.loading = false;
+                self.fetched = true;
+                self.authorized_keys = events
+                    .first()
+                    .map(nostr::authorized_keys_from_event)
+                    .unwrap_or_default();
+                Action::None
+            }
+            Message::LatestFetched(_, Ok(events)) => {
+                self.loading = false;
+                self.fetched = true;
+                self.profile = events
+                    .first()
+                    .and_then(|event| event.get("content")?.as_str())
+                    .and_then(|content| serde_json::from_str(content).ok())
+                    .unwrap_or_default();
+                let field = |name: &str| {
+                    self.profile
+                        .get(name)
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                self.profile_name = field("name");
+                self.profile_about = field("about");
+                self.profile_picture = field("picture");
+                Action::None
+            }
+            Message::ProfileNameInput(name) => {
+                self.profile_name = name;
+                Action::None
+            }
+            Message::ProfileAboutInput(about) => {
+                self.profile_about = about;
+                Action::None
+            }
+            Message::ProfilePictureInput(picture) => {
+                self.profile_picture = picture;
+                Action::None
+            }
+            Message::ProfileSubmit => {
+                // fields set by other clients are kept as they are
+                let mut profile = self.profile.clone();
+                for (name, value) in [
+                    ("name", &self.profile_name),
+                    ("about", &self.profile_about),
+                    ("picture", &self.profile_picture),
+                ] {
+                    if value.is_empty() {
+                        profile.remove(name);
+                    } else {
+                        profile.insert(name.to_string(), value.clone().into());
+                    }
+                }
+                self.publishing = true;
+                self.published = Vec::new();
+                Action::SignAndPublish(
+                    self.slabel.as_ref().unwrap().clone(),
+                    nostr::profile_event(profile),
+                )
+            }
+            Message::KeyInput(key) => {
+                self.key_input = key;
+                Action::None
+            }
+            Message::KeyDaysInput(days) => {
+                if days.chars().all(|c| c.is_ascii_digit()) {
+                    self.key_days = days;
+                }
+                Action::None
+            }
+            Message::AuthorizeKeySubmit => {
+                let pubkey = nostr::parse_pubkey(&self.key_input).unwrap();
+                let days: u64 = self.key_days.parse().unwrap_or(365);
+                let until = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs())
+                    + days * 24 * 60 * 60;
+                self.authorized_keys.retain(|(p, _)| p != &pubkey);
+                self.authorized_keys
+                    .push((pubkey, format!("created_at<{}", until)));
+                self.key_input = String::new();
+                self.publish_authorized_keys()
+            }
+            Message::RevokeKeyPress(index) => {
+                self.authorized_keys.remove(index);
+                self.publish_authorized_keys()
+            }
+            Message::VerifyPathPress => Action::VerifyFilePick,
+            Message::VerifyFileLoaded(result) => {
+                match result {
+                    Ok(Some((path, content))) => {
+                        self.verify_path = path;
+                        self.verify_input = text_editor::Content::with_text(content.trim());
+                        self.verification = None;
+                    }
+                    Ok(None) => {}
+                    Err(err) => self.error = Some(err),
+                }
+                Action::None
+            }
+            Message::VerifySpaceInput(space) => {
+                self.verify_space = space;
+                self.verification = None;
+                Action::None
+            }
+            Message::VerifySubmit => {
+                let input = self.verify_input.text();
+                let (space, event, signature_valid) = if let Ok(signed_message) =
+                    serde_json::from_str::<SignedMessage>(&input)
+                {
+                    let event = serde_json::to_value(&signed_message.event).unwrap();
+                    let signature_valid = event.get("content").and_then(|c| c.as_str())
+                        == Some(signed_message.message.as_str())
+                        && nostr::event_tag(&event, "space") == Some(signed_message.space.as_str());
+                    (signed_message.space, event, signature_valid)
+                } else {
+                    let event = match serde_json::from_str::<serde_json::Value>(&input) {
+                        Ok(event) if event.get("sig").is_some() => event,
+                        Ok(_) => {
+                            self.error = Some("Event is not signed".to_string());
+                            return Action::None;
+                        }
+                        Err(err) => {
+                            self.error = Some(format!("Invalid JSON: {}", err));
+                            return Action::None;
+                        }
+                    };
+                    // events signed by a space may name it in a tag,
+                    // otherwise the space has to be entered
+                    let space = match nostr::event_tag(&event, "space") {
+                        Some(space) => space.to_string(),
+                        None if !self.verify_space.is_empty() => self.verify_space.clone(),
+                        None => {
+                            self.error = Some("Enter the space that signed this event".to_string());
+                            return Action::None;
+                        }
+                    };
+                    (space, event, true)
+                };
+                let Ok(slabel) = SLabel::from_str(&space) else {
+                    self.error = Some("Invalid space name".to_string());
+                    return Action::None;
+                };
+                let signature_valid = signature_valid && nostr::verify_event(&event);
+                self.verification = Some(Verification {
+                    slabel: slabel.clone(),
+                    pubkey: event
+                        .get("pubkey")
+                        .and_then(|p| p.as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                    signature_valid,
+                });
+                Action::GetSpaceInfo(slabel)
+            }
+        }
+    }
+
+    fn verification_view<'a>(
+        verification: &'a Verification,
+        spaces: &'a SpacesCollection,
+    ) -> Element<'a, Message> {
+        let slabel = &verification.slabel;
+        if !verification.signature_valid {
+            return text("Signature is invalid").style(text::danger).into();
+        }
+        match spaces.get_covenant(slabel) {
+            None => text("Checking space ownership").into(),
+            Some(None) => text(format!("{} does not exist", slabel))
+                .style(text::danger)
+                .into(),
+            Some(Some(_)) => {
+                let owner = spaces
+                    .get_outpoint(slabel)
+                    .and_then(|(_, public_key)| public_key.as_ref())
+                    .map(|public_key| public_key.to_string());
+                if owner.as_ref() == Some(&verification.pubkey) {
+                    text(format!("Valid signature by {}", slabel))
+                        .style(text::success)
+                        .into()
+                } else {
+                    text(format!(
+                        "Signed by a key that does not currently own {}",
+                        slabel
+                    ))
+                    .style(text::danger)
+                    .into()
+                }
+            }
         }
     }
 
-    pub fn view<'a>(&'a self, owned_spaces: &'a Vec<SLabel>) -> Element<'a, Message> {
+    pub fn view<'a>(
+        &'a self,
+        owned_spaces: &'a Vec<SLabel>,
+        spaces: &'a SpacesCollection,
+    ) -> Element<'a, Message> {
         base_container(
             column![
-                text_big("Sign Nostr event"),
-                result_column(
-                    self.error.as_ref(),
-                    None,
-                    [Form::new(
-                        "Save",
-                        (self.slabel.is_some() && self.event.is_some())
-                            .then_some(Message::SignSubmit),
+                TabsRow::new()
+                    .add_tab(
+                        "Nostr event",
+                        self.tab == Tab::Event,
+                        Message::TabPress(Tab::Event),
                     )
-                    .add_pick_list(
-                        "Space",
-                        owned_spaces.as_slice(),
-                        self.slabel.as_ref(),
-                        Message::SLabelSelect
+                    .add_tab(
+                        "Message",
+                        self.tab == Tab::Message,
+                        Message::TabPress(Tab::Message),
                     )
-                    .add_text_button(
-                        "Nostr event",
-                        "JSON file",
-                        self.event.as_ref().map_or("", |p| &p.0),
-                        Message::PathPress,
+                    .add_tab(
+                        "Profile",
+                        self.tab == Tab::Profile,
+                        Message::TabPress(Tab::Profile),
                     )
-                    .into()]
-                )
-                .spacing(40),
+                    .add_tab(
+                        "Authorized keys",
+                        self.tab == Tab::AuthorizedKeys,
+                        Message::TabPress(Tab::AuthorizedKeys),
+                    )
+                    .add_tab(
+                        "Verify",
+                        self.tab == Tab::Verify,
+                        Message::TabPress(Tab::Verify),
+                    ),
+                match self.tab {
+                    Tab::Event => column![
+                        text_big("Sign Nostr event"),
+                        result_column(
+                            self.error.as_ref(),
+                            None,
+                            [Form::new(
+                                "Sign",
+                                (self.slabel.is_some() && self.event.is_some())
+                                    .then_some(Message::SignSubmit),
+                            )
+                            .add_pick_list(
+                                "Space",
+                                owned_spaces.as_slice(),
+                                self.slabel.as_ref(),
+                                Message::SLabelSelect
+                            )
+                            .add_text_button(
+                                "Nostr event",
+                                "JSON file",
+                                self.event.as_ref().map_or("", |p| &p.0),
+                                Message::PathPress,
+                            )
+                            .into()]
+                        )
+                        .spacing(40),
+                    ]
+                    .push_maybe(self.signed_event.as_ref().map(|_| {
+                        column![row![
+                            submit_button(
+                                text("Save to file").width(Fill).align_x(Center),
+                                Some(Message::SaveEventPress),
+                            ),
+                            submit_button(
+                                text(if self.publishing {
+                                    "Publishing"
+                                } else {
+                                    "Publish to relays"
+                                })
+                                .width(Fill)
+                                .align_x(Center),
+                                (!self.publishing).then_some(Message::PublishEventPress),
+                            ),
+                        ]
+                        .spacing(10)]
+                        .extend(self.published.iter().map(|(relay, result)| {
+                            row![
+                                text_monospace(relay).width(Fill),
+                                match result {
+                                    Ok(()) => text_small("Published").style(text::success),
+                                    Err(err) => text_small(err).style(text::danger),
+                                }
+                            ]
+                            .spacing(10)
+                            .into()
+                        }))
+                        .spacing(10)
+                    }))
+                    .spacing(40),
+                    Tab::Message => column![
+                        text_big("Sign message"),
+                        result_column(
+                            self.error.as_ref(),
+                            None,
+                            [Form::new(
+                                "Sign",
+                                (self.slabel.is_some() && !self.message.text().trim().is_empty())
+                                    .then_some(Message::SignMessageSubmit),
+                            )
+                            .add_pick_list(
+                                "Space",
+                                owned_spaces.as_slice(),
+                                self.slabel.as_ref(),
+                                Message::SLabelSelect
+                            )
+                            .add_text_editor(
+                                "Message",
+                                "Text to sign",
+                                &self.message,
+                                Message::TextAction,
+                            )
+                            .into()]
+                        )
+                        .spacing(40),
+                    ]
+                    .push_maybe(self.signed_message.as_ref().map(|signed_message| {
+                        container(row![
+                            text_monospace(signed_message).width(Fill),
+                            button_icon(Icon::Copy).on_press(Message::CopyPress)
+                        ])
+                        .padding(10)
+                        .style(|theme: &Theme| {
+                            let palette = theme.extended_palette();
+                            container::Style::default()
+                                .background(palette.background.base.color)
+                                .border(Border {
+                                    radius: 6.0.into(),
+                                    width: 1.0,
+                                    color: palette.background.strong.color,
+                                })
+                        })
+                    }))
+                    .spacing(40),
+                    Tab::Profile => column![
+                        text_big("Nostr profile"),
+                        result_column(
+                            self.error.as_ref(),
+                            None,
+                            [Form::new(
+                                if self.publishing {
+                                    "Publishing"
+                                } else {
+                                    "Sign and publish"
+                                },
+                                (self.slabel.is_some()
+                                    && self.fetched
+                                    && !self.loading
+                                    && !self.publishing)
+                                    .then_some(Message::ProfileSubmit),
+                            )
+                            .add_pick_list(
+                                "Space",
+                                owned_spaces.as_slice(),
+                                self.slabel.as_ref(),
+                                Message::SLabelSelect
+                            )
+                            .add_text_input(
+                                "Name",
+                                "",
+                                &self.profile_name,
+                                Message::ProfileNameInput,
+                            )
+                            .add_text_input(
+                                "About",
+                                "",
+                                &self.profile_about,
+                                Message::ProfileAboutInput,
+                            )
+                            .add_text_input(
+                                "Picture",
+                                "https://",
+                                &self.profile_picture,
+                                Message::ProfilePictureInput,
+                            )
+                            .into()]
+                        )
+                        .spacing(40),
+                    ]
+                    .push_maybe(self.loading.then(|| text("Loading profile from relays")))
+                    .extend(self.published.iter().map(|(relay, result)| {
+                        row![
+                            text_monospace(relay).width(Fill),
+                            match result {
+                                Ok(()) => text_small("Published").style(text::success),
+                                Err(err) => text_small(err).style(text::danger),
+                            }
+                        ]
+                        .spacing(10)
+                        .into()
+                    }))
+                    .spacing(40),
+                    Tab::AuthorizedKeys => column![
+                        text_big("Authorized keys"),
+                        result_column(
+                            self.error.as_ref(),
+                            None,
+                            [Form::new(
+                                if self.publishing {
+                                    "Publishing"
+                                } else {
+                                    "Authorize key"
+                                },
+                                (self.slabel.is_some()
+                                    && self.fetched
+                                    && !self.loading
+                                    && !self.publishing
+                                    && nostr::parse_pubkey(&self.key_input).is_some())
+                                .then_some(Message::AuthorizeKeySubmit),
+                            )
+                            .add_pick_list(
+                                "Space",
+                                owned_spaces.as_slice(),
+                                self.slabel.as_ref(),
+                                Message::SLabelSelect
+                            )
+                            .add_text_input(
+                                "Public key",
+                                "npub or hex",
+                                &self.key_input,
+                                Message::KeyInput,
+                            )
+                            .add_text_input(
+                                "Valid for",
+                                "365 days",
+                                &self.key_days,
+                                Message::KeyDaysInput,
+                            )
+                            .into()]
+                        )
+                        .spacing(40),
+                    ]
+                    .push(if self.loading {
+                        Element::from(text("Loading authorized keys from relays"))
+                    } else if !self.fetched {
+                        text("The authorized keys couldn't be loaded from the relays").into()
+                    } else if self.authorized_keys.is_empty() {
+                        text("No keys are authorized for this space").into()
+                    } else {
+                        Column::with_children(self.authorized_keys.iter().enumerate().map(
+                            |(index, (pubkey, conditions))| {
+                                row![
+                                    column![text_monospace(pubkey), text_small(conditions)]
+                                        .spacing(5)
+                                        .width(Fill),
+                                    button(text("Revoke").size(14))
+                                        .style(button::danger)
+                                        .on_press_maybe(
+                                            (!self.publishing)
+                                                .then_some(Message::RevokeKeyPress(index))
+                                        ),
+                                ]
+                                .spacing(10)
+                                .align_y(Center)
+                                .into()
+                            },
+                        ))
+                        .spacing(10)
+                        .into()
+                    })
+                    .extend(self.published.iter().map(|(relay, result)| {
+                        row![
+                            text_monospace(relay).width(Fill),
+                            match result {
+                                Ok(()) => text_small("Published").style(text::success),
+                                Err(err) => text_small(err).style(text::danger),
+                            }
+                        ]
+                        .spacing(10)
+                        .into()
+                    }))
+                    .spacing(40),
+                    Tab::Verify => column![
+                        text_big("Verify signature"),
+                        result_column(
+                            self.error.as_ref(),
+                            None,
+                            [Form::new(
+                                "Verify",
+                                (!self.verify_input.text().trim().is_empty())
+                                    .then_some(Message::VerifySubmit),
+                            )
+                            .add_text_button(
+                                "File",
+                                "JSON file",
+                                &self.verify_path,
+                                Message::VerifyPathPress,
+                            )
+                            .add_text_editor(
+                                "Signed message or Nostr event",
+                                "JSON",
+                                &self.verify_input,
+                                Message::VerifyAction,
+                            )
+                            .add_text_input(
+                                "Space",
+                                "@space, if the event doesn't name it",
+                                &self.verify_space,
+                                Message::VerifySpaceInput,
+                            )
+                            .into()]
+                        )
+                        .spacing(40),
+                    ]
+                    .push_maybe(
+                        self.verification
+                            .as_ref()
+                            .map(|verification| Self::verification_view(verification, spaces))
+                    )
+                    .spacing(40),
+                }
             ]
             .spacing(40),
         )
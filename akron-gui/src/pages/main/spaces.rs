@@ -1,4 +1,4 @@
-use super::state::SpacesCollection;
+use super::state::{PriceHistory, SaleRecord, SpacesCollection};
 use crate::widget::base::{base_container, result_column};
 use crate::widget::form::STANDARD_PADDING;
 use crate::widget::text::text_semibold;
@@ -31,13 +31,20 @@ pub enum Filter {
     #[default]
     Owned,
     Bidding,
+    Watching,
 }
 
+/// Cards shown at first and added by each press on "Show more", so wallets with hundreds
+/// of spaces don't build them all
+const SPACES_PAGE_SIZE: usize = 50;
+
 #[derive(Debug, Default)]
 pub struct State {
     slabel: Option<SLabel>,
     search: String,
     filter: Filter,
+    /// Pages shown after the first one
+    more_pages: usize,
     amount: String,
     error: Option<String>,
     tx_result: Option<TxResultWidget>,
@@ -50,8 +57,10 @@ pub enum Message {
     CopySLabelPress(SLabel),
     CopyOutpointPress(OutPoint),
     CopyPublicKeyPress(XOnlyPublicKey),
+    WatchPress(SLabel),
     SearchInput(String),
     FilterPress(Filter),
+    ShowMorePress,
     AmountInput(String),
     OpenSubmit,
     BidSubmit,
@@ -66,6 +75,8 @@ pub enum Action {
     None,
     WriteClipboard(String),
     GetSpaceInfo { slabel: SLabel },
+    GetWatchlistInfo,
+    ToggleWatch(SLabel),
     OpenSpace { slabel: SLabel, amount: Amount },
     BidSpace { slabel: SLabel, amount: Amount },
     RegisterSpace { slabel: SLabel },
@@ -112,9 +123,11 @@ impl State {
             Message::CopySLabelPress(slabel) => Action::WriteClipboard(slabel.to_string()),
             Message::CopyOutpointPress(outpoint) => Action::WriteClipboard(outpoint.to_string()),
             Message::CopyPublicKeyPress(pubkey) => Action::WriteClipboard(pubkey.to_string()),
+            Message::WatchPress(slabel) => Action::ToggleWatch(slabel),
             Message::SearchInput(search) => {
                 if is_slabel_input(&search) {
                     self.search = search;
+                    self.more_pages = 0;
                     if let Some(slabel) = slabel_from_str(&self.search) {
                         Action::GetSpaceInfo { slabel }
                     } else {
@@ -126,6 +139,15 @@ impl State {
             }
             Message::FilterPress(filter) => {
                 self.filter = filter;
+                self.more_pages = 0;
+                if self.filter == Filter::Watching {
+                    Action::GetWatchlistInfo
+                } else {
+                    Action::None
+                }
+            }
+            Message::ShowMorePress => {
+                self.more_pages += 1;
                 Action::None
             }
             Message::AmountInput(amount) => {
@@ -295,6 +317,7 @@ impl State {
         expire_height: u32,
         owner: (&'a OutPoint, &'a Option<XOnlyPublicKey>),
         is_owned: bool,
+        sales: Vec<&'a SaleRecord>,
     ) -> Element<'a, Message> {
         let (outpoint, pubkey) = owner;
         base_container(
@@ -410,6 +433,24 @@ impl State {
                     }
                 })
                 .padding(40),
+                if sales.is_empty() {
+                    column![]
+                } else {
+                    column![text_big("Sale history")]
+                        .extend(sales.into_iter().map(|sale| {
+                            row![
+                                text_monospace(sale.txid.to_string()).width(Fill),
+                                text(sale.block_height.map_or("Unconfirmed".to_string(), |h| {
+                                    format!("Block {}", h)
+                                })),
+                                text_bold(format_amount_number(sale.price)),
+                            ]
+                            .spacing(20)
+                            .into()
+                        }))
+                        .spacing(10)
+                }
+                .width(Fill),
                 if is_owned {
                     column![
                         text_big("Actions"),
@@ -432,6 +473,7 @@ impl State {
         .into()
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn view<'a>(
         &'a self,
         tip_height: u32,
@@ -440,6 +482,8 @@ impl State {
         winning_spaces: &'a [SLabel],
         outbid_spaces: &'a [SLabel],
         owned_spaces: &'a [SLabel],
+        watchlist: &'a [SLabel],
+        price_history: &'a PriceHistory,
     ) -> Element<'a, Message> {
         if let Some(slabel) = self.slabel.as_ref() {
             container(
@@ -452,6 +496,17 @@ impl State {
                         button_icon(Icon::Copy)
                             .style(button::text)
                             .on_press(Message::CopySLabelPress(slabel.clone())),
+                        Space::with_width(Fill),
+                        button(
+                            text(if watchlist.contains(slabel) {
+                                "Unwatch"
+                            } else {
+                                "Watch"
+                            })
+                            .size(14)
+                        )
+                        .style(button::text)
+                        .on_press(Message::WatchPress(slabel.clone())),
                     ]
                     .spacing(5)
                     .align_y(Center),
@@ -488,6 +543,7 @@ impl State {
                                     *expire_height,
                                     spaces.get_outpoint(slabel).unwrap(),
                                     is_owned,
+                                    price_history.get(&slabel.to_string()),
                                 )
                             }
                             Some(Some(Covenant::Reserved)) => {
@@ -504,16 +560,26 @@ impl State {
                 match self.filter {
                     Filter::Owned => owned_spaces.iter().collect(),
                     Filter::Bidding => winning_spaces.iter().chain(outbid_spaces).collect(),
+                    Filter::Watching => watchlist.iter().collect(),
                 }
             } else {
                 owned_spaces
                     .iter()
                     .chain(winning_spaces.iter())
                     .chain(outbid_spaces.iter())
+                    .chain(watchlist.iter())
                     .filter(|s| s.as_str_unprefixed().unwrap().contains(&self.search))
                     .collect()
             };
             slabels.sort_unstable_by_key(|s| s.as_str_unprefixed().unwrap());
+            slabels.dedup();
+            // an exact name not in the lists is shown on top, checked before the pages are cut
+            let searched =
+                slabel_from_str(&self.search).filter(|slabel| !slabels.contains(&slabel));
+            let hidden = slabels
+                .len()
+                .saturating_sub((self.more_pages + 1) * SPACES_PAGE_SIZE);
+            slabels.truncate(slabels.len() - hidden);
 
             let card = |slabel: &SLabel| -> Element<'a, Message> {
                 enum State {
@@ -690,6 +756,11 @@ impl State {
                                             "Bidding",
                                             self.filter == Filter::Bidding,
                                             Message::FilterPress(Filter::Bidding),
+                                        )
+                                        .add_tab(
+                                            "Watching",
+                                            self.filter == Filter::Watching,
+                                            Message::FilterPress(Filter::Watching),
                                         ),
                                 )
                             } else {
@@ -706,6 +777,7 @@ impl State {
                                                 match &self.filter {
                                                     Filter::Owned => "owned spaces",
                                                     Filter::Bidding => "bids",
+                                                    Filter::Watching => "watched spaces",
                                                 }
                                             ))
                                             .size(16)
@@ -723,12 +795,17 @@ impl State {
                             } else {
                                 None
                             })
-                            .push_maybe(
-                                slabel_from_str(&self.search)
-                                    .filter(|slabel| !slabels.contains(&slabel))
-                                    .map(|slabel| card(&slabel)),
-                            )
+                            .push_maybe(searched.map(|slabel| card(&slabel)),)
                             .extend(slabels.into_iter().map(card))
+                            .push_maybe((hidden > 0).then(|| {
+                                container(
+                                    button(text(format!("Show more ({} not shown)", hidden)))
+                                        .style(button::secondary)
+                                        .on_press(Message::ShowMorePress),
+                                )
+                                .width(Fill)
+                                .align_x(Center)
+                            }))
                             .push(Space::with_height(5))
                             .spacing(10),
                     ]
@@ -1,9 +1,10 @@
-use iced::widget::qr_code::Data as QrCode;
+use crate::widget::qr_code::QrMatrix;
 
 use spaces_client::wallets::{TxInfo, WalletInfoWithProgress, WalletStatus};
 use spaces_protocol::bitcoin::XOnlyPublicKey;
 use spaces_protocol::{slabel::SLabel, Covenant, FullSpaceOut, SpaceOut};
-use spaces_wallet::bitcoin::{Amount, OutPoint};
+use spaces_wallet::bitcoin::{Amount, OutPoint, Txid};
+use std::path::PathBuf;
 
 #[derive(Debug)]
 pub struct SpaceData {
@@ -25,6 +26,40 @@ impl SpacesCollection {
         );
     }
 
+    /// Whether the space was last seen at the outpoint of `out`, a space only changes
+    /// by moving to another one
+    fn is_at(&self, slabel: &SLabel, out: &FullSpaceOut) -> bool {
+        out.spaceout
+            .space
+            .as_ref()
+            .is_some_and(|space| &space.name == slabel)
+            && self
+                .0
+                .get(slabel)
+                .is_some_and(|data| data.as_ref().is_some_and(|s| s.outpoint == out.outpoint()))
+    }
+
+    /// Replaces `names` with the spaces of a wallet list, only the spaces that moved since
+    /// the last refresh are stored again
+    pub fn sync_list(&mut self, names: &mut Vec<SLabel>, spaces: Vec<FullSpaceOut>) {
+        if names.len() == spaces.len()
+            && names
+                .iter()
+                .zip(&spaces)
+                .all(|(name, out)| self.is_at(name, out))
+        {
+            return;
+        }
+        names.clear();
+        for out in spaces {
+            let name = out.spaceout.space.as_ref().unwrap().name.clone();
+            if !self.is_at(&name, &out) {
+                self.set(name.clone(), Some(out));
+            }
+            names.push(name);
+        }
+    }
+
     pub fn get_outpoint(&self, slabel: &SLabel) -> Option<(&OutPoint, &Option<XOnlyPublicKey>)> {
         self.0
             .get(slabel)
@@ -39,20 +74,37 @@ impl SpacesCollection {
 #[derive(Debug)]
 pub struct AddressData {
     text: String,
-    qr_code: QrCode,
+    /// Generated in the background, `None` until it's done
+    qr_code: Option<Result<QrMatrix, String>>,
 }
 impl AddressData {
+    /// Address without its QR code yet, see [`AddressData::qr_code`]
     pub fn new(text: String) -> Self {
-        let qr_code = QrCode::new(&text).unwrap();
-        Self { text, qr_code }
+        Self {
+            text,
+            qr_code: None,
+        }
+    }
+
+    /// Builds the QR code of `text` off the UI thread
+    pub async fn qr_code(text: String) -> Result<QrMatrix, String> {
+        tokio::task::spawn_blocking(move || QrMatrix::encode(&text))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+
+    pub fn set_qr_code(&mut self, qr_code: Result<QrMatrix, String>) {
+        self.qr_code = Some(qr_code);
     }
 
     pub fn as_str(&self) -> &str {
         &self.text
     }
 
-    pub fn as_qr_code(&self) -> &QrCode {
-        &self.qr_code
+    pub fn as_qr_code(&self) -> Option<Result<&QrMatrix, &str>> {
+        self.qr_code
+            .as_ref()
+            .map(|qr_code| qr_code.as_ref().map_err(String::as_str))
     }
 }
 
@@ -148,6 +200,51 @@ impl WalletsCollection {
         self.current = None;
     }
 
+    /// Keeps the wallet refreshed without making it current, returns false if it's
+    /// unknown or already loaded
+    pub fn load(&mut self, label: &str) -> bool {
+        match self.wallets.get_mut(label) {
+            Some(wallet_state @ None) => {
+                *wallet_state = Some(WalletData::default());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn unload(&mut self, label: &str) {
+        if self.current.as_deref() == Some(label) {
+            return;
+        }
+        if let Some(wallet_state) = self.wallets.get_mut(label) {
+            *wallet_state = None;
+        }
+    }
+
+    /// Loaded wallets sorted by label
+    pub fn get_loaded(&self) -> Vec<WalletEntry<'_>> {
+        let mut entries: Vec<WalletEntry<'_>> = self
+            .wallets
+            .iter()
+            .filter_map(|(label, wallet_state)| {
+                wallet_state
+                    .as_ref()
+                    .map(|state| WalletEntry { label, state })
+            })
+            .collect();
+        entries.sort_by(|a, b| a.label.cmp(b.label));
+        entries
+    }
+
+    /// Sum of the loaded wallets balances, `None` until all of them are known
+    pub fn get_total_balance(&self) -> Option<Amount> {
+        self.wallets
+            .values()
+            .flatten()
+            .map(|state| state.balance)
+            .sum()
+    }
+
     pub fn get_current(&self) -> Option<WalletEntry<'_>> {
         self.current.as_ref().and_then(|label| {
             self.wallets
@@ -165,6 +262,63 @@ impl WalletsCollection {
     }
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SaleRecord {
+    pub space: String,
+    pub price: u64,
+    pub txid: Txid,
+    pub block_height: Option<u32>,
+}
+
+/// Sale prices seen by this wallet, persisted next to the config
+#[derive(Debug, Default)]
+pub struct PriceHistory {
+    path: PathBuf,
+    records: Vec<SaleRecord>,
+}
+impl PriceHistory {
+    pub fn load(path: PathBuf) -> Self {
+        let records = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_default();
+        Self { path, records }
+    }
+
+    fn save(&self) {
+        let records = serde_json::to_string_pretty(&self.records).unwrap();
+        if let Err(e) = crate::write_atomic(&self.path, records.as_bytes()) {
+            eprintln!("Could not save {}: {}", self.path.display(), e);
+        }
+    }
+
+    /// Adds the record, an existing record for the same transaction keeps its price
+    pub fn record(&mut self, record: SaleRecord) {
+        match self.records.iter_mut().find(|r| r.txid == record.txid) {
+            Some(existing) => {
+                if existing.block_height == record.block_height {
+                    return;
+                }
+                existing.block_height = record.block_height;
+            }
+            None => self.records.push(record),
+        }
+        self.save();
+    }
+
+    /// Sales of the space, most recent first
+    pub fn get(&self, space: &str) -> Vec<&SaleRecord> {
+        let mut records: Vec<&SaleRecord> =
+            self.records.iter().filter(|r| r.space == space).collect();
+        records.sort_by_key(|r| std::cmp::Reverse(r.block_height.unwrap_or(u32::MAX)));
+        records
+    }
+
+    pub fn last(&self, space: &str) -> Option<&SaleRecord> {
+        self.get(space).into_iter().next()
+    }
+}
+
 fn public_key_from_spaceout(out: &SpaceOut) -> Option<XOnlyPublicKey> {
     match out.script_pubkey.is_p2tr() {
         true => XOnlyPublicKey::from_slice(&out.script_pubkey.as_bytes()[2..]).ok(),
@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+
+use crate::client::ServiceKind;
+use crate::ConfigBackend;
+
+/// Spaced catching up that doesn't process a block for this long is considered stuck
+pub const TIP_STALL_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// Blocks come minutes apart, hours without a new header means the chain source lost
+/// its peers
+pub const HEADERS_STALL_TIMEOUT: Duration = Duration::from_secs(2 * 3600);
+
+/// What stopped moving
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stall {
+    /// No new header from the Bitcoin source
+    Headers,
+    /// Spaced is behind the headers and stays at the same block
+    Tip,
+}
+
+impl Stall {
+    /// Service Akron runs whose restart may get the sync going again
+    pub fn service(&self, backend: &ConfigBackend) -> Option<ServiceKind> {
+        match (self, backend) {
+            (Stall::Headers, ConfigBackend::Akrond { .. }) => Some(ServiceKind::Yuki),
+            (Stall::Headers, ConfigBackend::ManagedBitcoind { .. }) => Some(ServiceKind::Bitcoind),
+            (
+                Stall::Tip,
+                ConfigBackend::Akrond { .. }
+                | ConfigBackend::ManagedBitcoind { .. }
+                | ConfigBackend::Bitcoind { .. },
+            ) => Some(ServiceKind::Spaces),
+            _ => None,
+        }
+    }
+
+    pub fn message(&self, tip: u32, headers: u32) -> String {
+        match self {
+            Stall::Headers => format!(
+                "No new block for {} hours, the Bitcoin source may have lost its peers. \
+                 Restart it or change its peers on the Node page",
+                HEADERS_STALL_TIMEOUT.as_secs() / 3600
+            ),
+            Stall::Tip => format!(
+                "Sync is stuck at block {} of {} for {} minutes. Restarting spaced usually \
+                 gets it going again",
+                tip,
+                headers,
+                TIP_STALL_TIMEOUT.as_secs() / 60
+            ),
+        }
+    }
+}
+
+/// Notices when the sync stops progressing, from the server info fetched on each tick
+#[derive(Debug, Default)]
+pub struct SyncWatchdog {
+    /// Last values seen and since when
+    headers: Option<(u32, Instant)>,
+    tip: Option<(u32, Instant)>,
+    stall: Option<Stall>,
+}
+
+fn observe_value(value: &mut Option<(u32, Instant)>, new: u32, now: Instant) -> Duration {
+    match value {
+        Some((old, since)) if *old == new => now - *since,
+        _ => {
+            *value = Some((new, now));
+            Duration::ZERO
+        }
+    }
+}
+
+impl SyncWatchdog {
+    /// Records the heights, returning the stall when it's noticed for the first time
+    pub fn observe(&mut self, tip: u32, headers: u32) -> Option<Stall> {
+        self.observe_at(tip, headers, Instant::now())
+    }
+
+    fn observe_at(&mut self, tip: u32, headers: u32, now: Instant) -> Option<Stall> {
+        let headers_unchanged = observe_value(&mut self.headers, headers, now);
+        let tip_unchanged = observe_value(&mut self.tip, tip, now);
+        let stall = if tip < headers && tip_unchanged >= TIP_STALL_TIMEOUT {
+            Some(Stall::Tip)
+        } else if headers_unchanged >= HEADERS_STALL_TIMEOUT {
+            Some(Stall::Headers)
+        } else {
+            None
+        };
+        let noticed = stall.filter(|stall| self.stall != Some(*stall));
+        self.stall = stall;
+        noticed
+    }
+
+    pub fn stall(&self) -> Option<Stall> {
+        self.stall
+    }
+
+    /// Gives the sync a new period to progress, after the stuck service was restarted
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stuck_tip_is_noticed_once() {
+        let start = Instant::now();
+        let mut watchdog = SyncWatchdog::default();
+        assert_eq!(watchdog.observe_at(100, 200, start), None);
+        assert_eq!(
+            watchdog.observe_at(100, 200, start + TIP_STALL_TIMEOUT / 2),
+            None
+        );
+        assert_eq!(
+            watchdog.observe_at(100, 200, start + TIP_STALL_TIMEOUT),
+            Some(Stall::Tip)
+        );
+        assert_eq!(
+            watchdog.observe_at(100, 200, start + TIP_STALL_TIMEOUT * 2),
+            None
+        );
+        assert_eq!(watchdog.stall(), Some(Stall::Tip));
+        // progress clears the stall
+        assert_eq!(
+            watchdog.observe_at(101, 200, start + TIP_STALL_TIMEOUT * 3),
+            None
+        );
+        assert_eq!(watchdog.stall(), None);
+    }
+
+    #[test]
+    fn synced_tip_is_not_stuck() {
+        let start = Instant::now();
+        let mut watchdog = SyncWatchdog::default();
+        watchdog.observe_at(200, 200, start);
+        assert_eq!(
+            watchdog.observe_at(200, 200, start + TIP_STALL_TIMEOUT),
+            None
+        );
+        assert_eq!(
+            watchdog.observe_at(200, 200, start + HEADERS_STALL_TIMEOUT),
+            Some(Stall::Headers)
+        );
+    }
+
+    #[test]
+    fn new_headers_restart_the_period() {
+        let start = Instant::now();
+        let mut watchdog = SyncWatchdog::default();
+        watchdog.observe_at(200, 200, start);
+        watchdog.observe_at(201, 201, start + HEADERS_STALL_TIMEOUT / 2);
+        assert_eq!(
+            watchdog.observe_at(201, 201, start + HEADERS_STALL_TIMEOUT),
+            None
+        );
+        watchdog.reset();
+        assert_eq!(
+            watchdog.observe_at(201, 201, start + HEADERS_STALL_TIMEOUT * 2),
+            None
+        );
+        assert_eq!(watchdog.stall(), None);
+    }
+}
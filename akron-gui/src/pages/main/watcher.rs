@@ -0,0 +1,140 @@
+use serde::Serialize;
+use spaces_client::wallets::{ListSpacesResponse, TxInfo};
+use spaces_protocol::{slabel::SLabel, Covenant, FullSpaceOut};
+use spaces_wallet::bitcoin::Txid;
+use std::collections::{HashMap, HashSet};
+
+/// Names of the events, as used in the webhook settings
+pub const EVENT_NAMES: [&str; 4] = ["tx_confirmed", "outbid", "space_claimable", "listing_sold"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WalletEvent {
+    TxConfirmed {
+        wallet: String,
+        txid: Txid,
+        height: u32,
+    },
+    Outbid {
+        wallet: String,
+        space: SLabel,
+    },
+    SpaceClaimable {
+        wallet: String,
+        space: SLabel,
+    },
+    /// An owned space left the wallet without the wallet sending it, i.e. its listing
+    /// was bought
+    ListingSold {
+        wallet: String,
+        space: SLabel,
+    },
+}
+
+impl WalletEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::TxConfirmed { .. } => EVENT_NAMES[0],
+            Self::Outbid { .. } => EVENT_NAMES[1],
+            Self::SpaceClaimable { .. } => EVENT_NAMES[2],
+            Self::ListingSold { .. } => EVENT_NAMES[3],
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SpacesSnapshot {
+    pending: HashSet<SLabel>,
+    winning: HashSet<SLabel>,
+    owned: HashSet<SLabel>,
+    claimable: HashSet<SLabel>,
+}
+
+/// Turns the lists fetched for each wallet into events by comparing them with the
+/// previous ones. The first lists of a wallet only set what's known
+#[derive(Debug, Default)]
+pub struct Watcher {
+    spaces: HashMap<String, SpacesSnapshot>,
+    unconfirmed: HashMap<String, HashSet<Txid>>,
+}
+
+fn names(spaces: &[FullSpaceOut]) -> impl Iterator<Item = SLabel> + '_ {
+    spaces
+        .iter()
+        .filter_map(|out| out.spaceout.space.as_ref())
+        .map(|space| space.name.clone())
+}
+
+impl Watcher {
+    pub fn spaces(
+        &mut self,
+        wallet: &str,
+        tip_height: u32,
+        spaces: &ListSpacesResponse,
+    ) -> Vec<WalletEvent> {
+        let snapshot = SpacesSnapshot {
+            pending: spaces.pending.iter().cloned().collect(),
+            winning: names(&spaces.winning).collect(),
+            owned: names(&spaces.owned).collect(),
+            claimable: spaces
+                .winning
+                .iter()
+                .filter_map(|out| out.spaceout.space.as_ref())
+                .filter(|space| {
+                    matches!(space.covenant, Covenant::Bid { claim_height: Some(height), .. }
+                        if height <= tip_height)
+                })
+                .map(|space| space.name.clone())
+                .collect(),
+        };
+        let Some(previous) = self.spaces.insert(wallet.to_string(), snapshot) else {
+            return Vec::new();
+        };
+        let current = &self.spaces[wallet];
+        let wallet = wallet.to_string();
+        let outbid = names(&spaces.outbid)
+            .filter(|space| previous.winning.contains(space))
+            .map(|space| WalletEvent::Outbid {
+                wallet: wallet.clone(),
+                space,
+            });
+        let claimable = current
+            .claimable
+            .difference(&previous.claimable)
+            .map(|space| WalletEvent::SpaceClaimable {
+                wallet: wallet.clone(),
+                space: space.clone(),
+            });
+        let sold = previous
+            .owned
+            .difference(&current.owned)
+            .filter(|space| !previous.pending.contains(space) && !current.pending.contains(space))
+            .map(|space| WalletEvent::ListingSold {
+                wallet: wallet.clone(),
+                space: space.clone(),
+            });
+        outbid.chain(claimable).chain(sold).collect()
+    }
+
+    pub fn transactions(&mut self, wallet: &str, transactions: &[TxInfo]) -> Vec<WalletEvent> {
+        let unconfirmed = transactions
+            .iter()
+            .filter(|tx| tx.block_height.is_none())
+            .map(|tx| tx.txid)
+            .collect();
+        let Some(previous) = self.unconfirmed.insert(wallet.to_string(), unconfirmed) else {
+            return Vec::new();
+        };
+        transactions
+            .iter()
+            .filter(|tx| previous.contains(&tx.txid))
+            .filter_map(|tx| {
+                Some(WalletEvent::TxConfirmed {
+                    wallet: wallet.to_string(),
+                    txid: tx.txid,
+                    height: tx.block_height?,
+                })
+            })
+            .collect()
+    }
+}
@@ -1,60 +1,365 @@
+mod seed_qr;
+
+use std::str::FromStr;
+
 use iced::{
-    widget::{button, column, container, horizontal_space, row, scrollable, text, Column},
-    Bottom, Center, Color, Element, Fill, Font, Subscription, Task, Theme,
+    widget::{
+        button, checkbox, column, container, horizontal_space, progress_bar, row, scrollable, text,
+        Column, Row,
+    },
+    Bottom, Center, Color, Element, Fill, Font, Shrink, Subscription, Task, Theme,
 };
 use ringbuffer::{ConstGenericRingBuffer, RingBuffer};
+use tokio_stream::{wrappers::BroadcastStream, StreamExt};
 
-use spaces_client::config::ExtendedNetwork;
+use spaces_client::config::{default_spaces_rpc_port, ExtendedNetwork};
 use spaces_protocol::constants::ChainAnchor;
+use spaces_wallet::{
+    bdk_wallet::keys::bip39::Language,
+    bitcoin::bip32::{ChildNumber, DerivationPath},
+};
 
 use crate::{
-    client::{Client, ClientResult, ServerInfo},
+    app_backup::AppBackup,
+    backoff::Backoff,
+    client::{
+        service_ports, spaced_cli_wallets_dir, unused_wallet_label, CheckpointProgress,
+        CliWalletsImport, Client, ClientResult, RestoreOptions, ServerInfo,
+    },
+    crash,
+    helpers::{format_bytes, height_from_str},
     widget::{
         base::base_container,
-        form::{submit_button, text_input, Form},
+        form::{pick_list, submit_button, text_input, Form},
         icon::{button_icon, text_icon, Icon},
         text::{error_block, text_big, text_bold, text_monospace, text_semibold, text_small},
     },
     Config, ConfigBackend,
 };
 
+const MNEMONIC_LENGTHS: [usize; 5] = [12, 15, 18, 21, 24];
+
+const MAX_WORD_SUGGESTIONS: usize = 5;
+
+/// Number of words asked back after a new mnemonic is shown
+const QUIZ_WORDS: usize = 3;
+
+/// Accounts after the restored one that can be imported as separate wallets
+const EXTRA_ACCOUNTS: [usize; 6] = [0, 1, 2, 3, 4, 5];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitcoindAuth {
+    Password,
+    Cookie,
+}
+
+impl std::fmt::Display for BitcoindAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Password => "User and password",
+            Self::Cookie => "Cookie file",
+        })
+    }
+}
+
+/// Cookie file written by bitcoind in its default data directory
+fn default_cookie_path(network: ExtendedNetwork) -> Option<String> {
+    let base_dirs = directories::BaseDirs::new()?;
+    let data_dir = if cfg!(any(target_os = "windows", target_os = "macos")) {
+        base_dirs.data_dir().join("Bitcoin")
+    } else {
+        base_dirs.home_dir().join(".bitcoin")
+    };
+    let data_dir = match network {
+        ExtendedNetwork::Mainnet => data_dir,
+        ExtendedNetwork::Testnet => data_dir.join("testnet3"),
+        ExtendedNetwork::Testnet4 => data_dir.join("testnet4"),
+        ExtendedNetwork::Signet => data_dir.join("signet"),
+        ExtendedNetwork::Regtest => data_dir.join("regtest"),
+    };
+    data_dir.join(".cookie").to_str().map(str::to_string)
+}
+
+/// A backend found running on this computer
+#[derive(Debug, Clone)]
+pub struct DetectedBackend {
+    label: String,
+    backend: ConfigBackend,
+}
+
+/// Whether something answers JSON-RPC requests at `url`, a missing login still counts
+async fn is_rpc_server(client: &reqwest::Client, url: &str) -> bool {
+    client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .body(r#"{"jsonrpc":"2.0","id":0,"method":"getblockchaininfo","params":[]}"#)
+        .send()
+        .await
+        .is_ok_and(|response| {
+            response.status().is_success() || response.status() == reqwest::StatusCode::UNAUTHORIZED
+        })
+}
+
+/// Probes the default local ports of bitcoind and spaced
+async fn detect_backends() -> Vec<DetectedBackend> {
+    let Ok(client) = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(2))
+        .build()
+    else {
+        return Vec::new();
+    };
+    let mut detected = Vec::new();
+    for (network, bitcoind_port) in [
+        (ExtendedNetwork::Mainnet, 8332),
+        (ExtendedNetwork::Testnet4, 48332),
+    ] {
+        let url = format!("http://127.0.0.1:{}", default_spaces_rpc_port(&network));
+        if is_rpc_server(&client, &url).await {
+            detected.push(DetectedBackend {
+                label: format!("Spaced on {} at {}", network, url),
+                backend: ConfigBackend::Spaced {
+                    network,
+                    url,
+                    fallback_urls: Vec::new(),
+                    user: String::new(),
+                    password: String::new(),
+                    ca_cert: None,
+                    cert_fingerprint: None,
+                    timeouts: Default::default(),
+                },
+            });
+        }
+        let url = format!("http://127.0.0.1:{}", bitcoind_port);
+        if is_rpc_server(&client, &url).await {
+            let cookie =
+                default_cookie_path(network).filter(|path| std::path::Path::new(path).is_file());
+            detected.push(DetectedBackend {
+                label: format!("Bitcoin node on {} at {}", network, url),
+                backend: ConfigBackend::Bitcoind {
+                    network,
+                    url,
+                    user: String::new(),
+                    password: String::new(),
+                    cookie,
+                    spaced_password: None,
+                },
+            });
+        }
+    }
+    detected
+}
+
+fn is_http_url(value: &str) -> bool {
+    reqwest::Url::parse(value)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
+}
+
+/// URLs of a comma separated text input
+fn url_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Sets `port` from a text input, empty clears it
+/// Keeps the input when it's empty or a number, which is then set to `number`
+fn number_input<T: std::str::FromStr>(value: String, input: &mut String, number: &mut Option<T>) {
+    if value.is_empty() {
+        *number = None;
+    } else if let Ok(value) = value.parse() {
+        *number = Some(value);
+    } else {
+        return;
+    }
+    *input = value;
+}
+
+fn word_input_id(i: usize) -> iced::widget::text_input::Id {
+    iced::widget::text_input::Id::new(format!("mnemonic-word-{}", i))
+}
+
+/// BIP39 words starting with `prefix`
+fn word_suggestions(prefix: &str) -> &[&'static str] {
+    let words = Language::English.words_by_prefix(prefix);
+    &words[..words.len().min(MAX_WORD_SUGGESTIONS)]
+}
+
+/// Checkpoint download shown while connecting
+#[derive(Debug)]
+struct CheckpointDownload {
+    started: std::time::Instant,
+    /// Bytes already there when the download started or resumed
+    start_bytes: u64,
+    progress: CheckpointProgress,
+}
+
+impl CheckpointDownload {
+    /// Time left at the average speed so far
+    fn eta(&self) -> Option<std::time::Duration> {
+        let fetched = self.progress.downloaded.checked_sub(self.start_bytes)?;
+        let elapsed = self.started.elapsed();
+        if fetched == 0 || elapsed.as_secs() < 2 {
+            return None;
+        }
+        let left = self.progress.total.saturating_sub(self.progress.downloaded);
+        Some(elapsed.mul_f64(left as f64 / fetched as f64))
+    }
+
+    fn view<'a>(&self) -> Element<'a, Message> {
+        let CheckpointProgress { downloaded, total } = self.progress;
+        let eta = match self.eta().map(|eta| eta.as_secs()) {
+            None => String::new(),
+            Some(secs) if secs < 60 => ", less than a minute left".to_string(),
+            Some(secs) if secs < 3600 => format!(", about {} min left", secs / 60),
+            Some(secs) => format!(", about {} h {} min left", secs / 3600, secs % 3600 / 60),
+        };
+        column![
+            text_bold("Downloading checkpoint"),
+            progress_bar(0.0..=total as f32, downloaded as f32).height(10),
+            text_small(format!(
+                "{} of {}{}",
+                format_bytes(downloaded),
+                format_bytes(total),
+                eta
+            )),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
+
 #[derive(Debug)]
 pub struct State {
     config: Config,
     client: Option<Client>,
     connecting: bool,
+    /// Checkpoint progress channel while connecting, with its subscription id
+    checkpoint_progress: Option<(usize, tokio::sync::broadcast::Sender<CheckpointProgress>)>,
+    checkpoint_download: Option<CheckpointDownload>,
+    /// Chain data is removed before connecting
+    rebuild_chain_data: bool,
+    /// Setting up an additional session, which can be left to go back to the others
+    cancellable: bool,
+    /// Ports of the services run by the other sessions
+    ports_in_use: Vec<u16>,
     logs: ConstGenericRingBuffer<String, 100>,
-    mnemonic: Option<[String; 12]>,
-    mnemonic_target: Option<[String; 12]>,
+    /// Server info requests failed in a row while connecting
+    server_info_failures: u32,
+    mnemonic: Option<Vec<String>>,
+    mnemonic_target: Option<Vec<String>>,
+    word_count: usize,
+    active_word: Option<usize>,
+    quiz: Vec<usize>,
+    seed_qr: Option<(seed_qr::Format, iced::widget::qr_code::Data)>,
+    seed_qr_input: String,
+    birthday: String,
+    passphrase: Option<String>,
+    passphrase_confirmation: String,
+    advanced: bool,
+    account_path: String,
+    extra_accounts: usize,
+    spaced_port: String,
+    yuki_port: String,
+    fallback_urls: String,
+    connect_timeout: String,
+    request_timeout: String,
+    checkpoint_mirrors: String,
+    serve_peers: String,
+    filters_endpoints: String,
+    detected: Vec<DetectedBackend>,
+    /// App backup waiting for its password
+    restore_backup: Option<std::path::PathBuf>,
+    restore_password: String,
+    restoring_backup: bool,
+    /// Wallets of a restored app backup, imported once spaced is ready
+    pending_imports: Vec<String>,
+    /// spaced run from the command line has wallets on this computer
+    spaced_cli_wallets: bool,
+    testing: bool,
+    test_result: Option<String>,
     error: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     BackendSet(ConfigBackend),
+    BackendsDetected(Vec<DetectedBackend>),
     NetworkSelect(ExtendedNetwork),
     UrlInput(String),
     UserInput(String),
     PasswordInput(String),
+    AuthSelect(BitcoindAuth),
+    CookiePick,
+    CookiePicked(Option<String>),
+    DataDirPick,
+    DataDirPicked(Option<std::path::PathBuf>),
+    SpacedPortInput(String),
+    YukiPortInput(String),
+    RegtestDataDirInput(String),
+    FallbackUrlsInput(String),
+    ConnectTimeoutInput(String),
+    RequestTimeoutInput(String),
+    CheckpointMirrorsInput(String),
+    ServePeersInput(String),
+    FiltersEndpointsInput(String),
+    CaCertPick,
+    CaCertPicked(Option<String>),
+    CertFingerprintInput(String),
+    TestPress,
+    TestResult(Result<String, String>),
     Connect,
     ConnectResult(Result<(Client, ConfigBackend), String>),
     GetServerInfoResult(ClientResult<ServerInfo>),
     ListWalletsResult(ClientResult<Vec<String>>),
+    RestoreBackupPick,
+    RestoreBackupPicked(Option<std::path::PathBuf>),
+    RestorePasswordInput(String),
+    RestoreCancelPress,
+    RestoreConfirmPress,
+    /// Restored config with the wallets to import
+    BackupRestored(Result<(Config, Vec<String>), String>),
+    WalletsImported(ClientResult<()>),
+    ImportCliWallets,
+    /// Labels of the wallets imported from spaced
+    CliWalletsImported(ClientResult<CliWalletsImport>),
     Reset,
     Disconnect,
+    Cancel,
     MnemonicClear,
     MnemonicBlank,
     MnemonicWordInput(usize, String),
+    MnemonicWordSubmit(usize),
+    MnemonicSuggestionPress(usize, &'static str),
+    WordCountSelect(usize),
+    SeedQrSelect(seed_qr::Format),
+    SeedQrHide,
+    SeedQrInput(String),
+    SeedQrImport,
+    BirthdayInput(String),
+    PassphraseToggle(bool),
+    PassphraseInput(String),
+    PassphraseConfirmationInput(String),
+    AdvancedToggle(bool),
+    AccountPathInput(String),
+    ExtraAccountsSelect(usize),
+    AccountRestored(Result<String, String>),
+    /// Wallets spaced has, additional accounts are restored under labels they don't use
+    RestoreWalletsListed(ClientResult<Vec<String>>),
     CreateWallet,
     RestoreWallet,
     ImportWallet,
     ImportWalletPicked(Result<String, String>),
     SetWalletResult(Result<String, String>),
     LogReceived(String),
+    CheckpointProgress(CheckpointProgress),
 }
 
 pub enum Action {
     Return(Config, Client),
+    Cancel,
     Task(Task<Message>),
 }
 
@@ -69,22 +374,142 @@ impl State {
         let task = if config.backend.is_some() {
             Task::done(Message::Connect)
         } else {
-            Task::none()
+            Task::perform(detect_backends(), Message::BackendsDetected)
+        };
+        let (spaced_port, yuki_port) = match &config.backend {
+            Some(ConfigBackend::Akrond {
+                spaced_rpc_port,
+                yuki_rpc_port,
+                ..
+            }) => (
+                spaced_rpc_port.map_or_else(String::new, |p| p.to_string()),
+                yuki_rpc_port.map_or_else(String::new, |p| p.to_string()),
+            ),
+            _ => Default::default(),
+        };
+        let fallback_urls = match &config.backend {
+            Some(ConfigBackend::Spaced { fallback_urls, .. }) => fallback_urls.join(", "),
+            _ => String::new(),
+        };
+        let (connect_timeout, request_timeout) = match &config.backend {
+            Some(ConfigBackend::Spaced { timeouts, .. }) => (
+                timeouts
+                    .connect_secs
+                    .map_or_else(String::new, |s| s.to_string()),
+                timeouts
+                    .request_secs
+                    .map_or_else(String::new, |s| s.to_string()),
+            ),
+            _ => Default::default(),
+        };
+        let checkpoint_mirrors = match &config.backend {
+            Some(ConfigBackend::Akrond {
+                checkpoint_mirrors, ..
+            }) => checkpoint_mirrors.join(", "),
+            _ => String::new(),
+        };
+        let filters_endpoints = match &config.backend {
+            Some(ConfigBackend::Akrond {
+                filters_endpoints, ..
+            }) => filters_endpoints.join(", "),
+            _ => String::new(),
+        };
+        let serve_peers = match &config.backend {
+            Some(ConfigBackend::Akrond { serve_peers, .. }) => {
+                serve_peers.clone().unwrap_or_default()
+            }
+            _ => String::new(),
         };
         (
             Self {
                 config,
                 client: None,
                 connecting: false,
+                checkpoint_progress: None,
+                checkpoint_download: None,
+                rebuild_chain_data: false,
+                cancellable: false,
+                ports_in_use: Vec::new(),
                 logs: Default::default(),
+                server_info_failures: 0,
                 mnemonic: None,
                 mnemonic_target: None,
+                word_count: MNEMONIC_LENGTHS[0],
+                active_word: None,
+                quiz: Vec::new(),
+                seed_qr: None,
+                seed_qr_input: String::new(),
+                birthday: String::new(),
+                passphrase: None,
+                passphrase_confirmation: String::new(),
+                advanced: false,
+                account_path: String::new(),
+                extra_accounts: 0,
+                spaced_port,
+                yuki_port,
+                fallback_urls,
+                connect_timeout,
+                request_timeout,
+                checkpoint_mirrors,
+                serve_peers,
+                filters_endpoints,
+                detected: Vec::new(),
+                restore_backup: None,
+                restore_password: String::new(),
+                restoring_backup: false,
+                pending_imports: Vec::new(),
+                spaced_cli_wallets: false,
+                testing: false,
+                test_result: None,
                 error: None,
             },
             task,
         )
     }
 
+    /// Connects again after removing the chain data, the connection logs show the
+    /// progress while it's rebuilt
+    pub fn rebuild(config: Config) -> (Self, Task<Message>) {
+        let (mut state, task) = Self::run(config);
+        state.rebuild_chain_data = true;
+        (state, task)
+    }
+
+    /// Sets up another session next to the running ones
+    /// Sets up an additional session next to the ones running the services on
+    /// `ports_in_use`, `error` is shown when its config couldn't be prepared
+    pub fn run_session(
+        config: Config,
+        ports_in_use: Vec<u16>,
+        error: Option<String>,
+    ) -> (Self, Task<Message>) {
+        let (mut state, task) = Self::run(config);
+        state.cancellable = true;
+        state.ports_in_use = ports_in_use;
+        state.error = error;
+        (state, task)
+    }
+
+    fn test_message(&self) -> Option<Message> {
+        (!self.testing).then_some(Message::TestPress)
+    }
+
+    /// Continues once spaced is ready, wallets of a restored backup are imported first
+    fn ready(&mut self) -> Action {
+        let client = self.client.as_ref().unwrap();
+        if !self.pending_imports.is_empty() {
+            Action::Task(
+                client
+                    .import_wallets(self.pending_imports.clone())
+                    .map(Message::WalletsImported),
+            )
+        } else if self.config.wallet.is_none() {
+            Action::Task(client.list_wallets().map(Message::ListWalletsResult))
+        } else {
+            self.finish()
+        }
+    }
+
     fn finish(&mut self) -> Action {
         self.config.save();
         Action::Return(self.config.clone(), self.client.take().unwrap())
@@ -93,15 +518,34 @@ impl State {
     pub fn update(&mut self, message: Message) -> Action {
         if !matches!(message, Message::LogReceived(..)) {
             self.error = None;
+            self.test_result = None;
         }
         match message {
             Message::BackendSet(value) => {
                 self.config.backend = Some(value);
                 Action::none()
             }
+            Message::BackendsDetected(detected) => {
+                self.detected = detected;
+                Action::none()
+            }
             Message::NetworkSelect(value) => {
+                // keep a default cookie path pointing at the selected chain
+                if let Some(ConfigBackend::Bitcoind {
+                    network,
+                    cookie: Some(cookie),
+                    ..
+                }) = self.config.backend.as_mut()
+                {
+                    if default_cookie_path(*network).as_ref() == Some(cookie) {
+                        if let Some(path) = default_cookie_path(value) {
+                            *cookie = path;
+                        }
+                    }
+                }
                 match self.config.backend.as_mut() {
                     Some(ConfigBackend::Akrond { network, .. })
+                    | Some(ConfigBackend::ManagedBitcoind { network, .. })
                     | Some(ConfigBackend::Bitcoind { network, .. })
                     | Some(ConfigBackend::Spaced { network, .. }) => *network = value,
                     _ => unreachable!(),
@@ -132,44 +576,293 @@ impl State {
                 }
                 Action::none()
             }
+            Message::AuthSelect(auth) => {
+                if let Some(ConfigBackend::Bitcoind {
+                    network, cookie, ..
+                }) = self.config.backend.as_mut()
+                {
+                    *cookie = match auth {
+                        BitcoindAuth::Password => None,
+                        BitcoindAuth::Cookie => {
+                            Some(default_cookie_path(*network).unwrap_or_default())
+                        }
+                    };
+                }
+                Action::none()
+            }
+            Message::CookiePick => {
+                let directory = match self.config.backend.as_ref() {
+                    Some(ConfigBackend::Bitcoind {
+                        cookie: Some(cookie),
+                        ..
+                    }) => std::path::Path::new(cookie)
+                        .parent()
+                        .map(|p| p.to_path_buf()),
+                    _ => None,
+                };
+                Action::Task(Task::perform(
+                    async move {
+                        let mut dialog = rfd::AsyncFileDialog::new();
+                        if let Some(directory) = directory {
+                            dialog = dialog.set_directory(directory);
+                        }
+                        dialog
+                            .pick_file()
+                            .await
+                            .and_then(|file| file.path().to_str().map(str::to_string))
+                    },
+                    Message::CookiePicked,
+                ))
+            }
+            Message::DataDirPick => {
+                let directory = self.config.data_dir().to_path_buf();
+                Action::Task(Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .set_title("Folder for the Akron data")
+                            .set_directory(directory)
+                            .pick_folder()
+                            .await
+                            .map(|folder| folder.path().to_path_buf())
+                    },
+                    Message::DataDirPicked,
+                ))
+            }
+            Message::DataDirPicked(dir) => {
+                let Some(dir) = dir else {
+                    return Action::none();
+                };
+                match crate::use_data_dir(dir) {
+                    Ok(config) => {
+                        // an existing install in the chosen folder is connected right away
+                        let (state, task) = Self::run(config);
+                        *self = state;
+                        Action::Task(task)
+                    }
+                    Err(e) => {
+                        self.error = Some(format!("Could not use the folder: {}", e));
+                        Action::none()
+                    }
+                }
+            }
+            Message::CookiePicked(path) => {
+                if let (
+                    Some(ConfigBackend::Bitcoind {
+                        cookie: Some(cookie),
+                        ..
+                    }),
+                    Some(path),
+                ) = (self.config.backend.as_mut(), path)
+                {
+                    *cookie = path;
+                }
+                Action::none()
+            }
+            Message::CaCertPick => Action::Task(Task::perform(
+                async move {
+                    rfd::AsyncFileDialog::new()
+                        .add_filter("PEM certificate", &["pem", "crt"])
+                        .pick_file()
+                        .await
+                        .and_then(|file| file.path().to_str().map(str::to_string))
+                },
+                Message::CaCertPicked,
+            )),
+            Message::CaCertPicked(path) => {
+                // cancelling the dialog goes back to the public CAs
+                if let Some(ConfigBackend::Spaced { ca_cert, .. }) = self.config.backend.as_mut() {
+                    *ca_cert = path;
+                }
+                Action::none()
+            }
+            Message::CertFingerprintInput(value) => {
+                if let Some(ConfigBackend::Spaced {
+                    cert_fingerprint, ..
+                }) = self.config.backend.as_mut()
+                {
+                    *cert_fingerprint = (!value.is_empty()).then_some(value);
+                }
+                Action::none()
+            }
+            Message::SpacedPortInput(value) => {
+                if let Some(ConfigBackend::Akrond {
+                    spaced_rpc_port, ..
+                }) = self.config.backend.as_mut()
+                {
+                    number_input(value, &mut self.spaced_port, spaced_rpc_port);
+                }
+                Action::none()
+            }
+            Message::YukiPortInput(value) => {
+                if let Some(ConfigBackend::Akrond { yuki_rpc_port, .. }) =
+                    self.config.backend.as_mut()
+                {
+                    number_input(value, &mut self.yuki_port, yuki_rpc_port);
+                }
+                Action::none()
+            }
+            Message::RegtestDataDirInput(value) => {
+                if let Some(ConfigBackend::Akrond { regtest, .. }) = self.config.backend.as_mut() {
+                    regtest.data_dir = (!value.is_empty()).then_some(value);
+                }
+                Action::none()
+            }
+            Message::TestPress => {
+                self.testing = true;
+                Action::Task(Task::perform(
+                    Client::test_backend(self.config.backend.clone().unwrap()),
+                    Message::TestResult,
+                ))
+            }
+            Message::TestResult(result) => {
+                self.testing = false;
+                match result {
+                    Ok(summary) => self.test_result = Some(summary),
+                    Err(err) => self.error = Some(err),
+                }
+                Action::none()
+            }
+            Message::FallbackUrlsInput(value) => {
+                if let Some(ConfigBackend::Spaced { fallback_urls, .. }) =
+                    self.config.backend.as_mut()
+                {
+                    *fallback_urls = url_list(&value);
+                }
+                self.fallback_urls = value;
+                Action::none()
+            }
+            Message::ConnectTimeoutInput(value) => {
+                if let Some(ConfigBackend::Spaced { timeouts, .. }) = self.config.backend.as_mut() {
+                    number_input(value, &mut self.connect_timeout, &mut timeouts.connect_secs);
+                }
+                Action::none()
+            }
+            Message::RequestTimeoutInput(value) => {
+                if let Some(ConfigBackend::Spaced { timeouts, .. }) = self.config.backend.as_mut() {
+                    number_input(value, &mut self.request_timeout, &mut timeouts.request_secs);
+                }
+                Action::none()
+            }
+            Message::CheckpointMirrorsInput(value) => {
+                if let Some(ConfigBackend::Akrond {
+                    checkpoint_mirrors, ..
+                }) = self.config.backend.as_mut()
+                {
+                    *checkpoint_mirrors = url_list(&value);
+                }
+                self.checkpoint_mirrors = value;
+                Action::none()
+            }
+            Message::FiltersEndpointsInput(value) => {
+                if let Some(ConfigBackend::Akrond {
+                    filters_endpoints, ..
+                }) = self.config.backend.as_mut()
+                {
+                    *filters_endpoints = url_list(&value);
+                }
+                self.filters_endpoints = value;
+                Action::none()
+            }
+            Message::ServePeersInput(value) => {
+                if let Some(ConfigBackend::Akrond { serve_peers, .. }) =
+                    self.config.backend.as_mut()
+                {
+                    // kept off until the address is complete
+                    *serve_peers = value
+                        .parse::<std::net::SocketAddr>()
+                        .ok()
+                        .map(|_| value.clone());
+                }
+                self.serve_peers = value;
+                Action::none()
+            }
             Message::Connect => {
                 if self.connecting {
                     return Action::none();
                 }
+                if let Some(ConfigBackend::Akrond {
+                    filters_endpoints, ..
+                }) = self.config.backend.as_ref()
+                {
+                    if let Some(endpoint) = filters_endpoints.iter().find(|e| !is_http_url(e)) {
+                        self.error = Some(format!(
+                            "The filters endpoint {} is not an http(s) URL",
+                            endpoint
+                        ));
+                        return Action::none();
+                    }
+                }
+                let backend = self.config.backend.as_ref().unwrap();
+                if service_ports(backend)
+                    .iter()
+                    .any(|port| self.ports_in_use.contains(port))
+                {
+                    self.error = Some(format!(
+                        "Another session already runs the services of {}, switch to it or \
+                         set other service ports",
+                        backend.network()
+                    ));
+                    return Action::none();
+                }
                 self.logs.clear();
+                self.server_info_failures = 0;
                 self.connecting = true;
                 let data_dir = self.config.data_dir().to_path_buf();
                 let backend_config = self.config.backend.clone().unwrap();
+                let proxy = self.config.proxy.clone();
+                let capture_logs = self.config.capture_logs();
+                let rebuild_chain_data = std::mem::take(&mut self.rebuild_chain_data);
+                let progress = tokio::sync::broadcast::Sender::new(10);
+                self.checkpoint_progress = Some((rand::random(), progress.clone()));
                 Action::Task(Task::perform(
-                    async move { Client::create(data_dir, backend_config).await },
+                    async move {
+                        if rebuild_chain_data {
+                            Client::remove_chain_data(&data_dir, &backend_config).await?;
+                        }
+                        Client::create(
+                            data_dir,
+                            backend_config,
+                            proxy,
+                            Some(progress),
+                            capture_logs,
+                        )
+                        .await
+                    },
                     Message::ConnectResult,
                 ))
             }
-            Message::ConnectResult(result) => match result {
-                Ok((client, backend_config)) => {
-                    self.client = Some(client);
-                    self.config.backend = Some(backend_config);
-                    Action::Task(
-                        self.client
-                            .as_ref()
-                            .unwrap()
-                            .get_server_info()
-                            .map(Message::GetServerInfoResult),
-                    )
-                }
-                Err(err) => {
-                    self.connecting = false;
-                    self.error = Some(err);
-                    Action::none()
+            Message::ConnectResult(result) => {
+                self.checkpoint_progress = None;
+                self.checkpoint_download = None;
+                match result {
+                    Ok((client, backend_config)) => {
+                        self.client = Some(client);
+                        self.config.backend = Some(backend_config);
+                        Action::Task(
+                            self.client
+                                .as_ref()
+                                .unwrap()
+                                .get_server_info()
+                                .map(Message::GetServerInfoResult),
+                        )
+                    }
+                    Err(err) => {
+                        self.connecting = false;
+                        self.error = Some(err);
+                        Action::none()
+                    }
                 }
-            },
+            }
             Message::GetServerInfoResult(result) => {
+                let mut delay = std::time::Duration::from_secs(1);
                 match result {
                     Ok(server_info) => {
+                        self.server_info_failures = 0;
                         let backend_config = self.config.backend.as_ref().unwrap();
                         match backend_config {
                             ConfigBackend::Akrond { .. } => {}
-                            ConfigBackend::Bitcoind { network, .. }
+                            ConfigBackend::ManagedBitcoind { network, .. }
+                            | ConfigBackend::Bitcoind { network, .. }
                             | ConfigBackend::Spaced { network, .. } => {
                                 if server_info.network != network.to_string() {
                                     self.client = None;
@@ -185,7 +878,8 @@ impl State {
                                     ConfigBackend::Akrond { prune_point, .. } => {
                                         prune_point.map_or(0, |p| p.height)
                                     }
-                                    ConfigBackend::Bitcoind { network, .. }
+                                    ConfigBackend::ManagedBitcoind { network, .. }
+                                    | ConfigBackend::Bitcoind { network, .. }
                                     | ConfigBackend::Spaced { network, .. } => match network {
                                         ExtendedNetwork::Mainnet => ChainAnchor::MAINNET().height,
                                         ExtendedNetwork::Testnet4 => ChainAnchor::TESTNET4().height,
@@ -193,35 +887,27 @@ impl State {
                                     },
                                 })
                         {
-                            return if self.config.wallet.is_none() {
-                                Action::Task(
-                                    self.client
-                                        .as_ref()
-                                        .unwrap()
-                                        .list_wallets()
-                                        .map(Message::ListWalletsResult),
-                                )
-                            } else {
-                                self.finish()
-                            };
+                            return self.ready();
                         }
                     }
                     Err(err) => {
                         self.logs.push(err);
+                        self.server_info_failures += 1;
+                        delay = Backoff::POLL.delay(self.server_info_failures);
                     }
                 }
-                Action::Task(
-                    Task::future(tokio::time::sleep(std::time::Duration::from_secs(1)))
-                        .discard()
-                        .chain(self.client.as_ref().map_or(Task::none(), |client| {
-                            client.get_server_info().map(Message::GetServerInfoResult)
-                        })),
-                )
+                Action::Task(Task::future(tokio::time::sleep(delay)).discard().chain(
+                    self.client.as_ref().map_or(Task::none(), |client| {
+                        client.get_server_info().map(Message::GetServerInfoResult)
+                    }),
+                ))
             }
             Message::ListWalletsResult(result) => match result {
                 Ok(wallets) => {
                     self.connecting = false;
                     if wallets.is_empty() {
+                        let network = self.config.backend.as_ref().unwrap().network();
+                        self.spaced_cli_wallets = spaced_cli_wallets_dir(network).is_some();
                         Action::none()
                     } else {
                         if wallets.contains(&"default".to_string()) {
@@ -236,31 +922,263 @@ impl State {
                     Action::none()
                 }
             },
+            Message::RestoreBackupPick => Action::Task(Task::perform(
+                async move {
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Akron app backup")
+                        .add_filter("Encrypted file", &["age"])
+                        .add_filter("All files", &["*"])
+                        .pick_file()
+                        .await
+                        .map(|file| file.path().to_path_buf())
+                },
+                Message::RestoreBackupPicked,
+            )),
+            Message::RestoreBackupPicked(path) => {
+                if path.is_some() {
+                    self.restore_backup = path;
+                    self.restore_password.clear();
+                }
+                Action::none()
+            }
+            Message::RestorePasswordInput(password) => {
+                self.restore_password = password;
+                Action::none()
+            }
+            Message::RestoreCancelPress => {
+                if !self.restoring_backup {
+                    self.restore_backup = None;
+                    self.restore_password.clear();
+                }
+                Action::none()
+            }
+            Message::RestoreConfirmPress => {
+                let Some(path) = self.restore_backup.clone() else {
+                    return Action::none();
+                };
+                if self.restoring_backup || self.restore_password.is_empty() {
+                    return Action::none();
+                }
+                self.restoring_backup = true;
+                let password = self.restore_password.clone();
+                let config = self.config.clone();
+                Action::Task(Task::perform(
+                    async move {
+                        let contents = tokio::fs::read_to_string(&path)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        // scrypt takes a moment, off the UI thread
+                        tokio::task::spawn_blocking(move || {
+                            AppBackup::decrypt(&contents, password)?.restore(&config)
+                        })
+                        .await
+                        .map_err(|e| e.to_string())?
+                    },
+                    Message::BackupRestored,
+                ))
+            }
+            Message::BackupRestored(result) => {
+                self.restoring_backup = false;
+                match result {
+                    Ok((config, wallets)) => {
+                        let cancellable = self.cancellable;
+                        let (state, task) = Self::run(config);
+                        *self = state;
+                        self.cancellable = cancellable;
+                        self.pending_imports = wallets;
+                        Action::Task(task)
+                    }
+                    Err(err) => {
+                        self.error = Some(err);
+                        Action::none()
+                    }
+                }
+            }
+            Message::WalletsImported(result) => match result {
+                Ok(()) => {
+                    self.pending_imports.clear();
+                    self.ready()
+                }
+                Err(err) => {
+                    self.connecting = false;
+                    self.client = None;
+                    self.error = Some(format!(
+                        "Could not import the wallets of the backup: {}",
+                        err
+                    ));
+                    Action::none()
+                }
+            },
             Message::Reset => {
                 if self.connecting {
                     return Action::none();
                 }
                 self.config.backend = None;
                 self.client = None;
-                Action::none()
+                Action::Task(Task::perform(detect_backends(), Message::BackendsDetected))
             }
+            Message::Cancel => Action::Cancel,
             Message::Disconnect => {
                 self.connecting = false;
                 self.client = None;
+                self.checkpoint_progress = None;
+                self.checkpoint_download = None;
                 Action::none()
             }
             Message::MnemonicClear => {
+                self.birthday.clear();
+                self.seed_qr = None;
+                self.seed_qr_input.clear();
                 self.mnemonic = None;
                 self.mnemonic_target = None;
+                self.quiz.clear();
+                self.passphrase = None;
+                self.passphrase_confirmation.clear();
+                self.advanced = false;
+                self.account_path.clear();
+                self.extra_accounts = 0;
                 Action::none()
             }
             Message::MnemonicBlank => {
-                self.mnemonic = Some(Default::default());
+                self.seed_qr = None;
+                self.active_word = None;
+                match self.mnemonic_target.as_ref() {
+                    // a new mnemonic is verified by asking back a few random words
+                    Some(target) => {
+                        let mut quiz = rand::seq::index::sample(
+                            &mut rand::thread_rng(),
+                            target.len(),
+                            QUIZ_WORDS,
+                        )
+                        .into_vec();
+                        quiz.sort_unstable();
+                        let mut mnemonic = target.clone();
+                        for i in &quiz {
+                            mnemonic[*i].clear();
+                        }
+                        self.mnemonic = Some(mnemonic);
+                        self.quiz = quiz;
+                    }
+                    None => {
+                        self.mnemonic = Some(vec![String::new(); self.word_count]);
+                        self.quiz.clear();
+                    }
+                }
                 Action::none()
             }
             Message::MnemonicWordInput(i, word) => {
                 if word.chars().all(|c| c.is_ascii_lowercase()) {
                     self.mnemonic.as_mut().unwrap()[i] = word;
+                    self.active_word = Some(i);
+                }
+                Action::none()
+            }
+            Message::MnemonicWordSubmit(i) => {
+                let word = &self.mnemonic.as_ref().unwrap()[i];
+                let suggestion = word_suggestions(word)
+                    .first()
+                    .copied()
+                    .filter(|_| !word.is_empty());
+                match suggestion {
+                    Some(suggestion) => {
+                        self.update(Message::MnemonicSuggestionPress(i, suggestion))
+                    }
+                    None => Action::none(),
+                }
+            }
+            Message::MnemonicSuggestionPress(i, word) => {
+                let mnemonic = self.mnemonic.as_mut().unwrap();
+                mnemonic[i] = word.to_string();
+                self.active_word = None;
+                Action::Task(
+                    match (i + 1..mnemonic.len()).find(|j| mnemonic[*j].is_empty()) {
+                        Some(next) => iced::widget::text_input::focus(word_input_id(next)),
+                        None => Task::none(),
+                    },
+                )
+            }
+            Message::WordCountSelect(word_count) => {
+                self.word_count = word_count;
+                if let Some(mnemonic) = self.mnemonic.as_mut() {
+                    mnemonic.resize(word_count, String::new());
+                    Action::none()
+                } else {
+                    self.update(Message::CreateWallet)
+                }
+            }
+            Message::SeedQrSelect(format) => {
+                if let Some(target) = self.mnemonic_target.as_ref() {
+                    match seed_qr::encode(target, format).and_then(|payload| {
+                        iced::widget::qr_code::Data::new(payload).map_err(|e| e.to_string())
+                    }) {
+                        Ok(data) => self.seed_qr = Some((format, data)),
+                        Err(err) => self.error = Some(err),
+                    }
+                }
+                Action::none()
+            }
+            Message::SeedQrHide => {
+                self.seed_qr = None;
+                Action::none()
+            }
+            Message::SeedQrInput(content) => {
+                self.seed_qr_input = content;
+                Action::none()
+            }
+            Message::SeedQrImport => {
+                match seed_qr::decode(&self.seed_qr_input) {
+                    Ok(words) => {
+                        self.word_count = words.len();
+                        self.mnemonic = Some(words);
+                        self.seed_qr_input.clear();
+                    }
+                    Err(err) => self.error = Some(err),
+                }
+                Action::none()
+            }
+            Message::BirthdayInput(birthday) => {
+                if birthday.chars().all(|c| c.is_ascii_digit() || c == '-') {
+                    self.birthday = birthday;
+                }
+                Action::none()
+            }
+            Message::PassphraseToggle(enabled) => {
+                self.passphrase = enabled.then(String::new);
+                self.passphrase_confirmation.clear();
+                Action::none()
+            }
+            Message::PassphraseInput(passphrase) => {
+                self.passphrase = Some(passphrase);
+                Action::none()
+            }
+            Message::PassphraseConfirmationInput(passphrase) => {
+                self.passphrase_confirmation = passphrase;
+                Action::none()
+            }
+            Message::AdvancedToggle(advanced) => {
+                self.advanced = advanced;
+                if !advanced {
+                    self.account_path.clear();
+                    self.extra_accounts = 0;
+                }
+                Action::none()
+            }
+            Message::AccountPathInput(path) => {
+                self.account_path = path;
+                Action::none()
+            }
+            Message::ExtraAccountsSelect(extra_accounts) => {
+                self.extra_accounts = extra_accounts;
+                Action::none()
+            }
+            Message::AccountRestored(result) => {
+                match result {
+                    Ok(wallet) => {
+                        if !self.config.loaded_wallets.contains(&wallet) {
+                            self.config.loaded_wallets.push(wallet);
+                        }
+                    }
+                    Err(err) => self.error = Some(err),
                 }
                 Action::none()
             }
@@ -272,16 +1190,21 @@ impl State {
                     },
                     miniscript::Tap,
                 };
+                let word_count = match self.word_count {
+                    15 => WordCount::Words15,
+                    18 => WordCount::Words18,
+                    21 => WordCount::Words21,
+                    24 => WordCount::Words24,
+                    _ => WordCount::Words12,
+                };
                 let mnemonic: GeneratedKey<_, Tap> =
-                    Mnemonic::generate((WordCount::Words12, Language::English)).unwrap();
+                    Mnemonic::generate((word_count, Language::English)).unwrap();
                 self.mnemonic_target = Some(
                     mnemonic
                         .to_string()
                         .split(' ')
                         .map(|s| s.to_string())
-                        .collect::<Vec<_>>()
-                        .try_into()
-                        .unwrap(),
+                        .collect(),
                 );
                 self.mnemonic = None;
                 Action::none()
@@ -290,12 +1213,45 @@ impl State {
                 self.client
                     .as_ref()
                     .unwrap()
-                    .restore_wallet(
-                        "default".to_string(),
-                        self.mnemonic.as_ref().unwrap().join(" "),
-                    )
-                    .map(|r| Message::SetWalletResult(r.result.map(|_| r.label))),
+                    .list_wallets()
+                    .map(Message::RestoreWalletsListed),
             ),
+            Message::RestoreWalletsListed(Err(err)) => {
+                self.error = Some(err);
+                Action::none()
+            }
+            Message::RestoreWalletsListed(Ok(mut existing)) => {
+                let client = self.client.as_ref().unwrap();
+                let mnemonic = self.mnemonic.as_ref().unwrap().join(" ");
+                let options = RestoreOptions {
+                    passphrase: self.passphrase.clone().unwrap_or_default(),
+                    birthday: self.birthday_height().ok().flatten(),
+                    account_path: self.account_path().ok().flatten(),
+                };
+                // additional accounts are restored first so they are opened together
+                // with the default wallet
+                let extra_accounts = self.extra_account_paths().into_iter().map(|(index, path)| {
+                    let label = unused_wallet_label(&format!("account-{}", index), &existing);
+                    existing.push(label.clone());
+                    client
+                        .restore_wallet(
+                            label,
+                            mnemonic.clone(),
+                            RestoreOptions {
+                                account_path: Some(path),
+                                ..options.clone()
+                            },
+                        )
+                        .map(|r| Message::AccountRestored(r.result.map(|_| r.label)))
+                });
+                Action::Task(
+                    Task::batch(extra_accounts).chain(
+                        client
+                            .restore_wallet("default".to_string(), mnemonic, options)
+                            .map(|r| Message::SetWalletResult(r.result.map(|_| r.label))),
+                    ),
+                )
+            }
             Message::ImportWallet => Action::Task(Task::perform(
                 async move {
                     let result = rfd::AsyncFileDialog::new()
@@ -324,6 +1280,39 @@ impl State {
                     Action::none()
                 }
             },
+            Message::ImportCliWallets => Action::Task(
+                self.client
+                    .as_ref()
+                    .unwrap()
+                    .import_cli_wallets()
+                    .map(Message::CliWalletsImported),
+            ),
+            Message::CliWalletsImported(result) => match result {
+                Ok(import) if import.imported.is_empty() && import.existing.is_empty() => {
+                    self.error = Some(if import.skipped.is_empty() {
+                        "spaced has no wallets to import".to_string()
+                    } else {
+                        format!("Could not import {}", import.skipped.join(", "))
+                    });
+                    Action::none()
+                }
+                Ok(import) => {
+                    let labels: Vec<String> =
+                        import.imported.into_iter().chain(import.existing).collect();
+                    let default = "default".to_string();
+                    self.config.wallet = Some(if labels.contains(&default) {
+                        default
+                    } else {
+                        labels[0].clone()
+                    });
+                    self.config.loaded_wallets = labels;
+                    self.finish()
+                }
+                Err(err) => {
+                    self.error = Some(err);
+                    Action::none()
+                }
+            },
             Message::SetWalletResult(result) => match result {
                 Ok(wallet) => {
                     self.config.wallet = Some(wallet);
@@ -335,18 +1324,325 @@ impl State {
                 }
             },
             Message::LogReceived(log) => {
+                crash::record_log(log.clone());
                 self.logs.push(log);
                 Action::Task(Task::none())
             }
+            Message::CheckpointProgress(progress) => {
+                match self.checkpoint_download.as_mut() {
+                    // a resumed download starts over from where it stopped
+                    Some(download) if progress.downloaded >= download.progress.downloaded => {
+                        download.progress = progress
+                    }
+                    _ => {
+                        self.checkpoint_download = Some(CheckpointDownload {
+                            started: std::time::Instant::now(),
+                            start_bytes: progress.downloaded,
+                            progress,
+                        })
+                    }
+                }
+                Action::none()
+            }
         }
     }
 
+    /// Checks the words against the BIP39 checksum once all of them are entered
+    fn is_mnemonic_valid(mnemonic: &[String]) -> bool {
+        use spaces_wallet::bdk_wallet::keys::bip39::Mnemonic;
+        mnemonic.iter().all(|word| !word.is_empty()) && Mnemonic::parse(&mnemonic.join(" ")).is_ok()
+    }
+
+    fn word_input<'a>(&'a self, i: usize, word: &'a str) -> Element<'a, Message> {
+        let is_invalid = !word.is_empty()
+            && (self.active_word != Some(i) || word_suggestions(word).is_empty())
+            && Language::English.find_word(word).is_none();
+        let suggestions = if self.active_word == Some(i) && !word.is_empty() {
+            word_suggestions(word)
+        } else {
+            &[]
+        };
+        column![row![
+            text_monospace(format!("{:02}.", i + 1)).size(30),
+            text_input("", word)
+                .id(word_input_id(i))
+                .on_input(move |w| Message::MnemonicWordInput(i, w))
+                .on_submit(Message::MnemonicWordSubmit(i))
+                .style(move |theme: &Theme, status| {
+                    let mut style = iced::widget::text_input::default(theme, status);
+                    style.border = style.border.rounded(7);
+                    if is_invalid {
+                        style.border.color = theme.extended_palette().danger.base.color;
+                    }
+                    style
+                }),
+        ]
+        .align_y(Center)
+        .spacing(5)]
+        .push_maybe((!suggestions.is_empty() && suggestions != [word]).then(|| {
+            row(suggestions.iter().map(|suggestion| {
+                button(text_monospace(*suggestion).size(14))
+                    .style(button::secondary)
+                    .on_press(Message::MnemonicSuggestionPress(i, suggestion))
+                    .into()
+            }))
+            .spacing(5)
+            .padding(iced::Padding::ZERO.left(45))
+        }))
+        .spacing(5)
+        .into()
+    }
+
+    fn seed_qr_view(&self) -> Element<Message> {
+        let picker = row![pick_list(
+            seed_qr::Format::ALL,
+            self.seed_qr.as_ref().map(|(format, _)| *format),
+            Message::SeedQrSelect
+        )
+        .placeholder("Show as SeedQR")
+        .width(250),]
+        .push_maybe(self.seed_qr.is_some().then(|| {
+            button(text("Hide"))
+                .style(button::text)
+                .on_press(Message::SeedQrHide)
+        }))
+        .spacing(10)
+        .align_y(Center);
+
+        column![picker]
+            .push_maybe(self.seed_qr.as_ref().map(|(_, data)| {
+                row![
+                    iced::widget::qr_code(data).cell_size(5),
+                    text(
+                        "Anyone who sees this code can take the funds. Only show it to \
+                         engrave or scan it on an offline device, make sure nothing is \
+                         recording or sharing the screen and don't take screenshots."
+                    )
+                    .style(text::danger),
+                ]
+                .spacing(20)
+                .align_y(Center)
+            }))
+            .spacing(10)
+            .padding([0, 100])
+            .into()
+    }
+
+    fn word_count_picker(&self) -> Element<Message> {
+        row![
+            text("Words"),
+            pick_list(
+                MNEMONIC_LENGTHS,
+                Some(self.word_count),
+                Message::WordCountSelect
+            )
+            .width(100),
+        ]
+        .spacing(10)
+        .align_y(Center)
+        .into()
+    }
+
+    /// A new wallet needs the passphrase typed twice, a restored one has to match
+    /// whatever was used when it was created
+    fn is_passphrase_valid(&self) -> bool {
+        match &self.passphrase {
+            None => true,
+            Some(passphrase) => {
+                !passphrase.is_empty()
+                    && (self.mnemonic_target.is_none()
+                        || *passphrase == self.passphrase_confirmation)
+            }
+        }
+    }
+
+    fn birthday_height(&self) -> Result<Option<u32>, ()> {
+        if self.birthday.is_empty() || self.mnemonic_target.is_some() {
+            return Ok(None);
+        }
+        let network = self.config.backend.as_ref().unwrap().network();
+        height_from_str(&self.birthday, network).map(Some).ok_or(())
+    }
+
+    /// Custom account level path, only hardened paths are accepted as that's what
+    /// wallets use for accounts
+    fn account_path(&self) -> Result<Option<DerivationPath>, ()> {
+        if self.account_path.is_empty() {
+            return Ok(None);
+        }
+        let path = DerivationPath::from_str(&self.account_path).map_err(|_| ())?;
+        if path.is_empty() || path.into_iter().any(|child| child.is_normal()) {
+            return Err(());
+        }
+        Ok(Some(path))
+    }
+
+    /// Paths of the accounts following the restored one along with their index
+    fn extra_account_paths(&self) -> Vec<(u32, DerivationPath)> {
+        let network = self.config.backend.as_ref().unwrap().network();
+        let path = self.account_path().ok().flatten().unwrap_or_else(|| {
+            let coin_type = if network == ExtendedNetwork::Mainnet {
+                0
+            } else {
+                1
+            };
+            DerivationPath::from_str(&format!("m/86'/{}'/0'", coin_type)).unwrap()
+        });
+        let children: Vec<ChildNumber> = path.into();
+        let Some((ChildNumber::Hardened { index }, parent)) = children.split_last() else {
+            return Vec::new();
+        };
+        (1..=self.extra_accounts as u32)
+            .filter_map(|i| {
+                let child = ChildNumber::from_hardened_idx(index + i).ok()?;
+                let mut path = parent.to_vec();
+                path.push(child);
+                Some((index + i, DerivationPath::from(path)))
+            })
+            .collect()
+    }
+
+    fn advanced_view(&self) -> Element<Message> {
+        let mut col =
+            column![checkbox("Advanced restore options", self.advanced)
+                .on_toggle(Message::AdvancedToggle)]
+            .spacing(10)
+            .padding([0, 100]);
+        if self.advanced {
+            col = col
+                .push(text_bold("Account derivation path"))
+                .push(
+                    text_input("m/86'/0'/0'", &self.account_path)
+                        .on_input(Message::AccountPathInput),
+                )
+                .push(text_small(if self.account_path().is_err() {
+                    "Enter a hardened account path like m/86'/0'/1'"
+                } else {
+                    "For wallets that don't use the default path. Spaces wallets are taproot \
+                     only, coins received on other address types won't be found."
+                }))
+                .push(
+                    row![
+                        text("Additional accounts"),
+                        pick_list(
+                            EXTRA_ACCOUNTS,
+                            Some(self.extra_accounts),
+                            Message::ExtraAccountsSelect
+                        )
+                        .width(100),
+                    ]
+                    .spacing(10)
+                    .align_y(Center),
+                )
+                .push_maybe((self.extra_accounts != 0).then(|| {
+                    text_small(
+                        "Each following account is restored as a separate wallet named after \
+                         its account number.",
+                    )
+                }));
+        }
+        col.into()
+    }
+
+    fn birthday_view(&self) -> Element<Message> {
+        column![
+            text_bold("Wallet birthday (optional)"),
+            text_input("Block height or date (YYYY-MM-DD)", &self.birthday)
+                .on_input(Message::BirthdayInput),
+            text_small(if self.birthday_height().is_err() {
+                "Enter a block height or a date like 2025-01-31"
+            } else {
+                "Skips scanning blocks from before the wallet was created, \
+                 an estimate a bit earlier than the actual date is fine"
+            }),
+        ]
+        .spacing(10)
+        .padding([0, 100])
+        .into()
+    }
+
+    fn passphrase_view(&self) -> Element<Message> {
+        let creating = self.mnemonic_target.is_some();
+        let mut col = column![checkbox(
+            "Protect the wallet with a passphrase (25th word)",
+            self.passphrase.is_some()
+        )
+        .on_toggle(Message::PassphraseToggle)]
+        .spacing(10)
+        .padding([0, 100]);
+        if let Some(passphrase) = &self.passphrase {
+            col = col
+                .push(text(if creating {
+                    "The passphrase is not part of the mnemonic phrase. It's required together \
+                     with the mnemonic to recover this wallet and can't be reset, losing it \
+                     means losing access to the funds."
+                } else {
+                    "Enter the exact passphrase used when the wallet was created. A different \
+                     passphrase restores a different, empty wallet."
+                }))
+                .push(
+                    text_input("Passphrase", passphrase)
+                        .secure(true)
+                        .on_input(Message::PassphraseInput),
+                )
+                .push_maybe(creating.then(|| {
+                    text_input("Confirm passphrase", &self.passphrase_confirmation)
+                        .secure(true)
+                        .on_input(Message::PassphraseConfirmationInput)
+                }));
+        }
+        col.into()
+    }
+
     pub fn view(&self) -> Element<Message> {
         const DESCRIPTION_TEXT_HEIGHT: u16 = 100;
 
-        container(if self.config.backend.is_none() {
+        container(if let Some(path) = &self.restore_backup {
             column![
-                text_big("Select backend"),
+                row![
+                    button_icon(Icon::ChevronLeft)
+                        .style(button::text)
+                        .on_press(Message::RestoreCancelPress),
+                    text_big("Restore app backup"),
+                ]
+                .align_y(Center),
+                column![
+                    text_monospace(path.to_string_lossy()),
+                    text(
+                        "Restores the settings and wallets of the backup on this computer, \
+                         then connects to its backend. Wallets already in spaced are kept."
+                    ),
+                    text_input("Password", &self.restore_password)
+                        .secure(true)
+                        .on_input(Message::RestorePasswordInput)
+                        .on_submit(Message::RestoreConfirmPress),
+                    error_block(self.error.as_ref()),
+                    submit_button(
+                        text(if self.restoring_backup {
+                            "Restoring"
+                        } else {
+                            "Restore"
+                        })
+                        .width(Fill)
+                        .align_x(Center),
+                        (!self.restoring_backup && !self.restore_password.is_empty())
+                            .then_some(Message::RestoreConfirmPress),
+                    ),
+                ]
+                .spacing(10)
+                .padding([0, 100]),
+            ]
+            .spacing(10)
+        } else if self.config.backend.is_none() {
+            column![
+                row![]
+                    .push_maybe(self.cancellable.then(|| {
+                        button_icon(Icon::ChevronLeft)
+                            .style(button::text)
+                            .on_press(Message::Cancel)
+                    }))
+                    .push(text_big("Select backend"))
+                    .align_y(Center),
                 row![
                     column![
                         text_icon(Icon::Bolt).size(150),
@@ -359,11 +1655,38 @@ impl State {
                                 network: ExtendedNetwork::Mainnet,
                                 prune_point: None,
                                 spaced_password: None,
+                                checkpoint_mirrors: Vec::new(),
+                                filters_endpoints: Vec::new(),
+                                serve_peers: None,
+                                prune_refresh_days: None,
+                                spaced_rpc_port: None,
+                                yuki_rpc_port: None,
+                                regtest: Default::default(),
                             }))
                         ),
                     ]
                     .align_x(Center)
                     .spacing(30),
+                    column![
+                        text_icon(Icon::FolderDown).size(150),
+                        text_bold("Installed Bitcoin Core"),
+                        text("Runs the bitcoind installed on this computer as a pruned node, Akron doesn't download it. Full validation with about 20 GB of storage, takes longer to sync.")
+                        .height(DESCRIPTION_TEXT_HEIGHT),
+                        submit_button(
+                            text("Start").width(Fill).align_x(Center),
+                            Some(Message::BackendSet(ConfigBackend::ManagedBitcoind {
+                                network: ExtendedNetwork::Mainnet,
+                                rpc_password: None,
+                                spaced_password: None,
+                            }))
+                        ).style(|theme: &Theme, status: button::Status| {
+                            let mut style = button::secondary(theme, status);
+                            style.border = style.border.rounded(7);
+                            style
+                        }),
+                    ]
+                    .align_x(Center)
+                    .spacing(30),
                     column![
                         text_icon(Icon::Bitcoin).size(150),
                         text_bold("Full Node"),
@@ -376,6 +1699,7 @@ impl State {
                                 url: "http://127.0.0.1:8332".to_string(),
                                 user: String::new(),
                                 password: String::new(),
+                                cookie: None,
                                 spaced_password: None,
                             }))
                         ).style(|theme: &Theme, status: button::Status| {
@@ -396,8 +1720,12 @@ impl State {
                             Some(Message::BackendSet(ConfigBackend::Spaced {
                                 network: ExtendedNetwork::Mainnet,
                                 url: "http://127.0.0.1:7225".to_string(),
+                                fallback_urls: Vec::new(),
                                 user: String::new(),
                                 password: String::new(),
+                                ca_cert: None,
+                                cert_fingerprint: None,
+                                timeouts: Default::default(),
                             }))
                         ).style(|theme: &Theme, status: button::Status| {
                             let mut style = button::secondary(theme, status);
@@ -407,8 +1735,56 @@ impl State {
                     ]
                     .align_x(Center)
                     .spacing(30),
-                ].align_y(Bottom).padding([0, 80]).spacing(80)
+                ].align_y(Bottom).padding([0, 40]).spacing(40)
             ]
+            .push_maybe(
+                (self.config.data_dir_choice && self.config.is_new()).then(|| {
+                    column![
+                        row![
+                            text_bold("Data folder: "),
+                            text_monospace(self.config.data_dir().to_string_lossy()).width(Fill),
+                            button(text("Change").size(14))
+                                .style(button::text)
+                                .on_press(Message::DataDirPick),
+                        ]
+                        .align_y(Center),
+                        error_block(self.error.as_ref()),
+                    ]
+                    .spacing(10)
+                    .padding([20, 40])
+                }),
+            )
+            .push_maybe((!self.detected.is_empty()).then(|| {
+                column![text_bold("Found on this computer")]
+                    .extend(self.detected.iter().map(|detected| {
+                        row![
+                            text(&detected.label).width(Fill),
+                            submit_button(
+                                text("Use").align_x(Center),
+                                Some(Message::BackendSet(detected.backend.clone())),
+                            )
+                            .width(Shrink),
+                        ]
+                        .spacing(20)
+                        .align_y(Center)
+                        .into()
+                    }))
+                    .spacing(10)
+                    .padding([20, 40])
+            }))
+            .push(
+                row![
+                    text("Moving Akron from another computer?").width(Fill),
+                    submit_button(
+                        text("Restore app backup").align_x(Center),
+                        Some(Message::RestoreBackupPick),
+                    )
+                    .width(Shrink),
+                ]
+                .spacing(20)
+                .align_y(Center)
+                .padding([20, 40]),
+            )
             .spacing(10)
         } else if self.connecting {
             column![
@@ -419,6 +1795,13 @@ impl State {
                     text_big("Connecting"),
                 ]
                 .align_y(Center),
+            ]
+            .push_maybe(
+                self.checkpoint_download
+                    .as_ref()
+                    .map(|download| container(download.view()).padding([0, 10])),
+            )
+            .push(
                 container(
                     scrollable(column(
                         self.logs
@@ -438,7 +1821,7 @@ impl State {
                 .padding(10)
                 .height(Fill)
                 .width(Fill),
-            ]
+            )
         } else if self.client.is_none() {
             column![
                 row![
@@ -449,6 +1832,11 @@ impl State {
                 ]
                 .align_y(Center),
                 error_block(self.error.as_ref()),
+                Column::new().push_maybe(
+                    self.test_result
+                        .as_ref()
+                        .map(|summary| text(summary).center().width(Fill))
+                ),
                 {
                     let networks = [
                         ExtendedNetwork::Mainnet,
@@ -456,10 +1844,66 @@ impl State {
                         ExtendedNetwork::Regtest,
                     ];
                     match self.config.backend.as_ref().unwrap() {
-                        ConfigBackend::Akrond { network, .. } => base_container(
-                            Form::new("Connect", Some(Message::Connect)).add_pick_list(
+                        ConfigBackend::Akrond {
+                            network, regtest, ..
+                        } => {
+                            let form = Form::new("Connect", Some(Message::Connect)).add_pick_list(
+                                "Chain",
+                                [
+                                    ExtendedNetwork::Mainnet,
+                                    ExtendedNetwork::Testnet4,
+                                    ExtendedNetwork::Regtest,
+                                ],
+                                Some(network),
+                                Message::NetworkSelect,
+                            )
+                            .add_text_input(
+                                "Spaced RPC port",
+                                "default",
+                                &self.spaced_port,
+                                Message::SpacedPortInput,
+                            )
+                            .add_text_input(
+                                "Yuki RPC port",
+                                "default",
+                                &self.yuki_port,
+                                Message::YukiPortInput,
+                            );
+                            base_container(if *network == ExtendedNetwork::Regtest {
+                                form.add_text_input(
+                                    "Data directory",
+                                    "default",
+                                    regtest.data_dir.as_deref().unwrap_or_default(),
+                                    Message::RegtestDataDirInput,
+                                )
+                            } else if *network == ExtendedNetwork::Mainnet {
+                                form.add_text_input(
+                                    "Checkpoint mirrors to compare with, comma separated",
+                                    "none",
+                                    &self.checkpoint_mirrors,
+                                    Message::CheckpointMirrorsInput,
+                                )
+                                .add_text_input(
+                                    "Compact filters endpoints, comma separated. The fastest \
+                                     one is chosen at every start",
+                                    "https://checkpoint.akron.io/",
+                                    &self.filters_endpoints,
+                                    Message::FiltersEndpointsInput,
+                                )
+                                .add_text_input(
+                                    "Serve checkpoint and filters to peers on",
+                                    "off, e.g. 127.0.0.1:7226",
+                                    &self.serve_peers,
+                                    Message::ServePeersInput,
+                                )
+                            } else {
+                                form
+                            })
+                        }
+                        ConfigBackend::ManagedBitcoind { network, .. } => base_container(
+                            Form::new("Start", Some(Message::Connect)).add_pick_list(
                                 "Chain",
-                                [ExtendedNetwork::Mainnet, ExtendedNetwork::Testnet4],
+                                networks,
                                 Some(network),
                                 Message::NetworkSelect,
                             )),
@@ -468,39 +1912,72 @@ impl State {
                             url,
                             user,
                             password,
+                            cookie,
                             spaced_password: _,
-                        } => base_container(Form::new("Connect", Some(Message::Connect))
-                            .add_text_input(
-                                "Bitcoind JSON-RPC URL",
-                                "http://127.0.0.1:7225",
-                                url,
-                                Message::UrlInput,
-                            )
-                            .add_text_input("User login", "none", user, Message::UserInput)
-                            .add_text_input(
-                                "User password",
-                                "none",
-                                password,
-                                Message::PasswordInput,
-                            )
-                            .add_pick_list(
+                        } => {
+                            let form = Form::new("Connect", Some(Message::Connect))
+                                .secondary_button("Test", self.test_message())
+                                .add_text_input(
+                                    "Bitcoind JSON-RPC URL",
+                                    "http://127.0.0.1:7225",
+                                    url,
+                                    Message::UrlInput,
+                                )
+                                .add_pick_list(
+                                    "Authentication",
+                                    [BitcoindAuth::Password, BitcoindAuth::Cookie],
+                                    Some(if cookie.is_some() {
+                                        BitcoindAuth::Cookie
+                                    } else {
+                                        BitcoindAuth::Password
+                                    }),
+                                    Message::AuthSelect,
+                                );
+                            let form = match cookie {
+                                Some(cookie) => form.add_text_button(
+                                    "Cookie file",
+                                    "Choose the .cookie file in the bitcoind data directory",
+                                    cookie,
+                                    Message::CookiePick,
+                                ),
+                                None => form
+                                    .add_text_input("User login", "none", user, Message::UserInput)
+                                    .add_text_input(
+                                        "User password",
+                                        "none",
+                                        password,
+                                        Message::PasswordInput,
+                                    ),
+                            };
+                            base_container(form.add_pick_list(
                                 "Chain",
                                 networks,
                                 Some(network),
                                 Message::NetworkSelect,
-                            )),
+                            ))
+                        }
                         ConfigBackend::Spaced {
                             network,
                             url,
                             user,
                             password,
+                            ca_cert,
+                            cert_fingerprint,
+                            ..
                         } => base_container(Form::new("Connect", Some(Message::Connect))
+                            .secondary_button("Test", self.test_message())
                             .add_text_input(
                                 "Spaced JSON-RPC URL",
                                 "http://127.0.0.1:8332",
                                 url,
                                 Message::UrlInput,
                             )
+                            .add_text_input(
+                                "Fallback URLs, comma separated",
+                                "none",
+                                &self.fallback_urls,
+                                Message::FallbackUrlsInput,
+                            )
                             .add_text_input("User login", "none", user, Message::UserInput)
                             .add_text_input(
                                 "User password",
@@ -508,6 +1985,30 @@ impl State {
                                 password,
                                 Message::PasswordInput,
                             )
+                            .add_text_button(
+                                "CA certificate for https",
+                                "Public certificate authorities",
+                                ca_cert.as_deref().unwrap_or_default(),
+                                Message::CaCertPick,
+                            )
+                            .add_text_input(
+                                "Pinned certificate SHA-256 fingerprint",
+                                "none",
+                                cert_fingerprint.as_deref().unwrap_or_default(),
+                                Message::CertFingerprintInput,
+                            )
+                            .add_text_input(
+                                "Connect timeout, seconds",
+                                "2 for a local node, 15 remote",
+                                &self.connect_timeout,
+                                Message::ConnectTimeoutInput,
+                            )
+                            .add_text_input(
+                                "Request timeout, seconds",
+                                "60 for a local node, 120 remote",
+                                &self.request_timeout,
+                                Message::RequestTimeoutInput,
+                            )
                             .add_pick_list(
                                 "Chain",
                                 networks,
@@ -524,23 +2025,64 @@ impl State {
                     button_icon(Icon::ChevronLeft)
                         .style(button::text)
                         .on_press(Message::MnemonicClear),
-                    text_big("Enter the mnemonic phrase"),
+                    text_big(if self.mnemonic_target.is_some() {
+                        "Verify the mnemonic phrase"
+                    } else {
+                        "Enter the mnemonic phrase"
+                    }),
+                    horizontal_space(),
                 ]
+                .push_maybe(self.mnemonic_target.is_none().then(|| self.word_count_picker()))
                 .align_y(Center),
-                error_block(self.error.as_ref()),
-                row![
+                error_block(
+                    self.error.as_deref().or(
+                        if !mnemonic.iter().all(|word| !word.is_empty()) {
+                            None
+                        } else if self.mnemonic_target.as_ref().is_some_and(|target| target != mnemonic) {
+                            Some("The words don't match the mnemonic phrase, go back to check it")
+                        } else if !Self::is_mnemonic_valid(mnemonic) {
+                            Some("Invalid mnemonic phrase, check the words and their order")
+                        } else {
+                            None
+                        }
+                    )
+                ),
+                Row::new().push_maybe(self.mnemonic_target.is_none().then(|| {
+                    row![
+                        text_input("Paste the content of a SeedQR", &self.seed_qr_input)
+                            .secure(true)
+                            .on_input(Message::SeedQrInput)
+                            .on_submit(Message::SeedQrImport),
+                        submit_button(
+                            text("Import SeedQR").align_x(Center),
+                            (!self.seed_qr_input.is_empty()).then_some(Message::SeedQrImport),
+                        )
+                        .width(Shrink),
+                    ]
+                    .spacing(10)
+                    .padding([0, 100])
+                })),
+                if self.mnemonic_target.is_some() {
+                    Element::from(
+                        column![
+                            text("Enter the following words from the phrase you wrote down"),
+                            Column::with_children(
+                                self.quiz.iter().map(|i| self.word_input(*i, &mnemonic[*i]))
+                            )
+                            .spacing(10),
+                        ]
+                        .spacing(30)
+                        .padding([30, 100])
+                        .height(Fill),
+                    )
+                } else {
+                    scrollable(row![
                     Column::with_children(
                         mnemonic
                             .iter()
                             .enumerate()
                             .step_by(2)
-                            .map(|(i, word)| {
-                                row![
-                                    text_monospace(format!("{:02}.", i + 1)).size(30),
-                                    text_input("", word)
-                                        .on_input(move |w| Message::MnemonicWordInput(i, w))
-                                ].align_y(Center).spacing(5).into()
-                            })
+                            .map(|(i, word)| self.word_input(i, word))
                     ).spacing(10),
                     horizontal_space(),
                     Column::with_children(
@@ -549,18 +2091,20 @@ impl State {
                             .enumerate()
                             .skip(1)
                             .step_by(2)
-                            .map(|(i, word)| {
-                                row![
-                                    text_monospace(format!("{:02}.", i + 1)).size(30),
-                                    text_input("", word)
-                                        .on_input(move |w| Message::MnemonicWordInput(i, w))
-                                ].align_y(Center).spacing(5).into()
-                            })
+                            .map(|(i, word)| self.word_input(i, word))
                     ).spacing(10),
-                ].padding([30, 100]).spacing(40),
+                ].padding([30, 100]).spacing(40)).height(Fill).into()
+                },
+                self.passphrase_view(),
+                Column::new().push_maybe(self.mnemonic_target.is_none().then(|| self.birthday_view())),
+                Column::new().push_maybe(self.mnemonic_target.is_none().then(|| self.advanced_view())),
                 submit_button(
                     text("Continue").width(Fill).align_x(Center),
-                    if mnemonic.iter().all(|word| !word.is_empty()) && self.mnemonic_target.as_ref().is_none_or(|target| target == mnemonic) {
+                    if Self::is_mnemonic_valid(mnemonic)
+                        && self.birthday_height().is_ok()
+                        && self.account_path().is_ok()
+                        && self.mnemonic_target.as_ref().is_none_or(|target| target == mnemonic)
+                        && self.is_passphrase_valid() {
                         Some(Message::RestoreWallet)
                     } else {
                         None
@@ -575,9 +2119,11 @@ impl State {
                         .style(button::text)
                         .on_press(Message::MnemonicClear),
                     text_big("Write down the mnemonic phrase"),
+                    horizontal_space(),
+                    self.word_count_picker(),
                 ]
                 .align_y(Center),
-                row![
+                scrollable(row![
                     Column::with_children(
                         mnemonic
                             .iter()
@@ -604,7 +2150,9 @@ impl State {
                                 ].align_y(Center).spacing(5).into()
                             })
                     ).spacing(10),
-                ].padding([30, 100]).spacing(40),
+                ].padding([30, 100]).spacing(40)).height(Fill),
+                error_block(self.error.as_ref()),
+                self.seed_qr_view(),
                 submit_button(
                     text("Continue").width(Fill).align_x(Center),
                     Some(Message::MnemonicBlank),
@@ -645,6 +2193,19 @@ impl State {
                     .spacing(30),
                 ].align_y(Bottom).padding([0, 80]).spacing(80)
             ]
+            .push_maybe(self.spaced_cli_wallets.then(|| {
+                row![
+                    text("Found wallets of spaced run from the command line").width(Fill),
+                    submit_button(
+                        text("Import them").align_x(Center),
+                        Some(Message::ImportCliWallets),
+                    )
+                    .width(Shrink),
+                ]
+                .spacing(20)
+                .align_y(Center)
+                .padding([40, 80])
+            }))
             .spacing(10)
         })
         .padding([60, 100])
@@ -652,10 +2213,20 @@ impl State {
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
-        if let Some(client) = self.client.as_ref() {
-            client.logs_subscription().map(Message::LogReceived)
-        } else {
-            Subscription::none()
-        }
+        let logs = match self.client.as_ref() {
+            Some(client) => client
+                .logs_subscription()
+                .map(|line| Message::LogReceived(line.to_string())),
+            None => Subscription::none(),
+        };
+        let checkpoint_progress = match self.checkpoint_progress.as_ref() {
+            Some((id, progress)) => Subscription::run_with_id(
+                *id,
+                BroadcastStream::new(progress.subscribe()).filter_map(|result| result.ok()),
+            )
+            .map(Message::CheckpointProgress),
+            None => Subscription::none(),
+        };
+        Subscription::batch([logs, checkpoint_progress])
     }
 }
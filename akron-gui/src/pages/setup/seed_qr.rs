@@ -0,0 +1,75 @@
+use spaces_wallet::bdk_wallet::keys::bip39::{Language, Mnemonic};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Standard,
+    Compact,
+}
+
+impl Format {
+    pub const ALL: [Self; 2] = [Self::Standard, Self::Compact];
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Standard => write!(f, "Standard SeedQR"),
+            Self::Compact => write!(f, "Compact SeedQR"),
+        }
+    }
+}
+
+/// Encodes the mnemonic as SeedQR payload, Standard uses the 4 digit word indexes
+/// and Compact the raw entropy which is only defined for 12 and 24 words
+pub fn encode(words: &[String], format: Format) -> Result<Vec<u8>, String> {
+    let mnemonic = Mnemonic::parse(&words.join(" ")).map_err(|e| e.to_string())?;
+    match format {
+        Format::Standard => Ok(words
+            .iter()
+            .map(|word| {
+                Language::English
+                    .find_word(word)
+                    .map(|index| format!("{:04}", index))
+                    .ok_or_else(|| format!("Invalid word: {}", word))
+            })
+            .collect::<Result<String, _>>()?
+            .into_bytes()),
+        Format::Compact => {
+            if words.len() != 12 && words.len() != 24 {
+                return Err("Compact SeedQR is only defined for 12 and 24 words".to_string());
+            }
+            Ok(mnemonic.to_entropy())
+        }
+    }
+}
+
+/// Decodes the content read from a SeedQR, Standard as digits or Compact as hex
+pub fn decode(content: &str) -> Result<Vec<String>, String> {
+    let content = content.trim();
+    let mnemonic = if content.chars().all(|c| c.is_ascii_digit()) {
+        if !content.len().is_multiple_of(4) {
+            return Err("Invalid SeedQR length".to_string());
+        }
+        let word_list = Language::English.word_list();
+        let words = content
+            .as_bytes()
+            .chunks(4)
+            .map(|chunk| {
+                std::str::from_utf8(chunk)
+                    .ok()
+                    .and_then(|digits| digits.parse::<usize>().ok())
+                    .and_then(|index| word_list.get(index))
+                    .copied()
+                    .ok_or("Invalid word index in SeedQR")
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Mnemonic::parse(&words.join(" ")).map_err(|e| e.to_string())?
+    } else {
+        let entropy = hex::decode(content).map_err(|_| "Unrecognized SeedQR content")?;
+        if entropy.len() != 16 && entropy.len() != 32 {
+            return Err("Invalid Compact SeedQR length".to_string());
+        }
+        Mnemonic::from_entropy(&entropy).map_err(|e| e.to_string())?
+    };
+    Ok(mnemonic.words().map(|word| word.to_string()).collect())
+}
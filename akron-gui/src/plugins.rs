@@ -0,0 +1,107 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Folder in the data directory plugins are loaded from. Each plugin is a folder with a
+/// `plugin.json` manifest and whatever its command needs
+pub const PLUGINS_DIR: &str = "plugins";
+
+const MANIFEST_FILE: &str = "plugin.json";
+
+const CALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    /// Program and its arguments, run from the plugin folder. Each call starts it with one
+    /// JSON line `{"method": .., "params": {..}}` on stdin and reads `{"result": ..}` or
+    /// `{"error": ".."}` from stdout
+    pub command: Vec<String>,
+    /// Title of the sidebar screen the plugin adds, filled by the `screen` method with a
+    /// list of lines
+    #[serde(default)]
+    pub screen: Option<String>,
+    /// Whether the `decorate` method labels transactions, answering `{"<txid>": "label"}`
+    #[serde(default)]
+    pub decorate_transactions: bool,
+    /// Events passed to the `hook` method, `block` and `transaction`
+    #[serde(default)]
+    pub hooks: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    dir: PathBuf,
+    pub manifest: Manifest,
+}
+
+/// Plugins found in the plugins folder, the ones with an invalid manifest are skipped
+pub fn load(data_dir: &Path) -> Vec<Plugin> {
+    let Ok(entries) = std::fs::read_dir(data_dir.join(PLUGINS_DIR)) else {
+        return Vec::new();
+    };
+    let mut plugins: Vec<Plugin> = entries
+        .filter_map(|entry| {
+            let dir = entry.ok()?.path();
+            let manifest = std::fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+            match serde_json::from_str::<Manifest>(&manifest) {
+                Ok(manifest) if !manifest.command.is_empty() => Some(Plugin { dir, manifest }),
+                _ => {
+                    eprintln!("Skipping plugin with invalid manifest in {}", dir.display());
+                    None
+                }
+            }
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+    plugins
+}
+
+impl Plugin {
+    pub fn has_hook(&self, event: &str) -> bool {
+        self.manifest.hooks.iter().any(|hook| hook == event)
+    }
+
+    pub async fn call(self, method: &'static str, params: Value) -> Result<Value, String> {
+        let name = self.manifest.name.clone();
+        tokio::time::timeout(CALL_TIMEOUT, self.run(method, params))
+            .await
+            .map_err(|_| format!("Plugin {} timed out", name))?
+    }
+
+    async fn run(self, method: &'static str, params: Value) -> Result<Value, String> {
+        let mut child = tokio::process::Command::new(&self.manifest.command[0])
+            .args(&self.manifest.command[1..])
+            .current_dir(&self.dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Could not start plugin {}: {}", self.manifest.name, e))?;
+        let request = json!({ "method": method, "params": params });
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(format!("{}\n", request).as_bytes())
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        let mut output = String::new();
+        if let Some(mut stdout) = child.stdout.take() {
+            stdout
+                .read_to_string(&mut output)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        let _ = child.wait().await;
+        let response: Value = serde_json::from_str(&output)
+            .map_err(|e| format!("Invalid response from plugin {}: {}", self.manifest.name, e))?;
+        match response.get("error") {
+            Some(err) => Err(err.as_str().map_or_else(|| err.to_string(), str::to_string)),
+            None => Ok(response.get("result").cloned().unwrap_or_default()),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+
+/// Proxy URL used for requests to third party services
+static WEB_PROXY: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn set_web_proxy(url: Option<String>) {
+    *WEB_PROXY.lock().unwrap() = url;
+}
+
+/// Address of the SOCKS5 proxy for third party services, when set
+pub(crate) fn web_proxy_addr() -> Option<String> {
+    let proxy = WEB_PROXY.lock().unwrap().clone()?;
+    Some(
+        proxy
+            .strip_prefix("socks5h://")
+            .unwrap_or(&proxy)
+            .to_string(),
+    )
+}
+
+/// HTTP client for fee rates, listings and callbacks, routed through the proxy when set
+pub fn http_client() -> reqwest::Client {
+    let proxy = WEB_PROXY.lock().unwrap().clone();
+    proxy
+        .and_then(|url| reqwest::Proxy::all(url).ok())
+        .and_then(|proxy| reqwest::Client::builder().proxy(proxy).build().ok())
+        .unwrap_or_default()
+}
+
+/// Opens a connection to `host:port` through a SOCKS5 proxy, the host is resolved by the
+/// proxy so onion addresses work too
+pub(crate) async fn socks5_connect(proxy: &str, host: &str, port: u16) -> io::Result<TcpStream> {
+    let host_len = u8::try_from(host.len()).map_err(|_| io::Error::other("Host is too long"))?;
+    let mut stream = TcpStream::connect(proxy).await?;
+
+    // no authentication
+    stream.write_all(&[5, 1, 0]).await?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    if reply != [5, 0] {
+        return Err(io::Error::other("Proxy requires authentication"));
+    }
+
+    let mut request = vec![5, 1, 0, 3, host_len];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[1] != 0 {
+        return Err(io::Error::other(format!(
+            "Proxy connection failed with code {}",
+            reply[1]
+        )));
+    }
+    let address_len = match reply[3] {
+        1 => 4,
+        4 => 16,
+        3 => stream.read_u8().await? as usize,
+        _ => return Err(io::Error::other("Invalid proxy reply")),
+    };
+    let mut bound_address = vec![0u8; address_len + 2];
+    stream.read_exact(&mut bound_address).await?;
+    Ok(stream)
+}
+
+/// Listens on a local port and tunnels every connection to `host:port` through the proxy,
+/// for clients that can't use a proxy themselves. Stops on `shutdown`
+pub async fn forward(
+    proxy: String,
+    host: String,
+    port: u16,
+    mut shutdown: broadcast::Receiver<()>,
+) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                Ok((mut inbound, _)) = listener.accept() => {
+                    let (proxy, host) = (proxy.clone(), host.clone());
+                    tokio::spawn(async move {
+                        if let Ok(mut outbound) = socks5_connect(&proxy, &host, port).await {
+                            _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+                        }
+                    });
+                }
+                _ = shutdown.recv() => return,
+            }
+        }
+    });
+    Ok(addr)
+}
@@ -0,0 +1,236 @@
+use iced::futures::{SinkExt, Stream};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio::sync::oneshot;
+
+use crate::automation;
+use crate::nostr::NostrEvent;
+
+/// Local address external apps connect to, each connection sends one JSON line
+/// `{"token": "..", "space": "@name", "event": {..}, "callback": "https://.."}` and
+/// receives one JSON line with either `{"event": {..}}` or `{"error": ".."}`
+pub const SIGN_REQUEST_ADDR: &str = "127.0.0.1:7229";
+
+/// File in the data directory holding the token requests over the socket need, a new
+/// one is written on every start
+pub const SIGN_TOKEN_FILE: &str = "sign_request.token";
+
+/// Requests waiting for the user at once, more are refused so an app can't bury the
+/// user in prompts
+const MAX_PENDING_REQUESTS: usize = 3;
+
+/// `akron://sign?space=@name&event=<url encoded JSON>&callback=<url encoded URL>`
+pub const DEEP_LINK_PREFIX: &str = "akron://sign?";
+
+const MAX_REQUEST_SIZE: u64 = 64 * 1024;
+
+/// Request received from a deep link while no instance was running
+static STARTUP_REQUEST: Mutex<Option<Value>> = Mutex::new(None);
+
+type Reply = Arc<Mutex<Option<oneshot::Sender<Result<NostrEvent, String>>>>>;
+
+#[derive(Debug, Clone)]
+pub struct SignRequest {
+    pub space: String,
+    pub event: NostrEvent,
+    pub callback: Option<String>,
+    reply: Reply,
+}
+
+impl SignRequest {
+    fn from_value(value: &Value, reply: Reply) -> Result<Self, String> {
+        let space = value
+            .get("space")
+            .and_then(Value::as_str)
+            .ok_or("Missing space")?
+            .to_string();
+        let event = serde_json::from_value(value.get("event").ok_or("Missing event")?.clone())
+            .map_err(|e| format!("Invalid event: {}", e))?;
+        let callback = value
+            .get("callback")
+            .and_then(Value::as_str)
+            .map(|s| s.to_string());
+        if callback
+            .as_ref()
+            .is_some_and(|c| !c.starts_with("https://") && !c.starts_with("http://"))
+        {
+            return Err("Invalid callback URL".to_string());
+        }
+        Ok(Self {
+            space,
+            event,
+            callback,
+            reply,
+        })
+    }
+
+    /// Sends the outcome back to the requesting app
+    pub async fn respond(self, result: Result<NostrEvent, String>) {
+        if let Some(callback) = &self.callback {
+            let body = match &result {
+                Ok(event) => json!({ "event": event }),
+                Err(err) => json!({ "error": err }),
+            };
+            let _ = crate::proxy::http_client()
+                .post(callback)
+                .header("Content-Type", "application/json")
+                .body(body.to_string())
+                .send()
+                .await;
+        }
+        if let Some(reply) = self.reply.lock().unwrap().take() {
+            let _ = reply.send(result);
+        }
+    }
+}
+
+fn percent_decode(s: &str) -> Result<String, String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'%' => {
+                let hex = [
+                    iter.next().ok_or("Invalid escape")?,
+                    iter.next().ok_or("Invalid escape")?,
+                ];
+                let hex = std::str::from_utf8(&hex).map_err(|e| e.to_string())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|e| e.to_string())?);
+            }
+            b'+' => bytes.push(b' '),
+            b => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+/// Converts a deep link to the JSON request sent over the local socket
+pub fn parse_deep_link(link: &str) -> Result<Value, String> {
+    let query = link
+        .strip_prefix(DEEP_LINK_PREFIX)
+        .ok_or("Unsupported link")?;
+    let mut request = serde_json::Map::new();
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(value)?;
+        match key {
+            "space" | "callback" => {
+                request.insert(key.to_string(), Value::String(value));
+            }
+            "event" => {
+                let event: Value =
+                    serde_json::from_str(&value).map_err(|e| format!("Invalid event: {}", e))?;
+                request.insert(key.to_string(), event);
+            }
+            _ => {}
+        }
+    }
+    let request = Value::Object(request);
+    SignRequest::from_value(&request, Default::default())?;
+    Ok(request)
+}
+
+/// Hands the request to an already running instance with the data directory, fails if
+/// there is none
+pub fn forward(data_dir: &Path, request: &Value) -> std::io::Result<()> {
+    let token = std::fs::read_to_string(data_dir.join(SIGN_TOKEN_FILE))?;
+    let mut request = request.clone();
+    request["token"] = Value::String(token.trim().to_string());
+    let mut stream = std::net::TcpStream::connect(SIGN_REQUEST_ADDR)?;
+    // the outcome goes to the callback, there's no need to wait for it
+    writeln!(stream, "{}", request)
+}
+
+/// Keeps the request until the app is ready to show it
+pub fn set_startup_request(request: Value) {
+    *STARTUP_REQUEST.lock().unwrap() = Some(request);
+}
+
+pub fn listen(data_dir: PathBuf) -> impl Stream<Item = SignRequest> {
+    iced::stream::channel(10, |mut output| async move {
+        let startup = STARTUP_REQUEST.lock().unwrap().take();
+        if let Some(Ok(request)) = startup.map(|r| SignRequest::from_value(&r, Default::default()))
+        {
+            let _ = output.send(request).await;
+        }
+
+        let Ok(listener) = tokio::net::TcpListener::bind(SIGN_REQUEST_ADDR).await else {
+            return;
+        };
+        let token_path = data_dir.join(SIGN_TOKEN_FILE);
+        let token = match automation::write_token(&token_path) {
+            Ok(token) => Arc::new(token),
+            Err(e) => {
+                eprintln!("Could not write {}: {}", token_path.display(), e);
+                return;
+            }
+        };
+        let pending = Arc::new(AtomicUsize::new(0));
+        while let Ok((stream, _)) = listener.accept().await {
+            let output = output.clone();
+            tokio::spawn(handle_connection(
+                stream,
+                output,
+                token.clone(),
+                pending.clone(),
+            ));
+        }
+    })
+}
+
+async fn handle_connection(
+    stream: tokio::net::TcpStream,
+    mut output: iced::futures::channel::mpsc::Sender<SignRequest>,
+    token: Arc<String>,
+    pending: Arc<AtomicUsize>,
+) {
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    let mut reader =
+        tokio::io::BufReader::new(tokio::io::AsyncReadExt::take(reader, MAX_REQUEST_SIZE));
+    if reader.read_line(&mut line).await.is_err() {
+        return;
+    }
+
+    let (sender, receiver) = oneshot::channel();
+    let result = serde_json::from_str::<Value>(&line)
+        .map_err(|e| e.to_string())
+        .and_then(|value| {
+            let authorized = value
+                .get("token")
+                .and_then(Value::as_str)
+                .is_some_and(|given| automation::token_matches(given, &token));
+            if !authorized {
+                return Err("Unauthorized".to_string());
+            }
+            SignRequest::from_value(&value, Arc::new(Mutex::new(Some(sender))))
+        });
+    let response = match result {
+        Ok(_) if pending.fetch_add(1, Ordering::SeqCst) >= MAX_PENDING_REQUESTS => {
+            pending.fetch_sub(1, Ordering::SeqCst);
+            json!({ "error": "Too many pending requests" })
+        }
+        Ok(request) => {
+            let response = if output.send(request).await.is_err() {
+                None
+            } else {
+                Some(match receiver.await {
+                    Ok(Ok(event)) => json!({ "event": event }),
+                    Ok(Err(err)) => json!({ "error": err }),
+                    Err(_) => json!({ "error": "Request dropped" }),
+                })
+            };
+            pending.fetch_sub(1, Ordering::SeqCst);
+            let Some(response) = response else {
+                return;
+            };
+            response
+        }
+        Err(err) => json!({ "error": err }),
+    };
+    let _ = writer.write_all(format!("{}\n", response).as_bytes()).await;
+}
@@ -0,0 +1,168 @@
+use crate::proxy;
+use spaces_wallet::bitcoin::hashes::{sha256, Hash};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_rustls::rustls::{
+    self,
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use tokio_rustls::TlsConnector;
+
+/// Accepts only the certificate with the pinned SHA-256 fingerprint, for self-signed nodes
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    fingerprint: [u8; 32],
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if sha256::Hash::hash(end_entity).to_byte_array() == self.fingerprint {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(
+                "Certificate doesn't match the pinned fingerprint".to_string(),
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Parses a SHA-256 certificate fingerprint, colons as printed by openssl are allowed
+pub fn parse_fingerprint(fingerprint: &str) -> Option<[u8; 32]> {
+    hex::decode(fingerprint.trim().replace(':', ""))
+        .ok()?
+        .try_into()
+        .ok()
+}
+
+/// TLS settings for a remote node: a pinned certificate if given, otherwise the CA from
+/// `ca_cert` or the public web roots
+pub fn client_config(
+    ca_cert: Option<&str>,
+    fingerprint: Option<&str>,
+) -> Result<Arc<ClientConfig>, String> {
+    let provider = Arc::new(ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?;
+    let config = if let Some(fingerprint) = fingerprint {
+        let fingerprint = parse_fingerprint(fingerprint)
+            .ok_or_else(|| "Invalid certificate fingerprint".to_string())?;
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(PinnedCertVerifier {
+                fingerprint,
+                provider,
+            }))
+            .with_no_client_auth()
+    } else {
+        let mut roots = RootCertStore::empty();
+        match ca_cert {
+            Some(path) => {
+                let pem = std::fs::read(path)
+                    .map_err(|e| format!("Could not read the CA certificate: {}", e))?;
+                for cert in rustls_pemfile::certs(&mut pem.as_slice()) {
+                    let cert = cert.map_err(|e| format!("Invalid CA certificate: {}", e))?;
+                    roots
+                        .add(cert)
+                        .map_err(|e| format!("Invalid CA certificate: {}", e))?;
+                }
+                if roots.is_empty() {
+                    return Err("No certificate found in the CA file".to_string());
+                }
+            }
+            None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+        }
+        builder.with_root_certificates(roots).with_no_client_auth()
+    };
+    Ok(Arc::new(config))
+}
+
+/// Listens on a local port and wraps every connection in TLS to `host:port`, optionally
+/// through a SOCKS5 proxy, for clients that can't take custom TLS settings. Requests keep
+/// the local address as their `Host`, the server name is only sent in the handshake.
+/// Stops on `shutdown`
+pub async fn forward(
+    config: Arc<ClientConfig>,
+    proxy: Option<String>,
+    host: String,
+    port: u16,
+    mut shutdown: broadcast::Receiver<()>,
+) -> io::Result<SocketAddr> {
+    let server_name =
+        ServerName::try_from(host.clone()).map_err(|_| io::Error::other("Invalid server name"))?;
+    let connector = TlsConnector::from(config);
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                Ok((mut inbound, _)) = listener.accept() => {
+                    let (connector, server_name) = (connector.clone(), server_name.clone());
+                    let (proxy, host) = (proxy.clone(), host.clone());
+                    tokio::spawn(async move {
+                        let stream = match proxy {
+                            Some(proxy) => proxy::socks5_connect(&proxy, &host, port).await,
+                            None => TcpStream::connect((host.as_str(), port)).await,
+                        };
+                        let Ok(stream) = stream else {
+                            return;
+                        };
+                        if let Ok(mut outbound) = connector.connect(server_name, stream).await {
+                            _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+                        }
+                    });
+                }
+                _ = shutdown.recv() => return,
+            }
+        }
+    });
+    Ok(addr)
+}
@@ -0,0 +1,53 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::write_atomic;
+
+/// Where the app was left, restored on the next launch. Kept apart from the config
+/// since it changes on every close and isn't worth a backup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UiState {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(default)]
+    pub window: Option<WindowGeometry>,
+    /// Screen of the first session, by its name
+    #[serde(default)]
+    pub screen: Option<String>,
+    #[serde(default)]
+    pub logs_expanded: bool,
+}
+
+/// Size and position of the window in logical pixels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub width: f32,
+    pub height: f32,
+    /// Unknown until the window is moved, the system places it then
+    #[serde(default)]
+    pub position: Option<(f32, f32)>,
+}
+
+impl UiState {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join("ui_state.json");
+        let state: Option<Self> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok());
+        Self {
+            path,
+            ..state.unwrap_or_default()
+        }
+    }
+
+    pub fn save(&self) {
+        let state = serde_json::to_string_pretty(&self).unwrap();
+        if let Err(e) = write_atomic(&self.path, state.as_bytes()) {
+            eprintln!("Could not save {}: {}", self.path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use serde_json::Value;
+use spaces_wallet::bitcoin::{
+    hashes::{sha256, Hash, HashEngine},
+    secp256k1::{schnorr::Signature, Message, Secp256k1, XOnlyPublicKey},
+};
+use std::path::PathBuf;
+
+use crate::proxy;
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/imperviousinc/akron/releases/latest";
+
+/// Key the release artifacts are signed with, given at build time. Every artifact has a
+/// `<name>.sig` asset next to it holding the hex BIP-340 signature of its
+/// [`signed_digest`], made by `.github/sign_release.py`
+const RELEASE_KEY: Option<&str> = option_env!("AKRON_RELEASE_KEY");
+
+/// Release newer than the running version
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub version: String,
+    pub notes: String,
+    pub url: String,
+    /// Download for this platform, `None` when there's none or this build can't verify it
+    pub asset: Option<Asset>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Asset {
+    pub name: String,
+    version: String,
+    url: String,
+    signature_url: String,
+}
+
+/// What the signature of an artifact commits to, so a signed artifact can't be served
+/// under another name or as another version
+fn signed_digest(name: &str, version: &str, contents: &[u8]) -> [u8; 32] {
+    let mut engine = sha256::Hash::engine();
+    engine.input(format!("{}\n{}\n", name, version).as_bytes());
+    engine.input(sha256::Hash::hash(contents).as_byte_array());
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+/// Numbers of a version like `1.2.3` or `v1.2.3`
+fn version_numbers(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split('.')
+        .map(|n| n.parse().unwrap_or_default())
+        .collect()
+}
+
+/// Part of the bundle names of this platform, e.g. `-linux-amd64.`
+fn platform_suffix() -> String {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    let arch = match std::env::consts::ARCH {
+        "aarch64" => "arm64",
+        _ => "amd64",
+    };
+    format!("-{}-{}.", os, arch)
+}
+
+fn release_key() -> Option<XOnlyPublicKey> {
+    let key = RELEASE_KEY.filter(|key| !key.is_empty())?;
+    XOnlyPublicKey::from_slice(&hex::decode(key).ok()?).ok()
+}
+
+async fn get(url: &str) -> Result<reqwest::Response, String> {
+    proxy::http_client()
+        .get(url)
+        // required by the GitHub API
+        .header("User-Agent", concat!("akron/", env!("CARGO_PKG_VERSION")))
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| e.to_string())
+}
+
+/// Latest release on GitHub when it's newer than this build
+pub async fn check() -> Result<Option<Release>, String> {
+    let body = get(LATEST_RELEASE_URL)
+        .await?
+        .text()
+        .await
+        .map_err(|e| e.to_string())?;
+    let release: Value = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+    let version = release["tag_name"]
+        .as_str()
+        .ok_or("Release has no version")?
+        .trim_start_matches('v')
+        .to_string();
+    if version_numbers(&version) <= version_numbers(env!("CARGO_PKG_VERSION")) {
+        return Ok(None);
+    }
+
+    let assets: Vec<(&str, &str)> = release["assets"]
+        .as_array()
+        .map(|assets| {
+            assets
+                .iter()
+                .filter_map(|asset| {
+                    Some((
+                        asset["name"].as_str()?,
+                        asset["browser_download_url"].as_str()?,
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let suffix = platform_suffix();
+    let asset = assets
+        .iter()
+        .filter(|(name, _)| name.contains(&suffix) && !name.ends_with(".sig"))
+        .find_map(|(name, url)| {
+            let signature_name = format!("{}.sig", name);
+            let (_, signature_url) = assets.iter().find(|(name, _)| *name == signature_name)?;
+            Some(Asset {
+                name: name.to_string(),
+                version: version.clone(),
+                url: url.to_string(),
+                signature_url: signature_url.to_string(),
+            })
+        })
+        .filter(|_| release_key().is_some());
+    Ok(Some(Release {
+        version,
+        notes: release["body"]
+            .as_str()
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+        url: release["html_url"].as_str().unwrap_or_default().to_string(),
+        asset,
+    }))
+}
+
+/// Downloads the asset to `path`, nothing is written unless its signature is valid
+pub async fn download(asset: Asset, path: PathBuf) -> Result<PathBuf, String> {
+    let key = release_key().ok_or("This build has no release key to verify downloads")?;
+    let signature = get(&asset.signature_url)
+        .await?
+        .text()
+        .await
+        .map_err(|e| e.to_string())?;
+    let signature = hex::decode(signature.trim())
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or("Release signature is malformed")?;
+    let contents = get(&asset.url)
+        .await?
+        .bytes()
+        .await
+        .map_err(|e| e.to_string())?;
+    let digest = signed_digest(&asset.name, &asset.version, &contents);
+    Secp256k1::verification_only()
+        .verify_schnorr(&signature, &Message::from_digest(digest), &key)
+        .map_err(|_| format!("{} is not signed by the Akron release key", asset.name))?;
+    tokio::fs::write(&path, contents)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(path)
+}
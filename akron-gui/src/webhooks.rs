@@ -0,0 +1,83 @@
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+use crate::Webhook;
+
+/// Failed deliveries are retried this many times, waiting twice as long each time
+const MAX_ATTEMPTS: u32 = 6;
+const FIRST_RETRY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    url: String,
+    body: String,
+    attempts: u32,
+    next_attempt: Instant,
+}
+
+/// Fills the webhook template, `{event}`, `{wallet}`, `{space}`, `{txid}` and `{height}`
+/// are replaced with the event's values. Without a template the event is sent as JSON
+fn render(webhook: &Webhook, event: &Value) -> String {
+    let Some(template) = webhook.template.as_ref() else {
+        return event.to_string();
+    };
+    ["event", "wallet", "space", "txid", "height"]
+        .iter()
+        .fold(template.clone(), |body, field| {
+            let value = match event.get(field) {
+                Some(Value::String(s)) => s.clone(),
+                Some(value) => value.to_string(),
+                None => String::new(),
+            };
+            body.replace(&format!("{{{}}}", field), &value)
+        })
+}
+
+impl Delivery {
+    /// Deliveries of the event to the webhooks configured for it
+    pub fn for_event(webhooks: &[Webhook], name: &str, event: &Value) -> Vec<Self> {
+        webhooks
+            .iter()
+            .filter(|webhook| webhook.events.is_empty() || webhook.events.iter().any(|e| e == name))
+            .map(|webhook| Self {
+                url: webhook.url.clone(),
+                body: render(webhook, event),
+                attempts: 0,
+                next_attempt: Instant::now(),
+            })
+            .collect()
+    }
+
+    pub fn is_due(&self) -> bool {
+        self.next_attempt <= Instant::now()
+    }
+
+    /// Sends the body, giving the delivery back when it failed and should be retried
+    pub async fn send(mut self) -> Result<(), (Option<Self>, String)> {
+        let content_type = if serde_json::from_str::<Value>(&self.body).is_ok() {
+            "application/json"
+        } else {
+            "text/plain"
+        };
+        let result = crate::proxy::http_client()
+            .post(&self.url)
+            .header("Content-Type", content_type)
+            .body(self.body.clone())
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let err = format!("Webhook {} failed: {}", self.url, e);
+                self.attempts += 1;
+                if self.attempts >= MAX_ATTEMPTS {
+                    return Err((None, err));
+                }
+                self.next_attempt = Instant::now() + FIRST_RETRY * 2u32.pow(self.attempts - 1);
+                Err((Some(self), err))
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::widget::text::error_block;
-use iced::widget::{container, scrollable, text, Column};
-use iced::{Center, Element, Fill};
+use iced::border::rounded;
+use iced::widget::{center, container, opaque, scrollable, text, Column};
+use iced::{Center, Color, Element, Fill, Theme};
 
 pub fn result_column<'a, Message: 'a>(
     error: Option<impl text::IntoFragment<'a>>,
@@ -38,3 +39,29 @@ pub fn base_container<'a, Message: 'a>(
     .height(Fill)
     .into()
 }
+
+/// Dims the screen behind `content` and blocks interaction with it
+pub fn modal<'a, Message: 'a>(content: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    opaque(
+        center(opaque(container(content).width(600).style(
+            |theme: &Theme| {
+                let palette = theme.extended_palette();
+                container::Style {
+                    background: Some(palette.background.weak.color.into()),
+                    border: rounded(12),
+                    ..container::Style::default()
+                }
+            },
+        )))
+        .style(|_theme| container::Style {
+            background: Some(
+                Color {
+                    a: 0.8,
+                    ..Color::BLACK
+                }
+                .into(),
+            ),
+            ..container::Style::default()
+        }),
+    )
+}
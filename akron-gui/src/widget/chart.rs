@@ -0,0 +1,97 @@
+use iced::{
+    advanced::{layout, mouse, renderer, widget::Tree, Layout, Widget},
+    Color, Element, Length, Rectangle, Size, Theme,
+};
+
+/// Small bar chart of the values, the largest one filling the height
+pub struct Sparkline {
+    values: Vec<f32>,
+    width: f32,
+    height: f32,
+}
+
+impl Sparkline {
+    pub fn new(values: Vec<f32>, width: f32, height: f32) -> Self {
+        Self {
+            values,
+            width,
+            height,
+        }
+    }
+}
+
+impl<Message, Renderer> Widget<Message, Theme, Renderer> for Sparkline
+where
+    Renderer: renderer::Renderer,
+{
+    fn size(&self) -> Size<Length> {
+        Size {
+            width: Length::Shrink,
+            height: Length::Shrink,
+        }
+    }
+
+    fn layout(
+        &self,
+        _tree: &mut Tree,
+        _renderer: &Renderer,
+        _limits: &layout::Limits,
+    ) -> layout::Node {
+        layout::Node::new(Size::new(self.width, self.height))
+    }
+
+    fn draw(
+        &self,
+        _state: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        _style: &renderer::Style,
+        layout: Layout<'_>,
+        _cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+        let palette = theme.extended_palette();
+        renderer.fill_quad(
+            renderer::Quad {
+                bounds,
+                ..renderer::Quad::default()
+            },
+            palette.background.weak.color,
+        );
+
+        let max = self.values.iter().copied().fold(0.0, f32::max);
+        if self.values.is_empty() || max <= 0.0 {
+            return;
+        }
+        let bar_width = bounds.width / self.values.len() as f32;
+        for (index, value) in self.values.iter().enumerate() {
+            let height = bounds.height * value / max;
+            renderer.fill_quad(
+                renderer::Quad {
+                    bounds: Rectangle {
+                        x: bounds.x + index as f32 * bar_width,
+                        y: bounds.y + bounds.height - height,
+                        width: bar_width,
+                        height,
+                    },
+                    ..renderer::Quad::default()
+                },
+                Color {
+                    a: 0.8,
+                    ..palette.primary.base.color
+                },
+            );
+        }
+    }
+}
+
+impl<'a, Message, Renderer> From<Sparkline> for Element<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Renderer: 'a + renderer::Renderer,
+{
+    fn from(sparkline: Sparkline) -> Element<'a, Message, Theme, Renderer> {
+        Element::new(sparkline)
+    }
+}
@@ -10,7 +10,7 @@ use iced::widget::{
 };
 use iced::{border, font, keyboard, widget, Fill, Padding, Shrink, Theme};
 use iced::{Color, Element, Subscription, Task};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Default, Debug)]
 pub struct FeeRateSelector {
@@ -20,6 +20,7 @@ pub struct FeeRateSelector {
     selected_option: Option<FeeRateOption>,
     selected_fee_rate: Option<u32>,
     custom_fee_rate: String,
+    default_option: FeeRateOption,
 }
 
 #[derive(Debug, Clone)]
@@ -52,8 +53,9 @@ enum FeeFetchState {
     Failed,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FeeRateOption {
+    #[default]
     Fastest,
     HalfHour,
     Hour,
@@ -63,6 +65,9 @@ pub enum FeeRateOption {
 impl FeeRateOption {
     pub const ALL: &'static [Self] = &[Self::Fastest, Self::HalfHour, Self::Hour, Self::Custom];
 
+    /// Options that can be preselected, a custom rate has to be entered each time
+    pub const PRESETS: &'static [Self] = &[Self::Fastest, Self::HalfHour, Self::Hour];
+
     fn label(&self) -> &'static str {
         match self {
             Self::Fastest => "Fast",
@@ -96,33 +101,55 @@ impl FeeRateOption {
     }
 
     fn fee_rate(&self, fee_rates: &FeeRates) -> u32 {
+        self.preset_fee_rate(fee_rates).unwrap_or(0)
+    }
+
+    /// Rate in sat/vB of a preset, `None` for a custom rate
+    pub fn preset_fee_rate(&self, fee_rates: &FeeRates) -> Option<u32> {
         match self {
-            Self::Fastest => fee_rates.fastest_fee,
-            Self::HalfHour => fee_rates.half_hour_fee,
-            Self::Hour => fee_rates.hour_fee,
-            Self::Custom => 0,
+            Self::Fastest => Some(fee_rates.fastest_fee),
+            Self::HalfHour => Some(fee_rates.half_hour_fee),
+            Self::Hour => Some(fee_rates.hour_fee),
+            Self::Custom => None,
         }
     }
 }
 
+/// Fetches the recommended fee rates from mempool.space
+pub async fn fetch_fee_rates() -> Result<FeeRates, String> {
+    match crate::proxy::http_client()
+        .get("https://mempool.space/api/v1/fees/recommended")
+        .send()
+        .await
+    {
+        Ok(response) => match response.json::<FeeRates>().await {
+            Ok(fee_rates) => Ok(fee_rates),
+            Err(e) => Err(format!("Could not fetch fee rates: {}", e)),
+        },
+        Err(e) => Err(format!("Could not fetch fee rates: {}", e)),
+    }
+}
+
+impl std::fmt::Display for FeeRateOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.label(), self.description())
+    }
+}
+
 impl FeeRateSelector {
+    /// Option selected when the modal opens
+    pub fn set_default_option(&mut self, option: FeeRateOption) {
+        if option != FeeRateOption::Custom {
+            self.default_option = option;
+        }
+    }
+
     pub fn subscription(&self) -> Subscription<FeeRateMessage> {
         event::listen().map(FeeRateMessage::Event)
     }
 
     fn fetch_fee_rates() -> Task<FeeRateMessage> {
-        Task::perform(
-            async {
-                match reqwest::get("https://mempool.space/api/v1/fees/recommended").await {
-                    Ok(response) => match response.json::<FeeRates>().await {
-                        Ok(fee_rates) => Ok(fee_rates),
-                        Err(e) => Err(format!("Could not fetch fee rates: {}", e)),
-                    },
-                    Err(e) => Err(format!("Could not fetch fee rates: {}", e)),
-                }
-            },
-            FeeRateMessage::FeeRatesFetched,
-        )
+        Task::perform(fetch_fee_rates(), FeeRateMessage::FeeRatesFetched)
     }
 
     pub fn update(&mut self, message: FeeRateMessage) -> Task<FeeRateMessage> {
@@ -130,13 +157,13 @@ impl FeeRateSelector {
             FeeRateMessage::ShowModal => {
                 self.show_modal = true;
                 self.fee_fetch_state = FeeFetchState::Fetching;
-                self.selected_option = Some(FeeRateOption::Fastest);
+                self.selected_option = Some(self.default_option);
                 Self::fetch_fee_rates()
             }
             FeeRateMessage::HideModal => {
                 self.show_modal = false;
                 self.custom_fee_rate.clear();
-                self.selected_option = Some(FeeRateOption::Fastest);
+                self.selected_option = Some(self.default_option);
                 self.selected_fee_rate = None;
                 Task::none()
             }
@@ -158,7 +185,7 @@ impl FeeRateSelector {
                 }) => {
                     self.show_modal = false;
                     self.custom_fee_rate.clear();
-                    self.selected_option = Some(FeeRateOption::Fastest);
+                    self.selected_option = Some(self.default_option);
                     self.selected_fee_rate = None;
                     Task::none()
                 }
@@ -169,8 +196,8 @@ impl FeeRateSelector {
                     Ok(fee_rates) => {
                         self.fee_rates = Some(fee_rates.clone());
                         self.fee_fetch_state = FeeFetchState::Idle;
-                        self.selected_option = Some(FeeRateOption::Fastest);
-                        self.selected_fee_rate = Some(fee_rates.fastest_fee);
+                        self.selected_option = Some(self.default_option);
+                        self.selected_fee_rate = Some(self.default_option.fee_rate(&fee_rates));
                     }
                     Err(e) => {
                         eprintln!("Error fetching fee rates: {}", e);
@@ -205,7 +232,7 @@ impl FeeRateSelector {
                 if let Some(fee_rate) = self.selected_fee_rate {
                     self.show_modal = false;
                     self.custom_fee_rate.clear();
-                    self.selected_option = Some(FeeRateOption::Fastest);
+                    self.selected_option = Some(self.default_option);
                     self.selected_fee_rate = None;
                     Task::done(FeeRateMessage::Confirmed(fee_rate))
                 } else {
@@ -2,7 +2,7 @@ use iced::widget::text;
 use iced::{
     widget::{
         button, column, pick_list as _pick_list, text_editor, text_input as _text_input, Button,
-        Column, Container, PickList, Text, TextInput,
+        Column, Container, PickList, Row, Text, TextInput,
     },
     Background, Border, Center, Element, Fill, Font, Padding, Theme,
 };
@@ -90,6 +90,7 @@ where
 pub struct Form<'a, Message> {
     submit_label: &'a str,
     submit_message: Option<Message>,
+    secondary: Option<(&'a str, Option<Message>)>,
     elements: Vec<Element<'a, Message>>,
 }
 
@@ -98,10 +99,17 @@ impl<'a, Message: Clone + 'a> Form<'a, Message> {
         Self {
             submit_label,
             submit_message,
+            secondary: None,
             elements: Vec::new(),
         }
     }
 
+    /// Adds a button next to the submit one
+    pub fn secondary_button(mut self, label: &'a str, message: Option<Message>) -> Self {
+        self.secondary = Some((label, message));
+        self
+    }
+
     pub fn add_text_input(
         mut self,
         label: &'a str,
@@ -221,11 +229,24 @@ impl<'a, Message: 'a + Clone> From<Form<'a, Message>> for Element<'a, Message> {
         Column::from_vec(form.elements)
             .push(
                 Container::new(
-                    submit_button(
-                        text(form.submit_label).width(Fill).align_x(Center),
-                        form.submit_message,
-                    )
-                    .width(Fill),
+                    Row::new()
+                        .push_maybe(form.secondary.map(|(label, message)| {
+                            submit_button(text(label).width(Fill).align_x(Center), message)
+                                .style(|theme: &Theme, status: button::Status| {
+                                    let mut style = button::secondary(theme, status);
+                                    style.border = style.border.rounded(7);
+                                    style
+                                })
+                                .width(Fill)
+                        }))
+                        .push(
+                            submit_button(
+                                text(form.submit_label).width(Fill).align_x(Center),
+                                form.submit_message,
+                            )
+                            .width(Fill),
+                        )
+                        .spacing(10),
                 )
                 .align_x(Center)
                 .width(Fill),
@@ -1,7 +1,9 @@
 pub mod base;
+pub mod chart;
 pub mod fee_rate;
 pub mod form;
 pub mod icon;
+pub mod qr_code;
 pub mod rect;
 pub mod tabs;
 pub mod text;
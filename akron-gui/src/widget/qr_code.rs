@@ -0,0 +1,129 @@
+use iced::{
+    advanced::{layout, mouse, renderer, widget::Tree, Layout, Widget},
+    Element, Length, Rectangle, Size, Theme,
+};
+
+/// Light modules around the code, as many as the QR code widget of iced leaves
+const QUIET_ZONE: usize = 2;
+
+/// Modules of a QR code. Unlike the data of the QR code widget of iced it can be encoded
+/// off the UI thread
+#[derive(Debug, Clone)]
+pub struct QrMatrix {
+    width: usize,
+    dark: Vec<bool>,
+}
+
+impl QrMatrix {
+    pub fn encode(data: &str) -> Result<Self, String> {
+        let code = qrcode::QrCode::new(data.as_bytes()).map_err(|e| e.to_string())?;
+        Ok(Self {
+            width: code.width(),
+            dark: code
+                .into_colors()
+                .into_iter()
+                .map(|color| color == qrcode::Color::Dark)
+                .collect(),
+        })
+    }
+}
+
+pub struct QrCode<'a> {
+    matrix: &'a QrMatrix,
+    cell_size: f32,
+}
+
+impl QrCode<'_> {
+    pub fn cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+}
+
+pub fn qr_code(matrix: &QrMatrix) -> QrCode<'_> {
+    QrCode {
+        matrix,
+        cell_size: 4.0,
+    }
+}
+
+impl<Message, Renderer> Widget<Message, Theme, Renderer> for QrCode<'_>
+where
+    Renderer: renderer::Renderer,
+{
+    fn size(&self) -> Size<Length> {
+        Size {
+            width: Length::Shrink,
+            height: Length::Shrink,
+        }
+    }
+
+    fn layout(
+        &self,
+        _tree: &mut Tree,
+        _renderer: &Renderer,
+        _limits: &layout::Limits,
+    ) -> layout::Node {
+        let side = (self.matrix.width + 2 * QUIET_ZONE) as f32 * self.cell_size;
+        layout::Node::new(Size::new(side, side))
+    }
+
+    fn draw(
+        &self,
+        _state: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        _style: &renderer::Style,
+        layout: Layout<'_>,
+        _cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+        let palette = theme.extended_palette();
+        renderer.fill_quad(
+            renderer::Quad {
+                bounds,
+                ..renderer::Quad::default()
+            },
+            palette.background.base.color,
+        );
+
+        // runs of dark modules are drawn as one quad
+        let origin = QUIET_ZONE as f32 * self.cell_size;
+        for (y, row) in self.matrix.dark.chunks(self.matrix.width).enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                if !row[x] {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < row.len() && row[x] {
+                    x += 1;
+                }
+                renderer.fill_quad(
+                    renderer::Quad {
+                        bounds: Rectangle {
+                            x: bounds.x + origin + start as f32 * self.cell_size,
+                            y: bounds.y + origin + y as f32 * self.cell_size,
+                            width: (x - start) as f32 * self.cell_size,
+                            height: self.cell_size,
+                        },
+                        ..renderer::Quad::default()
+                    },
+                    palette.background.base.text,
+                );
+            }
+        }
+    }
+}
+
+impl<'a, Message, Renderer> From<QrCode<'a>> for Element<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Renderer: 'a + renderer::Renderer,
+{
+    fn from(qr_code: QrCode<'a>) -> Self {
+        Self::new(qr_code)
+    }
+}
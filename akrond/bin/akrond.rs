@@ -1,11 +1,30 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::{env, fs};
 
 use akrond::runner::{ServiceKind, ServiceRunner};
-use akrond::Akron;
+use akrond::{Akron, CheckpointProgress, LogFormat};
+use anyhow::{anyhow, Context};
 use directories::ProjectDirs;
 use spaces_client::config::{safe_exit, ExtendedNetwork};
+use spaces_client::rpc::RootAnchor;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::broadcast;
 
+const USAGE: &str = "Usage: akrond [--data-dir <dir>] [--log-format text|json] <command>
+
+Commands:
+  start                  Run the services in the foreground (default)
+  stop                   Stop a running akrond
+  status                 Show whether akrond is running
+  logs [-f]              Print the service logs, -f keeps following them
+  checkpoint download    Download the checkpoint without starting the services";
+
+/// Address of the control socket of a running akrond, kept in the data directory
+const CONTROL_FILE: &str = "akrond.control";
+const LOG_FILE: &str = "akrond.log";
+
 fn main() {
     let args: Vec<String> = env::args().collect();
     if let Some(service) = ServiceRunner::parse(&args) {
@@ -16,66 +35,220 @@ fn main() {
         return;
     }
 
-    let (akrond, shutdown) = Akron::create(false);
+    let mut args: Vec<String> = args.into_iter().skip(1).collect();
+    let data_dir = take_arg("--data-dir", &mut args)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| get_default_dirs().data_dir().to_path_buf());
+    let log_format = match take_arg("--log-format", &mut args).map(|f| f.parse::<LogFormat>()) {
+        Some(Ok(format)) => format,
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            safe_exit(1);
+        }
+        None => LogFormat::default(),
+    };
     let rt = tokio::runtime::Runtime::new().expect("Failed to build tokio runtime");
-    if let Err(e) = rt.block_on(async_main(akrond, shutdown)) {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = rt.block_on(async {
+        match args.as_slice() {
+            [] | ["start"] => start(&data_dir, log_format).await,
+            ["stop"] => stop(&data_dir).await,
+            ["status"] => status(&data_dir).await,
+            ["logs"] => logs(&data_dir, false).await,
+            ["logs", "-f" | "--follow"] => logs(&data_dir, true).await,
+            ["checkpoint", "download"] => {
+                let (akrond, _shutdown) = Akron::create(false);
+                download_checkpoint(&akrond, &data_dir).await.map(|_| ())
+            }
+            _ => Err(anyhow!(USAGE)),
+        }
+    });
+    if let Err(e) = result {
         eprintln!("{}", e);
         safe_exit(1);
     }
 }
 
-async fn async_main(akrond: Akron, shutdown: broadcast::Sender<()>) -> anyhow::Result<()> {
-    let sigterm = tokio::signal::ctrl_c();
-    let sigterm_shutdown = shutdown.clone();
-    tokio::spawn(async move {
-        sigterm.await.expect("could not listen for shutdown");
-        let _ = sigterm_shutdown.send(());
-    });
+fn take_arg(name: &str, args: &mut Vec<String>) -> Option<String> {
+    let index = args.iter().position(|arg| arg == name)?;
+    args.remove(index);
+    (index < args.len()).then(|| args.remove(index))
+}
 
-    let chain = ExtendedNetwork::Mainnet;
-    let project_dirs = get_default_dirs();
+fn checkpoint_dir(data_dir: &Path) -> PathBuf {
+    data_dir
+        .join("spaces")
+        .join(ExtendedNetwork::Mainnet.to_string())
+}
 
-    fs::create_dir_all(project_dirs.data_dir())?;
+async fn load_checkpoint(
+    akrond: &Akron,
+    data_dir: &Path,
+    progress: Option<broadcast::Sender<CheckpointProgress>>,
+) -> anyhow::Result<RootAnchor> {
+    let checkpoint_path = checkpoint_dir(data_dir);
+    akrond
+        .load_checkpoint(
+            &akrond::CHECKPOINT_MIRRORS
+                .iter()
+                .map(|url| url.to_string())
+                .collect::<Vec<_>>(),
+            &[],
+            &checkpoint_path,
+            progress,
+            None,
+        )
+        .await
+}
 
+async fn download_checkpoint(akrond: &Akron, data_dir: &Path) -> anyhow::Result<RootAnchor> {
+    let progress = broadcast::Sender::<CheckpointProgress>::new(10);
+    let mut progress_rx = progress.subscribe();
+    tokio::spawn(async move {
+        while let Ok(progress) = progress_rx.recv().await {
+            if let Some(percent) = (progress.downloaded * 100).checked_div(progress.total) {
+                eprint!("\rDownloading checkpoint: {}%", percent);
+            }
+        }
+    });
+    let checkpoint = load_checkpoint(akrond, data_dir, Some(progress)).await?;
     println!(
-        "Configuration directory: {}",
-        project_dirs.data_dir().to_str().expect("No data dir")
+        "\nCheckpoint loaded at height = {}",
+        checkpoint.block.height
     );
-    let yuki_data_dir = project_dirs.data_dir().join("yuki");
-    let spaces_data_dir = project_dirs.data_dir().join("spaces");
+    Ok(checkpoint)
+}
 
-    let mut yuki_args = vec!["--data-dir", yuki_data_dir.to_str().expect("valid path")];
+/// Runs the services until Ctrl-C or `akrond stop`, writing their logs to the log file
+async fn start(data_dir: &Path, log_format: LogFormat) -> anyhow::Result<()> {
+    fs::create_dir_all(data_dir)?;
+    let control_path = data_dir.join(CONTROL_FILE);
+    if TcpStream::connect(fs::read_to_string(&control_path).unwrap_or_default().trim())
+        .await
+        .is_ok()
+    {
+        return Err(anyhow!("akrond is already running"));
+    }
 
-    let spaces_args = vec![
-        "--bitcoin-rpc-url",
-        "http://127.0.0.1:8225",
-        "--data-dir",
-        spaces_data_dir.to_str().expect("valid path"),
-        "--bitcoin-rpc-light",
-    ];
+    println!("Configuration directory: {}", data_dir.display());
+    let (akrond, shutdown) = Akron::create(true);
+    let akrond = Arc::new(akrond);
+    let mut shutdown_recv = shutdown.subscribe();
+    let sigterm_shutdown = shutdown.clone();
+    tokio::spawn(async move {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("could not listen for shutdown");
+        let _ = sigterm_shutdown.send(());
+    });
 
-    let checkpoint_path = spaces_data_dir.join(chain.to_string());
+    let mut log_file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(data_dir.join(LOG_FILE))
+        .await
+        .context("Could not open the log file")?;
+    if let Some(logs) = akrond.subscribe_logs() {
+        let mut logs = logs.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let line = match logs.recv().await {
+                    Ok(line) => line,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        let line = format!("{} log lines skipped", skipped);
+                        eprintln!("{}", line);
+                        _ = log_file.write_all(format!("{}\n", line).as_bytes()).await;
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let line = line.format(log_format);
+                println!("{}", line);
+                _ = log_file.write_all(format!("{}\n", line).as_bytes()).await;
+            }
+        });
+    }
 
-    // Note: this loads the checkpoint and overrides the existing db
-    // everytime.
-    // TODO: check if the db already exists and store the initial checkpoint somewhere (to pass to yuki)
-    let checkpoint = akrond
-        .load_checkpoint(
-            "https://checkpoint.akron.io/protocol.sdb",
-            &checkpoint_path,
-            None,
-        )
-        .await?;
+    // the checkpoint is only downloaded on the first start, later ones start from the saved one
+    let checkpoint = if checkpoint_dir(data_dir)
+        .join("akron.checkpoint.json")
+        .exists()
+    {
+        load_checkpoint(&akrond, data_dir, None).await?
+    } else {
+        download_checkpoint(&akrond, data_dir).await?
+    };
 
-    yuki_args.push("--prune-point");
+    let control = TcpListener::bind("127.0.0.1:0").await?;
+    fs::write(&control_path, control.local_addr()?.to_string())?;
+    let status = format!(
+        "akrond is running, pid {}, checkpoint at block {}",
+        std::process::id(),
+        checkpoint.block.height
+    );
+    let control_shutdown = shutdown.clone();
+    let control_akrond = akrond.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = control.accept().await {
+            let (reader, mut writer) = stream.into_split();
+            let mut command = String::new();
+            if BufReader::new(reader)
+                .read_line(&mut command)
+                .await
+                .is_err()
+            {
+                continue;
+            }
+            let response = match command.trim() {
+                "status" => {
+                    let mut status = status.clone();
+                    for service in control_akrond.status().await.unwrap_or_default() {
+                        status.push_str(&format!(
+                            "; {} {} for {}s",
+                            service.kind.as_str(),
+                            if service.running {
+                                "running"
+                            } else {
+                                "stopped"
+                            },
+                            service.uptime.as_secs()
+                        ));
+                        if let Some(error) = service.last_error {
+                            status.push_str(&format!(", last error: {}", error));
+                        }
+                    }
+                    status
+                }
+                "stop" => {
+                    _ = control_shutdown.send(());
+                    "Stopping akrond".to_string()
+                }
+                _ => "Unknown command".to_string(),
+            };
+            _ = writer.write_all(format!("{}\n", response).as_bytes()).await;
+        }
+    });
 
+    let yuki_data_dir = data_dir.join("yuki");
+    let spaces_data_dir = data_dir.join("spaces");
     let prune_point = format!(
         "{}:{}",
         hex::encode(checkpoint.block.hash),
         checkpoint.block.height
     );
-    yuki_args.push(&prune_point);
-
+    let yuki_args = [
+        "--data-dir",
+        yuki_data_dir.to_str().expect("valid path"),
+        "--prune-point",
+        &prune_point,
+    ];
+    let spaces_args = [
+        "--bitcoin-rpc-url",
+        "http://127.0.0.1:8225",
+        "--data-dir",
+        spaces_data_dir.to_str().expect("valid path"),
+        "--bitcoin-rpc-light",
+    ];
     akrond
         .start(
             ServiceKind::Yuki,
@@ -89,13 +262,66 @@ async fn async_main(akrond: Akron, shutdown: broadcast::Sender<()>) -> anyhow::R
         )
         .await?;
 
-    println!("Checkpoint loaded at height = {}", checkpoint.block.height);
-
-    let mut shutdown_recv = shutdown.subscribe();
     _ = shutdown_recv.recv().await;
+    _ = akrond.shutdown(ServiceKind::Spaces).await;
+    _ = akrond.shutdown(ServiceKind::Yuki).await;
+    _ = fs::remove_file(control_path);
+    Ok(())
+}
+
+/// Sends `command` to the running akrond and returns its answer
+async fn control(data_dir: &Path, command: &str) -> anyhow::Result<String> {
+    let addr = fs::read_to_string(data_dir.join(CONTROL_FILE))
+        .map_err(|_| anyhow!("akrond is not running"))?;
+    let stream = TcpStream::connect(addr.trim())
+        .await
+        .map_err(|_| anyhow!("akrond is not running"))?;
+    let (reader, mut writer) = stream.into_split();
+    writer
+        .write_all(format!("{}\n", command).as_bytes())
+        .await?;
+    let mut response = String::new();
+    BufReader::new(reader).read_line(&mut response).await?;
+    Ok(response.trim().to_string())
+}
+
+async fn stop(data_dir: &Path) -> anyhow::Result<()> {
+    println!("{}", control(data_dir, "stop").await?);
     Ok(())
 }
 
+async fn status(data_dir: &Path) -> anyhow::Result<()> {
+    match control(data_dir, "status").await {
+        Ok(status) => println!("{}", status),
+        Err(e) => println!("{}", e),
+    }
+    println!("Configuration directory: {}", data_dir.display());
+    Ok(())
+}
+
+async fn logs(data_dir: &Path, follow: bool) -> anyhow::Result<()> {
+    let mut file = tokio::fs::File::open(data_dir.join(LOG_FILE))
+        .await
+        .context("No logs yet, akrond hasn't been started")?;
+    let mut stdout = tokio::io::stdout();
+    tokio::io::copy(&mut file, &mut stdout).await?;
+    if !follow {
+        return Ok(());
+    }
+    loop {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        let position = file.stream_position().await?;
+        if file.metadata().await?.len() < position {
+            // truncated, start over
+            file.seek(std::io::SeekFrom::Start(0)).await?;
+        }
+        let mut new = Vec::new();
+        file.read_to_end(&mut new).await?;
+        stdout.write_all(&new).await?;
+        stdout.flush().await?;
+    }
+}
+
 fn get_default_dirs() -> ProjectDirs {
     ProjectDirs::from("", "", "akron").unwrap_or_else(|| {
         eprintln!("error: could not retrieve default project directories from os");
@@ -0,0 +1,50 @@
+use anyhow::{anyhow, Context};
+use spaces_client::rpc::RootAnchor;
+use spaces_protocol::bitcoin::hashes::{sha256, Hash, HashEngine};
+use std::io::Read;
+use std::path::Path;
+
+/// SHA-256 digest published next to a checkpoint, as written by `sha256sum`
+pub fn digest_url(url: &str) -> String {
+    format!("{}.sha256", url)
+}
+
+/// Anchors manifest published next to a checkpoint, as written by a checkpoint export
+pub fn anchors_url(url: &str) -> String {
+    match url.rsplit_once('/') {
+        Some((base, _)) => format!("{}/protocol.anchors.json", base),
+        None => "protocol.anchors.json".to_string(),
+    }
+}
+
+pub fn same_anchor(a: &RootAnchor, b: &RootAnchor) -> bool {
+    a.block.height == b.block.height
+        && a.block.hash == b.block.hash
+        && a.spaces_root == b.spaces_root
+}
+
+pub fn file_hash(path: &Path) -> anyhow::Result<sha256::Hash> {
+    let mut file = std::fs::File::open(path).context("Could not open checkpoint file")?;
+    let mut engine = sha256::Hash::engine();
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .context("Could not read checkpoint file")?;
+        if read == 0 {
+            return Ok(sha256::Hash::from_engine(engine));
+        }
+        engine.input(&buffer[..read]);
+    }
+}
+
+/// Checks the checkpoint at `path` against the `published` digest, the first word of it
+pub fn check_digest(path: &Path, published: &str) -> anyhow::Result<()> {
+    let published = published.split_whitespace().next().unwrap_or_default();
+    if !file_hash(path)?.to_string().eq_ignore_ascii_case(published) {
+        return Err(anyhow!(
+            "Checkpoint doesn't match its published digest, it's corrupted"
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use crate::runner::{ServiceHealth, ServiceKind};
+use spaces_client::config::{default_spaces_rpc_port, ExtendedNetwork};
+use spaces_client::jsonrpsee::core::__reexports::serde_json::{self, json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Time between two calls to the service's RPC
+const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// RPC port yuki listens on when none is given
+const YUKI_RPC_PORT: u16 = 8225;
+
+/// RPC call that tells whether a service is ready and how far it synced
+struct Probe {
+    url: String,
+    login: Option<(String, String)>,
+    method: &'static str,
+}
+
+/// Value of an argument given as `--name value` or `-name=value`
+fn value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|pos| args.get(pos + 1))
+        .map(String::as_str)
+        .or_else(|| {
+            args.iter()
+                .find_map(|arg| arg.strip_prefix(name)?.strip_prefix('='))
+        })
+}
+
+fn network(chain: &str) -> Option<ExtendedNetwork> {
+    match chain {
+        "mainnet" => Some(ExtendedNetwork::Mainnet),
+        "testnet" => Some(ExtendedNetwork::Testnet),
+        "testnet4" => Some(ExtendedNetwork::Testnet4),
+        "signet" => Some(ExtendedNetwork::Signet),
+        "regtest" => Some(ExtendedNetwork::Regtest),
+        _ => None,
+    }
+}
+
+fn login(args: &[String], user: &str, password: &str) -> Option<(String, String)> {
+    Some((
+        value(args, user)?.to_string(),
+        value(args, password)?.to_string(),
+    ))
+}
+
+impl Probe {
+    fn new(kind: ServiceKind, args: &[String]) -> Option<Self> {
+        let local = |port: u16| format!("http://127.0.0.1:{}", port);
+        match kind {
+            ServiceKind::Spaces => {
+                let port = match value(args, "--rpc-port") {
+                    Some(port) => port.parse().ok()?,
+                    None => default_spaces_rpc_port(&network(value(args, "--chain")?)?),
+                };
+                Some(Self {
+                    url: local(port),
+                    login: login(args, "--rpc-user", "--rpc-password"),
+                    method: "getserverinfo",
+                })
+            }
+            ServiceKind::Yuki => Some(Self {
+                url: local(
+                    value(args, "--rpc-port")
+                        .map_or(Some(YUKI_RPC_PORT), |port| port.parse().ok())?,
+                ),
+                login: None,
+                method: "getblockchaininfo",
+            }),
+            ServiceKind::Bitcoind => Some(Self {
+                url: local(value(args, "-rpcport")?.parse().ok()?),
+                login: login(args, "-rpcuser", "-rpcpassword"),
+                method: "getblockchaininfo",
+            }),
+            ServiceKind::Mirror => None,
+        }
+    }
+
+    /// Height the service synced to
+    async fn call(&self, client: &reqwest::Client) -> Result<Option<u32>, String> {
+        let mut request = client
+            .post(&self.url)
+            .timeout(PROBE_TIMEOUT)
+            .header("Content-Type", "application/json")
+            .body(
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "method": self.method,
+                    "params": [],
+                })
+                .to_string(),
+            );
+        if let Some((user, password)) = self.login.as_ref() {
+            request = request.basic_auth(user, Some(password));
+        }
+        let body = request
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .text()
+            .await
+            .map_err(|e| e.to_string())?;
+        let body: Value = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+        if !body["error"].is_null() {
+            return Err(body["error"]["message"]
+                .as_str()
+                .map_or_else(|| body["error"].to_string(), str::to_string));
+        }
+        let height = match self.method {
+            "getserverinfo" => &body["result"]["tip"]["height"],
+            _ => &body["result"]["blocks"],
+        };
+        Ok(height.as_u64().map(|height| height as u32))
+    }
+}
+
+/// Keeps the health of the service up to date by calling its RPC
+pub(crate) async fn watch(kind: ServiceKind, args: Vec<String>, health: Arc<Mutex<ServiceHealth>>) {
+    let Some(probe) = Probe::new(kind, &args) else {
+        // nothing to ask, the service is ready once it runs
+        health.lock().unwrap().ready = true;
+        return;
+    };
+    let client = reqwest::Client::new();
+    let mut interval = tokio::time::interval(PROBE_INTERVAL);
+    loop {
+        interval.tick().await;
+        let result = probe.call(&client).await;
+        let mut health = health.lock().unwrap();
+        match result {
+            Ok(height) => {
+                health.ready = true;
+                health.height = height;
+            }
+            Err(e) => {
+                // failures while starting are expected, only later ones are errors
+                if health.ready {
+                    health.last_error = Some(e);
+                }
+                health.ready = false;
+            }
+        }
+    }
+}
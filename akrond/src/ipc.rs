@@ -0,0 +1,106 @@
+use std::io;
+use std::time::SystemTime;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+pub(crate) trait Io: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Io for T {}
+
+pub(crate) type Stream = Box<dyn Io>;
+
+/// Name unique to this akrond, so several can run side by side
+fn unique_name() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    format!("akrond-{}-{}", std::process::id(), nanos)
+}
+
+/// Where services connect to akrond: a Unix domain socket in a directory only the user
+/// can open, rather than a TCP port any local process could connect to
+#[cfg(unix)]
+pub(crate) struct Listener {
+    dir: std::path::PathBuf,
+    listener: tokio::net::UnixListener,
+}
+
+#[cfg(unix)]
+impl Listener {
+    pub fn bind() -> io::Result<Self> {
+        use std::os::unix::fs::DirBuilderExt;
+
+        let dir = std::env::temp_dir().join(unique_name());
+        std::fs::DirBuilder::new().mode(0o700).create(&dir)?;
+        let listener = tokio::net::UnixListener::bind(dir.join("services.sock"))?;
+        Ok(Self { dir, listener })
+    }
+
+    /// Address services connect to
+    pub fn addr(&self) -> String {
+        self.dir
+            .join("services.sock")
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub async fn accept(&mut self) -> io::Result<Stream> {
+        let (stream, _) = self.listener.accept().await?;
+        Ok(Box::new(stream))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Listener {
+    fn drop(&mut self) {
+        _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(unix)]
+pub(crate) async fn connect(addr: &str) -> io::Result<Stream> {
+    Ok(Box::new(tokio::net::UnixStream::connect(addr).await?))
+}
+
+/// Where services connect to akrond: a named pipe refusing remote clients
+#[cfg(windows)]
+pub(crate) struct Listener {
+    name: String,
+    server: tokio::net::windows::named_pipe::NamedPipeServer,
+}
+
+#[cfg(windows)]
+impl Listener {
+    pub fn bind() -> io::Result<Self> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        let name = format!(r"\\.\pipe\{}", unique_name());
+        let server = ServerOptions::new()
+            .first_pipe_instance(true)
+            .reject_remote_clients(true)
+            .create(&name)?;
+        Ok(Self { name, server })
+    }
+
+    /// Address services connect to
+    pub fn addr(&self) -> String {
+        self.name.clone()
+    }
+
+    pub async fn accept(&mut self) -> io::Result<Stream> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        self.server.connect().await?;
+        // each connection takes an instance of the pipe, the next one waits on a new one
+        let next = ServerOptions::new()
+            .reject_remote_clients(true)
+            .create(&self.name)?;
+        Ok(Box::new(std::mem::replace(&mut self.server, next)))
+    }
+}
+
+#[cfg(windows)]
+pub(crate) async fn connect(addr: &str) -> io::Result<Stream> {
+    use tokio::net::windows::named_pipe::ClientOptions;
+
+    Ok(Box::new(ClientOptions::new().open(addr)?))
+}
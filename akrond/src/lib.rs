@@ -1,6 +1,9 @@
 use futures_util::stream::StreamExt;
 
-use crate::runner::{ServiceCommand, ServiceKind};
+use crate::runner::{
+    read_frame, write_frame, ServiceCommand, ServiceHealth, ServiceKind, ServiceResponse,
+    ATTACH_TOKEN_ENV,
+};
 use anyhow::{anyhow, Context};
 use log::{error, info};
 use reqwest::Client;
@@ -8,47 +11,191 @@ use spaces_client::jsonrpsee::core::__reexports::serde_json;
 use spaces_client::rpc::RootAnchor;
 use std::env;
 use std::env::temp_dir;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::{TcpListener, TcpStream};
 use tokio::process::{Child, Command};
 use tokio::select;
 use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio::time::interval;
 
+mod checkpoint;
+mod health;
+mod ipc;
+mod logs;
 pub mod runner;
 pub mod services;
+mod usage;
+
+pub use logs::{days_from_civil, LogFormat, LogLevel, LogLine, LogStream};
+pub use usage::{ResourceSample, SAMPLE_INTERVAL};
+
+/// Version of akrond and of the services built into it
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+/// Services that don't answer a command in this time are considered stopped
+const SERVICE_REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+/// Time a connection to the services listener has to present the token
+const ATTACH_TIMEOUT: Duration = Duration::from_secs(5);
+const ENDPOINT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Written next to the checkpoint when other mirrors publish a different anchor, kept
+/// until the chain data is rebuilt
+pub const CHECKPOINT_WARNING_FILE: &str = "akron.checkpoint.warning";
+
+/// Tries at downloading a checkpoint before moving on to the next mirror
+const DOWNLOAD_ATTEMPTS: usize = 5;
+
+/// Mirrors the mainnet checkpoint is downloaded from, tried in order. Only the official
+/// one until checkpoints can be verified, other mirrors are only compared with
+pub const CHECKPOINT_MIRRORS: &[&str] = &["https://checkpoint.akron.io/protocol.sdb"];
+
+/// Index of the endpoint answering first, the others are probed at the same time. Any
+/// HTTP answer but a server error counts, endpoints failing or timing out are skipped.
+/// The probes go through `proxy` when set
+pub async fn fastest_endpoint(endpoints: &[String], proxy: Option<String>) -> Option<usize> {
+    let mut client = Client::builder().timeout(ENDPOINT_PROBE_TIMEOUT);
+    if let Some(proxy) = proxy {
+        client = client.proxy(reqwest::Proxy::all(proxy).ok()?);
+    }
+    let client = client.build().ok()?;
+    let mut probes: futures_util::stream::FuturesUnordered<_> = endpoints
+        .iter()
+        .enumerate()
+        .map(|(index, url)| {
+            let request = client.get(url).send();
+            async move { (index, request.await) }
+        })
+        .collect();
+    while let Some((index, result)) = probes.next().await {
+        match result {
+            Ok(response) if !response.status().is_server_error() => return Some(index),
+            Ok(response) => info!(
+                "Endpoint {} answered {}",
+                endpoints[index],
+                response.status()
+            ),
+            Err(e) => info!("Endpoint {} is unreachable: {}", endpoints[index], e),
+        }
+    }
+    None
+}
 
 #[derive(Debug)]
 pub struct Akron {
     stream_tx: mpsc::Sender<AkronCommand>,
-    log_tx: Option<broadcast::Sender<String>>,
+    log_tx: Option<broadcast::Sender<LogLine>>,
+    /// Services restarted after they crashed, spaced loses its loaded wallets then
+    restart_tx: broadcast::Sender<ServiceKind>,
+}
+
+/// State of a service run by akrond
+#[derive(Debug, Clone)]
+pub struct ServiceStatus {
+    pub kind: ServiceKind,
+    pub pid: Option<u32>,
+    pub running: bool,
+    pub uptime: Duration,
+    /// Last error the service logged, only known when logs are captured
+    pub last_error: Option<String>,
+    /// What the service reports about itself, `None` when it didn't answer
+    pub health: Option<ServiceHealth>,
+    /// Times the service stopped on its own since it last ran steadily
+    pub crashes: u32,
+    /// Set once the service crashed more often than its restart policy allows, it
+    /// stays stopped
+    pub gave_up: bool,
+    /// Recent resource usage, oldest first. Only sampled on Linux
+    pub usage: Vec<ResourceSample>,
 }
 
+#[derive(Debug, Clone, Copy)]
 pub struct CheckpointProgress {
     pub downloaded: u64,
     pub total: u64,
 }
 
 enum AkronCommand {
-    SpawnService {
-        kind: ServiceKind,
-        args: Vec<String>,
+    SpawnServices {
+        services: Vec<(ServiceKind, Vec<String>)>,
         oneshot: oneshot::Sender<anyhow::Result<()>>,
     },
     Shutdown {
         kind: ServiceKind,
         oneshot: oneshot::Sender<anyhow::Result<()>>,
     },
+    Status {
+        oneshot: oneshot::Sender<Vec<ServiceStatus>>,
+    },
+    Restart {
+        kind: ServiceKind,
+        oneshot: oneshot::Sender<anyhow::Result<()>>,
+    },
+}
+
+/// Where a service runs
+enum Process {
+    Child(Child),
+    /// Thread of akrond running the service, with the `in-process` feature
+    #[cfg(feature = "in-process")]
+    Thread(std::thread::JoinHandle<()>),
+}
+
+impl Process {
+    fn id(&self) -> Option<u32> {
+        match self {
+            Process::Child(child) => child.id(),
+            #[cfg(feature = "in-process")]
+            Process::Thread(_) => None,
+        }
+    }
+
+    fn is_running(&mut self) -> bool {
+        match self {
+            Process::Child(child) => matches!(child.try_wait(), Ok(None)),
+            #[cfg(feature = "in-process")]
+            Process::Thread(thread) => !thread.is_finished(),
+        }
+    }
+
+    /// Kills a child, a thread can only be asked to stop with a shutdown command
+    async fn kill(&mut self) {
+        match self {
+            Process::Child(child) => _ = child.kill().await,
+            #[cfg(feature = "in-process")]
+            Process::Thread(_) => {}
+        }
+    }
+
+    async fn wait(&mut self) {
+        match self {
+            Process::Child(child) => _ = child.wait().await,
+            #[cfg(feature = "in-process")]
+            Process::Thread(thread) => {
+                while !thread.is_finished() {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        }
+    }
 }
 
 #[allow(dead_code)]
 struct Service {
     pub(crate) kind: ServiceKind,
-    pub(crate) stream: TcpStream,
-    pub(crate) child: Child,
+    pub(crate) stream: ipc::Stream,
+    process: Process,
+    args: Vec<String>,
+    started: Instant,
+    last_error: Arc<Mutex<Option<String>>>,
+    usage: usage::Samples,
+    crashes: u32,
+    /// When the service is restarted after it crashed
+    restart_at: Option<Instant>,
+    gave_up: bool,
 }
 
 impl Akron {
@@ -61,33 +208,56 @@ impl Akron {
             None
         };
 
+        let restart_tx = broadcast::Sender::new(20);
+
         let task_shutdown = shutdown.clone();
         let err_shutdown = shutdown.clone();
         let task_logs = log_tx.clone();
+        let task_restarts = restart_tx.clone();
         std::thread::spawn(move || {
             let result = tokio::runtime::Builder::new_current_thread()
                 .enable_all()
                 .build()
                 .expect("Failed to start Tokio runtime")
-                .block_on(async move { Self::handle_services(rx, task_shutdown, task_logs).await });
+                .block_on(async move {
+                    Self::handle_services(rx, task_shutdown, task_logs, task_restarts).await
+                });
             if let Err(e) = result {
                 error!("Runtime exited with error: {}", e);
                 _ = err_shutdown.send(());
             }
         });
 
-        (Self { stream_tx, log_tx }, shutdown)
+        (
+            Self {
+                stream_tx,
+                log_tx,
+                restart_tx,
+            },
+            shutdown,
+        )
     }
 
-    pub fn subscribe_logs(&self) -> Option<broadcast::Sender<String>> {
+    pub fn subscribe_logs(&self) -> Option<broadcast::Sender<LogLine>> {
         self.log_tx.clone()
     }
 
+    /// Services restarted by akrond on its own after they crashed, restarts asked for
+    /// with [`Akron::restart`] aren't sent
+    pub fn subscribe_restarts(&self) -> broadcast::Sender<ServiceKind> {
+        self.restart_tx.clone()
+    }
+
+    /// Loads the checkpoint from the first of `mirrors` that serves a valid one, reporting
+    /// the mirror used in the logs. Its anchor is compared with the ones published by the
+    /// other mirrors and `compare_with`, which are never downloaded from
     pub async fn load_checkpoint(
         &self,
-        url: &str,
+        mirrors: &[String],
+        compare_with: &[String],
         data_dir: &PathBuf,
-        mut progress: Option<mpsc::Sender<CheckpointProgress>>,
+        progress: Option<broadcast::Sender<CheckpointProgress>>,
+        proxy: Option<String>,
     ) -> anyhow::Result<RootAnchor> {
         tokio::fs::create_dir_all(data_dir).await?;
         let checkpoint_init = data_dir.join("akron.checkpoint.json");
@@ -107,32 +277,296 @@ impl Akron {
 
         let spaces_path = data_dir.join("protocol.sdb");
         // Create HTTP client
-        let client = Client::new();
-        let response = client
-            .get(url)
-            .send()
+        let client = match proxy {
+            Some(proxy) => Client::builder()
+                .proxy(reqwest::Proxy::all(proxy).context("Invalid proxy")?)
+                .build()
+                .context("Failed to create HTTP client")?,
+            None => Client::new(),
+        };
+
+        let mut last_error = anyhow!("No checkpoint mirror configured");
+        for url in mirrors {
+            match Self::download_checkpoint(&client, url, &spaces_path, progress.as_ref()).await {
+                Ok(root_anchor) => {
+                    self.report(format!("Loaded checkpoint from {}", url));
+                    let others: Vec<String> = mirrors
+                        .iter()
+                        .chain(compare_with)
+                        .filter(|other| *other != url)
+                        .cloned()
+                        .collect();
+                    let (confirmations, disagreeing) =
+                        Self::cross_verify(&client, &root_anchor, &others).await;
+                    if confirmations > 0 {
+                        self.report(format!(
+                            "Checkpoint anchor at block {} matched by {} other mirrors",
+                            root_anchor.block.height, confirmations
+                        ));
+                    } else if disagreeing.is_empty() {
+                        self.report(format!(
+                            "The checkpoint anchor at block {} couldn't be compared with another \
+                             source, it's trusted as served by {}",
+                            root_anchor.block.height, url
+                        ));
+                    }
+                    if !disagreeing.is_empty() {
+                        let warning = format!(
+                            "The checkpoint at block {} from {} doesn't match the one served \
+                             by {}. One of them may have been tampered with, rebuild the \
+                             chain data from a mirror you trust",
+                            root_anchor.block.height,
+                            url,
+                            disagreeing.join(", ")
+                        );
+                        self.report(format!("WARNING: {}", warning));
+                        tokio::fs::write(data_dir.join(CHECKPOINT_WARNING_FILE), warning)
+                            .await
+                            .map_err(|e| anyhow!("Could not write checkpoint warning: {}", e))?;
+                    }
+                    let content = serde_json::to_string(&root_anchor)?;
+                    tokio::fs::write(checkpoint_init, content)
+                        .await
+                        .map_err(|e| anyhow!("Could not write checkpoint init file: {}", e))?;
+                    return Ok(root_anchor);
+                }
+                Err(e) => {
+                    self.report(format!("Checkpoint mirror {} failed: {:#}", url, e));
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error.context("No checkpoint mirror could be used"))
+    }
+
+    /// Compares `anchor` with the anchors published by `mirrors`, returning how many list
+    /// the same anchor and the ones listing a different anchor at the same height. Mirrors
+    /// that can't be reached or have no anchor at that height are left out
+    async fn cross_verify(
+        client: &Client,
+        anchor: &RootAnchor,
+        mirrors: &[String],
+    ) -> (usize, Vec<String>) {
+        let checks = mirrors.iter().map(|url| async move {
+            let anchors: Vec<RootAnchor> = client
+                .get(checkpoint::anchors_url(url))
+                .timeout(ENDPOINT_PROBE_TIMEOUT)
+                .send()
+                .await
+                .ok()?
+                .error_for_status()
+                .ok()?
+                .json()
+                .await
+                .ok()?;
+            let published = anchors
+                .into_iter()
+                .find(|a| a.block.height == anchor.block.height)?;
+            Some((url, checkpoint::same_anchor(anchor, &published)))
+        });
+        let results = futures_util::future::join_all(checks).await;
+        let confirmations = results.iter().flatten().filter(|(_, same)| *same).count();
+        let disagreeing = results
+            .into_iter()
+            .flatten()
+            .filter(|(_, same)| !same)
+            .map(|(url, _)| url.clone())
+            .collect();
+        (confirmations, disagreeing)
+    }
+
+    /// Logs `message` and shows it with the captured service logs
+    fn report(&self, message: String) {
+        info!("{}", message);
+        if let Some(log_tx) = self.log_tx.as_ref() {
+            _ = log_tx.send(LogLine::new(message));
+        }
+    }
+
+    async fn download_checkpoint(
+        client: &Client,
+        url: &str,
+        spaces_path: &Path,
+        progress: Option<&broadcast::Sender<CheckpointProgress>>,
+    ) -> anyhow::Result<RootAnchor> {
+        let part_path = spaces_path.with_extension("sdb.part");
+        let resumed = Self::download_resuming(client, url, &part_path, progress).await?;
+        if let Err(e) = Self::check_digest(client, url, &part_path, resumed).await {
+            info!("{:#}, downloading it again", e);
+            let resumed = Self::download_resuming(client, url, &part_path, progress).await?;
+            Self::check_digest(client, url, &part_path, resumed).await?;
+        }
+        tokio::fs::rename(&part_path, spaces_path)
             .await
-            .context("Failed to send HTTP request")?;
+            .context("Could not move the downloaded checkpoint")?;
+        _ = tokio::fs::remove_file(part_path.with_extension("part.info")).await;
 
-        // Check if request was successful
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!(
-                "HTTP request failed with status: {}",
-                response.status()
-            ));
+        let spaces_path = spaces_path.to_path_buf();
+        let root_anchor = tokio::task::spawn_blocking(move || {
+            let tmp = temp_dir().join("anchors");
+            let db = spaces_client::store::Store::open(spaces_path)?;
+            let mut anchors = db.update_anchors(&tmp, 1)?;
+            if anchors.is_empty() {
+                return Err(anyhow::anyhow!("No Anchors found"));
+            }
+            _ = std::fs::remove_file(tmp);
+            Ok(anchors.remove(0))
+        })
+        .await
+        .expect("Could not spawn task")?;
+
+        Ok(root_anchor)
+    }
+
+    /// Downloads `url` into `part_path`, resuming it when it's interrupted. Returns whether
+    /// any part was resumed
+    async fn download_resuming(
+        client: &Client,
+        url: &str,
+        part_path: &Path,
+        progress: Option<&broadcast::Sender<CheckpointProgress>>,
+    ) -> anyhow::Result<bool> {
+        let mut attempt = 1;
+        let mut resumed = false;
+        loop {
+            match Self::download_part(client, url, part_path, progress, &mut resumed).await {
+                Ok(()) => return Ok(resumed),
+                Err(e) if attempt == DOWNLOAD_ATTEMPTS => return Err(e),
+                Err(e) => info!("Checkpoint download interrupted, resuming: {:#}", e),
+            }
+            tokio::time::sleep(Duration::from_secs(2 * attempt as u64)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Checks the downloaded checkpoint against the digest the mirror publishes next to it.
+    /// A resumed download is only kept when there's a digest to check it with, the parts
+    /// may not fit together. The download starts over after a failed check
+    async fn check_digest(
+        client: &Client,
+        url: &str,
+        part_path: &Path,
+        resumed: bool,
+    ) -> anyhow::Result<()> {
+        let digest = match client
+            .get(checkpoint::digest_url(url))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+        {
+            Ok(response) => Some(
+                response
+                    .text()
+                    .await
+                    .context("Could not read the checkpoint digest")?,
+            ),
+            Err(_) => None,
+        };
+        let result = match digest {
+            Some(digest) => {
+                let path = part_path.to_path_buf();
+                tokio::task::spawn_blocking(move || checkpoint::check_digest(&path, &digest))
+                    .await
+                    .expect("Could not spawn task")
+            }
+            None if resumed => Err(anyhow!(
+                "The checkpoint download was resumed and the mirror publishes no digest to \
+                 check it with"
+            )),
+            None => Ok(()),
+        };
+        if result.is_err() {
+            _ = tokio::fs::remove_file(part_path).await;
+            _ = tokio::fs::remove_file(part_path.with_extension("part.info")).await;
         }
+        result
+    }
 
-        // Get content length for progress tracking (if available)
-        let total = response
-            .content_length()
-            .context("Failed to get content length, does the path exist?")?;
+    /// Downloads `url` into `part_path`, continuing a previous partial download of the same
+    /// file. The `.info` marker next to it keeps the URL, size and validator of the file so
+    /// a changed file on the server starts over
+    async fn download_part(
+        client: &Client,
+        url: &str,
+        part_path: &Path,
+        progress: Option<&broadcast::Sender<CheckpointProgress>>,
+        resumed: &mut bool,
+    ) -> anyhow::Result<()> {
+        let marker_path = part_path.with_extension("part.info");
+        let marker = tokio::fs::read_to_string(&marker_path)
+            .await
+            .unwrap_or_default();
+        let mut lines = marker.lines();
+        let resume = match (lines.next(), lines.next(), lines.next()) {
+            (Some(marker_url), Some(total), Some(validator)) if marker_url == url => {
+                let downloaded = tokio::fs::metadata(part_path).await.map_or(0, |m| m.len());
+                total
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|total| downloaded > 0 && downloaded <= *total)
+                    .map(|total| (downloaded, total, validator.to_string()))
+            }
+            _ => None,
+        };
 
-        let mut file = tokio::fs::File::create(spaces_path.clone())
+        let mut request = client.get(url);
+        if let Some((downloaded, _, validator)) = resume.as_ref() {
+            request = request
+                .header(reqwest::header::RANGE, format!("bytes={}-", downloaded))
+                .header(reqwest::header::IF_RANGE, validator);
+        }
+        let response = request
+            .send()
             .await
-            .context("Could not create spaces db file for checkpoint")?;
+            .context("Failed to send HTTP request")?;
+
+        let (mut downloaded, total, mut file) = match (response.status(), resume) {
+            (reqwest::StatusCode::PARTIAL_CONTENT, Some((downloaded, total, _))) => {
+                info!("Resuming checkpoint download at {} bytes", downloaded);
+                *resumed = true;
+                let file = tokio::fs::OpenOptions::new()
+                    .append(true)
+                    .open(part_path)
+                    .await
+                    .context("Could not open partial checkpoint file")?;
+                (downloaded, total, file)
+            }
+            (status, _) if status.is_success() => {
+                // Get content length for progress tracking
+                let total = response
+                    .content_length()
+                    .context("Failed to get content length, does the path exist?")?;
+                // resuming needs a validator to know the file is still the same
+                let validator = [reqwest::header::ETAG, reqwest::header::LAST_MODIFIED]
+                    .iter()
+                    .find_map(|name| response.headers().get(name)?.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                let file = tokio::fs::File::create(part_path)
+                    .await
+                    .context("Could not create spaces db file for checkpoint")?;
+                if !validator.is_empty() {
+                    tokio::fs::write(&marker_path, format!("{}\n{}\n{}", url, total, validator))
+                        .await
+                        .context("Could not write checkpoint download marker")?;
+                } else {
+                    _ = tokio::fs::remove_file(&marker_path).await;
+                }
+                (0, total, file)
+            }
+            (status, _) => {
+                return Err(anyhow::anyhow!(
+                    "HTTP request failed with status: {}",
+                    status
+                ))
+            }
+        };
+
+        if let Some(progress) = progress {
+            _ = progress.send(CheckpointProgress { downloaded, total });
+        }
 
         // Download and write file in chunks
-        let mut downloaded = 0;
         let mut stream = response.bytes_stream();
         while let Some(chunk) = stream.next().await {
             let chunk = chunk.context("Failed to read chunk")?;
@@ -141,48 +575,79 @@ impl Akron {
                 .context("Failed to write chunk to file")?;
             downloaded += chunk.len() as u64;
 
-            if let Some(progress) = progress.as_mut() {
-                _ = progress
-                    .send(CheckpointProgress { downloaded, total })
-                    .await;
+            if let Some(progress) = progress {
+                _ = progress.send(CheckpointProgress { downloaded, total });
             }
         }
 
         // Ensure file is fully written
         file.flush().await.context("Failed to flush file")?;
-        let root_anchor = tokio::task::spawn_blocking(move || {
-            let tmp = temp_dir().join("anchors");
-            let db = spaces_client::store::Store::open(spaces_path)?;
-            let mut anchors = db.update_anchors(&tmp, 1)?;
-            if anchors.is_empty() {
-                return Err(anyhow::anyhow!("No Anchors found"));
-            }
+        if downloaded != total {
+            return Err(anyhow!(
+                "Checkpoint download incomplete, got {} of {} bytes",
+                downloaded,
+                total
+            ));
+        }
+        Ok(())
+    }
+
+    /// Copies the spaces database at `db_path` into `destination` as a checkpoint other
+    /// installs can load, with a manifest of its root anchors. spaced must not be running
+    pub async fn export_checkpoint(
+        db_path: &Path,
+        destination: &Path,
+    ) -> anyhow::Result<RootAnchor> {
+        tokio::fs::create_dir_all(destination).await?;
+        let spaces_path = destination.join("protocol.sdb");
+        tokio::fs::copy(db_path, &spaces_path)
+            .await
+            .context("Could not copy the spaces database")?;
+        let anchors = tokio::task::spawn_blocking(move || {
+            let tmp = temp_dir().join("export_anchors");
+            let anchors =
+                spaces_client::store::Store::open(spaces_path.clone())?.update_anchors(&tmp, 1)?;
             _ = std::fs::remove_file(tmp);
-            Ok(anchors.remove(0))
+            // mirrors publish it for resumed downloads to be checked
+            let digest = format!("{}  protocol.sdb\n", checkpoint::file_hash(&spaces_path)?);
+            std::fs::write(spaces_path.with_extension("sdb.sha256"), digest)
+                .context("Could not write the checkpoint digest")?;
+            anyhow::Ok(anchors)
         })
         .await
         .expect("Could not spawn task")?;
-
-        let content = serde_json::to_string(&root_anchor)?;
-        tokio::fs::write(checkpoint_init, content)
-            .await
-            .map_err(|e| anyhow!("Could not write checkpoint init file: {}", e))?;
-
+        let root_anchor = anchors
+            .first()
+            .cloned()
+            .ok_or_else(|| anyhow!("No Anchors found"))?;
+        tokio::fs::write(
+            destination.join("protocol.anchors.json"),
+            serde_json::to_string_pretty(&anchors)?,
+        )
+        .await
+        .context("Could not write the anchors manifest")?;
         Ok(root_anchor)
     }
 
     pub async fn start(&self, kind: ServiceKind, args: Vec<String>) -> anyhow::Result<()> {
+        self.start_all(vec![(kind, args)]).await
+    }
+
+    /// Starts the services at the same time, none is left running if one fails to start.
+    /// Services depending on another one must retry until it answers
+    pub async fn start_all(&self, services: Vec<(ServiceKind, Vec<String>)>) -> anyhow::Result<()> {
+        let names: Vec<&str> = services.iter().map(|(kind, _)| kind.as_str()).collect();
+        let names = names.join(", ");
         let (tx, rx) = oneshot::channel();
         self.stream_tx
-            .send(AkronCommand::SpawnService {
-                kind,
-                args,
+            .send(AkronCommand::SpawnServices {
+                services,
                 oneshot: tx,
             })
             .await
-            .map_err(|e| anyhow::anyhow!("Could not spawn service {}: {}", kind.as_str(), e))?;
+            .map_err(|e| anyhow::anyhow!("Could not spawn services {}: {}", names, e))?;
         rx.await
-            .map_err(|e| anyhow::anyhow!("Could not spawn service {}: {}", kind.as_str(), e))?
+            .map_err(|e| anyhow::anyhow!("Could not spawn services {}: {}", names, e))?
     }
 
     pub async fn shutdown(&self, kind: ServiceKind) -> anyhow::Result<()> {
@@ -195,14 +660,35 @@ impl Akron {
             .map_err(|e| anyhow::anyhow!("Could not shutdown service {}: {}", kind.as_str(), e))?
     }
 
+    /// Stops the service and starts it again with the arguments it was started with
+    pub async fn restart(&self, kind: ServiceKind) -> anyhow::Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.stream_tx
+            .send(AkronCommand::Restart { kind, oneshot: tx })
+            .await
+            .map_err(|e| anyhow::anyhow!("Could not restart service {}: {}", kind.as_str(), e))?;
+        rx.await
+            .map_err(|e| anyhow::anyhow!("Could not restart service {}: {}", kind.as_str(), e))?
+    }
+
+    /// Status of the running services
+    pub async fn status(&self) -> anyhow::Result<Vec<ServiceStatus>> {
+        let (tx, rx) = oneshot::channel();
+        self.stream_tx
+            .send(AkronCommand::Status { oneshot: tx })
+            .await
+            .map_err(|e| anyhow::anyhow!("Could not get the services status: {}", e))?;
+        rx.await
+            .map_err(|e| anyhow::anyhow!("Could not get the services status: {}", e))
+    }
+
     async fn handle_services(
         mut rx: mpsc::Receiver<AkronCommand>,
         shutdown: broadcast::Sender<()>,
-        logs_tx: Option<broadcast::Sender<String>>,
+        logs_tx: Option<broadcast::Sender<LogLine>>,
+        restart_tx: broadcast::Sender<ServiceKind>,
     ) -> anyhow::Result<()> {
-        let listener = TcpListener::bind("127.0.0.1:0")
-            .await
-            .context("Failed to bind TCP listener")?;
+        let mut listener = ipc::Listener::bind().context("Failed to bind services listener")?;
 
         let mut services = Vec::new();
         let mut interval = interval(Duration::from_secs(1));
@@ -211,14 +697,10 @@ impl Akron {
         loop {
             select! {
                 Some(cmd) = rx.recv() => {
-                   Self::handle_remote_commands(&listener, &mut services, cmd, &logs_tx).await?;
+                   Self::handle_remote_commands(&mut listener, &mut services, cmd, &logs_tx).await?;
                 }
                 _ = interval.tick() => {
-                    if Self::stopped(&mut services).await {
-                        info!("Shutting down one of processes stopped");
-                        _ = shutdown.send(());
-                        return Ok(());
-                    }
+                    Self::supervise(&mut listener, &mut services, &logs_tx, &restart_tx).await;
                 }
                 _ = shutdown_recv.recv() => {
                     info!("Received shutdown signal");
@@ -229,25 +711,26 @@ impl Akron {
     }
 
     async fn handle_remote_commands(
-        listener: &TcpListener,
+        listener: &mut ipc::Listener,
         services: &mut Vec<Service>,
         cmd: AkronCommand,
-        logs_tx: &Option<broadcast::Sender<String>>,
+        logs_tx: &Option<broadcast::Sender<LogLine>>,
     ) -> anyhow::Result<()> {
         match cmd {
-            AkronCommand::SpawnService {
-                kind,
-                args,
+            AkronCommand::SpawnServices {
+                services: new_services,
                 oneshot,
             } => {
-                match Self::handle_start_service(&listener, kind, args, logs_tx.clone()).await {
-                    Ok(service) => {
-                        // Remove existing ones
-                        let pos = services.iter().position(|s| s.kind == service.kind);
-                        if let Some(pos) = pos {
-                            services.remove(pos).shutdown().await;
+                match Self::handle_start_services(listener, new_services, logs_tx.clone()).await {
+                    Ok(started) => {
+                        for service in started {
+                            // Remove existing ones
+                            let pos = services.iter().position(|s| s.kind == service.kind);
+                            if let Some(pos) = pos {
+                                services.remove(pos).shutdown().await;
+                            }
+                            services.push(service);
                         }
-                        services.push(service);
                         _ = oneshot.send(Ok(()));
                     }
                     Err(err) => {
@@ -262,18 +745,214 @@ impl Akron {
                 }
                 _ = oneshot.send(Ok(()));
             }
+            AkronCommand::Restart { kind, oneshot } => {
+                let Some(pos) = services.iter().position(|s| s.kind == kind) else {
+                    _ = oneshot.send(Err(anyhow!("Service {} is not running", kind.as_str())));
+                    return Ok(());
+                };
+                let mut service = services.remove(pos);
+                service.shutdown().await;
+                match Self::handle_start_service(listener, kind, service.args, logs_tx.clone())
+                    .await
+                {
+                    Ok(service) => {
+                        services.push(service);
+                        _ = oneshot.send(Ok(()));
+                    }
+                    Err(err) => {
+                        _ = oneshot.send(Err(err));
+                    }
+                }
+            }
+            AkronCommand::Status { oneshot } => {
+                let mut status = Vec::with_capacity(services.len());
+                for service in services.iter_mut() {
+                    let running = service.restart_at.is_none() && service.process.is_running();
+                    let health = if running {
+                        service.health().await
+                    } else {
+                        None
+                    };
+                    status.push(ServiceStatus {
+                        kind: service.kind,
+                        pid: service.process.id(),
+                        running,
+                        uptime: service.started.elapsed(),
+                        last_error: service.last_error.lock().unwrap().clone(),
+                        health,
+                        crashes: service.crashes,
+                        gave_up: service.gave_up,
+                        usage: service.usage.lock().unwrap().iter().copied().collect(),
+                    });
+                }
+                _ = oneshot.send(status);
+            }
         }
 
         Ok(())
     }
 
     async fn handle_start_service(
-        listener: &TcpListener,
+        listener: &mut ipc::Listener,
         kind: ServiceKind,
         args: Vec<String>,
-        log_tx: Option<broadcast::Sender<String>>,
+        log_tx: Option<broadcast::Sender<LogLine>>,
     ) -> anyhow::Result<Service> {
-        let addr = listener.local_addr()?.to_string();
+        let mut services =
+            Self::handle_start_services(listener, vec![(kind, args)], log_tx).await?;
+        Ok(services.remove(0))
+    }
+
+    /// Launches all the services before waiting for any of them to connect, so they start
+    /// at the same time. If one fails, the others are stopped again
+    async fn handle_start_services(
+        listener: &mut ipc::Listener,
+        services: Vec<(ServiceKind, Vec<String>)>,
+        log_tx: Option<broadcast::Sender<LogLine>>,
+    ) -> anyhow::Result<Vec<Service>> {
+        let addr = listener.addr();
+        let mut launched = Vec::with_capacity(services.len());
+        for (kind, args) in services {
+            match Launched::launch(&addr, kind, args, log_tx.clone()) {
+                Ok(service) => launched.push(service),
+                Err(e) => {
+                    for mut service in launched {
+                        service.process.kill().await;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        match accept_children(listener, &launched).await {
+            Ok(streams) => Ok(launched
+                .into_iter()
+                .zip(streams)
+                .map(|(launched, stream)| Service {
+                    kind: launched.kind,
+                    stream,
+                    process: launched.process,
+                    args: launched.args,
+                    started: Instant::now(),
+                    last_error: launched.last_error,
+                    usage: launched.usage,
+                    crashes: 0,
+                    restart_at: None,
+                    gave_up: false,
+                })
+                .collect()),
+            Err(e) => {
+                for mut service in launched {
+                    service.process.kill().await;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Restarts the services that stopped on their own, following their restart policy
+    async fn supervise(
+        listener: &mut ipc::Listener,
+        services: &mut [Service],
+        logs_tx: &Option<broadcast::Sender<LogLine>>,
+        restart_tx: &broadcast::Sender<ServiceKind>,
+    ) {
+        for service in services.iter_mut() {
+            if service.gave_up {
+                continue;
+            }
+            let Some(restart_at) = service.restart_at else {
+                if !service.ping().await {
+                    service.crashed(logs_tx).await;
+                }
+                continue;
+            };
+            if Instant::now() < restart_at {
+                continue;
+            }
+            let kind = service.kind;
+            // a thread can't be killed, another one would run next to it
+            if service.process.is_running() {
+                service.gave_up = true;
+                service.restart_at = None;
+                let message = format!(
+                    "{} is not responding and can't be stopped, restart Akron",
+                    kind.as_str()
+                );
+                error!("{}", message);
+                if let Some(logs_tx) = logs_tx {
+                    _ = logs_tx.send(LogLine {
+                        service: Some(kind),
+                        level: LogLevel::Error,
+                        ..LogLine::new(message.clone())
+                    });
+                }
+                *service.last_error.lock().unwrap() = Some(message);
+                continue;
+            }
+            match Self::handle_start_service(listener, kind, service.args.clone(), logs_tx.clone())
+                .await
+            {
+                Ok(mut restarted) => {
+                    info!("Restarted {}", kind.as_str());
+                    restarted.crashes = service.crashes;
+                    *service = restarted;
+                    _ = restart_tx.send(kind);
+                }
+                Err(e) => {
+                    error!("Could not restart {}: {}", kind.as_str(), e);
+                    service.started = Instant::now();
+                    service.crashed(logs_tx).await;
+                }
+            }
+        }
+    }
+}
+
+/// Service started that didn't connect to akrond yet
+struct Launched {
+    kind: ServiceKind,
+    args: Vec<String>,
+    /// Presented by the service when it connects
+    token: String,
+    process: Process,
+    last_error: Arc<Mutex<Option<String>>>,
+    usage: usage::Samples,
+}
+
+impl Launched {
+    fn launch(
+        addr: &str,
+        kind: ServiceKind,
+        args: Vec<String>,
+        log_tx: Option<broadcast::Sender<LogLine>>,
+    ) -> anyhow::Result<Self> {
+        let token = hex::encode(rand::random::<[u8; 16]>());
+        #[cfg(feature = "in-process")]
+        if matches!(kind, ServiceKind::Spaces | ServiceKind::Yuki) {
+            let runner = runner::ServiceRunner::in_process(
+                kind,
+                addr.to_string(),
+                token.clone(),
+                args.clone(),
+            );
+            let thread = std::thread::Builder::new()
+                .name(format!("akrond-{}", kind.as_str()))
+                .spawn(move || {
+                    if let Err(e) = runner.run() {
+                        error!("{}: {}", kind.as_str(), e);
+                    }
+                })
+                .context(format!("Failed to start service {}", kind.as_str()))?;
+            return Ok(Self {
+                kind,
+                args,
+                token,
+                process: Process::Thread(thread),
+                last_error: Arc::new(Mutex::new(None)),
+                usage: usage::Samples::default(),
+            });
+        }
         let mut command = Command::new(env::args().next().context("No program name")?);
 
         #[cfg(unix)]
@@ -283,8 +962,9 @@ impl Akron {
             .arg("--service")
             .arg(kind.as_str())
             .arg("--attach")
-            .arg(&addr)
-            .args(&args);
+            .arg(addr)
+            .args(&args)
+            .env(ATTACH_TOKEN_ENV, &token);
 
         if log_tx.is_some() {
             command
@@ -302,60 +982,167 @@ impl Akron {
             .spawn()
             .context(format!("Failed to spawn child service {}", kind.as_str()))?;
 
+        let last_error = Arc::new(Mutex::new(None));
         if let Some(log_tx) = log_tx {
             let stdout = child.stdout.take().unwrap();
             let stdout_logs = log_tx.clone();
-            tokio::spawn(async move { redirect_logs(stdout_logs, stdout).await });
+            let stdout_error = last_error.clone();
+            tokio::spawn(async move {
+                redirect_logs(stdout_logs, kind, LogStream::Stdout, stdout, stdout_error).await
+            });
             let stderr = child.stderr.take().unwrap();
-            tokio::spawn(async move { redirect_logs(log_tx, stderr).await });
+            let stderr_error = last_error.clone();
+            tokio::spawn(async move {
+                redirect_logs(log_tx, kind, LogStream::Stderr, stderr, stderr_error).await
+            });
         }
 
-        // Accept connection from the child
-        let (stream, _) = listener
-            .accept()
-            .await
-            .context("Failed to accept child connection")?;
+        let usage = usage::Samples::default();
+        #[cfg(target_os = "linux")]
+        if let Some(pid) = child.id() {
+            tokio::spawn(usage::sample(pid, usage.clone()));
+        }
 
-        Ok(Service {
+        Ok(Self {
             kind,
-            stream,
-            child,
+            args,
+            token,
+            process: Process::Child(child),
+            last_error,
+            usage,
         })
     }
+}
 
-    async fn stopped(pipes: &mut Vec<Service>) -> bool {
-        for pipe in pipes {
-            if !pipe.ping().await {
-                return true;
-            }
+impl Service {
+    /// Schedules a restart of the stopped service, or gives up on it
+    async fn crashed(&mut self, logs_tx: &Option<broadcast::Sender<LogLine>>) {
+        self.process.kill().await;
+        let policy = self.kind.restart_policy();
+        if self.started.elapsed() >= policy.reset_after {
+            self.crashes = 0;
         }
-        false
+        self.crashes += 1;
+        let message = if self.crashes > policy.max_restarts {
+            self.gave_up = true;
+            self.restart_at = None;
+            format!(
+                "{} stopped {} times, not restarting it",
+                self.kind.as_str(),
+                self.crashes
+            )
+        } else {
+            let delay = policy.delay(self.crashes);
+            self.restart_at = Some(Instant::now() + delay);
+            format!(
+                "{} stopped, restarting it in {} seconds",
+                self.kind.as_str(),
+                delay.as_secs()
+            )
+        };
+        error!("{}", message);
+        if let Some(logs_tx) = logs_tx {
+            _ = logs_tx.send(LogLine {
+                service: Some(self.kind),
+                level: LogLevel::Error,
+                ..LogLine::new(message.clone())
+            });
+        }
+        *self.last_error.lock().unwrap() = Some(message);
     }
-}
 
-impl Service {
-    pub async fn ping(&mut self) -> bool {
-        self.stream
-            .write(&[ServiceCommand::Ping.to_byte()])
+    async fn request(&mut self, command: ServiceCommand) -> std::io::Result<ServiceResponse> {
+        write_frame(&mut self.stream, &command).await?;
+        tokio::time::timeout(SERVICE_REPLY_TIMEOUT, read_frame(&mut self.stream))
             .await
-            .is_ok()
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "no answer"))?
+    }
+
+    pub async fn ping(&mut self) -> bool {
+        matches!(
+            self.request(ServiceCommand::Ping).await,
+            Ok(ServiceResponse::Pong)
+        )
     }
+
+    pub async fn health(&mut self) -> Option<ServiceHealth> {
+        match self.request(ServiceCommand::Status).await {
+            Ok(ServiceResponse::Status(health)) => Some(health),
+            _ => None,
+        }
+    }
+
+    /// Asks the service to stop and waits for it to exit so its ports and databases
+    /// are free when it's started again
     pub async fn shutdown(&mut self) -> bool {
-        self.stream
-            .write(&[ServiceCommand::Shutdown.to_byte()])
+        let sent = write_frame(&mut self.stream, &ServiceCommand::Shutdown)
             .await
-            .is_ok()
+            .is_ok();
+        if !sent
+            || tokio::time::timeout(SHUTDOWN_TIMEOUT, self.process.wait())
+                .await
+                .is_err()
+        {
+            self.process.kill().await;
+        }
+        sent
     }
 }
 
+/// Accepts the connections of the launched services, in their order, dropping the ones
+/// without a token. Services may crash before they connect
+async fn accept_children(
+    listener: &mut ipc::Listener,
+    launched: &[Launched],
+) -> anyhow::Result<Vec<ipc::Stream>> {
+    let mut streams: Vec<Option<ipc::Stream>> = launched.iter().map(|_| None).collect();
+    let accept = async {
+        while streams.iter().any(Option::is_none) {
+            let mut stream = listener
+                .accept()
+                .await
+                .context("Failed to accept child connection")?;
+            let presented =
+                tokio::time::timeout(ATTACH_TIMEOUT, read_frame::<String>(&mut stream)).await;
+            let index = match presented {
+                Ok(Ok(presented)) => launched.iter().position(|l| l.token == presented),
+                _ => None,
+            };
+            match index {
+                Some(index) => streams[index] = Some(stream),
+                None => error!("Rejected a connection to the services listener without the token"),
+            }
+        }
+        anyhow::Ok(())
+    };
+    let Ok(accepted) = tokio::time::timeout(SERVICE_REPLY_TIMEOUT, accept).await else {
+        let missing: Vec<&str> = launched
+            .iter()
+            .zip(&streams)
+            .filter(|(_, stream)| stream.is_none())
+            .map(|(launched, _)| launched.kind.as_str())
+            .collect();
+        return Err(anyhow!("Service {} did not connect", missing.join(", ")));
+    };
+    accepted?;
+    Ok(streams.into_iter().flatten().collect())
+}
+
 async fn redirect_logs<R: tokio::io::AsyncRead + Unpin + Send + 'static>(
-    tx: broadcast::Sender<String>,
+    tx: broadcast::Sender<LogLine>,
+    kind: ServiceKind,
+    stream: LogStream,
     reader: R,
+    last_error: Arc<Mutex<Option<String>>>,
 ) {
     // remove colors
     let r = regex::Regex::new(r"\x1b\[[0-9;]*[mK]").expect("regex");
     let mut lines = BufReader::new(reader).lines();
     while let Ok(Some(line)) = lines.next_line().await {
-        let _ = tx.send(r.replace_all(&line, "").into_owned());
+        let line = r.replace_all(&line, "").into_owned();
+        if line.contains("ERROR") {
+            *last_error.lock().unwrap() = Some(line.clone());
+        }
+        let _ = tx.send(LogLine::service(kind, stream, line));
     }
 }
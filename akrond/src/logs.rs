@@ -0,0 +1,255 @@
+use crate::runner::ServiceKind;
+use spaces_client::jsonrpsee::core::__reexports::serde_json::json;
+use std::fmt;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+/// How log lines are written to the terminal and the log file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line, for journald, ELK and the like
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("Unknown log format {}, expected text or json", s)),
+        }
+    }
+}
+
+/// Output of a service a line was printed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// Severity of a log line, ordered from the least severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [Self::Debug, Self::Info, Self::Warn, Self::Error];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Error => "error",
+        }
+    }
+
+    /// Level printed by the services' loggers in the line, e.g. `[.. ERROR yuki] ..`
+    fn parse(text: &str) -> Option<Self> {
+        text.split_whitespace().take(4).find_map(|word| {
+            match word.trim_matches(|c: char| !c.is_alphabetic()) {
+                "TRACE" | "DEBUG" => Some(Self::Debug),
+                "INFO" => Some(Self::Info),
+                "WARN" | "WARNING" => Some(Self::Warn),
+                "ERROR" => Some(Self::Error),
+                _ => None,
+            }
+        })
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Debug => "Debug",
+            Self::Info => "Info",
+            Self::Warn => "Warning",
+            Self::Error => "Error",
+        })
+    }
+}
+
+// civil dates, see http://howardhinnant.github.io/date_algorithms.html
+
+/// Days since the epoch of a proleptic Gregorian date
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // years start in March, so the leap day is the last of the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Year, month and day of the days since the epoch
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// Line of the captured logs
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    /// Service that printed the line, `None` for messages of akrond and the wallet
+    pub service: Option<ServiceKind>,
+    pub stream: LogStream,
+    pub level: LogLevel,
+    pub time: SystemTime,
+    pub text: String,
+}
+
+impl LogLine {
+    /// Message of akrond or the wallet itself
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            service: None,
+            stream: LogStream::Stdout,
+            level: LogLevel::Info,
+            time: SystemTime::now(),
+            text: text.into(),
+        }
+    }
+
+    /// Error of akrond or the wallet itself
+    pub fn error(text: impl Into<String>) -> Self {
+        Self {
+            level: LogLevel::Error,
+            ..Self::new(text)
+        }
+    }
+
+    pub(crate) fn service(kind: ServiceKind, stream: LogStream, text: String) -> Self {
+        Self {
+            service: Some(kind),
+            stream,
+            // lines without a level are usually panics and errors printed directly
+            level: LogLevel::parse(&text).unwrap_or(match stream {
+                LogStream::Stdout => LogLevel::Info,
+                LogStream::Stderr => LogLevel::Error,
+            }),
+            time: SystemTime::now(),
+            text,
+        }
+    }
+
+    /// Name of where the line comes from
+    pub fn source(&self) -> &'static str {
+        self.service.map_or("akron", |kind| kind.as_str())
+    }
+
+    /// Time of the line as `HH:MM:SS` in UTC
+    pub fn clock(&self) -> String {
+        let seconds = self
+            .time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        format!(
+            "{:02}:{:02}:{:02}",
+            seconds / 3600 % 24,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    }
+
+    /// Time of the line in RFC 3339, e.g. `2025-01-31T12:00:00.000Z`
+    pub fn timestamp(&self) -> String {
+        let since_epoch = self
+            .time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        let (year, month, day) = civil_from_days((since_epoch.as_secs() / 86400) as i64);
+        format!(
+            "{:04}-{:02}-{:02}T{}.{:03}Z",
+            year,
+            month,
+            day,
+            self.clock(),
+            since_epoch.subsec_millis()
+        )
+    }
+
+    pub fn format(&self, format: LogFormat) -> String {
+        match format {
+            LogFormat::Text => self.to_string(),
+            LogFormat::Json => json!({
+                "timestamp": self.timestamp(),
+                "service": self.source(),
+                "stream": match self.stream {
+                    LogStream::Stdout => "stdout",
+                    LogStream::Stderr => "stderr",
+                },
+                "level": self.level.as_str(),
+                "message": self.text,
+            })
+            .to_string(),
+        }
+    }
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}] {}", self.clock(), self.source(), self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn line_at(millis: u64) -> LogLine {
+        LogLine {
+            service: None,
+            stream: LogStream::Stdout,
+            level: LogLevel::Info,
+            time: SystemTime::UNIX_EPOCH + Duration::from_millis(millis),
+            text: String::new(),
+        }
+    }
+
+    #[test]
+    fn timestamp() {
+        assert_eq!(line_at(0).timestamp(), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            line_at(1_738_324_800_123).timestamp(),
+            "2025-01-31T12:00:00.123Z"
+        );
+        assert_eq!(
+            line_at(1_709_164_800_000).timestamp(),
+            "2024-02-29T00:00:00.000Z"
+        );
+    }
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        for days in [-1, 0, 59, 11_016, 11_017, 19_782, 100_000] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+}
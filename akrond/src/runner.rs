@@ -1,15 +1,29 @@
+use crate::services::bitcoind;
+use crate::services::mirror;
 use crate::services::spaces;
 use crate::services::yuki;
+use crate::{health, ipc};
 use anyhow::Context;
 use log::info;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spaces_client::jsonrpsee::core::__reexports::serde_json;
+use std::io::{Error, ErrorKind};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::io::AsyncReadExt;
-use tokio::net::TcpStream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::select;
 use tokio::sync::broadcast;
 
+/// Environment variable with the one-time token a service presents when it attaches
+pub(crate) const ATTACH_TOKEN_ENV: &str = "AKROND_ATTACH_TOKEN";
+
+/// Messages between akrond and its services larger than this are rejected
+const MAX_FRAME_SIZE: u32 = 1024 * 1024;
+
 pub struct ServiceRunner {
     attach_addr: Option<String>,
+    attach_token: String,
     kind: ServiceKind,
     args: Vec<String>,
 }
@@ -18,23 +32,95 @@ pub struct ServiceRunner {
 pub enum ServiceKind {
     Spaces,
     Yuki,
+    Bitcoind,
+    Mirror,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// Sent by akrond to a service, each is answered with a [`ServiceResponse`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ServiceCommand {
     Ping,
     Shutdown,
+    Status,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceResponse {
+    Pong,
+    ShuttingDown,
+    Status(ServiceHealth),
+}
+
+/// Whether a running service does its work, as opposed to only being alive
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServiceHealth {
+    /// Answers its RPC
+    pub ready: bool,
+    /// Block the service synced to
+    pub height: Option<u32>,
+    pub last_error: Option<String>,
+}
+
+/// Writes the message as its JSON length, a big endian u32, followed by the JSON
+pub(crate) async fn write_frame<T: Serialize>(
+    stream: &mut (impl AsyncWrite + Unpin),
+    message: &T,
+) -> std::io::Result<()> {
+    let json = serde_json::to_vec(message).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    stream.write_all(&(json.len() as u32).to_be_bytes()).await?;
+    stream.write_all(&json).await
+}
+
+/// Reads a message written by [`write_frame`]. A message that can't be parsed is
+/// consumed and reported as `InvalidData`
+pub(crate) async fn read_frame<T: DeserializeOwned>(
+    stream: &mut (impl AsyncRead + Unpin),
+) -> std::io::Result<T> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len).await?;
+    let len = u32::from_be_bytes(len);
+    if len > MAX_FRAME_SIZE {
+        return Err(Error::new(ErrorKind::InvalidData, "message too large"));
+    }
+    let mut json = vec![0u8; len as usize];
+    stream.read_exact(&mut json).await?;
+    serde_json::from_slice(&json).map_err(|e| Error::new(ErrorKind::InvalidData, e))
 }
 
 impl ServiceRunner {
+    /// Service attaching to akrond from the same process, with the token it was given
+    #[cfg(feature = "in-process")]
+    pub(crate) fn in_process(
+        kind: ServiceKind,
+        attach_addr: String,
+        attach_token: String,
+        args: Vec<String>,
+    ) -> Self {
+        Self {
+            attach_addr: Some(attach_addr),
+            attach_token,
+            kind,
+            // in place of the program name of a child
+            args: std::iter::once(format!("akrond-{}", kind.as_str()))
+                .chain(args)
+                .collect(),
+        }
+    }
+
     pub fn parse(args: &[String]) -> Option<Self> {
         let mut args = args.iter().cloned().collect();
         let service = read_arg("--service", &mut args)?;
         let kind = ServiceKind::from_str(&service)?;
         let attach_addr = read_arg("--attach", &mut args);
+        // read before any thread starts, and kept from the processes the service runs
+        let attach_token = std::env::var(ATTACH_TOKEN_ENV).unwrap_or_default();
+        std::env::remove_var(ATTACH_TOKEN_ENV);
 
         Some(Self {
             attach_addr,
+            attach_token,
             kind,
             args,
         })
@@ -47,7 +133,16 @@ impl ServiceRunner {
             let sigterm = tokio::signal::ctrl_c();
             let shutdown = broadcast::Sender::new(1);
             if let Some(addr) = self.attach_addr {
-                ServiceRunner::attach(self.kind, addr, shutdown.clone()).await?;
+                let health = Arc::new(Mutex::new(ServiceHealth::default()));
+                tokio::spawn(health::watch(self.kind, self.args.clone(), health.clone()));
+                ServiceRunner::attach(
+                    self.kind,
+                    addr,
+                    &self.attach_token,
+                    shutdown.clone(),
+                    health,
+                )
+                .await?;
             }
 
             let sigterm_shutdown = shutdown.clone();
@@ -59,6 +154,8 @@ impl ServiceRunner {
             match self.kind {
                 ServiceKind::Spaces => spaces::main(self.args, shutdown).await?,
                 ServiceKind::Yuki => yuki::main(self.args, shutdown).await?,
+                ServiceKind::Bitcoind => bitcoind::main(self.args, shutdown).await?,
+                ServiceKind::Mirror => mirror::main(self.args, shutdown).await?,
             }
             Ok(())
         })
@@ -67,15 +164,19 @@ impl ServiceRunner {
     pub async fn attach(
         kind: ServiceKind,
         addr: String,
+        token: &str,
         shutdown: broadcast::Sender<()>,
+        health: Arc<Mutex<ServiceHealth>>,
     ) -> anyhow::Result<()> {
-        let mut stream = TcpStream::connect(&addr)
+        let mut stream = ipc::connect(&addr)
             .await
             .context("Failed to connect to parent")?;
+        write_frame(&mut stream, &token)
+            .await
+            .context("Failed to authenticate with parent")?;
         // Spawn the reader/shutdown watcher
         tokio::task::spawn(async move {
             let mut shutdown_rx = shutdown.subscribe();
-            let mut buf = [0u8; 1];
 
             loop {
                 select! {
@@ -83,33 +184,38 @@ impl ServiceRunner {
                       info!("{}: shutdown requested, exiting …", kind.as_str());
                       return;
                   }
-                  result = stream.read(&mut buf) => match result {
-                      Ok(0) => {
-                          info!("{}: parent disconnected, exiting …", kind.as_str());
-                          let _ = shutdown.send(());
-                          return;
-                      }
-                      Err(e) => {
-                          info!("{}: read error {:?}, exiting …", kind.as_str(), e);
+                  result = read_frame(&mut stream) => {
+                      let response = match result {
+                          Ok(ServiceCommand::Ping) => ServiceResponse::Pong,
+                          Ok(ServiceCommand::Status) => {
+                              ServiceResponse::Status(health.lock().unwrap().clone())
+                          }
+                          Ok(ServiceCommand::Shutdown) => {
+                              info!("{}: shutdown command requested", kind.as_str());
+                              _ = write_frame(&mut stream, &ServiceResponse::ShuttingDown).await;
+                              let _ = shutdown.send(());
+                              return;
+                          }
+                          Err(e) if e.kind() == ErrorKind::InvalidData => {
+                              info!("{}: unknown command {}, ignoring", kind.as_str(), e);
+                              continue;
+                          }
+                          Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                              info!("{}: parent disconnected, exiting …", kind.as_str());
+                              let _ = shutdown.send(());
+                              return;
+                          }
+                          Err(e) => {
+                              info!("{}: read error {:?}, exiting …", kind.as_str(), e);
+                              let _ = shutdown.send(());
+                              return;
+                          }
+                      };
+                      if let Err(e) = write_frame(&mut stream, &response).await {
+                          info!("{}: write error {:?}, exiting …", kind.as_str(), e);
                           let _ = shutdown.send(());
                           return;
                       }
-                      Ok(_) => {
-                          match ServiceCommand::from_byte(buf[0]) {
-                              Some(ServiceCommand::Ping) => {
-                                  // do your ping logic
-                                  tokio::time::sleep(Duration::from_millis(20)).await;
-                              }
-                              Some(ServiceCommand::Shutdown) => {
-                                  info!("{}: shutdown command requested", kind.as_str());
-                                  let _ = shutdown.send(());
-                                  return;
-                              }
-                              None => {
-                                  info!("{}: unknown command {}, ignoring", kind.as_str(), buf[0]);
-                              }
-                          }
-                      }
                   }
                 }
             }
@@ -119,11 +225,52 @@ impl ServiceRunner {
     }
 }
 
+/// How a service is restarted when it stops on its own
+#[derive(Debug, Clone, Copy)]
+pub struct RestartPolicy {
+    /// Restarts before giving up on the service
+    pub max_restarts: u32,
+    /// Wait before the first restart, doubled for each following one
+    pub first_delay: Duration,
+    pub max_delay: Duration,
+    /// Crashes are forgotten once the service ran this long
+    pub reset_after: Duration,
+}
+
+impl RestartPolicy {
+    /// Wait before the restart following the given number of crashes
+    pub fn delay(&self, crashes: u32) -> Duration {
+        self.first_delay
+            .saturating_mul(2u32.saturating_pow(crashes.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
 impl ServiceKind {
-    pub(crate) fn as_str(&self) -> &'static str {
+    pub fn restart_policy(&self) -> RestartPolicy {
+        match self {
+            // bitcoind replays its blocks after a crash, which takes a while
+            ServiceKind::Bitcoind => RestartPolicy {
+                max_restarts: 3,
+                first_delay: Duration::from_secs(10),
+                max_delay: Duration::from_secs(120),
+                reset_after: Duration::from_secs(30 * 60),
+            },
+            ServiceKind::Spaces | ServiceKind::Yuki | ServiceKind::Mirror => RestartPolicy {
+                max_restarts: 5,
+                first_delay: Duration::from_secs(2),
+                max_delay: Duration::from_secs(60),
+                reset_after: Duration::from_secs(10 * 60),
+            },
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
         match self {
             ServiceKind::Spaces => "spaces",
             ServiceKind::Yuki => "yuki",
+            ServiceKind::Bitcoind => "bitcoind",
+            ServiceKind::Mirror => "mirror",
         }
     }
 
@@ -131,29 +278,14 @@ impl ServiceKind {
         match s {
             "spaces" => Some(ServiceKind::Spaces),
             "yuki" => Some(ServiceKind::Yuki),
+            "bitcoind" => Some(ServiceKind::Bitcoind),
+            "mirror" => Some(ServiceKind::Mirror),
             _ => None,
         }
     }
 }
 
-impl ServiceCommand {
-    pub(crate) fn to_byte(&self) -> u8 {
-        match self {
-            ServiceCommand::Ping => 0,
-            ServiceCommand::Shutdown => 1,
-        }
-    }
-
-    fn from_byte(byte: u8) -> Option<Self> {
-        match byte {
-            0 => Some(ServiceCommand::Ping),
-            1 => Some(ServiceCommand::Shutdown),
-            _ => None,
-        }
-    }
-}
-
-fn read_arg(arg: &str, args: &mut Vec<String>) -> Option<String> {
+pub(crate) fn read_arg(arg: &str, args: &mut Vec<String>) -> Option<String> {
     if let Some(pos) = args.iter().position(|a| a == arg) {
         args.remove(pos);
         if pos < args.len() {
@@ -162,3 +294,53 @@ fn read_arg(arg: &str, args: &mut Vec<String>) -> Option<String> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn frames_round_trip() {
+        let (mut a, mut b) = tokio::io::duplex(64);
+        write_frame(&mut a, &ServiceCommand::Status).await.unwrap();
+        write_frame(&mut a, &ServiceCommand::Ping).await.unwrap();
+        let first: ServiceCommand = read_frame(&mut b).await.unwrap();
+        let second: ServiceCommand = read_frame(&mut b).await.unwrap();
+        assert_eq!(first, ServiceCommand::Status);
+        assert_eq!(second, ServiceCommand::Ping);
+    }
+
+    #[tokio::test]
+    async fn invalid_frame_is_consumed() {
+        let (mut a, mut b) = tokio::io::duplex(64);
+        a.write_all(&4u32.to_be_bytes()).await.unwrap();
+        a.write_all(b"nope").await.unwrap();
+        write_frame(&mut a, &ServiceCommand::Shutdown)
+            .await
+            .unwrap();
+        let err = read_frame::<ServiceCommand>(&mut b).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let next: ServiceCommand = read_frame(&mut b).await.unwrap();
+        assert_eq!(next, ServiceCommand::Shutdown);
+    }
+
+    #[tokio::test]
+    async fn oversized_frame_is_refused() {
+        let (mut a, mut b) = tokio::io::duplex(64);
+        a.write_all(&(MAX_FRAME_SIZE + 1).to_be_bytes())
+            .await
+            .unwrap();
+        let err = read_frame::<ServiceCommand>(&mut b).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn truncated_frame_fails() {
+        let (mut a, mut b) = tokio::io::duplex(64);
+        a.write_all(&10u32.to_be_bytes()).await.unwrap();
+        a.write_all(b"\"pi").await.unwrap();
+        drop(a);
+        let err = read_frame::<ServiceCommand>(&mut b).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::{anyhow, Context};
+use log::info;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::process::Command;
+use tokio::select;
+use tokio::sync::broadcast;
+
+#[cfg(windows)]
+const BITCOIND: &str = "bitcoind.exe";
+#[cfg(not(windows))]
+const BITCOIND: &str = "bitcoind";
+
+/// Finds a bitcoind binary bundled next to the running executable or on the PATH
+pub fn find_bitcoind() -> Option<PathBuf> {
+    let bundled = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(BITCOIND)))
+        .filter(|path| path.is_file());
+    bundled.or_else(|| {
+        std::env::var_os("PATH").and_then(|paths| {
+            std::env::split_paths(&paths)
+                .map(|dir| dir.join(BITCOIND))
+                .find(|path| path.is_file())
+        })
+    })
+}
+
+fn read_option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .find_map(|arg| arg.strip_prefix(name)?.strip_prefix('='))
+}
+
+/// Asks bitcoind to stop over RPC so it can flush its databases, killing it could corrupt them
+async fn stop(args: &[String]) -> anyhow::Result<()> {
+    let port = read_option(args, "-rpcport").context("No -rpcport argument")?;
+    let user = read_option(args, "-rpcuser").unwrap_or_default();
+    let password = read_option(args, "-rpcpassword");
+    reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{}/", port))
+        .basic_auth(user, password)
+        .body(r#"{"jsonrpc":"1.0","id":"akron","method":"stop","params":[]}"#)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+// Bitcoind service main, runs bitcoind with the given arguments
+pub async fn main(args: Vec<String>, shutdown: broadcast::Sender<()>) -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let path = find_bitcoind()
+        .ok_or_else(|| anyhow!("Could not find bitcoind, install Bitcoin Core to use it"))?;
+    info!("Starting {}", path.display());
+    let mut child = Command::new(path)
+        .args(&args)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .context("Failed to start bitcoind")?;
+
+    let mut shutdown_rx = shutdown.subscribe();
+    select! {
+        status = child.wait() => {
+            let status = status?;
+            if !status.success() {
+                return Err(anyhow!("bitcoind exited with {}", status));
+            }
+        }
+        _ = shutdown_rx.recv() => {
+            info!("Stopping bitcoind");
+            if let Err(e) = stop(&args).await {
+                info!("Could not stop bitcoind over RPC: {}", e);
+                child.kill().await?;
+            }
+            child.wait().await?;
+        }
+    }
+    Ok(())
+}
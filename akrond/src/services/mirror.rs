@@ -0,0 +1,280 @@
+use crate::runner::read_arg;
+use anyhow::{anyhow, Context};
+use log::{info, warn};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::select;
+use tokio::sync::broadcast;
+
+/// Files of an exported checkpoint, served from `--checkpoint-dir`
+const CHECKPOINT_FILES: [&str; 3] = [
+    "protocol.sdb",
+    "protocol.sdb.sha256",
+    "protocol.anchors.json",
+];
+
+/// Cached filter responses newer than this are served without asking the upstream
+const CACHE_MAX_AGE: Duration = Duration::from_secs(600);
+
+/// Filters are served under this directory, peers use `http://<address>/filters/` as
+/// their filters endpoint. Only the rest of the path is forwarded to the upstream
+const FILTERS_PREFIX: &str = "filters";
+
+/// Upstream responses larger than this aren't cached or served
+const MAX_FILTERS_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Served on the local machine only unless `--bind` says otherwise
+const DEFAULT_BIND: &str = "127.0.0.1:7226";
+
+const MAX_REQUEST_HEAD: usize = 8192;
+
+struct Mirror {
+    checkpoint_dir: Option<PathBuf>,
+    /// Tried in turn, starting from the last one that answered
+    filters_upstreams: Vec<String>,
+    current_upstream: AtomicUsize,
+    filters_cache: PathBuf,
+    client: reqwest::Client,
+}
+
+struct Request {
+    head_only: bool,
+    path: String,
+    range_start: Option<u64>,
+}
+
+async fn read_request(stream: &mut TcpStream) -> anyhow::Result<Request> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 || head.len() > MAX_REQUEST_HEAD {
+            return Err(anyhow!("Incomplete request"));
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let head_only = match request_line.next() {
+        Some("GET") => false,
+        Some("HEAD") => true,
+        _ => return Err(anyhow!("Unsupported method")),
+    };
+    let path = request_line
+        .next()
+        .and_then(|target| target.split('?').next())
+        .ok_or_else(|| anyhow!("No request path"))?
+        .to_string();
+    let range_start = lines.find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if !name.eq_ignore_ascii_case("range") {
+            return None;
+        }
+        value
+            .trim()
+            .strip_prefix("bytes=")?
+            .strip_suffix('-')?
+            .parse()
+            .ok()
+    });
+    Ok(Request {
+        head_only,
+        path,
+        range_start,
+    })
+}
+
+async fn write_status(stream: &mut TcpStream, status: &str) -> anyhow::Result<()> {
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            )
+            .as_bytes(),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Sends a file, from `range_start` if the client resumes a download
+async fn write_file(stream: &mut TcpStream, path: &Path, request: &Request) -> anyhow::Result<()> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let len = file.metadata().await?.len();
+    let start = request.range_start.filter(|start| *start < len);
+    let status = match start {
+        Some(start) => {
+            file.seek(std::io::SeekFrom::Start(start)).await?;
+            format!(
+                "206 Partial Content\r\nContent-Range: bytes {}-{}/{}",
+                start,
+                len - 1,
+                len
+            )
+        }
+        None => "200 OK".to_string(),
+    };
+    let modified = file.metadata().await?.modified()?;
+    let validator = modified
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nAccept-Ranges: bytes\r\nETag: \"{:x}-{:x}\"\r\nConnection: close\r\n\r\n",
+                status,
+                len - start.unwrap_or(0),
+                validator,
+                len
+            )
+            .as_bytes(),
+        )
+        .await?;
+    if !request.head_only {
+        tokio::io::copy(&mut file, stream).await?;
+    }
+    Ok(())
+}
+
+/// Relative path of a request, `None` if it tries to leave the served directories
+fn relative_path(path: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(path.trim_start_matches('/'));
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then_some(path)
+        .filter(|path| path.components().next().is_some())
+}
+
+impl Mirror {
+    /// Refreshes the cached copy of a filters file from the upstream when it's stale. The
+    /// cached copy keeps being served while the upstream is unreachable
+    async fn cached_filters_file(&self, path: &Path) -> anyhow::Result<Option<PathBuf>> {
+        let cached = self.filters_cache.join(path);
+        let fresh = tokio::fs::metadata(&cached)
+            .await
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age < CACHE_MAX_AGE);
+        if fresh {
+            return Ok(Some(cached));
+        }
+        let Some(response) = self.fetch_upstream(path).await else {
+            return Ok(cached.is_file().then_some(cached));
+        };
+        let mut response = response;
+        if !response.status().is_success()
+            || response
+                .content_length()
+                .is_some_and(|len| len > MAX_FILTERS_FILE_SIZE)
+        {
+            return Ok(None);
+        }
+        if let Some(parent) = cached.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp = cached.with_extension("download");
+        let mut file = tokio::fs::File::create(&tmp).await?;
+        let mut written = 0;
+        while let Some(chunk) = response.chunk().await.context("Failed to read upstream")? {
+            written += chunk.len() as u64;
+            if written > MAX_FILTERS_FILE_SIZE {
+                drop(file);
+                tokio::fs::remove_file(&tmp).await?;
+                return Err(anyhow!(
+                    "Upstream response for {} is too large",
+                    path.display()
+                ));
+            }
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        drop(file);
+        tokio::fs::rename(&tmp, &cached).await?;
+        Ok(Some(cached))
+    }
+
+    /// Requests `path` from the upstreams, moving on to the next one when an upstream is
+    /// unreachable or failing
+    async fn fetch_upstream(&self, path: &Path) -> Option<reqwest::Response> {
+        let start = self.current_upstream.load(Ordering::Relaxed);
+        for i in 0..self.filters_upstreams.len() {
+            let index = (start + i) % self.filters_upstreams.len();
+            let url = format!(
+                "{}/{}",
+                self.filters_upstreams[index].trim_end_matches('/'),
+                path.to_string_lossy().replace('\\', "/")
+            );
+            match self.client.get(&url).send().await {
+                Ok(response) if !response.status().is_server_error() => {
+                    self.current_upstream.store(index, Ordering::Relaxed);
+                    return Some(response);
+                }
+                Ok(response) => warn!("Filters upstream {} failed: {}", url, response.status()),
+                Err(e) => warn!("Filters upstream unreachable: {}", e),
+            }
+        }
+        None
+    }
+
+    async fn handle(&self, mut stream: TcpStream) -> anyhow::Result<()> {
+        let request = read_request(&mut stream).await?;
+        let Some(path) = relative_path(&request.path) else {
+            return write_status(&mut stream, "404 Not Found").await;
+        };
+        let is_checkpoint = CHECKPOINT_FILES.iter().any(|name| path == Path::new(name));
+        let filters_path = path
+            .strip_prefix(FILTERS_PREFIX)
+            .ok()
+            .filter(|rest| rest.components().next().is_some());
+        let file = match (self.checkpoint_dir.as_ref(), filters_path) {
+            (Some(dir), _) if is_checkpoint => Some(dir.join(&path)).filter(|file| file.is_file()),
+            (_, Some(filters_path)) => self.cached_filters_file(filters_path).await?,
+            _ => None,
+        };
+        match file {
+            Some(file) => write_file(&mut stream, &file, &request).await,
+            None => write_status(&mut stream, "404 Not Found").await,
+        }
+    }
+}
+
+// Mirror service main, serves the checkpoint and compact filters to other Akron installs
+pub async fn main(mut args: Vec<String>, shutdown: broadcast::Sender<()>) -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let bind = read_arg("--bind", &mut args).unwrap_or_else(|| DEFAULT_BIND.to_string());
+    let mirror = Arc::new(Mirror {
+        checkpoint_dir: read_arg("--checkpoint-dir", &mut args).map(PathBuf::from),
+        filters_upstreams: std::iter::from_fn(|| read_arg("--filters-upstream", &mut args))
+            .collect(),
+        current_upstream: AtomicUsize::new(0),
+        filters_cache: read_arg("--filters-cache", &mut args)
+            .map(PathBuf::from)
+            .context("No --filters-cache argument")?,
+        client: reqwest::Client::new(),
+    });
+    let listener = TcpListener::bind(&bind)
+        .await
+        .with_context(|| format!("Could not listen on {}", bind))?;
+    info!("Serving checkpoint and filters on {}", bind);
+
+    let mut shutdown = shutdown.subscribe();
+    loop {
+        select! {
+            Ok((stream, peer)) = listener.accept() => {
+                let mirror = mirror.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = mirror.handle(stream).await {
+                        info!("Request from {} failed: {}", peer, e);
+                    }
+                });
+            }
+            _ = shutdown.recv() => return Ok(()),
+        }
+    }
+}
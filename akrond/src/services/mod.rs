@@ -1,2 +1,4 @@
+pub mod bitcoind;
+pub mod mirror;
 pub mod spaces;
 pub mod yuki;
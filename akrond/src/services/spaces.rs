@@ -4,7 +4,8 @@ use tokio::sync::broadcast;
 
 // Spaces service main
 pub async fn main(args: Vec<String>, shutdown: broadcast::Sender<()>) -> anyhow::Result<()> {
-    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+    // set already when running in the akrond process
+    _ = env_logger::Builder::from_env(Env::default().default_filter_or("info")).try_init();
     let mut app = App::new(shutdown.clone());
     app.run(args).await
 }
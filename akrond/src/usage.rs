@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Time between two samples of a service's resource usage
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Samples kept for each service, the last five minutes
+pub const SAMPLES_KEPT: usize = 60;
+
+/// Resource usage of a service over one sampling interval
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResourceSample {
+    /// Percent of one core
+    pub cpu: f32,
+    /// Resident memory in bytes
+    pub memory: u64,
+    /// Bytes read from and written to disk per second
+    pub disk_read: u64,
+    pub disk_written: u64,
+}
+
+pub(crate) type Samples = Arc<Mutex<VecDeque<ResourceSample>>>;
+
+/// Totals since the process started
+#[cfg(target_os = "linux")]
+struct Counters {
+    cpu_ticks: u64,
+    memory: u64,
+    read_bytes: u64,
+    written_bytes: u64,
+}
+
+/// Reads the counters from procfs, `None` once the process exited
+#[cfg(target_os = "linux")]
+fn read_counters(pid: u32) -> Option<Counters> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // the name in parentheses may contain spaces, fields are counted after it
+    let mut fields = stat.rsplit_once(')')?.1.split_whitespace();
+    if fields.next()? == "Z" {
+        return None;
+    }
+    let mut fields = fields.skip(10);
+    let cpu_ticks = fields.next()?.parse::<u64>().ok()? + fields.next()?.parse::<u64>().ok()?;
+
+    let field = |contents: &str, name: &str| {
+        contents
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|value| value.split_whitespace().next()?.parse::<u64>().ok())
+    };
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let memory = field(&status, "VmRSS:").unwrap_or_default() * 1024;
+    let io = std::fs::read_to_string(format!("/proc/{}/io", pid)).unwrap_or_default();
+    Some(Counters {
+        cpu_ticks,
+        memory,
+        read_bytes: field(&io, "read_bytes:").unwrap_or_default(),
+        written_bytes: field(&io, "write_bytes:").unwrap_or_default(),
+    })
+}
+
+/// Samples the usage of the process until it exits, keeping the last samples. Only procfs
+/// is read, elsewhere no samples are kept and the Node screen shows no charts
+#[cfg(target_os = "linux")]
+pub(crate) async fn sample(pid: u32, samples: Samples) {
+    // clock ticks per second, 100 on every platform Linux runs on
+    const TICKS: f32 = 100.0;
+    let seconds = SAMPLE_INTERVAL.as_secs();
+    let mut previous: Option<Counters> = None;
+    let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+    loop {
+        interval.tick().await;
+        let Some(counters) = read_counters(pid) else {
+            return;
+        };
+        if let Some(previous) = previous.as_ref() {
+            let sample = ResourceSample {
+                cpu: counters.cpu_ticks.saturating_sub(previous.cpu_ticks) as f32 * 100.0
+                    / TICKS
+                    / seconds as f32,
+                memory: counters.memory,
+                disk_read: counters.read_bytes.saturating_sub(previous.read_bytes) / seconds,
+                disk_written: counters
+                    .written_bytes
+                    .saturating_sub(previous.written_bytes)
+                    / seconds,
+            };
+            let mut samples = samples.lock().unwrap();
+            if samples.len() == SAMPLES_KEPT {
+                samples.pop_front();
+            }
+            samples.push_back(sample);
+        }
+        previous = Some(counters);
+    }
+}